@@ -0,0 +1,34 @@
+//! Records the git commit and the version of rustc of the build, for the metadata of the reports (see `metadata`).
+
+use std::process::Command;
+
+/// first line of the output of a command, if it succeeds.
+fn first_line(program: &str, args: &[&str]) -> Option<String> {
+    let output = Command::new(program).args(args).output().ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    let text = String::from_utf8(output.stdout).ok()?;
+    text.lines()
+        .next()
+        .map(|l| l.trim().to_string())
+        .filter(|l| !l.is_empty())
+}
+
+fn main() {
+    let commit = first_line("git", &["rev-parse", "--short=12", "HEAD"]);
+    println!(
+        "cargo:rustc-env=SYNTH_LIGHTS_GIT_COMMIT={}",
+        commit.as_deref().unwrap_or("unknown")
+    );
+    let rustc = std::env::var("RUSTC").unwrap_or_else(|_| "rustc".to_string());
+    println!(
+        "cargo:rustc-env=SYNTH_LIGHTS_RUSTC_VERSION={}",
+        first_line(&rustc, &["--version"])
+            .as_deref()
+            .unwrap_or("unknown")
+    );
+    for path in [".git/HEAD", ".git/refs/heads", ".git/packed-refs"] {
+        println!("cargo:rerun-if-changed={path}");
+    }
+}
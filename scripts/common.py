@@ -22,7 +22,7 @@ PASS_LINE_R = re.compile(r'\s*(\d+)\s*: PASS ([0-9sdSOH_]+)\s*')
 def cli_options(line: str):
     """returns a dictionary of run options if the line matches an entry of run options, or None otherwise."""
     if m := re.match(CLI_RUN_OPTIONS_R, line):
-        return { k.strip():v.strip() for k, v in map(lambda kv: kv.split(":"), m.group(1).split(",")) }
+        return { k.strip():v.strip() for k, v in re.findall(r'(\w+): ([^,{}]+)', m.group(1)) }
     else:
         return None
 
@@ -3,10 +3,14 @@ pub use strum::IntoEnumIterator;
 use strum::{Display, EnumString};
 
 use anyhow::{anyhow, bail, Context};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
 
 use crate::common::*;
 
-#[derive(Eq, PartialEq, Debug, Clone, Copy, EnumString, Display, PartialOrd, Ord)]
+#[derive(
+    Eq, PartialEq, Debug, Clone, Copy, EnumString, Display, PartialOrd, Ord, Serialize, Deserialize,
+)]
 pub enum Guard {
     LExternal(Color),             //< (other's color)
     LInternal(Color),             //< (my color)
@@ -129,7 +133,7 @@ impl Guard {
                     .map(Color::try_from)
                     .ok_or_else(|| anyhow!("missing color"))??;
                 let d = code
-                    .get(2..3)
+                    .get(1..2)
                     .map(Distance::try_parse)
                     .ok_or_else(|| anyhow!("missing distance"))??;
                 if model == External {
@@ -141,6 +145,58 @@ impl Guard {
         }
     }
 
+    /// checks if the guard holds for the given observation (as in the conditions of the Promela rules,
+    /// where distances [Distance::Near] and [Distance::Far] are not distinguished).
+    pub fn matches(&self, me: Color, other: Color, same_position: bool) -> bool {
+        use Guard::*;
+        let at = |d: &Distance| (*d == Distance::Same) == same_position;
+        match self {
+            LExternal(o) => *o == other,
+            LInternal(c) => *c == me,
+            LFull(c, o) => *c == me && *o == other,
+            External(o, d) => *o == other && at(d),
+            Internal(c, d) => *c == me && at(d),
+            Full(c, o, d) => *c == me && *o == other && at(d),
+        }
+    }
+
+    /// returns the default action for the guard, that is, the action taken when no rule applies:
+    /// stay in place and keep one's own color.
+    /// Returns `None` when the guard does not determine the robot's own color (e.g., [Guard::External]),
+    /// in which case the default cannot be expressed as an [Action].
+    pub fn default_action(&self) -> Option<Action> {
+        self.my_color().map(|c| Action(c, Move::Stay))
+    }
+
+    /// human-readable condition of the guard (e.g., `I am 0 and see 1 at distance > 0`).
+    pub fn describe(&self) -> String {
+        let seen = match (self.my_color(), self.other_color()) {
+            (Some(me), Some(other)) => format!("I am {me} and see {other}"),
+            (Some(me), None) => format!("I am {me}"),
+            (None, Some(other)) => format!("I see {other}"),
+            (None, None) => unreachable!("guards observe at least one color"),
+        };
+        match self.distance() {
+            Some(Distance::Same) => format!("{seen} at distance 0"),
+            Some(_) => format!("{seen} at distance > 0"),
+            None => seen,
+        }
+    }
+
+    /// returns the guard obtained by renaming every color `c` of the guard into `perm[c]`.
+    pub fn permute_colors(&self, perm: &[Color]) -> Self {
+        use Guard::*;
+        let p = |c: &Color| perm[c.0 as usize];
+        match self {
+            LExternal(c) => LExternal(p(c)),
+            LInternal(c) => LInternal(p(c)),
+            LFull(c1, c2) => LFull(p(c1), p(c2)),
+            External(c, d) => External(p(c), *d),
+            Internal(c, d) => Internal(p(c), *d),
+            Full(c1, c2, d) => Full(p(c1), p(c2), *d),
+        }
+    }
+
     pub fn number_for_model(model: crate::ModelKind, num_colors: u8, class_l: bool) -> usize {
         use crate::ModelKind::*;
         let basic_count = match model {
@@ -155,7 +211,7 @@ impl Guard {
     }
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
 pub struct Action(pub Color, pub Move); //<  Action(next color, movement)
 
 impl Action {
@@ -172,6 +228,16 @@ impl Action {
         format!("{}{}", self.1.as_code(), self.0 .0)
     }
 
+    /// human-readable command of the action (e.g., `set color 1 and move to half`).
+    pub fn describe(&self) -> String {
+        let movement = match self.1 {
+            Move::Stay => "stay",
+            Move::ToHalf => "move to half",
+            Move::ToOther => "move to other",
+        };
+        format!("set color {} and {movement}", self.0)
+    }
+
     pub fn try_parse(code: &str) -> anyhow::Result<Self> {
         if code.len() != 2 {
             bail!("wrong length for action: \"{}\"", code);
@@ -182,10 +248,26 @@ impl Action {
     }
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
-pub struct Rule(Guard, Action);
+/// rule of an algorithm: when the guard holds, the robot takes the action.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct Rule(pub Guard, pub Action);
 
-#[derive(Clone, PartialEq, Eq, PartialOrd, Ord)]
+impl Rule {
+    pub fn guard(&self) -> Guard {
+        self.0
+    }
+    pub fn action(&self) -> Action {
+        self.1
+    }
+
+    /// checks if the rule fires for the given observation (see [Guard::matches]).
+    pub fn fires_on(&self, me: Color, other: Color, same_position: bool) -> bool {
+        self.0.matches(me, other, same_position)
+    }
+}
+
+#[derive(Clone, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(try_from = "AlgorithmData")]
 pub struct Algorithm {
     num_colors: u8,
     guards: Vec<Guard>,
@@ -193,17 +275,51 @@ pub struct Algorithm {
 }
 
 impl Algorithm {
+    /// algorithm with the given rules; panics if they are inconsistent (see [Algorithm::try_new]).
     pub fn new(num_colors: u8, guards: &[Guard], actions: &[Action]) -> Self {
-        let guards = guards.to_vec();
-        let n_guards = guards.len();
-        let actions: Vec<_> = Vec::from(actions);
-        assert_eq!(actions.len(), n_guards);
-        assert!(actions.iter().all(|Action(c, _)| c < &Color(num_colors)));
-        Algorithm {
-            num_colors,
-            guards,
-            actions,
+        Self::try_new(num_colors, guards, actions).unwrap_or_else(|e| panic!("{e:#}"))
+    }
+
+    /// algorithm with the given rules (the guard and the action of each rule, in order); fails if there are no rules,
+    /// if the numbers of guards and actions differ, if the guards are of different models, or if a color is out of
+    /// range.
+    pub fn try_new(num_colors: u8, guards: &[Guard], actions: &[Action]) -> anyhow::Result<Self> {
+        let Some(first) = guards.first() else {
+            bail!("algorithm without rules");
+        };
+        if guards.len() != actions.len() {
+            bail!(
+                "guards and actions have different lengths ({} guards, {} actions)",
+                guards.len(),
+                actions.len()
+            );
+        }
+        if let Some(guard) = guards
+            .iter()
+            .find(|g| g.model_kind() != first.model_kind() || g.class_L() != first.class_L())
+        {
+            bail!("guards of different models ({first:?} and {guard:?})");
+        }
+        let out_of_range = |c: &Color| c.0 >= num_colors;
+        if let Some(guard) = guards.iter().find(|g| {
+            g.my_color()
+                .iter()
+                .chain(g.other_color().iter())
+                .any(out_of_range)
+        }) {
+            bail!(
+                "color out of range in guard {} ({num_colors} colors)",
+                guard.as_code()
+            );
+        }
+        if let Some(Action(c, _)) = actions.iter().find(|a| out_of_range(&a.0)) {
+            bail!("color {c} out of range in action ({num_colors} colors)");
         }
+        Ok(Algorithm {
+            num_colors,
+            guards: guards.to_vec(),
+            actions: actions.to_vec(),
+        })
     }
 
     pub fn model_kind(&self) -> crate::ModelKind {
@@ -215,12 +331,30 @@ impl Algorithm {
         self.guards[0].class_L()
     }
 
+    /// model of the algorithm.
+    pub fn model(&self) -> crate::model::Model {
+        crate::model::Model::from((self.model_kind(), self.num_colors, self.class_L()))
+    }
+
+    /// parses the code string of an algorithm of the given model (see [Algorithm::as_code]).
+    /// A code in the self-describing format (see [Algorithm::as_code_v2]) is also accepted if its model matches.
     pub fn try_parse(
         model: crate::ModelKind,
         num_colors: u8,
         class_l: bool,
         code: &str,
     ) -> anyhow::Result<Self> {
+        let code = match code.split_once(':') {
+            Some((prefix, code)) => {
+                let expected = crate::model::Model::from((model, num_colors, class_l));
+                let found = crate::model::Model::try_from(prefix)?;
+                if found != expected {
+                    bail!("algorithm of model {found} instead of {expected}");
+                }
+                code
+            }
+            None => code,
+        };
         let guards_actions: Vec<_> = code.split("__").collect();
         match guards_actions.as_slice() {
             #![allow(clippy::redundant_closure)]
@@ -233,13 +367,7 @@ impl Algorithm {
                     .split('_')
                     .map(|code| Action::try_parse(code))
                     .collect::<Result<Vec<_>, _>>()?;
-                if guards.len() != actions.len() {
-                    bail!(
-                        "guards and actions have different lengths ({} guards, {} actions)",
-                        guards.len(),
-                        actions.len()
-                    );
-                }
+                let algo = Algorithm::try_new(num_colors, &guards, &actions)?;
                 if guards.len() != Guard::number_for_model(model, num_colors, class_l) {
                     bail!(
                         "number of guards ({}) does not match model ({})",
@@ -247,7 +375,7 @@ impl Algorithm {
                         Guard::number_for_model(model, num_colors, class_l)
                     );
                 }
-                Ok(Algorithm::new(num_colors, &guards, &actions))
+                Ok(algo)
             }
             [_actions] => bail!("guards are missing"),
             _ => bail!("missing separation string (or too many)"),
@@ -273,41 +401,85 @@ impl Algorithm {
         format!("{}__{}", guard_part, action_part)
     }
 
+    /// code string in the self-describing format, prefixed by the model string (e.g., `E3L:0_1_2__S2_H0_O1`),
+    /// that can be parsed without knowing the model (see [Algorithm::try_parse_v2]).
+    pub fn as_code_v2(&self) -> String {
+        format!("{}:{}", self.model(), self.as_code())
+    }
+
+    /// parses a code string in the self-describing format (see [Algorithm::as_code_v2]).
+    pub fn try_parse_v2(code: &str) -> anyhow::Result<Self> {
+        let (model, _) = code
+            .split_once(':')
+            .ok_or_else(|| anyhow!("missing model prefix in algorithm code (e.g., F2:...)"))?;
+        let model = crate::model::Model::try_from(model)?;
+        Self::try_parse(model.category, model.n_colors, model.class_L, code)
+    }
+
+    /// human-readable listing of the rules, one per line, aligned on the arrows
+    /// (e.g., `when I am 0 and see 1 at distance > 0 → set color 1 and move to half`).
+    pub fn to_pretty_table(&self) -> String {
+        let conditions = self.guards.iter().map(Guard::describe).collect::<Vec<_>>();
+        let width = conditions.iter().map(String::len).max().unwrap_or(0);
+        conditions
+            .iter()
+            .zip(self.actions.iter())
+            .map(|(condition, action)| format!("when {condition:<width$} → {}", action.describe()))
+            .join("\n")
+    }
+
     pub fn num_colors(&self) -> u8 {
         self.num_colors
     }
 
-    pub fn rules(&self) -> impl Iterator<Item = (&Guard, &Action)> {
-        self.guards.iter().zip(self.actions.iter())
+    /// rules of the algorithm, in the order of its guards.
+    pub fn rules(&self) -> impl Iterator<Item = Rule> + '_ {
+        self.guards
+            .iter()
+            .zip(self.actions.iter())
+            .map(|(&g, &a)| Rule(g, a))
+    }
+
+    /// action of the first rule whose guard holds for the observation, if any.
+    pub fn action_for(&self, me: Color, other: Color, same_position: bool) -> Option<Action> {
+        self.rule_for(me, other, same_position)
+            .map(|index| self.actions[index])
+    }
+
+    /// index of the first rule whose guard holds for the observation, if any.
+    pub fn rule_for(&self, me: Color, other: Color, same_position: bool) -> Option<usize> {
+        self.guards
+            .iter()
+            .position(|g| g.matches(me, other, same_position))
     }
 
     /// checks if all gathered rules are stationary (i.e., [Move::Stay]).
     /// When the robots are already gathered, all moves ([Move::ToOther] and [Move::ToHalf]) are equivalent to [Move::Stay].
     pub fn all_gathered_are_stay(&self) -> bool {
         self.rules()
-            .filter(|(g, _)| g.is_gathered())
-            .all(|(_, a)| a.is_stationary())
+            .filter(|r| r.guard().is_gathered())
+            .all(|r| r.action().is_stationary())
     }
 
     /// checks if the algorithms contains a non-gathered rule such that the action is stationary (i.e., [Move::Stay]).
     /// An algorithm without such rule cannot achieve gathering under a centralized scheduler.
     pub fn some_non_gathered_is_stay(&self) -> bool {
         self.rules()
-            .any(|(g, a)| a.is_stationary() && !g.is_gathered())
+            .any(|Rule(g, a)| a.is_stationary() && !g.is_gathered())
     }
 
     /// checks if the algorithm contains a non-gathered rule such that the action has a [Move::ToOther].
     /// An algorithm without such rule cannot achieve gathering under a centralized scheduler.
     pub fn some_non_gathered_is_to_other(&self) -> bool {
         self.rules()
-            .any(|(g, Action(_, m))| m == &Move::ToOther && !g.is_gathered())
+            .any(|Rule(g, Action(_, m))| m == Move::ToOther && !g.is_gathered())
     }
 
     /// checks if the algorithm contains a non-gathered rule such that the action has a [Move::ToHalf].
     /// An algorithm without such rule cannot achieve gathering under an FSYNC scheduler.
     pub fn some_non_gathered_is_to_half(&self) -> bool {
         self.rules()
-            .any(|(g, Action(_, m))| m == &Move::ToHalf && !g.is_gathered())
+            .any(|Rule(g, Action(_, m))| m == Move::ToHalf && !g.is_gathered())
     }
 
     /// checks if all colors are used in the non-gathered actions.
@@ -316,7 +488,7 @@ impl Algorithm {
     pub fn all_colors_used_in_non_gathered(&self) -> bool {
         Color::iter_ncols(self.num_colors).all(|c| {
             self.rules()
-                .any(|(g, Action(c2, _))| c2 == &c && !g.is_gathered())
+                .any(|Rule(g, Action(c2, _))| c2 == c && !g.is_gathered())
         })
     }
 
@@ -335,12 +507,12 @@ impl Algorithm {
     pub fn is_pseudo_canonical(&self) -> bool {
         let non_gathered = self
             .rules()
-            .filter(|(g, _)| !g.is_gathered())
+            .filter(|r| !r.guard().is_gathered())
             .collect::<Vec<_>>();
         let same_colors_same_sorted = non_gathered
             .iter()
-            .filter(|(g, _)| g.same_colors())
-            .map(|(_, a)| a.1)
+            .filter(|r| r.guard().same_colors())
+            .map(|r| r.action().movement())
             .fold((true, Move::Stay), |(res, ref_mv), mv| {
                 (res && ref_mv <= mv, Move::max(ref_mv, mv))
             })
@@ -348,10 +520,192 @@ impl Algorithm {
         same_colors_same_sorted
     }
 
+    /// returns the indices of the rules that can be replaced by the default action
+    /// (see [Guard::default_action]) without changing the behavior of the algorithm.
+    /// This is the case when a rule keeps the robot's color and either stays, or moves while
+    /// the robots are already gathered (all moves are then equivalent to [Move::Stay]).
+    ///
+    /// The analysis is static and does not look for rules that never fire: since the initial colors
+    /// are selected non-deterministically, every guard is enabled in some initial configuration.
+    /// Use [crate::runner::minimize_algorithm] for a (costlier) analysis based on re-verification.
+    pub fn redundant_rules(&self) -> Vec<usize> {
+        self.rules()
+            .positions(|Rule(g, a)| {
+                g.default_action().is_some_and(|default| {
+                    a.color() == default.color() && (a.is_stationary() || g.is_gathered())
+                })
+            })
+            .collect()
+    }
+
+    /// returns a copy of the algorithm where the action of the rule at `index` is replaced by `action`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is out of range or if the color of `action` is not in the model.
+    pub fn with_action(&self, index: usize, action: Action) -> Algorithm {
+        assert!(action.color() < Color(self.num_colors));
+        let mut algo = self.clone();
+        algo.actions[index] = action;
+        algo
+    }
+
+    /// iterates over the algorithms differing from `self` in exactly one action (its color, its move, or both), by
+    /// rule, then by color, then by move; there are `rules × (3 × colors − 1)` of them.
+    pub fn neighbors(&self) -> impl Iterator<Item = Algorithm> + '_ {
+        let actions = Color::iter_ncols(self.num_colors)
+            .cartesian_product(Move::iter())
+            .map(|(c, m)| Action(c, m))
+            .collect::<Vec<_>>();
+        self.actions
+            .iter()
+            .enumerate()
+            .flat_map(move |(index, &current)| {
+                actions
+                    .clone()
+                    .into_iter()
+                    .filter(move |&action| action != current)
+                    .map(move |action| self.with_action(index, action))
+            })
+    }
+
+    /// returns a copy of the algorithm where the rule at `index` is replaced by its default action.
+    /// Returns `None` if the guard has no expressible default action (see [Guard::default_action]).
+    pub fn with_default_rule(&self, index: usize) -> Option<Algorithm> {
+        let default = self.guards.get(index)?.default_action()?;
+        Some(self.with_action(index, default))
+    }
+
+    /// returns the algorithm obtained by renaming every color `c` into `perm[c]`,
+    /// both in the guards and in the actions.
+    /// The resulting algorithm keeps the same guard ordering, so that it can be compared directly with `self`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `perm` is not a permutation of the colors of the algorithm.
+    pub fn permute_colors(&self, perm: &[Color]) -> Algorithm {
+        assert_eq!(perm.len(), self.num_colors as usize);
+        let mut actions = self.actions.clone();
+        for Rule(g, Action(c, m)) in self.rules() {
+            let g2 = g.permute_colors(perm);
+            let idx = self
+                .guards
+                .iter()
+                .position(|g| g == &g2)
+                .expect("guard set is not closed under color permutation");
+            actions[idx] = Action(perm[c.0 as usize], m);
+        }
+        Algorithm {
+            num_colors: self.num_colors,
+            guards: self.guards.clone(),
+            actions,
+        }
+    }
+
+    /// returns the algorithm obtained by exchanging the colors `a` and `b` (see [Algorithm::permute_colors]).
+    ///
+    /// # Panics
+    ///
+    /// Panics if `a` or `b` is not a color of the algorithm.
+    pub fn swap_colors(&self, a: Color, b: Color) -> Algorithm {
+        assert!(a.0 < self.num_colors && b.0 < self.num_colors);
+        let mut perm = Color::iter_ncols(self.num_colors).collect::<Vec<_>>();
+        perm.swap(a.0 as usize, b.0 as usize);
+        self.permute_colors(&perm)
+    }
+
+    /// returns the algorithm of the same model with the first `num_colors` colors, made of the rules of `self` whose
+    /// guards only observe these colors (in the order of the guards of the smaller model).
+    /// Then `self` embeds the result (see [Algorithm::embeds]) whenever it has more colors.
+    /// Fails if `num_colors` is zero or larger than the number of colors of the algorithm, or if one of these rules
+    /// sets another color.
+    pub fn restrict_to_colors(&self, num_colors: u8) -> anyhow::Result<Algorithm> {
+        if num_colors == 0 || num_colors > self.num_colors {
+            bail!(
+                "cannot restrict an algorithm with {} colors to {num_colors} colors",
+                self.num_colors
+            );
+        }
+        let model = crate::model::Model::from((self.model_kind(), num_colors, self.class_L()));
+        let guards = crate::generator::guards_for_model(model);
+        let actions = guards
+            .iter()
+            .map(|g| {
+                let index = self
+                    .guards
+                    .iter()
+                    .position(|g2| g2 == g)
+                    .ok_or_else(|| anyhow!("guard {} is not in the algorithm", g.as_code()))?;
+                Ok(self.actions[index])
+            })
+            .collect::<anyhow::Result<Vec<_>>>()?;
+        Algorithm::try_new(num_colors, &guards, &actions)
+            .with_context(|| format!("cannot restrict {} to {num_colors} colors", self.as_code()))
+    }
+
+    /// key used to order algorithms of the same permutation class.
+    /// The moves of non-gathered rules with same colors come first, so that the minimal
+    /// element of a class is always pseudo-canonical (see [Algorithm::is_pseudo_canonical]).
+    fn canonical_key(&self) -> (Vec<Move>, &[Action]) {
+        let same_colors_moves = self
+            .rules()
+            .filter(|r| !r.guard().is_gathered() && r.guard().same_colors())
+            .map(|r| r.action().movement())
+            .collect();
+        (same_colors_moves, &self.actions)
+    }
+
+    /// iterates over all permutations of the colors of the algorithm.
+    fn color_permutations(&self) -> impl Iterator<Item = Vec<Color>> {
+        let n = self.num_colors as usize;
+        Color::iter_ncols(self.num_colors).permutations(n)
+    }
+
+    /// computes the canonical representative of the algorithm with respect to color permutations,
+    /// that is, the minimal algorithm among all those obtained by renaming colors.
+    /// Two algorithms are equivalent up to color renaming iff they have the same canonical form.
+    pub fn canonical(&self) -> Algorithm {
+        self.color_permutations()
+            .map(|perm| self.permute_colors(&perm))
+            .min_by(|a, b| a.canonical_key().cmp(&b.canonical_key()))
+            .unwrap_or_else(|| self.clone())
+    }
+
+    /// checks whether the algorithm is the canonical representative of its permutation class (see [Algorithm::canonical]).
+    /// Unlike [Algorithm::is_pseudo_canonical], the check is exact: exactly one algorithm per class passes it.
+    pub fn is_canonical(&self) -> bool {
+        let key = self.canonical_key();
+        self.color_permutations()
+            .all(|perm| self.permute_colors(&perm).canonical_key() >= key)
+    }
+
+    /// checks whether `lower`, an algorithm with fewer colors in the same model, is embedded in the algorithm,
+    /// that is, whether there is an injective renaming of the colors of `lower` into colors of `self` such that
+    /// every rule of `lower`, once renamed, is also a rule of `self`.
+    /// Then, as long as the robots only use the renamed colors, `self` behaves exactly as `lower`.
+    pub fn embeds(&self, lower: &Algorithm) -> bool {
+        if lower.num_colors >= self.num_colors
+            || lower.model_kind() != self.model_kind()
+            || lower.class_L() != self.class_L()
+        {
+            return false;
+        }
+        Color::iter_ncols(self.num_colors)
+            .permutations(lower.num_colors as usize)
+            .any(|sigma| {
+                lower.rules().all(|Rule(g, Action(c, m))| {
+                    let g2 = g.permute_colors(&sigma);
+                    self.rules()
+                        .find(|r| r.guard() == g2)
+                        .is_some_and(|r| r.action() == Action(sigma[c.0 as usize], m))
+                })
+            })
+    }
+
     /// checks whether the algorithm satisfies the following condition expressed by Viglietta (ALGOSENSOR 2013)
     /// "A robot retains its color if and only if it sees the other robot set to a different color."
     pub fn retains_color_iif_other_color_different(&self) -> bool {
-        self.rules().all(|(&g, &a)| match g {
+        self.rules().all(|Rule(g, a)| match g {
             Guard::LFull(my, _) | Guard::Full(my, _, _) =>
             // - a robot always change its color when the other robot has the same color
             {
@@ -363,6 +717,77 @@ impl Algorithm {
             _ => true,
         })
     }
+
+    /// color transition graph of the algorithm: for each color, the other colors a robot of that color can take in one
+    /// action (from all colors if the guard does not observe the color of the robot).
+    pub fn color_transitions(&self) -> BTreeMap<Color, BTreeSet<Color>> {
+        let mut next: BTreeMap<Color, BTreeSet<Color>> = BTreeMap::new();
+        for Rule(guard, action) in self.rules() {
+            let from_colors = match guard.my_color() {
+                Some(c) => vec![c],
+                None => Color::iter_ncols(self.num_colors).collect(),
+            };
+            for from in from_colors.into_iter().filter(|&c| c != action.color()) {
+                next.entry(from).or_default().insert(action.color());
+            }
+        }
+        next
+    }
+
+    /// checks whether the color transition graph (see [Algorithm::color_transitions]) is strongly connected, that is,
+    /// whether every color can be reached from every other one: no color is unreachable, and some closed sequence of
+    /// color changes goes through all colors.
+    pub fn has_strongly_connected_colors(&self) -> bool {
+        let next = self.color_transitions();
+        Color::iter_ncols(self.num_colors).all(|start| {
+            let mut reached = BTreeSet::from([start]);
+            let mut stack = vec![start];
+            while let Some(c) = stack.pop() {
+                for &to in next.get(&c).into_iter().flatten() {
+                    if reached.insert(to) {
+                        stack.push(to);
+                    }
+                }
+            }
+            reached.len() == self.num_colors as usize
+        })
+    }
+
+    /// checks whether the algorithm is symmetric under exchanging the roles of the colors observed by the guards of full
+    /// lights: for each rule `(my, other) -> (c, m)`, the rule `(other, my)` is `-> (c', m)` where `c'` is `c` with
+    /// `my` and `other` exchanged (e.g., both robots keep their colors, or both take the color of the other).
+    /// Guards observing a single color, and those where both colors are the same, are unconstrained; an algorithm
+    /// unchanged when swapping two colors (see [Algorithm::swap_colors]) is symmetric with 2 colors, but not conversely;
+    /// the invariance under swapping colors 0 and 1 is the feature [crate::classify::Feature::ColorSwapInvariant].
+    pub fn is_swap_symmetric(&self) -> bool {
+        self.rules().all(|Rule(g, Action(c, m))| match g {
+            Guard::LFull(my, other) | Guard::Full(my, other, _) => {
+                let mut perm = Color::iter_ncols(self.num_colors).collect::<Vec<_>>();
+                perm.swap(my.0 as usize, other.0 as usize);
+                let mirror = g.permute_colors(&perm);
+                self.rules()
+                    .find(|r| r.guard() == mirror)
+                    .is_some_and(|r| r.action() == Action(perm[c.0 as usize], m))
+            }
+            _ => true,
+        })
+    }
+}
+
+/// unchecked serialized form of an [Algorithm], validated when converted back into an algorithm.
+#[derive(Deserialize)]
+struct AlgorithmData {
+    num_colors: u8,
+    guards: Vec<Guard>,
+    actions: Vec<Action>,
+}
+
+impl TryFrom<AlgorithmData> for Algorithm {
+    type Error = anyhow::Error;
+
+    fn try_from(data: AlgorithmData) -> anyhow::Result<Self> {
+        Algorithm::try_new(data.num_colors, &data.guards, &data.actions)
+    }
 }
 
 impl std::fmt::Debug for Algorithm {
@@ -377,6 +802,7 @@ impl std::fmt::Debug for Algorithm {
 #[cfg(test)]
 pub mod tests {
     use super::*;
+    use crate::generator::generate_algorithms_in_model;
     use crate::generator::tests::*;
 
     #[test]
@@ -426,6 +852,215 @@ pub mod tests {
         assert!(!algo.is_pseudo_canonical());
     }
 
+    #[test]
+    fn test_canonical() {
+        let num_colors = 2;
+        let guards = guards_for_full_lights_2_cols();
+        let actions = [
+            // gathered
+            Action(Color(0), Move::Stay),
+            Action(Color(1), Move::Stay),
+            Action(Color(0), Move::Stay),
+            Action(Color(1), Move::Stay),
+            // non-gathered
+            Action(Color(0), Move::Stay),
+            Action(Color(1), Move::ToHalf),
+            Action(Color(0), Move::Stay),
+            Action(Color(1), Move::ToOther),
+        ];
+        let algo = Algorithm::new(num_colors, &guards, &actions);
+        let swapped = algo.permute_colors(&[Color(1), Color(0)]);
+        assert_ne!(algo, swapped);
+        assert_eq!(swapped.permute_colors(&[Color(1), Color(0)]), algo);
+        assert_eq!(algo.canonical(), swapped.canonical());
+        assert_ne!(algo.is_canonical(), swapped.is_canonical());
+
+        let canonical = algo.canonical();
+        assert!(canonical.is_canonical());
+        assert!(canonical.is_pseudo_canonical());
+
+        // exactly one representative per class
+        let n_canonical = generate_algorithms_in_model(crate::ModelKind::External, 2, true)
+            .filter(|a| a.is_canonical())
+            .count();
+        let n_classes = generate_algorithms_in_model(crate::ModelKind::External, 2, true)
+            .map(|a| a.canonical())
+            .collect::<std::collections::BTreeSet<_>>()
+            .len();
+        assert_eq!(n_canonical, n_classes);
+    }
+
+    #[test]
+    fn test_embeds() {
+        use crate::ModelKind::External;
+        let lower = Algorithm::try_parse(External, 2, false, "0s_1s_0d_1d__S0_S1_H1_O0").unwrap();
+        // colors 0 and 1 of `lower` renamed into 2 and 0
+        let upper =
+            Algorithm::try_parse(External, 3, false, "0s_1s_2s_0d_1d_2d__S0_S1_S2_O2_H1_H0")
+                .unwrap();
+        assert!(upper.embeds(&lower));
+        assert!(!upper.embeds(&upper));
+        assert!(!lower.embeds(&upper));
+
+        let other = upper.with_action(3, Action(Color(1), Move::ToOther));
+        assert!(!other.embeds(&lower));
+
+        let lower_l = Algorithm::try_parse(External, 2, true, "0_1__S0_H1").unwrap();
+        assert!(!upper.embeds(&lower_l));
+    }
+
+    #[test]
+    fn test_swap_symmetric() {
+        use crate::ModelKind::*;
+        let mut n_symmetric = 0;
+        for algo in generate_algorithms_in_model(Full, 2, true) {
+            let symmetric = algo.is_swap_symmetric();
+            assert!(symmetric || algo.swap_colors(Color(0), Color(1)) != algo);
+            n_symmetric += symmetric as usize;
+        }
+        // the action of 10 is determined by that of 01
+        assert_eq!(n_symmetric, 6 * 6 * 6);
+
+        let algo =
+            Algorithm::try_parse_v2("F3L:00_01_02_10_11_12_20_21_22__S0_S1_H2_S0_S1_O2_H0_O1_H0")
+                .unwrap();
+        assert!(algo.is_swap_symmetric());
+        assert!(algo.swap_colors(Color(0), Color(2)).is_swap_symmetric());
+        assert!(!algo
+            .with_action(7, Action(Color(2), Move::ToOther))
+            .is_swap_symmetric());
+        let external = Algorithm::try_parse_v2("E3L:0_1_2__S2_H0_O1").unwrap();
+        assert!(external.is_swap_symmetric());
+    }
+
+    #[test]
+    fn test_color_graph() {
+        let parse = |code| Algorithm::try_parse_v2(code).unwrap();
+        let algo = parse("F2L:00_01_10_11__S1_S0_O0_H0");
+        assert_eq!(
+            algo.color_transitions(),
+            BTreeMap::from([
+                (Color(0), BTreeSet::from([Color(1)])),
+                (Color(1), BTreeSet::from([Color(0)]))
+            ])
+        );
+        assert!(algo.has_strongly_connected_colors());
+        // no color change, then color 1 never left
+        assert!(!parse("F2L:00_01_10_11__S0_S0_O1_H1").has_strongly_connected_colors());
+        assert!(!parse("F2L:00_01_10_11__S1_S1_O1_H1").has_strongly_connected_colors());
+        // external lights: a robot can take any color from any color
+        assert!(parse("E3L:0_1_2__S2_H0_O1").has_strongly_connected_colors());
+        assert!(parse("E1:0s_0d__S0_O0").has_strongly_connected_colors());
+    }
+
+    #[test]
+    fn test_neighbors() {
+        let algo = Algorithm::try_parse_v2("E3L:0_1_2__S2_H0_O1").unwrap();
+        let neighbors = algo.neighbors().collect::<Vec<_>>();
+        assert_eq!(neighbors.len(), 3 * (3 * 3 - 1));
+        assert_eq!(neighbors[0].as_code(), "0_1_2__S0_H0_O1");
+        assert!(neighbors.iter().all(|n| {
+            n.rules()
+                .zip(algo.rules())
+                .filter(|(r1, r2)| r1 != r2)
+                .count()
+                == 1
+        }));
+        assert_eq!(
+            neighbors
+                .iter()
+                .collect::<std::collections::BTreeSet<_>>()
+                .len(),
+            neighbors.len()
+        );
+        assert!(neighbors.iter().any(|n| n.as_code() == "0_1_2__S2_H2_O1"));
+    }
+
+    #[test]
+    fn test_edit() {
+        use crate::ModelKind::External;
+        let lower = Algorithm::try_parse(External, 2, false, "0s_1s_0d_1d__S0_S1_H1_O0").unwrap();
+        let upper =
+            Algorithm::try_parse(External, 3, false, "0s_1s_2s_0d_1d_2d__S0_S1_S2_O2_H1_H0")
+                .unwrap();
+        // colors 0 and 1 of `lower` were renamed into 2 and 0: renamed back by two swaps
+        let renamed = upper
+            .swap_colors(Color(0), Color(2))
+            .swap_colors(Color(1), Color(2));
+        assert_eq!(renamed.swap_colors(Color(1), Color(1)), renamed);
+        assert_eq!(renamed.restrict_to_colors(2).unwrap(), lower);
+        assert!(renamed.embeds(&lower));
+        assert_eq!(renamed.restrict_to_colors(3).unwrap(), renamed);
+        // the rule 0d -> O2 of `upper` sets color 2
+        let error = upper.restrict_to_colors(2).unwrap_err();
+        assert!(format!("{error:#}").contains("color 2 out of range"));
+        assert!(upper.restrict_to_colors(0).is_err());
+        assert!(upper.restrict_to_colors(4).is_err());
+    }
+
+    #[test]
+    fn test_redundant_rules() {
+        let num_colors = 2;
+        let guards = guards_for_full_lights_2_cols();
+        let actions = [
+            // gathered
+            Action(Color(0), Move::Stay),
+            Action(Color(1), Move::Stay),
+            Action(Color(1), Move::ToHalf),
+            Action(Color(1), Move::Stay),
+            // non-gathered
+            Action(Color(0), Move::Stay),
+            Action(Color(1), Move::ToHalf),
+            Action(Color(0), Move::Stay),
+            Action(Color(0), Move::ToOther),
+        ];
+        let algo = Algorithm::new(num_colors, &guards, &actions);
+        assert_eq!(algo.redundant_rules(), vec![0, 2, 3, 4]);
+
+        let simplified = algo.with_default_rule(6).unwrap();
+        assert_eq!(
+            simplified.as_code(),
+            "00s_01s_10s_11s_00d_01d_10d_11d__S0_S1_H1_S1_S0_H1_S1_O0"
+        );
+        assert_eq!(simplified.redundant_rules(), vec![0, 2, 3, 4, 6]);
+
+        let guards = guards_for_external_3_cols();
+        let algo = Algorithm::new(3, &guards, &[Action(Color(0), Move::Stay); 6]);
+        assert!(algo.redundant_rules().is_empty());
+        assert!(algo.with_default_rule(0).is_none());
+    }
+
+    #[test]
+    fn test_serde() {
+        let num_colors = 2;
+        let guards = guards_for_full_lights_2_cols();
+        let actions = [
+            Action(Color(0), Move::Stay),
+            Action(Color(1), Move::Stay),
+            Action(Color(0), Move::Stay),
+            Action(Color(1), Move::Stay),
+            Action(Color(0), Move::ToHalf),
+            Action(Color(1), Move::ToHalf),
+            Action(Color(0), Move::ToOther),
+            Action(Color(1), Move::Stay),
+        ];
+        let algo = Algorithm::new(num_colors, &guards, &actions);
+
+        let json = serde_json::to_string(&algo).unwrap();
+        let parsed: Algorithm = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed, algo);
+
+        let json = serde_json::to_string(&Guard::Full(Color(0), Color(1), Distance::Near)).unwrap();
+        assert_eq!(json, r#"{"Full":[0,1,"Near"]}"#);
+        let json = serde_json::to_string(&Action(Color(2), Move::ToHalf)).unwrap();
+        assert_eq!(json, r#"[2,"ToHalf"]"#);
+
+        let invalid = r#"{"num_colors":1,"guards":[{"LExternal":0}],"actions":[[1,"Stay"]]}"#;
+        assert!(serde_json::from_str::<Algorithm>(invalid).is_err());
+        let invalid = r#"{"num_colors":1,"guards":[{"LExternal":0}],"actions":[]}"#;
+        assert!(serde_json::from_str::<Algorithm>(invalid).is_err());
+    }
+
     #[test]
     fn test_action() {
         let a1 = Action(Color(1), Move::Stay);
@@ -459,6 +1094,32 @@ pub mod tests {
             algo.as_code(),
             "00s_01s_10s_11s_00d_01d_10d_11d__S0_S1_S0_S1_H0_H1_O0_S1"
         );
+        let rule = algo.rules().nth(6).unwrap();
+        assert_eq!(rule, Rule(guards[6], actions[6]));
+        assert_eq!(rule.action().movement(), Move::ToOther);
+        assert!(rule.fires_on(Color(1), Color(0), false));
+        assert!(!rule.fires_on(Color(1), Color(0), true));
+        assert_eq!(
+            algo.rules()
+                .position(|r| r.fires_on(Color(1), Color(0), false)),
+            algo.rule_for(Color(1), Color(0), false)
+        );
+
+        let error = |guards: &[Guard], actions: &[Action]| {
+            Algorithm::try_new(num_colors, guards, actions)
+                .unwrap_err()
+                .to_string()
+        };
+        assert!(error(&guards, &actions[1..]).contains("8 guards, 7 actions"));
+        let mut invalid = actions;
+        invalid[5] = Action(Color(2), Move::Stay);
+        assert!(error(&guards, &invalid).contains("color 2 out of range"));
+        let mut mixed = guards.clone();
+        mixed[0] = Guard::LFull(Color(0), Color(0));
+        assert!(error(&mixed, &actions).contains("different models"));
+        mixed[0] = Guard::Full(Color(0), Color(3), Distance::Same);
+        assert!(error(&mixed, &actions).contains("guard 03s"));
+        assert!(error(&[], &[]).contains("without rules"));
     }
 
     #[test]
@@ -487,4 +1148,60 @@ pub mod tests {
 
         assert_eq!(algo.unwrap(), algo_ref);
     }
+
+    #[test]
+    fn test_guard_parse() {
+        use crate::model::Model;
+        use crate::ModelKind::*;
+
+        for (kind, class_l) in itertools::iproduct!([External, Internal, Full], [false, true]) {
+            for guard in crate::generator::guards_for_model(Model::from((kind, 3, class_l))) {
+                let code = guard.as_code();
+                assert_eq!(
+                    Guard::try_parse(kind, class_l, &code).unwrap(),
+                    guard,
+                    "{kind:?} {code}"
+                );
+            }
+        }
+        // the distance of the external and internal guards follows the color
+        assert_eq!(
+            Guard::try_parse(External, false, "2s").unwrap(),
+            Guard::External(Color(2), Distance::Same)
+        );
+        assert!(Guard::try_parse(Internal, false, "1").is_err());
+    }
+
+    #[test]
+    fn test_code_v2() {
+        let code = "E3L:0_1_2__S2_H0_O1";
+        let algo = Algorithm::try_parse_v2(code).unwrap();
+        assert_eq!(algo.as_code_v2(), code);
+        assert_eq!(algo.model().to_string(), "E3L");
+        assert_eq!(
+            Algorithm::try_parse(crate::ModelKind::External, 3, true, code).unwrap(),
+            algo
+        );
+        assert!(Algorithm::try_parse(crate::ModelKind::External, 3, false, code).is_err());
+        assert!(Algorithm::try_parse_v2("0_1_2__S2_H0_O1").is_err());
+        assert!(Algorithm::try_parse_v2("X3:0_1_2__S2_H0_O1").is_err());
+    }
+
+    #[test]
+    fn test_pretty_table() {
+        let algo = Algorithm::try_parse_v2("E3L:0_1_2__S2_H0_O1").unwrap();
+        assert_eq!(
+            algo.to_pretty_table(),
+            "when I see 0 → set color 2 and stay\n\
+             when I see 1 → set color 0 and move to half\n\
+             when I see 2 → set color 1 and move to other"
+        );
+        let algo = Algorithm::try_parse_v2("I2:0s_1s_0d_1d__S0_S1_H1_O0").unwrap();
+        let table = algo.to_pretty_table();
+        assert_eq!(
+            table.lines().nth(2),
+            Some("when I am 0 at distance > 0 → set color 1 and move to half")
+        );
+        assert!(table.lines().all(|l| l.find('→') == table.find('→')));
+    }
 }
@@ -0,0 +1,197 @@
+//! Matrix of the outcomes of algorithms (typically those passing a run) under several schedulers and the four
+//! variants of the model (rigid or non-rigid moves, self-stabilizing or quasi self-stabilizing), rendered as a
+//! Markdown or LaTeX table (see the `analyze` subcommand).
+
+use anyhow::Result;
+use std::io::Write;
+
+use crate::algorithm::Algorithm;
+use crate::common::Scheduler;
+use crate::promela::ModelRunOptions;
+use crate::runner::{SpinOutcome, VerificationBackend};
+
+/// format of the table of a [Matrix].
+#[derive(clap::ValueEnum, strum::Display, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[strum(serialize_all = "lowercase")]
+pub enum TableFormat {
+    #[default]
+    Markdown,
+    Latex,
+}
+
+/// outcomes of algorithms under each scheduler and variant.
+#[derive(Clone, Debug)]
+pub struct Matrix {
+    pub schedulers: Vec<Scheduler>,
+    /// options of the verification, for the first scheduler (the variants are those of [ModelRunOptions::variants])
+    pub options: ModelRunOptions,
+    /// each algorithm with its outcomes, by scheduler then by variant
+    pub rows: Vec<(Algorithm, Vec<SpinOutcome>)>,
+}
+
+impl Matrix {
+    /// variants of the columns of each scheduler.
+    pub fn variants(&self) -> [ModelRunOptions; 4] {
+        self.options.variants()
+    }
+
+    /// writes the matrix as a table, one row per algorithm and one column per scheduler and variant.
+    pub fn write(&self, output: &mut dyn Write, format: TableFormat) -> Result<()> {
+        match format {
+            TableFormat::Markdown => self.write_markdown(output)?,
+            TableFormat::Latex => self.write_latex(output)?,
+        }
+        output.flush()?;
+        Ok(())
+    }
+
+    fn write_markdown(&self, output: &mut dyn Write) -> std::io::Result<()> {
+        let columns = self
+            .schedulers
+            .iter()
+            .flat_map(|s| self.variants().map(|v| format!("{s} {}", v.variant_name())))
+            .collect::<Vec<_>>();
+        writeln!(output, "| algorithm | {} |", columns.join(" | "))?;
+        writeln!(output, "| --- |{}", " :-: |".repeat(columns.len()))?;
+        for (algo, outcomes) in self.rows.iter() {
+            let cells = outcomes
+                .iter()
+                .map(|outcome| match outcome {
+                    SpinOutcome::Pass => "✓",
+                    SpinOutcome::Fail => "✗",
+                    SpinOutcome::OutOfMemory => "OOM",
+                    SpinOutcome::SearchIncomplete => "?",
+                })
+                .collect::<Vec<_>>();
+            writeln!(output, "| `{}` | {} |", algo.as_code(), cells.join(" | "))?;
+        }
+        Ok(())
+    }
+
+    fn write_latex(&self, output: &mut dyn Write) -> std::io::Result<()> {
+        let n = self.schedulers.len();
+        writeln!(output, "\\begin{{tabular}}{{l|{}}}", "cccc|".repeat(n))?;
+        let schedulers = self
+            .schedulers
+            .iter()
+            .map(|s| {
+                format!(
+                    "\\multicolumn{{4}}{{c|}}{{{}}}",
+                    s.to_string().replace('_', "\\_")
+                )
+            })
+            .collect::<Vec<_>>();
+        writeln!(output, "algorithm & {} \\\\", schedulers.join(" & "))?;
+        let variants = self.variants().map(|v| v.variant_name()).join(" & ");
+        writeln!(output, " & {} \\\\ \\hline", vec![variants; n].join(" & "))?;
+        for (algo, outcomes) in self.rows.iter() {
+            let cells = outcomes
+                .iter()
+                .map(|outcome| match outcome {
+                    SpinOutcome::Pass => "\\checkmark",
+                    SpinOutcome::Fail => "$\\times$",
+                    SpinOutcome::OutOfMemory => "OOM",
+                    SpinOutcome::SearchIncomplete => "?",
+                })
+                .collect::<Vec<_>>();
+            writeln!(
+                output,
+                "\\texttt{{{}}} & {} \\\\",
+                algo.as_code().replace('_', "\\_"),
+                cells.join(" & ")
+            )?;
+        }
+        writeln!(output, "\\end{{tabular}}")
+    }
+}
+
+/// verifies each algorithm under each scheduler and variant of the options (see [ModelRunOptions::variants]),
+/// calling `on_row` with each algorithm once verified; stops at the first error.
+pub fn analyze(
+    backend: &dyn VerificationBackend,
+    algos: Vec<Algorithm>,
+    schedulers: &[Scheduler],
+    options: ModelRunOptions,
+    on_row: &mut dyn FnMut(&Algorithm),
+) -> Result<Matrix> {
+    let mut rows = Vec::with_capacity(algos.len());
+    for algo in algos {
+        let mut outcomes = Vec::with_capacity(schedulers.len() * 4);
+        for &scheduler in schedulers {
+            let options = ModelRunOptions {
+                scheduler,
+                ..options
+            };
+            for variant in options.variants() {
+                outcomes.push(backend.verify(&algo, &variant)?);
+            }
+        }
+        on_row(&algo);
+        rows.push((algo, outcomes));
+    }
+    Ok(Matrix {
+        schedulers: schedulers.to_vec(),
+        options,
+        rows,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// passes with rigid moves under SSYNC only.
+    struct RigidSsync;
+
+    impl VerificationBackend for RigidSsync {
+        fn verify(&self, _: &Algorithm, options: &ModelRunOptions) -> Result<SpinOutcome> {
+            Ok(match options.scheduler {
+                Scheduler::SSYNC if options.rigid => SpinOutcome::Pass,
+                Scheduler::SSYNC if options.quasi_ss => SpinOutcome::SearchIncomplete,
+                _ => SpinOutcome::Fail,
+            })
+        }
+    }
+
+    #[test]
+    fn test_analyze() {
+        let algos = ["E3L:0_1_2__S2_H0_O1", "E3L:0_1_2__S1_H2_O0"]
+            .map(|code| Algorithm::try_parse_v2(code).unwrap())
+            .to_vec();
+        let options = ModelRunOptions::new(Scheduler::SSYNC);
+        let mut n_rows = 0;
+        let matrix = analyze(
+            &RigidSsync,
+            algos,
+            &[Scheduler::SSYNC, Scheduler::ASYNC_LC_Strict],
+            options,
+            &mut |_| n_rows += 1,
+        )
+        .unwrap();
+        assert_eq!(n_rows, 2);
+        assert_eq!(matrix.rows[1].1.len(), 8);
+
+        let mut markdown = Vec::new();
+        matrix.write(&mut markdown, TableFormat::Markdown).unwrap();
+        let markdown = String::from_utf8(markdown).unwrap();
+        let lines = markdown.lines().collect::<Vec<_>>();
+        assert_eq!(lines.len(), 4);
+        assert!(lines[0].starts_with(
+            "| algorithm | SSYNC non-rigid/ss | SSYNC non-rigid/qss | SSYNC rigid/ss | SSYNC rigid/qss | ASYNC_LC_Strict"
+        ));
+        assert_eq!(
+            lines[2],
+            "| `0_1_2__S2_H0_O1` | ✗ | ? | ✓ | ✓ | ✗ | ✗ | ✗ | ✗ |"
+        );
+
+        let mut latex = Vec::new();
+        matrix.write(&mut latex, TableFormat::Latex).unwrap();
+        let latex = String::from_utf8(latex).unwrap();
+        assert!(latex.starts_with("\\begin{tabular}{l|cccc|cccc|}\n"));
+        assert!(latex.contains("\\multicolumn{4}{c|}{ASYNC\\_LC\\_Strict}"));
+        assert!(latex.contains(
+            "\\texttt{0\\_1\\_2\\_\\_S1\\_H2\\_O0} & $\\times$ & ? & \\checkmark & \\checkmark & $\\times$"
+        ));
+        assert!(latex.ends_with("\\end{tabular}\n"));
+    }
+}
@@ -0,0 +1,232 @@
+//! Benchmark of the verification on the host (`synth-lights bench`), to choose the number of threads, the working
+//! directory and the memory limits of the runs.
+//!
+//! The workload is the verifications of the reference corpus (see [selftest]), repeated a few times; it is run once
+//! per kind of working directory and number of threads, each thread verifying in its own enclosure as in a parallel
+//! synthesis, and the throughput of each configuration is reported in verifications per minute.
+
+use anyhow::Result;
+use std::io::Write;
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use crate::algorithm::Algorithm;
+use crate::promela::ModelRunOptions;
+use crate::runner::{self, VerificationBackend, Workdir};
+use crate::{selftest, throttle};
+
+/// interval between two samples of the free memory of the host during a measure.
+const MEMORY_POLL_INTERVAL: Duration = Duration::from_millis(200);
+
+/// kind of root working directory (see [runner::create_root_workdir], [runner::create_shm_workdir] and
+/// [runner::create_temp_workdir]).
+#[derive(clap::ValueEnum, strum::Display, Clone, Copy, Debug, PartialEq, Eq)]
+#[strum(serialize_all = "lowercase")]
+pub enum BenchWorkdir {
+    /// ramdisk mounted for the run (needs sudo)
+    Ramdisk,
+    /// directory in /dev/shm (or $XDG_RUNTIME_DIR)
+    Shm,
+    /// temporary directory of the system (usually disk-backed)
+    Temp,
+}
+
+impl BenchWorkdir {
+    pub fn create(&self) -> Result<Workdir> {
+        match self {
+            BenchWorkdir::Ramdisk => runner::create_root_workdir(None),
+            BenchWorkdir::Shm => runner::create_shm_workdir(),
+            BenchWorkdir::Temp => runner::create_temp_workdir(),
+        }
+    }
+}
+
+/// throughput of the workload in one configuration.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Measure {
+    pub workdir: BenchWorkdir,
+    pub threads: usize,
+    pub n_verifications: usize,
+    pub n_errors: usize,
+    pub duration: Duration,
+    /// lowest free memory of the host during the measure, in MB (if known)
+    pub min_free_mb: Option<usize>,
+}
+
+impl Measure {
+    /// verifications (without errors) per minute.
+    pub fn per_minute(&self) -> f64 {
+        let ok = (self.n_verifications - self.n_errors) as f64;
+        ok * 60.0 / self.duration.as_secs_f64().max(f64::EPSILON)
+    }
+}
+
+/// verifications of the reference corpus, repeated `rounds` times.
+pub fn workload(rounds: usize) -> Result<Vec<(Algorithm, ModelRunOptions)>> {
+    let once = selftest::expectations(selftest::select(None))
+        .map(|(reference, expected)| Ok((reference.algorithm()?, expected.options())))
+        .collect::<Result<Vec<_>>>()?;
+    Ok(std::iter::repeat_n(once, rounds).flatten().collect())
+}
+
+/// default numbers of threads: powers of two up to the number of cores, and the number of cores.
+pub fn default_threads(n_cpus: usize) -> Vec<usize> {
+    let mut threads = std::iter::successors(Some(1), |&n| Some(n * 2))
+        .take_while(|&n| n < n_cpus)
+        .collect::<Vec<_>>();
+    threads.push(n_cpus.max(1));
+    threads
+}
+
+/// runs the workload on `threads` threads, each with its own enclosure in the root working directory and its own
+/// backend (given the enclosure); the verifications are dealt to the threads as they become free.
+/// Errors of the verifications are counted; errors of the enclosures stop the measure.
+pub fn measure<B: VerificationBackend>(
+    workdir: BenchWorkdir,
+    root: &Path,
+    threads: usize,
+    workload: &[(Algorithm, ModelRunOptions)],
+    backend: &(dyn Fn(&Path) -> B + Sync),
+) -> Result<Measure> {
+    let next = AtomicUsize::new(0);
+    let n_errors = AtomicUsize::new(0);
+    let done = AtomicBool::new(false);
+    let min_free_mb = Mutex::new(throttle::available_memory_mb());
+    let start = Instant::now();
+    let outcome = std::thread::scope(|scope| {
+        scope.spawn(|| {
+            while !done.load(Ordering::Relaxed) {
+                std::thread::sleep(MEMORY_POLL_INTERVAL);
+                if let Some(free) = throttle::available_memory_mb() {
+                    let mut min = min_free_mb.lock().unwrap();
+                    *min = Some(min.map_or(free, |min| min.min(free)));
+                }
+            }
+        });
+        let workers = (0..threads.max(1))
+            .map(|_| {
+                scope.spawn(|| -> Result<()> {
+                    let enclosure = runner::create_enclosure(root)?;
+                    let backend = backend(&enclosure);
+                    loop {
+                        let i = next.fetch_add(1, Ordering::Relaxed);
+                        let Some((algo, options)) = workload.get(i) else {
+                            break;
+                        };
+                        if let Err(e) = backend.verify(algo, options) {
+                            log::debug!("bench: error verifying {}: {e:#}", algo.as_code());
+                            n_errors.fetch_add(1, Ordering::Relaxed);
+                        }
+                    }
+                    Ok(())
+                })
+            })
+            .collect::<Vec<_>>();
+        let outcome = workers
+            .into_iter()
+            .try_for_each(|worker| worker.join().expect("bench thread panicked"));
+        done.store(true, Ordering::Relaxed);
+        outcome.map(|_| start.elapsed())
+    });
+    let duration = outcome?;
+    Ok(Measure {
+        workdir,
+        threads,
+        n_verifications: workload.len(),
+        n_errors: n_errors.into_inner(),
+        duration,
+        min_free_mb: min_free_mb.into_inner().unwrap(),
+    })
+}
+
+/// writes the measures as a table, followed by the fastest configuration.
+pub fn write_measures(output: &mut dyn Write, measures: &[Measure]) -> Result<()> {
+    writeln!(
+        output,
+        "| workdir | threads | verifications | errors | seconds | verifications/min | min free MB |"
+    )?;
+    writeln!(
+        output,
+        "| ------- | ------- | ------------- | ------ | ------- | ----------------- | ----------- |"
+    )?;
+    for m in measures {
+        writeln!(
+            output,
+            "| {} | {} | {} | {} | {:.1} | {:.0} | {} |",
+            m.workdir,
+            m.threads,
+            m.n_verifications,
+            m.n_errors,
+            m.duration.as_secs_f64(),
+            m.per_minute(),
+            m.min_free_mb.map_or("?".to_string(), |mb| mb.to_string())
+        )?;
+    }
+    let fastest = measures
+        .iter()
+        .filter(|m| m.n_errors < m.n_verifications)
+        .max_by(|a, b| a.per_minute().total_cmp(&b.per_minute()));
+    if let Some(m) = fastest {
+        writeln!(
+            output,
+            "Fastest: {} working directory with {} threads ({:.0} verifications/min)",
+            m.workdir,
+            m.threads,
+            m.per_minute()
+        )?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::checker::NativeBackend;
+
+    #[test]
+    fn test_default_threads() {
+        assert_eq!(default_threads(1), [1]);
+        assert_eq!(default_threads(6), [1, 2, 4, 6]);
+        assert_eq!(default_threads(8), [1, 2, 4, 8]);
+    }
+
+    #[test]
+    fn test_measure() {
+        let workload = workload(2)
+            .unwrap()
+            .into_iter()
+            .filter(|(_, options)| crate::checker::supports_options(*options))
+            .collect::<Vec<_>>();
+        let workdir = BenchWorkdir::Temp.create().unwrap();
+        let measures = [1, 3]
+            .map(|threads| {
+                measure(
+                    BenchWorkdir::Temp,
+                    workdir.path(),
+                    threads,
+                    &workload,
+                    &|_| NativeBackend,
+                )
+                .unwrap()
+            })
+            .to_vec();
+        runner::close_workdir(workdir).unwrap();
+        for m in measures.iter() {
+            assert_eq!(m.n_verifications, workload.len());
+            assert_eq!(m.n_errors, 0);
+            assert!(m.per_minute() > 0.0);
+        }
+
+        let mut table = Vec::new();
+        write_measures(&mut table, &measures).unwrap();
+        let table = String::from_utf8(table).unwrap();
+        assert_eq!(table.lines().count(), 2 + measures.len() + 1);
+        assert!(table
+            .lines()
+            .last()
+            .unwrap()
+            .starts_with("Fastest: temp working directory"));
+    }
+}
@@ -0,0 +1,304 @@
+//! Native explicit-state model checker for the schedulers whose activations are complete Look-Compute-Move cycles
+//! (centralized, FSYNC, SSYNC), selected with `--backend native` to verify algorithms without spin and clang.
+//!
+//! Under these schedulers, each activation of the Promela model (`Schedulers.pml`) is atomic, so the state of the model
+//! between activations is the configuration of the system ([Config]) together with the fairness counters of the
+//! scheduler. The checker explores all the states reachable from the initial configurations with the steps of the
+//! [simulator], and reports a failure iff some execution cycles forever without gathering, which violates the
+//! property checked by spin (`<> [] (position == SAME)`, the scheduler stopping once the robots are gathered).
+//! The ASYNC schedulers interleave the phases of the robots and are left to spin.
+//!
+//! The fairness limits of the scheduler activate both robots infinitely often in every infinite execution, so the
+//! outcome is the same under weak fairness and fair activation ([Fairness]), which the checker ignores; verifications
+//! without fairness are left to spin.
+
+use anyhow::{bail, Result};
+use std::cell::Cell;
+use std::collections::HashMap;
+
+use crate::algorithm::Algorithm;
+use crate::common::Scheduler;
+use crate::promela::{Fairness, ModelRunOptions, DEFAULT_DELTA_STEPS};
+use crate::runner::{SpinOutcome, VerificationBackend};
+use crate::simulator::{self, Config, Counterexample, Round};
+
+/// checks if the native model checker handles the scheduler.
+pub fn supports(scheduler: Scheduler) -> bool {
+    matches!(
+        scheduler,
+        Scheduler::Centralized | Scheduler::FSYNC | Scheduler::SSYNC
+    )
+}
+
+/// checks if the native model checker handles the scheduler, the moves and the fairness of the options: non-rigid
+/// moves are only modeled with their original granularity ([DEFAULT_DELTA_STEPS]), and the fairness limits of the
+/// scheduler rule out verifications without fairness ([Fairness::None]).
+pub fn supports_options(options: ModelRunOptions) -> bool {
+    supports(options.scheduler)
+        && (options.rigid || options.delta_steps == DEFAULT_DELTA_STEPS)
+        && options.fairness != Fairness::None
+}
+
+/// maximal number of consecutive activations of the same robot
+/// (`FAIR_LIMIT` in `Types.pml`, with one phase per cycle).
+fn fairness_limit(num_colors: u8) -> usize {
+    1 + 2 * num_colors as usize
+}
+
+/// node of the state space: configuration, and consecutive activations of robots A and B.
+type Node = (Config, [usize; 2]);
+
+/// rounds of the scheduler, or an error if the native model checker does not support it.
+fn scheduler_rounds(scheduler: Scheduler) -> Result<&'static [Round]> {
+    Ok(match scheduler {
+        Scheduler::Centralized => &[Round::A, Round::B],
+        Scheduler::FSYNC => &[Round::Both],
+        Scheduler::SSYNC => &[Round::Both, Round::A, Round::B],
+        scheduler => bail!("scheduler {scheduler} is not supported by the native model checker"),
+    })
+}
+
+/// successors of a node with the rounds of the scheduler, within the fairness limit;
+/// the configurations where no rule of the algorithm applies are recorded in `blocked`.
+fn successors(
+    algo: &Algorithm,
+    rounds: &[Round],
+    (config, [a, b]): Node,
+    blocked: &Cell<Option<Config>>,
+) -> Vec<(Round, Node)> {
+    let limit = fairness_limit(algo.num_colors());
+    rounds
+        .iter()
+        .filter_map(|&round| {
+            let counts = match round {
+                Round::Both => [0, 0],
+                Round::A if a < limit => [a + 1, 0],
+                Round::B if b < limit => [0, b + 1],
+                _ => return None,
+            };
+            match simulator::step(algo, config, round) {
+                Some(next) => Some((round, (next, counts))),
+                None => {
+                    blocked.set(Some(config));
+                    None
+                }
+            }
+        })
+        .collect()
+}
+
+/// initial nodes of the state space.
+fn roots(algo: &Algorithm, options: ModelRunOptions) -> impl Iterator<Item = Node> {
+    simulator::initial_configs(algo.num_colors(), options)
+        .into_iter()
+        .map(|config| (config, [0, 0]))
+}
+
+/// execution of the algorithm that never gathers, if any.
+/// Fails if the scheduler is not supported, or if no rule of the algorithm applies in a reachable configuration.
+pub fn counterexample(
+    algo: &Algorithm,
+    options: ModelRunOptions,
+) -> Result<Option<Counterexample>> {
+    let rounds = scheduler_rounds(options.scheduler)?;
+    if options.fairness == Fairness::None {
+        bail!("verifications without fairness are not supported by the native model checker");
+    }
+    if !supports_options(options) {
+        bail!(
+            "non-rigid moves with {} steps are not supported by the native model checker",
+            options.delta_steps
+        );
+    }
+    let blocked = Cell::new(None);
+    let counterexample = simulator::find_cycle(roots(algo, options), |node| {
+        successors(algo, rounds, node, &blocked)
+    });
+    match (counterexample, blocked.get()) {
+        (Some(counterexample), _) => Ok(Some(counterexample)),
+        (None, Some(config)) => bail!(
+            "no rule of algorithm {} applies in configuration {config}",
+            algo.as_code()
+        ),
+        (None, None) => Ok(None),
+    }
+}
+
+/// worst-case gathering time of the algorithm: maximal number of epochs before the robots are gathered, over all
+/// the executions from all the initial configurations, or `None` if some execution never gathers. An epoch is
+/// a shortest sequence of rounds in which both robots are activated (a single round under FSYNC), so that the
+/// time does not depend on the fairness limit of the model; the epoch during which the robots gather is counted.
+/// Fails as [counterexample].
+pub fn gathering_time(algo: &Algorithm, options: ModelRunOptions) -> Result<Option<usize>> {
+    if counterexample(algo, options)?.is_some() {
+        return Ok(None);
+    }
+    // node, and robots activated since the start of the current epoch (bit 0 for A, bit 1 for B)
+    type State = (Node, u8);
+
+    let rounds = scheduler_rounds(options.scheduler)?;
+    let blocked = Cell::new(None);
+    // next states, with the number of epochs completed by the round (gathering completes the epoch)
+    let next_states = |(node, activated): State| {
+        successors(algo, rounds, node, &blocked)
+            .into_iter()
+            .map(move |(round, next)| {
+                let activated = round
+                    .robots()
+                    .iter()
+                    .fold(activated, |bits, robot| bits | 1 << robot);
+                match (next.0.is_gathered(), activated) {
+                    (true, _) => (None, 1),
+                    (false, 0b11) => (Some((next, 0)), 1),
+                    (false, _) => (Some((next, activated)), 0),
+                }
+            })
+            .collect::<Vec<_>>()
+    };
+    // the states form an acyclic graph: longest paths by depth-first search, in post-order
+    let mut remaining: HashMap<State, usize> = HashMap::new();
+    let mut worst = 0;
+    for root in roots(algo, options) {
+        if root.0.is_gathered() {
+            continue;
+        }
+        let mut stack = vec![((root, 0), false)];
+        while let Some((state, expanded)) = stack.pop() {
+            if remaining.contains_key(&state) {
+                continue;
+            }
+            let next = next_states(state);
+            if expanded {
+                let time = next
+                    .iter()
+                    .map(|(next, epochs)| epochs + next.map_or(0, |next| remaining[&next]))
+                    .max()
+                    .unwrap_or(0);
+                remaining.insert(state, time);
+            } else {
+                stack.push((state, true));
+                stack.extend(
+                    next.into_iter()
+                        .filter_map(|(next, _)| next)
+                        .map(|s| (s, false)),
+                );
+            }
+        }
+        worst = worst.max(remaining[&(root, 0)]);
+    }
+    Ok(Some(worst))
+}
+
+/// verifies the algorithm (the outcome is never [SpinOutcome::SearchIncomplete], the search being exhaustive).
+pub fn verify(algo: &Algorithm, options: ModelRunOptions) -> Result<SpinOutcome> {
+    Ok(match counterexample(algo, options)? {
+        Some(_) => SpinOutcome::Fail,
+        None => SpinOutcome::Pass,
+    })
+}
+
+/// native model checker as a verification backend (for the supported schedulers only).
+#[derive(Clone, Copy, Debug, Default)]
+pub struct NativeBackend;
+
+impl VerificationBackend for NativeBackend {
+    fn verify(&self, algo: &Algorithm, options: &ModelRunOptions) -> Result<SpinOutcome> {
+        verify(algo, *options)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::algorithm::{Action, Guard};
+    use crate::common::{Color, Move};
+    use crate::generator::tests::*;
+
+    fn options(scheduler: Scheduler, rigid: bool, quasi_ss: bool) -> ModelRunOptions {
+        ModelRunOptions {
+            rigid,
+            quasi_ss,
+            ..ModelRunOptions::new(scheduler)
+        }
+    }
+
+    #[test]
+    fn test_verify() {
+        use Move::*;
+        // outcomes verified with spin (see the tests of the crate)
+        let full_2 = |moves: [Move; 4]| {
+            let mut actions = vec![Action(Color(0), Stay); 4];
+            actions.extend(moves.map(|m| Action(Color(0), m)));
+            Algorithm::new(2, &guards_for_full_lights_2_cols(), &actions)
+        };
+        let centralized = options(Scheduler::Centralized, false, false);
+        assert_eq!(
+            verify(&full_2([ToHalf; 4]), centralized).unwrap(),
+            SpinOutcome::Fail
+        );
+        assert_eq!(
+            verify(&full_2([ToOther; 4]), centralized).unwrap(),
+            SpinOutcome::Pass
+        );
+
+        let guards = (0..4).map(Color).map(Guard::LExternal).collect::<Vec<_>>();
+        let actions =
+            [(1, ToHalf), (2, Stay), (3, ToOther), (0, Stay)].map(|(c, m)| Action(Color(c), m));
+        let algo = Algorithm::new(4, &guards, &actions);
+        let outcome =
+            |rigid, quasi_ss| verify(&algo, options(Scheduler::SSYNC, rigid, quasi_ss)).unwrap();
+        assert_eq!(outcome(true, true), SpinOutcome::Pass);
+        assert_eq!(outcome(true, false), SpinOutcome::Fail);
+        assert_eq!(outcome(false, false), SpinOutcome::Fail);
+
+        assert_eq!(
+            gathering_time(&algo, options(Scheduler::SSYNC, true, true)).unwrap(),
+            Some(5)
+        );
+        assert_eq!(
+            gathering_time(&algo, options(Scheduler::SSYNC, true, false)).unwrap(),
+            None
+        );
+        // one of the robots moves to the other
+        assert_eq!(
+            gathering_time(&full_2([ToOther; 4]), centralized).unwrap(),
+            Some(1)
+        );
+        // the robots meet half-way (after moving closer from afar, with non-rigid moves)
+        let fsync = |rigid| options(Scheduler::FSYNC, rigid, false);
+        assert_eq!(
+            gathering_time(&full_2([ToHalf; 4]), fsync(true)).unwrap(),
+            Some(1)
+        );
+        assert_eq!(
+            gathering_time(&full_2([ToHalf; 4]), fsync(false)).unwrap(),
+            Some(2)
+        );
+
+        assert!(supports(Scheduler::SSYNC) && !supports(Scheduler::ASYNC));
+        let coarse = ModelRunOptions {
+            delta_steps: 3,
+            ..options(Scheduler::SSYNC, false, false)
+        };
+        assert!(!supports_options(coarse));
+        assert!(supports_options(ModelRunOptions {
+            rigid: true,
+            ..coarse
+        }));
+        assert!(counterexample(&algo, coarse).is_err());
+        let unfair = ModelRunOptions {
+            fairness: Fairness::None,
+            ..options(Scheduler::SSYNC, true, true)
+        };
+        assert!(!supports_options(unfair));
+        assert!(NativeBackend.verify(&algo, &unfair).is_err());
+        assert!(gathering_time(&algo, unfair).is_err());
+        assert!(verify(&algo, options(Scheduler::ASYNC, true, true)).is_err());
+        assert_eq!(
+            NativeBackend
+                .verify(&algo, &options(Scheduler::SSYNC, true, true))
+                .unwrap(),
+            SpinOutcome::Pass
+        );
+    }
+}
@@ -0,0 +1,159 @@
+//! Structural classification of algorithms, typically those passing the verification.
+//!
+//! Each algorithm is tagged with the structural features it has (see [Feature]),
+//! and algorithms with the same features form a class.
+
+use std::collections::{BTreeMap, BTreeSet};
+use strum::{Display, EnumIter};
+
+use crate::algorithm::{Algorithm, Rule};
+use crate::common::{Color, IntoEnumIterator, Move};
+use crate::ModelKind;
+
+/// structural feature of an algorithm.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Display, EnumIter)]
+#[strum(serialize_all = "kebab-case")]
+pub enum Feature {
+    /// moves to the half-way point only when both robots have the same color (full lights only)
+    HalfOnlySameColors,
+    /// the color changes form a single cycle through all colors
+    ColorCycle,
+    /// the algorithm is unchanged when swapping colors 0 and 1 (renaming the colors of both robots, unlike
+    /// [Algorithm::is_swap_symmetric] which exchanges the colors observed by a robot)
+    ColorSwapInvariant,
+    /// a robot retains its color iff the other robot has a different color (Viglietta's rule, full lights only)
+    RetainRule,
+}
+
+impl Feature {
+    /// checks whether the algorithm has the feature.
+    pub fn holds(&self, algo: &Algorithm) -> bool {
+        match self {
+            Feature::HalfOnlySameColors => {
+                algo.model_kind() == ModelKind::Full
+                    && algo.rules().any(|r| r.action().movement() == Move::ToHalf)
+                    && algo
+                        .rules()
+                        .filter(|r| r.action().movement() == Move::ToHalf)
+                        .all(|r| r.guard().same_colors())
+            }
+            Feature::ColorCycle => is_color_cycle(algo),
+            Feature::ColorSwapInvariant => {
+                let n = algo.num_colors();
+                n >= 2 && {
+                    let mut perm = Color::iter_ncols(n).collect::<Vec<_>>();
+                    perm.swap(0, 1);
+                    algo.permute_colors(&perm) == *algo
+                }
+            }
+            Feature::RetainRule => {
+                algo.model_kind() == ModelKind::Full
+                    && algo.retains_color_iif_other_color_different()
+            }
+        }
+    }
+}
+
+/// the features of the algorithm.
+pub fn features(algo: &Algorithm) -> BTreeSet<Feature> {
+    Feature::iter().filter(|f| f.holds(algo)).collect()
+}
+
+/// checks whether the color changes of the algorithm (edges from the color of a robot to its new color,
+/// from all colors if the robot cannot see its own color) form a single cycle through all colors.
+fn is_color_cycle(algo: &Algorithm) -> bool {
+    let n = algo.num_colors();
+    let next = algo.color_transitions();
+    if n < 2 || next.len() != n as usize || next.values().any(|to| to.len() != 1) {
+        return false;
+    }
+    // follow the unique successors from color 0: all colors must be visited before returning
+    let mut current = Color(0);
+    for step in 1..=n {
+        current = *next[&current].first().expect("one successor");
+        if current == Color(0) {
+            return step == n;
+        }
+    }
+    false
+}
+
+/// heuristic promise of the algorithm, compared lexicographically (the higher, the more likely to pass the
+/// verification): whether it follows the retain rule ([Feature::RetainRule]), then the number of distinct moves of
+/// its rules, then the balance of the moves (number of rules minus the gap between the most and least used moves).
+pub fn promise(algo: &Algorithm) -> (bool, usize, usize) {
+    let mut counts: BTreeMap<Move, usize> = Move::iter().map(|m| (m, 0)).collect();
+    for Rule(_, action) in algo.rules() {
+        *counts.entry(action.movement()).or_default() += 1;
+    }
+    let n_rules = counts.values().sum::<usize>();
+    let distinct = counts.values().filter(|&&n| n > 0).count();
+    let max = counts.values().max().copied().unwrap_or(0);
+    let min = counts.values().min().copied().unwrap_or(0);
+    (
+        Feature::RetainRule.holds(algo),
+        distinct,
+        n_rules - (max - min),
+    )
+}
+
+/// groups the algorithms by their features; classes are ordered by decreasing size.
+pub fn classify(algos: &[(usize, Algorithm)]) -> Vec<(BTreeSet<Feature>, Vec<usize>)> {
+    let mut classes: BTreeMap<BTreeSet<Feature>, Vec<usize>> = BTreeMap::new();
+    for (i, algo) in algos {
+        classes.entry(features(algo)).or_default().push(*i);
+    }
+    let mut classes = classes.into_iter().collect::<Vec<_>>();
+    classes.sort_by_key(|(_, members)| std::cmp::Reverse(members.len()));
+    classes
+}
+
+/// names of the features, or "none".
+pub fn feature_names(features: &BTreeSet<Feature>) -> String {
+    if features.is_empty() {
+        "none".to_string()
+    } else {
+        features
+            .iter()
+            .map(|f| f.to_string())
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(model: ModelKind, n_colors: u8, class_l: bool, code: &str) -> Algorithm {
+        Algorithm::try_parse(model, n_colors, class_l, code).unwrap()
+    }
+
+    #[test]
+    fn test_features() {
+        // 0 -> 1 -> 2 -> 0
+        let cycle = parse(ModelKind::Internal, 3, true, "0_1_2__O1_H2_S0");
+        assert_eq!(features(&cycle), BTreeSet::from([Feature::ColorCycle]));
+        let no_cycle = parse(ModelKind::Internal, 3, true, "0_1_2__O1_H0_S2");
+        assert!(!Feature::ColorCycle.holds(&no_cycle));
+
+        // full lights, 2 colors: half only with same colors; change color iff same colors
+        let full = parse(ModelKind::Full, 2, true, "00_01_10_11__H1_O0_O1_H0");
+        assert_eq!(
+            features(&full),
+            BTreeSet::from([
+                Feature::HalfOnlySameColors,
+                Feature::ColorCycle,
+                Feature::ColorSwapInvariant,
+                Feature::RetainRule
+            ])
+        );
+        let classes = classify(&[(0, cycle.clone()), (1, no_cycle.clone()), (2, no_cycle)]);
+        assert_eq!(classes[0], (BTreeSet::new(), vec![1, 2]));
+        assert_eq!(feature_names(&classes[1].0), "color-cycle");
+
+        assert_eq!(promise(&full), (true, 2, 2));
+        assert_eq!(promise(&cycle), (false, 3, 3));
+        assert!(promise(&full) > promise(&cycle));
+    }
+}
@@ -0,0 +1,456 @@
+//! Runs of the synthesis on an HPC cluster (see the `cluster` subcommand): the viable algorithms are split into
+//! shards of consecutive indices (see [shards]), verified by the tasks of a job array submitted to the batch system
+//! (SLURM or PBS, from a template, see [render]), and the reports of the shards are merged into one (see [merge])
+//! once all the tasks are complete (see [wait]). A task killed by the batch system (walltime, out of memory, node
+//! failure) never records its exit code: the wait fails once the job array has left the queue (see [Job::is_alive])
+//! or after a timeout, with the list of the shards missing a status.
+//!
+//! The directory of a cluster run holds the job array script, the list of the shards ([SHARDS_FILE]), the id of the
+//! job array ([JOB_FILE]), and for each shard its report (`shard-<k>.txt`) and the exit code of its task
+//! (`shard-<k>.status`).
+
+use anyhow::{bail, Context, Result};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+use crate::merge::{self, ShardReport};
+use crate::report::{Report, Summary};
+
+/// file listing the shards of a cluster run, one `<k> <offset> <limit>` per line.
+pub const SHARDS_FILE: &str = "shards.txt";
+
+/// job array script of a cluster run.
+pub const SCRIPT_FILE: &str = "job.sh";
+
+/// batch system and id of the job array of a cluster run, as `<system> <id>`.
+pub const JOB_FILE: &str = "job.id";
+
+/// batch system of the cluster.
+#[derive(clap::ValueEnum, strum::Display, Clone, Copy, Debug, PartialEq, Eq)]
+#[strum(serialize_all = "lowercase")]
+pub enum BatchSystem {
+    /// SLURM (`sbatch`)
+    Slurm,
+    /// PBS Pro / OpenPBS (`qsub`)
+    Pbs,
+}
+
+impl BatchSystem {
+    /// command submitting a job script.
+    pub fn submit_command(&self) -> &'static str {
+        match self {
+            BatchSystem::Slurm => "sbatch",
+            BatchSystem::Pbs => "qsub",
+        }
+    }
+
+    /// command listing the tasks of a job still queued or running.
+    pub fn queue_command(&self) -> &'static str {
+        match self {
+            BatchSystem::Slurm => "squeue",
+            BatchSystem::Pbs => "qstat",
+        }
+    }
+
+    /// id of the job in the output of its submission (`Submitted batch job <id>` for SLURM, `<id>[].<server>` for
+    /// PBS).
+    pub fn job_id(&self, submitted: &str) -> Option<String> {
+        let id = match self {
+            BatchSystem::Slurm => submitted.split_whitespace().last()?.split(';').next()?,
+            BatchSystem::Pbs => submitted.split_whitespace().next()?,
+        };
+        let number = id.split(|c: char| !c.is_ascii_digit()).next()?;
+        (!number.is_empty()).then(|| id.to_string())
+    }
+
+    /// default template of the job array script (see [render]).
+    pub fn default_template(&self) -> &'static str {
+        match self {
+            BatchSystem::Slurm => {
+                "#!/bin/sh\n#SBATCH --job-name={name}\n#SBATCH --array=0-{last}\n#SBATCH --output={dir}/shard-%a.log\n\
+                SHARD=$SLURM_ARRAY_TASK_ID\n{command}\n"
+            }
+            BatchSystem::Pbs => {
+                "#!/bin/sh\n#PBS -N {name}\n#PBS -J 0-{last}\n#PBS -j oe\n#PBS -o {dir}\n\
+                SHARD=$PBS_ARRAY_INDEX\n{command}\n"
+            }
+        }
+    }
+}
+
+/// job array submitted to the batch system.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Job {
+    pub system: BatchSystem,
+    pub id: String,
+}
+
+impl Job {
+    /// saves the job in the directory of the cluster run (see [JOB_FILE]).
+    pub fn save(&self, dir: &Path) -> Result<()> {
+        std::fs::write(dir.join(JOB_FILE), format!("{} {}\n", self.system, self.id))
+            .context("failed to write the id of the job array")
+    }
+
+    /// reads the job of a cluster run from its directory, if saved (see [JOB_FILE]).
+    pub fn read(dir: &Path) -> Result<Option<Job>> {
+        let path = dir.join(JOB_FILE);
+        let Ok(text) = std::fs::read_to_string(&path) else {
+            return Ok(None);
+        };
+        let invalid = || anyhow::anyhow!("invalid job in {}: {}", path.display(), text.trim());
+        let (system, id) = text.trim().split_once(' ').ok_or_else(invalid)?;
+        let system =
+            <BatchSystem as clap::ValueEnum>::from_str(system, true).map_err(|_| invalid())?;
+        Ok(Some(Job {
+            system,
+            id: id.to_string(),
+        }))
+    }
+
+    /// checks whether tasks of the job are still queued or running, according to the batch system: the job is alive
+    /// if the query succeeds with a non-empty output (`squeue` lists nothing once the job is over, `qstat` fails).
+    pub fn is_alive(&self) -> Result<bool> {
+        let command = self.system.queue_command();
+        let args: &[&str] = match self.system {
+            BatchSystem::Slurm => &["-h", "-j", &self.id],
+            BatchSystem::Pbs => &[&self.id],
+        };
+        let output = duct::cmd(command, args)
+            .stderr_null()
+            .stdout_capture()
+            .unchecked()
+            .run()
+            .with_context(|| format!("failed to run {command}"))?;
+        Ok(output.status.success() && !String::from_utf8_lossy(&output.stdout).trim().is_empty())
+    }
+}
+
+/// consecutive indices of viable algorithms verified by one task of the job array.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Shard {
+    pub index: usize,
+    pub offset: usize,
+    pub limit: usize,
+}
+
+impl Shard {
+    /// report of the shard in the directory of the cluster run.
+    pub fn report(&self, dir: &Path) -> PathBuf {
+        dir.join(format!("shard-{}.txt", self.index))
+    }
+
+    /// exit code of the task of the shard, written when it is complete.
+    pub fn status(&self, dir: &Path) -> PathBuf {
+        dir.join(format!("shard-{}.status", self.index))
+    }
+}
+
+/// splits the `count` viable algorithms from index `offset` into at most `n_shards` shards of the same size (except
+/// the last one).
+pub fn shards(offset: usize, count: usize, n_shards: usize) -> Vec<Shard> {
+    let size = count.div_ceil(n_shards.max(1)).max(1);
+    (0..count.div_ceil(size))
+        .map(|index| Shard {
+            index,
+            offset: offset + index * size,
+            limit: size.min(count - index * size),
+        })
+        .collect()
+}
+
+/// writes the list of the shards in the directory (see [SHARDS_FILE]).
+pub fn write_shards(dir: &Path, shards: &[Shard]) -> Result<()> {
+    let text = shards
+        .iter()
+        .map(|s| format!("{} {} {}\n", s.index, s.offset, s.limit))
+        .collect::<String>();
+    std::fs::write(dir.join(SHARDS_FILE), text).context("failed to write the list of the shards")
+}
+
+/// reads the list of the shards of a cluster run from its directory (see [SHARDS_FILE]).
+pub fn read_shards(dir: &Path) -> Result<Vec<Shard>> {
+    let path = dir.join(SHARDS_FILE);
+    let text = std::fs::read_to_string(&path)
+        .with_context(|| format!("failed to read the list of the shards {}", path.display()))?;
+    text.lines()
+        .map(|line| {
+            let fields = line
+                .split_whitespace()
+                .map(str::parse)
+                .collect::<Result<Vec<usize>, _>>()
+                .ok()
+                .filter(|fields| fields.len() == 3);
+            match fields.as_deref() {
+                Some(&[index, offset, limit]) => Ok(Shard {
+                    index,
+                    offset,
+                    limit,
+                }),
+                _ => bail!("invalid line in {}: {line}", path.display()),
+            }
+        })
+        .collect()
+}
+
+/// quotes an argument for the shell.
+pub fn shell_quote(arg: &str) -> String {
+    if !arg.is_empty()
+        && arg
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || "-_./:=,+@%".contains(c))
+    {
+        arg.to_string()
+    } else {
+        format!("'{}'", arg.replace('\'', r"'\''"))
+    }
+}
+
+/// commands of a task of the job array, with the index of its shard in `$SHARD`: runs the synthesis (the program
+/// with the arguments) on the slice of the shard, then records its exit code.
+pub fn task_command(dir: &Path, program: &Path, args: &[String], shards: &[Shard]) -> String {
+    let dir = shell_quote(&dir.to_string_lossy());
+    let size = shards.first().map_or(0, |s| s.limit);
+    let offset = shards.first().map_or(0, |s| s.offset);
+    let last = shards.last().copied().unwrap_or(Shard {
+        index: 0,
+        offset,
+        limit: 0,
+    });
+    let args = args.iter().map(|a| shell_quote(a)).collect::<Vec<_>>();
+    format!(
+        "cd {dir} || exit 1\nOFFSET=$(({offset} + SHARD * {size}))\nLIMIT={size}\n\
+        if [ \"$SHARD\" -eq {} ]; then LIMIT={}; fi\n\
+        {} synth {} --offset \"$OFFSET\" --limit \"$LIMIT\" --out \"shard-$SHARD.txt\" --force\n\
+        echo $? > \"shard-$SHARD.status\"",
+        last.index,
+        last.limit,
+        shell_quote(&program.to_string_lossy()),
+        args.join(" "),
+    )
+}
+
+/// job array script from a template, where `{name}`, `{last}` (index of the last shard), `{dir}` and `{command}`
+/// (see [task_command]) are replaced.
+pub fn render(template: &str, name: &str, last: usize, dir: &Path, command: &str) -> String {
+    template
+        .replace("{name}", name)
+        .replace("{last}", &last.to_string())
+        .replace("{dir}", &dir.to_string_lossy())
+        .replace("{command}", command)
+}
+
+/// submits the job array script to the batch system; returns the output of the submission (e.g., the job id).
+pub fn submit(system: BatchSystem, script: &Path) -> Result<String> {
+    let output = duct::cmd(system.submit_command(), [script])
+        .stderr_to_stdout()
+        .stdout_capture()
+        .unchecked()
+        .run()
+        .with_context(|| format!("failed to run {}", system.submit_command()))?;
+    let text = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    if !output.status.success() {
+        bail!("{} failed: {text}", system.submit_command());
+    }
+    Ok(text)
+}
+
+/// waits until the tasks of all the shards are complete, checking every `poll`; fails if a task failed, and if some
+/// shards have no status once the job has left the queue of the batch system (if known) or after the timeout.
+pub fn wait(
+    dir: &Path,
+    shards: &[Shard],
+    poll: Duration,
+    timeout: Option<Duration>,
+    job: Option<&Job>,
+) -> Result<()> {
+    let start = Instant::now();
+    let read_statuses = || {
+        shards
+            .iter()
+            .map(|shard| std::fs::read_to_string(shard.status(dir)).ok())
+            .collect::<Vec<_>>()
+    };
+    let missing = |statuses: &[Option<String>]| {
+        shards
+            .iter()
+            .zip(statuses)
+            .filter(|(_, status)| status.is_none())
+            .map(|(shard, _)| shard.index.to_string())
+            .collect::<Vec<_>>()
+            .join(", ")
+    };
+    let mut reported = 0;
+    loop {
+        let statuses = read_statuses();
+        let n_done = statuses.iter().flatten().count();
+        if n_done > reported {
+            log::info!("{n_done}/{} shards complete", shards.len());
+            reported = n_done;
+        }
+        if n_done == shards.len() {
+            let failed = shards
+                .iter()
+                .zip(statuses.iter().flatten())
+                .filter(|(_, status)| status.trim() != "0")
+                .map(|(shard, status)| format!("{} (exit code {})", shard.index, status.trim()))
+                .collect::<Vec<_>>();
+            if !failed.is_empty() {
+                bail!(
+                    "shards failed: {} (see their reports in {})",
+                    failed.join(", "),
+                    dir.display()
+                );
+            }
+            return Ok(());
+        }
+        if crate::interrupt::is_interrupted() {
+            bail!(
+                "interrupted with {n_done}/{} shards complete (merge them later with --merge-only)",
+                shards.len()
+            );
+        }
+        if let Some(job) = job {
+            match job.is_alive() {
+                Ok(false) => {
+                    // the statuses written by the last tasks just before they left the queue are read again
+                    let statuses = read_statuses();
+                    if statuses.iter().flatten().count() < shards.len() {
+                        bail!(
+                            "job {} is no longer queued nor running on {}, but shards are missing a status: {} \
+                            (their tasks were killed, e.g., walltime, memory or node failure; see their logs in {})",
+                            job.id,
+                            job.system,
+                            missing(&statuses),
+                            dir.display()
+                        );
+                    }
+                }
+                Ok(true) => {}
+                Err(e) => log::warn!("cannot check the job {}: {e:#}", job.id),
+            }
+        }
+        if timeout.is_some_and(|timeout| start.elapsed() >= timeout) {
+            bail!(
+                "timed out after {} with shards missing a status: {} (merge them later with --merge-only)",
+                crate::interrupt::format_duration(start.elapsed()),
+                missing(&statuses)
+            );
+        }
+        std::thread::sleep(poll);
+    }
+}
+
+/// merges the reports of the shards into one (see [merge::merge]); returns the merged summary.
+pub fn merge(dir: &Path, shards: &[Shard], output: &mut dyn Write) -> Result<Summary> {
+    let reports = shards
+        .iter()
+        .map(|shard| {
+            Ok(ShardReport {
+                name: format!("shard-{}", shard.index),
+                report: Report::read(&shard.report(dir))?,
+            })
+        })
+        .collect::<Result<Vec<_>>>()?;
+    let merged = merge::merge(&reports)?;
+    merged.write(output)?;
+    Ok(merged.summary)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_shards() {
+        let shards = shards(100, 10, 3);
+        assert_eq!(
+            shards
+                .iter()
+                .map(|s| (s.offset, s.limit))
+                .collect::<Vec<_>>(),
+            [(100, 4), (104, 4), (108, 2)]
+        );
+        assert_eq!(super::shards(0, 2, 5).len(), 2);
+        assert!(super::shards(0, 0, 5).is_empty());
+
+        let dir = std::env::temp_dir().join(format!("synth-lights-cluster-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        write_shards(&dir, &shards).unwrap();
+        assert_eq!(read_shards(&dir).unwrap(), shards);
+
+        let command = task_command(
+            &dir,
+            Path::new("/bin/synth-lights"),
+            &["-L".into(), "it's".into()],
+            &shards,
+        );
+        assert!(command.contains("OFFSET=$((100 + SHARD * 4))\n"));
+        assert!(command.contains("if [ \"$SHARD\" -eq 2 ]; then LIMIT=2; fi\n"));
+        assert!(command.contains("/bin/synth-lights synth -L 'it'\\''s' --offset"));
+        let script = render(
+            BatchSystem::Slurm.default_template(),
+            "E4L",
+            2,
+            &dir,
+            &command,
+        );
+        assert!(script.contains("#SBATCH --array=0-2\n"));
+        assert!(script.ends_with("echo $? > \"shard-$SHARD.status\"\n"));
+
+        for (shard, text) in shards.iter().zip([
+            "   3 : PASS 0_1_2__S2_H0_O1\nVerification Finished with 1 pass, 3 fail, 0 incomplete, 0 errors (4 algorithms)\n",
+            "Verification Finished with 0 pass, 4 fail, 0 incomplete, 0 errors (4 algorithms)\n",
+            "INCOMPLETE >  109 : SearchIncomplete 0_1_2__S2_H1_O0\n   108 : PASS 0_1_2__S1_H0_O2\nVerification Finished with 1 pass, 0 fail, 1 incomplete, 0 errors (2 algorithms)\n",
+        ]) {
+            let slice = format!("Slice: viable algorithms from index {}, 4 at most\n", shard.offset);
+            std::fs::write(shard.report(&dir), text.to_string() + &slice).unwrap();
+            std::fs::write(shard.status(&dir), "0\n").unwrap();
+        }
+        wait(&dir, &shards, Duration::ZERO, Some(Duration::ZERO), None).unwrap();
+        let mut merged = Vec::new();
+        let total = merge(&dir, &shards, &mut merged).unwrap();
+        assert_eq!(
+            (total.n_pass, total.n_incomplete, total.n_algos),
+            (2, 1, 10)
+        );
+        let merged = String::from_utf8(merged).unwrap();
+        assert!(merged.starts_with(
+            "Merged report of 3 shards\nSHARD > shard-0 : 100..104 : Verification Finished with 1 pass"
+        ));
+        assert!(merged.contains(" 108 : PASS 0_1_2__S1_H0_O2\nINCOMPLETE >  109 : SearchIncomplete 0_1_2__S2_H1_O0\nVerification Finished with 2 pass, 7 fail, 1 incomplete, 0 errors (10 algorithms)\n"));
+
+        std::fs::write(shards[1].status(&dir), "1\n").unwrap();
+        assert!(wait(&dir, &shards, Duration::ZERO, None, None).is_err());
+
+        // a task killed before writing its status
+        std::fs::remove_file(shards[1].status(&dir)).unwrap();
+        let error = wait(&dir, &shards, Duration::ZERO, Some(Duration::ZERO), None).unwrap_err();
+        assert!(error.to_string().contains("missing a status: 1 "));
+
+        let job = Job {
+            system: BatchSystem::Pbs,
+            id: "1234[].server".into(),
+        };
+        job.save(&dir).unwrap();
+        assert_eq!(Job::read(&dir).unwrap(), Some(job));
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_job_id() {
+        let slurm = BatchSystem::Slurm;
+        assert_eq!(
+            slurm.job_id("Submitted batch job 4242\n").as_deref(),
+            Some("4242")
+        );
+        assert_eq!(slurm.job_id("4242;cluster").as_deref(), Some("4242"));
+        assert_eq!(
+            BatchSystem::Pbs.job_id("1234[].server\n").as_deref(),
+            Some("1234[].server")
+        );
+        assert_eq!(slurm.job_id("sbatch: queued"), None);
+        assert_eq!(slurm.job_id(""), None);
+    }
+}
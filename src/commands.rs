@@ -0,0 +1,1657 @@
+//! Subcommands of the main binary (`synth-lights synth|count|promela|dot|dot-all|simulate|check|verify-batch|analyze|cluster|prune|diff|merge|check-manifest|doctor|selftest|bench|presets`).
+//!
+//! Each subcommand has its own arguments and `run` method; the separate binaries
+//! (`count_filter`, `algo_from_string`, `dot_from_string`, `model_check_algo`) parse the same arguments.
+
+use anyhow::{Context, Result};
+use clap::{Parser, Subcommand};
+use indicatif::ProgressIterator;
+use num_format::{Locale, ToFormattedString};
+use std::ffi::OsString;
+use std::io::Write;
+use std::path::PathBuf;
+
+use crate::algorithm::Algorithm;
+use crate::logging::LogArgs;
+use crate::model::{Model, ModelArgs};
+use crate::promela::{Fairness, InitialConditions, ModelRunOptions, DEFAULT_DELTA_STEPS};
+use crate::runner::{self, PanBuildOptions, SpinOutcome, Violation, WorkdirArgs};
+use crate::synthesis::{self, Parallelism, ReportOutput, SynthesisConfig, WorkdirStrategy};
+use crate::throttle::MemoryLimits;
+use crate::{
+    analyze, bench, cluster, common, doctor, dot, filter, generator, interrupt, manifest, merge,
+    metadata, preset, promela, pruning, repl, report, selftest, Cli, Variants,
+};
+
+/// Algorithm synthesis for two robots gathering.
+/// Without a subcommand, runs the synthesis (same as `synth-lights synth`).
+#[derive(Debug, Parser)]
+#[command(author, version, long_about = None)]
+pub struct App {
+    #[command(subcommand)]
+    pub command: Command,
+}
+
+#[derive(Clone, Debug, Subcommand)]
+#[allow(clippy::large_enum_variant)] // parsed once
+pub enum Command {
+    /// Synthesizes the algorithms solving gathering in a model (default)
+    Synth(Cli),
+    /// Counts the algorithms of a model at each stage of filtering
+    Count(CountArgs),
+    /// Generates the Promela code of an algorithm given its code string
+    Promela(PromelaArgs),
+    /// Generates the dot code of an algorithm given its code string
+    Dot(DotArgs),
+    /// Generates the dot code (or svg) of each algorithm listed in a file (code strings or report of a previous run)
+    DotAll(DotAllArgs),
+    /// Simulates an algorithm interactively, round by round
+    Simulate(SimulateArgs),
+    /// Checks an algorithm given in Promela code with the model checker
+    Check(CheckArgs),
+    /// Simplifies an algorithm passing the verification by replacing its redundant rules with the default action
+    Minimize(MinimizeArgs),
+    /// Verifies the algorithms listed in a file (code strings or report of a previous run)
+    VerifyBatch(VerifyBatchArgs),
+    /// Verifies the algorithms listed in a file under several schedulers and variants, as a Markdown or LaTeX table
+    Analyze(AnalyzeArgs),
+    /// Runs a synthesis on a cluster as a job array (SLURM or PBS) and merges the reports of its shards
+    Cluster(ClusterArgs),
+    /// Synthesizes the algorithms by pruned enumeration with counterexample learning (experimental)
+    Prune(PruneArgs),
+    /// Compares the outcomes of the algorithms in two reports
+    Diff(DiffArgs),
+    /// Merges the reports of the shards of a run (text or JSON lines) into one
+    Merge(MergeArgs),
+    /// Checks the indices of the algorithms in reports against an enumeration manifest
+    CheckManifest(CheckManifestArgs),
+    /// Checks the environment (spin, clang, working directory, build of the verifier)
+    Doctor(DoctorArgs),
+    /// Verifies the reference algorithms of the bundled corpus and compares the outcomes with the expected ones
+    Selftest(SelftestArgs),
+    /// Measures the throughput of the verification by number of threads and working directory, to tune the runs
+    Bench(BenchArgs),
+    /// Lists the named presets of run options (see `--preset`)
+    Presets,
+}
+
+impl App {
+    /// parses the command line, where `synth` is implicit if no subcommand is given,
+    /// and presets are expanded (see [preset::expand_presets]).
+    pub fn parse_args() -> Self {
+        let args = preset::expand_presets(std::env::args_os().collect()).unwrap_or_else(|e| {
+            use clap::CommandFactory;
+            Self::command()
+                .error(clap::error::ErrorKind::InvalidValue, format!("{e:#}"))
+                .exit()
+        });
+        Self::parse_from(with_default_command(args))
+    }
+}
+
+/// inserts the `synth` subcommand when the first argument is neither a subcommand nor a top-level flag.
+fn with_default_command(mut args: Vec<OsString>) -> Vec<OsString> {
+    use clap::CommandFactory;
+    let app = App::command();
+    let explicit = match args.get(1).and_then(|a| a.to_str()) {
+        None => true,
+        Some("help" | "-h" | "--help" | "-V" | "--version") => true,
+        Some(first) => app.find_subcommand(first).is_some(),
+    };
+    if !explicit {
+        args.insert(1, OsString::from("synth"));
+    }
+    args
+}
+
+impl Command {
+    /// logging arguments of the subcommand (the default ones, i.e., from `RUST_LOG`, if it has none).
+    pub fn log_args(&self) -> LogArgs {
+        match self {
+            Command::Synth(cli) => cli.log.clone(),
+            Command::VerifyBatch(args) => args.log.clone(),
+            _ => LogArgs::default(),
+        }
+    }
+
+    pub fn run(&self) -> Result<()> {
+        match self {
+            Command::Synth(cli) => {
+                log::info!("Run options: {:?}", cli);
+                crate::run(cli)
+            }
+            Command::Count(args) => args.run(),
+            Command::Promela(args) => args.run(),
+            Command::Dot(args) => args.run(),
+            Command::DotAll(args) => args.run(),
+            Command::Simulate(args) => args.run(),
+            Command::Check(args) => args.run(),
+            Command::Minimize(args) => args.run(),
+            Command::VerifyBatch(args) => args.run(),
+            Command::Analyze(args) => args.run(),
+            Command::Cluster(args) => args.run(),
+            Command::Prune(args) => args.run(),
+            Command::Diff(args) => args.run(),
+            Command::Merge(args) => args.run(),
+            Command::CheckManifest(args) => args.run(),
+            Command::Doctor(args) => args.run(),
+            Command::Selftest(args) => args.run(),
+            Command::Bench(args) => args.run(),
+            Command::Presets => {
+                for (name, description) in preset::list() {
+                    println!("{name:<24} {description}");
+                }
+                if let Some(dir) = preset::presets_dir() {
+                    println!("\nUser presets directory: {}", dir.display());
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+/// Generates all algorithms for a given model and counts them at each stage of filtering.
+#[derive(Clone, Debug, Parser)]
+#[command(author, version, long_about = None)]
+pub struct CountArgs {
+    #[command(flatten)]
+    model: ModelArgs,
+
+    /// Outputs the counts as rows of a LaTeX table
+    #[arg(long = "latex")]
+    as_latex: bool,
+
+    /// Outputs the counts as JSON (see [generator::FilterStats])
+    #[arg(long = "json", conflicts_with = "as_latex")]
+    as_json: bool,
+
+    /// Reports only the stages counted without enumerating the algorithms (up to the first non-structural filter)
+    #[arg(long = "no-enumeration")]
+    no_enumeration: bool,
+
+    #[command(flatten)]
+    filters: filter::FilterArgs,
+}
+
+impl CountArgs {
+    pub fn run(&self) -> Result<()> {
+        let model = self.model.model()?;
+
+        let options = generator::FilterStatsOptions {
+            filters: self.filters.filter_set(),
+            enumerate: !self.no_enumeration,
+            progress: !self.as_json,
+        };
+        let stats = generator::filter_statistics(model, &options);
+        let total = stats
+            .total
+            .ok_or_else(|| anyhow::anyhow!("too many algorithms in model {model} to count"))?;
+
+        if self.as_json {
+            println!("{}", serde_json::to_string_pretty(&stats)?);
+        } else if self.as_latex {
+            let class_l = if model.class_L { "$\\mathcal{L}$" } else { "" };
+            let kind = model.category.to_string().to_lowercase();
+            let n_colors = model.n_colors;
+            let model_name = format!("{kind} {n_colors} {class_l}");
+
+            println!(" & {} \\\\ \\hline", model_name);
+            println!("{:<34}& {:>7} \\\\", "ALL", total);
+            for stage in stats.stages.iter() {
+                println!("{:<34}& {:>7} \\\\", stage.description, stage.remaining);
+            }
+        } else {
+            println!(
+                "Model: {} {}-colors {}",
+                model.category,
+                model.n_colors,
+                if model.class_L { "class L" } else { "" }
+            );
+            println!();
+            println!(
+                "{:<32}{:>11}",
+                "TOTAL:",
+                total.to_formatted_string(&Locale::en)
+            );
+            for stage in stats.stages.iter() {
+                println!(
+                    "{:<32}{:>11}",
+                    format!("{}:", stage.filter),
+                    stage.remaining.to_formatted_string(&Locale::en)
+                );
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// model followed by the code string of an algorithm (shared by the `promela` and `dot` subcommands).
+#[derive(Clone, Debug, clap::Args)]
+#[allow(non_snake_case)]
+pub struct AlgorithmArgs {
+    /// Model (e.g., `full 3` or `F3`) followed by the algorithm code string (e.g., 0_1_2__S2_H0_O1),
+    /// or only the code string prefixed by the model (e.g., E3L:0_1_2__S2_H0_O1)
+    #[arg(required = true, value_names = ["MODEL", "ALGORITHM"])]
+    args: Vec<String>,
+
+    /// Class L algorithms
+    #[arg(short = 'L')]
+    class_L: bool,
+
+    /// Robots cannot detect co-location (no `same_position` observation): only the guards of class L remain
+    #[arg(long = "no-multiplicity")]
+    no_multiplicity: bool,
+}
+
+impl AlgorithmArgs {
+    /// parses the algorithm given on the command line.
+    pub fn algorithm(&self) -> Result<Algorithm> {
+        let (code, model_args) = self
+            .args
+            .split_last()
+            .ok_or_else(|| anyhow::Error::msg("missing arguments"))?;
+        if model_args.is_empty() {
+            let algo = Algorithm::try_parse_v2(code)
+                .context("missing model (give it before the code string, or as a prefix of it)")?;
+            if self.no_multiplicity && !algo.class_L() {
+                anyhow::bail!("without multiplicity detection, the algorithm must be of class L");
+            }
+            return Ok(algo);
+        }
+        let model = Model::from_positionals(model_args, self.class_L || self.no_multiplicity)?;
+        Algorithm::try_parse(model.category, model.n_colors, model.class_L, code)
+    }
+}
+
+/// Generates the Promela code of an algorithm given its code string (e.g., 0_1_2__S2_H0_O1)
+#[derive(Clone, Debug, Parser)]
+#[command(author, version, long_about = None)]
+pub struct PromelaArgs {
+    #[command(flatten)]
+    algorithm: AlgorithmArgs,
+
+    /// Writes a self-contained bundle of the verification of the algorithm in the directory: the Promela files and a README of the commands of spin, clang and pan
+    #[arg(long = "emit-bundle", value_name = "DIR")]
+    emit_bundle: Option<PathBuf>,
+
+    /// Scheduler of the model of the bundle
+    #[arg(short = 's', long = "sched", value_enum, default_value = "async")]
+    scheduler: common::Scheduler,
+
+    /// Rigid moves restriction in the bundle (otherwise non-rigid)
+    #[arg(long = "rigid")]
+    rigid: bool,
+
+    /// Quasi self-stabilizing restriction in the bundle (otherwise self-stabilizing)
+    #[arg(short = 'Q', long = "quasi-ss")]
+    quasi_ss: bool,
+
+    /// Fairness assumption of the verification of the bundle
+    #[arg(long = "fairness", value_enum, default_value = "weak")]
+    fairness: Fairness,
+
+    /// Non-rigid moves: maximal number of moves stopped short before the robots are within reach (1 in the original model)
+    #[arg(long = "delta-steps", value_name = "K", default_value_t = DEFAULT_DELTA_STEPS, value_parser = clap::value_parser!(u8).range(1..))]
+    delta_steps: u8,
+
+    #[command(flatten)]
+    initial: InitialConditions,
+
+    #[command(flatten)]
+    pan_build: PanBuildOptions,
+}
+
+impl PromelaArgs {
+    pub fn run(&self) -> Result<()> {
+        let algorithm = self.algorithm.algorithm()?;
+        let promela = promela::generate_promela(&algorithm);
+
+        if let Some(dir) = &self.emit_bundle {
+            let model_run_options = ModelRunOptions {
+                rigid: self.rigid,
+                quasi_ss: self.quasi_ss,
+                fairness: self.fairness,
+                delta_steps: self.delta_steps,
+                initial: self.initial,
+                no_multiplicity: self.algorithm.no_multiplicity,
+                ..ModelRunOptions::new(self.scheduler)
+            };
+            let title = algorithm.as_code_v2();
+            runner::write_bundle(dir, &title, &promela, model_run_options, &self.pan_build)?;
+            println!("Bundle of {} written in {}", title, dir.display());
+            return Ok(());
+        }
+
+        println!("# Algorithm: {}", algorithm.as_code());
+        println!();
+        println!("{}", promela);
+        Ok(())
+    }
+}
+
+/// Generates the dot code of an algorithm given its code string (e.g., 0_1_2__S2_H0_O1)
+#[derive(Clone, Debug, Parser)]
+#[command(author, version, long_about = None)]
+pub struct DotArgs {
+    #[command(flatten)]
+    algorithm: AlgorithmArgs,
+}
+
+impl DotArgs {
+    pub fn run(&self) -> Result<()> {
+        let algorithm = self.algorithm.algorithm()?;
+        let dot_code = dot::algo_to_dot(&algorithm);
+
+        println!("# Algorithm: {}", algorithm.as_code());
+        println!();
+        println!("{}", dot_code);
+        Ok(())
+    }
+}
+
+/// Generates one dot file (and optionally one svg file) per algorithm listed in a file
+/// (one code string per line, or the report of a previous run whose PASS algorithms are exported)
+#[derive(Clone, Debug, Parser)]
+#[command(author, version, long_about = None)]
+pub struct DotAllArgs {
+    #[command(flatten)]
+    model: ModelArgs,
+
+    /// File listing the algorithms (standard input if omitted)
+    #[arg(short = 'i', long = "input", value_name = "FILE")]
+    input: Option<PathBuf>,
+
+    /// Directory of the generated files (created if needed)
+    #[arg(short = 'd', long = "dir", value_name = "DIR", default_value = "dot")]
+    dir: PathBuf,
+
+    /// Also renders each graph in svg with graphviz (`dot` must be in the PATH)
+    #[arg(long = "svg")]
+    svg: bool,
+}
+
+impl DotAllArgs {
+    pub fn run(&self) -> Result<()> {
+        let model = self.model.model()?;
+        let text = match &self.input {
+            Some(path) => std::fs::read_to_string(path)?,
+            None => std::io::read_to_string(std::io::stdin())?,
+        };
+        let algos = synthesis::read_algorithm_codes(&text, model)?;
+        if algos.is_empty() {
+            anyhow::bail!("no algorithm of model {model} found in the input");
+        }
+        let paths = dot::export_all(&algos, &self.dir, self.svg)?;
+        println!(
+            "Exported {} algorithms to {} ({} files)",
+            algos.len(),
+            self.dir.display(),
+            paths.len()
+        );
+        Ok(())
+    }
+}
+
+/// Simulates an algorithm given its code string interactively: the robots are activated round by round
+/// by the user, or by a random or adversarial scheduler (commands are read from the standard input, `h` for help)
+#[derive(Clone, Debug, Parser)]
+#[command(author, version, long_about = None)]
+pub struct SimulateArgs {
+    #[command(flatten)]
+    algorithm: AlgorithmArgs,
+
+    /// Scheduler (only the rounds of complete Look-Compute-Move cycles are simulated)
+    #[arg(short = 's', long = "sched", value_enum, default_value = "ssync")]
+    scheduler: common::Scheduler,
+
+    /// Initial position of the robots: s (same), n (near) or f (far)
+    #[arg(long = "position", default_value = "n")]
+    position: String,
+
+    /// Initial colors of robots A and B
+    #[arg(long = "colors", value_delimiter = ',', default_values = ["0", "0"])]
+    colors: Vec<String>,
+
+    /// Seed of the random scheduler
+    #[arg(long = "seed", default_value_t = 0)]
+    seed: u64,
+}
+
+impl SimulateArgs {
+    pub fn run(&self) -> Result<()> {
+        use std::io::IsTerminal;
+        let algorithm = self.algorithm.algorithm()?;
+        let [a, b] = &self.colors[..] else {
+            anyhow::bail!("expected two colors (e.g., --colors 0,1)");
+        };
+        let config = repl::parse_config(algorithm.num_colors(), &self.position, a, b)?;
+        println!("Algorithm: {}", algorithm.as_code_v2());
+        println!("Scheduler: {}", self.scheduler);
+        let stdin = std::io::stdin();
+        let interactive = stdin.is_terminal();
+        if interactive {
+            println!("{}", repl::HELP);
+        }
+        let mut session = repl::Session::new(&algorithm, self.scheduler, config, self.seed);
+        session.run(stdin.lock(), &mut std::io::stdout(), interactive)
+    }
+}
+
+/// Given the VALID promela code for an algorithm, check that algorithm in the model checker
+#[derive(Clone, Debug, Parser)]
+#[command(author, version, long_about = None)]
+pub struct CheckArgs {
+    /// Scheduler of the model
+    #[arg(short = 's', long = "sched", value_enum, default_value = "async")]
+    scheduler: common::Scheduler,
+
+    /// Rigid moves restriction (otherwise non-rigid)
+    #[arg(long = "rigid")]
+    rigid: bool,
+
+    /// Quasi self-stabilizing restriction (otherwise self-stabilizing)
+    #[arg(short = 'Q', long = "quasi-ss")]
+    quasi_ss: bool,
+
+    /// Fairness assumption of the verification
+    #[arg(long = "fairness", value_enum, default_value = "weak")]
+    fairness: Fairness,
+
+    /// Non-rigid moves: maximal number of moves stopped short before the robots are within reach (1 in the original model)
+    #[arg(long = "delta-steps", value_name = "K", default_value_t = DEFAULT_DELTA_STEPS, value_parser = clap::value_parser!(u8).range(1..))]
+    delta_steps: u8,
+
+    #[command(flatten)]
+    initial: InitialConditions,
+
+    /// Robots cannot detect co-location (no `same_position` observation): the algorithm must be of class L
+    #[arg(long = "no-multiplicity")]
+    no_multiplicity: bool,
+
+    /// Promela file of the algorithm (standard input if omitted)
+    #[arg(short = 'a', long = "algo")]
+    algorithm: Option<PathBuf>,
+
+    /// Prints the rules of the algorithm in human-readable form instead of its Promela code
+    #[arg(long = "pretty")]
+    pretty: bool,
+
+    #[command(flatten)]
+    pan_build: PanBuildOptions,
+
+    /// Also writes a self-contained bundle of the verification in the directory (see `algo_from_string --emit-bundle`)
+    #[arg(long = "emit-bundle", value_name = "DIR")]
+    emit_bundle: Option<PathBuf>,
+
+    #[command(flatten)]
+    workdir: WorkdirArgs,
+}
+
+impl CheckArgs {
+    pub fn run(&self) -> Result<()> {
+        log::debug!("Run options: {:?}", self);
+
+        log::info!("Preparing environment");
+
+        let model_run_options = ModelRunOptions {
+            rigid: self.rigid,
+            quasi_ss: self.quasi_ss,
+            fairness: self.fairness,
+            delta_steps: self.delta_steps,
+            initial: self.initial,
+            no_multiplicity: self.no_multiplicity,
+            ..ModelRunOptions::new(self.scheduler)
+        };
+
+        let promela = match &self.algorithm {
+            Some(path) => std::fs::read_to_string(path)?,
+            None => std::io::read_to_string(std::io::stdin())?,
+        };
+        let algo = if self.pretty {
+            let algo = promela::parse_algorithm(&promela)
+                .context("the Promela code was not generated by this tool (see --pretty)")?;
+            Some(algo)
+        } else {
+            None
+        };
+
+        if let Some(dir) = &self.emit_bundle {
+            let title = match &algo {
+                Some(algo) => algo.as_code_v2(),
+                None => promela::parse_algorithm(&promela)
+                    .map(|algo| algo.as_code_v2())
+                    .unwrap_or_else(|_| "the algorithm of Algorithms.pml".to_string()),
+            };
+            runner::write_bundle(dir, &title, &promela, model_run_options, &self.pan_build)?;
+            log::info!("Bundle written in {}", dir.display());
+        }
+
+        let workdir = self.workdir.create()?;
+        let enclosure = runner::create_enclosure(workdir.path())?;
+
+        let result = check_promela(&enclosure, &promela, model_run_options, &self.pan_build);
+
+        runner::close_workdir(workdir)?;
+
+        let (outcome, violation, trail) = result?;
+
+        match algo {
+            Some(algo) => {
+                println!("{}", algo.as_code_v2());
+                println!("{}", algo.to_pretty_table());
+            }
+            None => println!("{}", promela),
+        }
+        println!();
+        println!("{}", outcome);
+        if let Some(violation) = violation {
+            println!("{}", violation);
+        }
+        if let Some(trail) = trail {
+            println!("{}", trail);
+        }
+
+        Ok(())
+    }
+}
+
+fn check_promela(
+    enclosure: &std::path::Path,
+    promela: &str,
+    model_run_options: ModelRunOptions,
+    pan_build: &PanBuildOptions,
+) -> Result<(SpinOutcome, Option<Violation>, Option<String>)> {
+    log::info!("Running verification");
+
+    let outcome =
+        runner::run_verification_from_code(enclosure, promela, model_run_options, pan_build)?;
+    let violation = match outcome {
+        SpinOutcome::Fail => runner::read_violation(enclosure)?,
+        _ => None,
+    };
+    let trail = runner::read_trail_file(enclosure)?;
+    Ok((outcome, violation, trail))
+}
+
+/// Simplifies an algorithm given its code string (e.g., 0_1_2__S2_H0_O1): each rule that can be replaced by the default
+/// action without failing the verification is replaced (see [runner::minimize_algorithm])
+#[derive(Clone, Debug, Parser)]
+#[command(author, version, long_about = None)]
+pub struct MinimizeArgs {
+    #[command(flatten)]
+    algorithm: AlgorithmArgs,
+
+    /// Scheduler of the model
+    #[arg(short = 's', long = "sched", value_enum, default_value = "async")]
+    scheduler: common::Scheduler,
+
+    /// Rigid moves restriction (otherwise non-rigid)
+    #[arg(long = "rigid")]
+    rigid: bool,
+
+    /// Quasi self-stabilizing restriction (otherwise self-stabilizing)
+    #[arg(short = 'Q', long = "quasi-ss")]
+    quasi_ss: bool,
+
+    /// Fairness assumption of the verification
+    #[arg(long = "fairness", value_enum, default_value = "weak")]
+    fairness: Fairness,
+
+    /// Non-rigid moves: maximal number of moves stopped short before the robots are within reach (1 in the original model)
+    #[arg(long = "delta-steps", value_name = "K", default_value_t = DEFAULT_DELTA_STEPS, value_parser = clap::value_parser!(u8).range(1..))]
+    delta_steps: u8,
+
+    #[command(flatten)]
+    initial: InitialConditions,
+
+    #[command(flatten)]
+    pan_build: PanBuildOptions,
+
+    #[command(flatten)]
+    workdir: WorkdirArgs,
+}
+
+impl MinimizeArgs {
+    pub fn run(&self) -> Result<()> {
+        log::debug!("Run options: {:?}", self);
+
+        let algo = self.algorithm.algorithm()?;
+        let model_run_options = ModelRunOptions {
+            rigid: self.rigid,
+            quasi_ss: self.quasi_ss,
+            fairness: self.fairness,
+            delta_steps: self.delta_steps,
+            initial: self.initial,
+            no_multiplicity: self.algorithm.no_multiplicity,
+            ..ModelRunOptions::new(self.scheduler)
+        };
+
+        let workdir = self.workdir.create()?;
+        let result = runner::create_enclosure(workdir.path()).and_then(|enclosure| {
+            let outcome = runner::run_verification_with_build(
+                &enclosure,
+                &algo,
+                model_run_options,
+                &self.pan_build,
+            )?;
+            if outcome != SpinOutcome::Pass {
+                anyhow::bail!("the algorithm does not pass the verification ({outcome})");
+            }
+            runner::minimize_algorithm(&enclosure, &algo, model_run_options, &self.pan_build)
+        });
+        runner::close_workdir(workdir)?;
+        let minimized = result?;
+
+        let replaced = algo
+            .rules()
+            .zip(minimized.rules())
+            .filter(|(rule, simplified)| rule != simplified)
+            .count();
+        println!("{}", algo.as_code_v2());
+        println!("{}", minimized.as_code_v2());
+        println!();
+        println!("{}", minimized.to_pretty_table());
+        println!(
+            "{replaced} of {} rules replaced by the default action",
+            algo.rules().count()
+        );
+        Ok(())
+    }
+}
+
+/// Verifies the algorithms listed in a file (one code string per line, or the report of a previous run whose PASS algorithms are verified again)
+#[derive(Clone, Debug, Parser)]
+#[command(author, version, long_about = None)]
+pub struct VerifyBatchArgs {
+    #[command(flatten)]
+    model: ModelArgs,
+
+    /// File listing the algorithms (standard input if omitted)
+    #[arg(short = 'i', long = "input", value_name = "FILE")]
+    input: Option<PathBuf>,
+
+    /// Scheduler of the model
+    #[arg(short = 's', long = "sched", value_enum, default_value = "async")]
+    scheduler: common::Scheduler,
+
+    /// Rigid moves restriction (otherwise non-rigid)
+    #[arg(long = "rigid")]
+    rigid: bool,
+
+    /// Quasi self-stabilizing restriction (otherwise self-stabilizing)
+    #[arg(short = 'Q', long = "quasi-ss")]
+    quasi_ss: bool,
+
+    /// Fairness assumption of the verification
+    #[arg(long = "fairness", value_enum, default_value = "weak")]
+    fairness: Fairness,
+
+    /// Non-rigid moves: maximal number of moves stopped short before the robots are within reach (1 in the original model)
+    #[arg(long = "delta-steps", value_name = "K", default_value_t = DEFAULT_DELTA_STEPS, value_parser = clap::value_parser!(u8).range(1..))]
+    delta_steps: u8,
+
+    #[command(flatten)]
+    initial: InitialConditions,
+
+    /// Variants of the model checked for each algorithm (all: the 4 combinations of rigid and quasi-ss)
+    #[arg(long = "variants", value_enum, default_value = "selected")]
+    variants: Variants,
+
+    /// Enables sequential execution
+    #[arg(short = 'S', long = "sequential")]
+    sequential: bool,
+
+    /// Groups PASS algorithms by equivalence class (color permutations) in the report
+    #[arg(short = 'G', long = "group-classes")]
+    group_classes: bool,
+
+    /// Output file for reporting outcomes (stdout by default)
+    #[arg(short = 'o', long = "out")]
+    output: Option<PathBuf>,
+
+    /// Overwrites the output file if it already exists
+    #[arg(long = "force", conflicts_with = "append")]
+    force: bool,
+
+    /// Appends the report to the output file if it already exists
+    #[arg(long = "append")]
+    append: bool,
+
+    #[command(flatten)]
+    workdir: WorkdirArgs,
+
+    #[command(flatten)]
+    pan_build: PanBuildOptions,
+
+    #[command(flatten)]
+    memory_limits: MemoryLimits,
+
+    #[command(flatten)]
+    interrupt: interrupt::InterruptArgs,
+
+    #[command(flatten)]
+    pub log: LogArgs,
+}
+
+impl VerifyBatchArgs {
+    pub fn config(&self) -> Result<SynthesisConfig> {
+        let config = SynthesisConfig::new(self.model.model()?)
+            .scheduler(self.scheduler)
+            .rigid(self.rigid)
+            .quasi_ss(self.quasi_ss)
+            .fairness(self.fairness)
+            .delta_steps(self.delta_steps)
+            .initial_conditions(self.initial)
+            .no_multiplicity(self.model.no_multiplicity)
+            .pan_build(self.pan_build.clone())
+            .memory_limits(self.memory_limits)
+            .variants(self.variants)
+            .group_classes(self.group_classes)
+            .parallelism(if self.sequential {
+                Parallelism::Sequential
+            } else {
+                Parallelism::Parallel
+            })
+            .output(match self.output {
+                Some(ref path) => ReportOutput::Tee(path.clone()),
+                None => ReportOutput::Stdout,
+            })
+            .workdir(WorkdirStrategy::from(&self.workdir))
+            .output_mode(synthesis::output_mode(self.force, self.append));
+        config.check_output()?;
+        Ok(config)
+    }
+
+    pub fn run(&self) -> Result<()> {
+        let config = self.config()?;
+        let model = config.models[0];
+        let text = match &self.input {
+            Some(path) => std::fs::read_to_string(path)?,
+            None => std::io::read_to_string(std::io::stdin())?,
+        };
+        let algos = synthesis::read_algorithm_codes(&text, model)?;
+        if algos.is_empty() {
+            anyhow::bail!("no algorithm of model {model} found in the input");
+        }
+
+        let mut output = synthesis::open_output(&config.output, config.output_mode)?;
+        writeln!(output, "Run options: {:?}", self)?;
+        let metadata = metadata::RunMetadata::collect(&config).with_model_hash(config.model_hash());
+        metadata.write(&mut output)?;
+        for path in config.output.files() {
+            metadata.save_next_to(&path)?;
+        }
+        writeln!(output, "Verifying {} algorithms", algos.len())?;
+        self.interrupt.install()?;
+        synthesis::verify_list(&mut output, &config, model, self.scheduler, algos, &())?;
+        Ok(())
+    }
+}
+
+/// Verifies the algorithms listed in a file (one code string per line, or the report of a previous run whose PASS
+/// algorithms are analyzed) under each scheduler and the four variants of the model (rigid or not, quasi self-stabilizing
+/// or not), and writes the matrix of the outcomes as a Markdown or LaTeX table (see [analyze])
+#[derive(Clone, Debug, Parser)]
+#[command(author, version, long_about = None)]
+pub struct AnalyzeArgs {
+    #[command(flatten)]
+    model: ModelArgs,
+
+    /// File listing the algorithms (standard input if omitted)
+    #[arg(short = 'i', long = "input", value_name = "FILE")]
+    input: Option<PathBuf>,
+
+    /// Schedulers of the columns, comma-separated
+    #[arg(
+        short = 's',
+        long = "sched",
+        value_enum,
+        value_delimiter = ',',
+        action = clap::ArgAction::Set,
+        default_value = "centralized,fsync,ssync,async"
+    )]
+    schedulers: Vec<common::Scheduler>,
+
+    /// Fairness assumption of the verification
+    #[arg(long = "fairness", value_enum, default_value = "weak")]
+    fairness: Fairness,
+
+    /// Non-rigid moves: maximal number of moves stopped short before the robots are within reach (1 in the original model)
+    #[arg(long = "delta-steps", value_name = "K", default_value_t = DEFAULT_DELTA_STEPS, value_parser = clap::value_parser!(u8).range(1..))]
+    delta_steps: u8,
+
+    #[command(flatten)]
+    initial: InitialConditions,
+
+    /// Format of the table
+    #[arg(long = "format", value_enum, default_value = "markdown")]
+    format: analyze::TableFormat,
+
+    /// Output file of the table (stdout by default)
+    #[arg(short = 'o', long = "out")]
+    output: Option<PathBuf>,
+
+    /// Model checker (native: built-in model checker for the centralized, FSYNC and SSYNC schedulers, spin otherwise)
+    #[arg(long = "backend", value_enum, default_value = "spin")]
+    backend: synthesis::Backend,
+
+    #[command(flatten)]
+    pan_build: PanBuildOptions,
+
+    #[command(flatten)]
+    workdir: WorkdirArgs,
+}
+
+impl AnalyzeArgs {
+    pub fn run(&self) -> Result<()> {
+        let model = self.model.model()?;
+        let text = match &self.input {
+            Some(path) => std::fs::read_to_string(path)?,
+            None => std::io::read_to_string(std::io::stdin())?,
+        };
+        let algos = synthesis::read_algorithm_codes(&text, model)?;
+        if algos.is_empty() {
+            anyhow::bail!("no algorithm of model {model} found in the input");
+        }
+        let options = ModelRunOptions {
+            fairness: self.fairness,
+            delta_steps: self.delta_steps,
+            initial: self.initial,
+            no_multiplicity: self.model.no_multiplicity,
+            ..ModelRunOptions::new(self.schedulers[0])
+        };
+
+        let needs_spin = self.schedulers.iter().any(|&scheduler| {
+            ModelRunOptions {
+                scheduler,
+                ..options
+            }
+            .variants()
+            .into_iter()
+            .any(|variant| self.backend.uses_spin(variant))
+        });
+        let workdir = if needs_spin {
+            self.workdir.create()?
+        } else {
+            runner::create_temp_workdir()?
+        };
+        let progress = indicatif::ProgressBar::new(algos.len() as u64);
+        let matrix = runner::create_enclosure(workdir.path()).and_then(|enclosure| {
+            let verifier = self.backend.verifier(&enclosure, &self.pan_build);
+            analyze::analyze(&verifier, algos, &self.schedulers, options, &mut |_| {
+                progress.inc(1)
+            })
+        });
+        progress.finish_and_clear();
+        runner::close_workdir(workdir)?;
+
+        let matrix = matrix?;
+        match &self.output {
+            Some(path) => {
+                let mut file = std::fs::File::create(path)
+                    .with_context(|| format!("failed to create {}", path.display()))?;
+                matrix.write(&mut file, self.format)?;
+                println!(
+                    "Outcomes of {} algorithms written to {}",
+                    matrix.rows.len(),
+                    path.display()
+                );
+            }
+            None => matrix.write(&mut std::io::stdout(), self.format)?,
+        }
+        Ok(())
+    }
+}
+
+/// Runs a synthesis on an HPC cluster: splits the viable algorithms into shards, submits them as a job array to the
+/// batch system, waits for the completion of the shards, and merges their reports into one (see [cluster])
+#[derive(Clone, Debug, Parser)]
+#[command(author, version, long_about = None)]
+pub struct ClusterArgs {
+    /// Batch system of the cluster
+    #[arg(long = "system", value_enum, default_value = "slurm")]
+    system: cluster::BatchSystem,
+
+    /// Number of shards (tasks of the job array)
+    #[arg(long = "shards", value_name = "N", default_value_t = 10, value_parser = clap::value_parser!(u32).range(1..))]
+    shards: u32,
+
+    /// Template of the job array script instead of the default one of the batch system ({name}, {last}, {dir} and {command} are replaced)
+    #[arg(long = "template", value_name = "FILE")]
+    template: Option<PathBuf>,
+
+    /// Directory of the job array script and of the reports of the shards (created if needed)
+    #[arg(long = "dir", value_name = "DIR", default_value = "cluster")]
+    dir: PathBuf,
+
+    /// Interval between two checks of the completion of the shards, in seconds
+    #[arg(long = "poll", value_name = "SECS", default_value_t = 30)]
+    poll: u64,
+
+    /// Fails if the shards are not all complete after this duration (e.g., 12h, 2d), listing those missing a status
+    #[arg(long = "timeout", value_name = "DURATION", value_parser = interrupt::parse_duration)]
+    timeout: Option<std::time::Duration>,
+
+    /// Submits the job array without waiting for its completion (merge the shards later with --merge-only)
+    #[arg(long = "no-wait", conflicts_with = "merge_only")]
+    no_wait: bool,
+
+    /// Only waits for the shards submitted before in the directory and merges their reports
+    #[arg(long = "merge-only")]
+    merge_only: bool,
+
+    /// Arguments of the synthesis, as for `synth-lights synth` (the merged report is written to its output)
+    #[arg(last = true, required = true, value_name = "SYNTH_ARGS")]
+    args: Vec<String>,
+}
+
+impl ClusterArgs {
+    pub fn run(&self) -> Result<()> {
+        let args = std::iter::once(OsString::from("synth"))
+            .chain(self.args.iter().map(OsString::from))
+            .collect();
+        let cli = Cli::try_parse_from(preset::expand_presets(args)?)?;
+        if cli.sample.is_some() || cli.append {
+            anyhow::bail!("--sample and --append are not supported in a cluster run");
+        }
+        let config = cli.config()?;
+        let [(model, scheduler)] = config.runs()[..] else {
+            anyhow::bail!("a cluster run has a single model and scheduler");
+        };
+        std::fs::create_dir_all(&self.dir)?;
+        let dir = self.dir.canonicalize()?;
+
+        let (shards, job) = if self.merge_only {
+            (cluster::read_shards(&dir)?, cluster::Job::read(&dir)?)
+        } else {
+            log::info!("Counting the viable algorithms of {model}");
+            let count = synthesis::generate_and_filter(&config, model, &[]).count();
+            let shards = cluster::shards(config.offset, count, self.shards as usize);
+            if shards.is_empty() {
+                anyhow::bail!("no viable algorithm to verify");
+            }
+            cluster::write_shards(&dir, &shards)?;
+            let template = match &self.template {
+                Some(path) => std::fs::read_to_string(path)
+                    .with_context(|| format!("failed to read template {}", path.display()))?,
+                None => self.system.default_template().to_string(),
+            };
+            let command =
+                cluster::task_command(&dir, &std::env::current_exe()?, &self.args, &shards);
+            let name = format!("synth-lights-{model}-{scheduler}");
+            let script = dir.join(cluster::SCRIPT_FILE);
+            let last = shards.len() - 1;
+            std::fs::write(
+                &script,
+                cluster::render(&template, &name, last, &dir, &command),
+            )?;
+            let submitted = cluster::submit(self.system, &script)?;
+            println!(
+                "Submitted {} shards of {count} algorithms to {}: {submitted}",
+                shards.len(),
+                self.system
+            );
+            let job = self.system.job_id(&submitted).map(|id| cluster::Job {
+                system: self.system,
+                id,
+            });
+            match job {
+                Some(ref job) => job.save(&dir)?,
+                None => {
+                    let _ = std::fs::remove_file(dir.join(cluster::JOB_FILE));
+                    log::warn!(
+                        "no job id in the output of {}: the tasks killed by the batch system are only detected with --timeout",
+                        self.system.submit_command()
+                    );
+                }
+            }
+            (shards, job)
+        };
+        if self.no_wait {
+            println!(
+                "Merge them once complete with --merge-only --dir {}",
+                dir.display()
+            );
+            return Ok(());
+        }
+        cluster::wait(
+            &dir,
+            &shards,
+            std::time::Duration::from_secs(self.poll),
+            self.timeout,
+            job.as_ref(),
+        )?;
+
+        let mut output = synthesis::open_output(&config.output, config.output_mode)?;
+        writeln!(output, "Run options: {:?}", cli)?;
+        writeln!(
+            output,
+            "Cluster run: {} shards in {}",
+            shards.len(),
+            dir.display()
+        )?;
+        cluster::merge(&dir, &shards, &mut output)?;
+        Ok(())
+    }
+}
+
+/// Synthesizes the correct algorithms of a model by an enumeration pruned with the clauses learnt from the
+/// counterexamples (see [pruning]), without verifying all the algorithms; experimental, and limited to the centralized,
+/// FSYNC and SSYNC schedulers
+#[derive(Clone, Debug, Parser)]
+#[command(author, version, long_about = None)]
+pub struct PruneArgs {
+    #[command(flatten)]
+    model: ModelArgs,
+
+    /// Scheduler of the model (centralized, fsync or ssync)
+    #[arg(short = 's', long = "sched", value_enum, default_value = "fsync")]
+    scheduler: common::Scheduler,
+
+    /// Rigid moves restriction (otherwise non-rigid)
+    #[arg(long = "rigid")]
+    rigid: bool,
+
+    /// Quasi self-stabilizing restriction (otherwise self-stabilizing)
+    #[arg(short = 'Q', long = "quasi-ss")]
+    quasi_ss: bool,
+
+    /// Stops at the first correct algorithm
+    #[arg(long = "first")]
+    first: bool,
+}
+
+impl PruneArgs {
+    pub fn run(&self) -> Result<()> {
+        let model = self.model.model()?;
+        let options = ModelRunOptions {
+            rigid: self.rigid,
+            quasi_ss: self.quasi_ss,
+            no_multiplicity: self.model.no_multiplicity,
+            ..ModelRunOptions::new(self.scheduler)
+        };
+        let mut search = pruning::PrunedEnumeration::new(model, options)?;
+        println!(
+            "Model: {model} {} {}",
+            options.scheduler,
+            options.variant_name()
+        );
+        let mut n_found = 0;
+        for algo in search.by_ref() {
+            n_found += 1;
+            println!("{n_found:4} : PASS {}", algo?.as_code());
+            if self.first {
+                break;
+            }
+        }
+        println!(
+            "Found {n_found} algorithms with {} candidates verified ({} counterexamples) out of {} algorithms",
+            search.candidates().to_formatted_string(&Locale::en),
+            search.counterexamples().to_formatted_string(&Locale::en),
+            search.space().to_formatted_string(&Locale::en),
+        );
+        Ok(())
+    }
+}
+
+/// Compares the outcomes of the algorithms in two reports (e.g., before and after a change of the Promela model)
+#[derive(Clone, Debug, Parser)]
+#[command(author, version, long_about = None)]
+pub struct DiffArgs {
+    /// Report of the first run
+    before: PathBuf,
+
+    /// Report of the second run
+    after: PathBuf,
+}
+
+impl DiffArgs {
+    pub fn run(&self) -> Result<()> {
+        let before = report::Report::read(&self.before)?;
+        let after = report::Report::read(&self.after)?;
+        let changes = report::diff(&before, &after);
+        for change in changes.iter() {
+            println!("{change}");
+        }
+        println!(
+            "{} algorithms changed outcome ('-': not listed, i.e., fail or not verified)",
+            changes.len()
+        );
+        Ok(())
+    }
+}
+
+/// Merges the reports of the shards of a run (e.g., slices verified on several machines) into one consolidated report,
+/// after checking that they were run with the same options and that their ranges of indices do not overlap
+#[derive(Clone, Debug, Parser)]
+#[command(author, version, long_about = None)]
+pub struct MergeArgs {
+    /// Reports of the shards (in JSON lines if their extension is jsonl or json)
+    #[arg(required = true)]
+    reports: Vec<PathBuf>,
+
+    /// Merged report (standard output if omitted)
+    #[arg(short = 'o', long = "out", value_name = "FILE")]
+    output: Option<PathBuf>,
+
+    /// Overwrites the merged report if it already exists
+    #[arg(long = "force")]
+    force: bool,
+}
+
+impl MergeArgs {
+    pub fn run(&self) -> Result<()> {
+        let reports = self
+            .reports
+            .iter()
+            .map(|path| {
+                Ok(merge::ShardReport {
+                    name: path.display().to_string(),
+                    report: report::Report::read(path)?,
+                })
+            })
+            .collect::<Result<Vec<_>>>()?;
+        let merged = merge::merge(&reports)?;
+        for gap in merged.gaps() {
+            log::warn!(
+                "indices {}..{} are not verified by any shard",
+                gap.start,
+                gap.end
+            );
+        }
+        let output = match self.output {
+            Some(ref path) => ReportOutput::File(path.clone()),
+            None => ReportOutput::Stdout,
+        };
+        let mode = synthesis::output_mode(self.force, false);
+        synthesis::check_output(&output, mode)?;
+        merged.write(&mut synthesis::open_output(&output, mode)?)
+    }
+}
+
+/// Checks that the algorithms listed in reports have the indices recorded in an enumeration manifest
+/// (e.g., before combining the reports of slices verified on different machines or with different versions)
+#[derive(Clone, Debug, Parser)]
+#[command(author, version, long_about = None)]
+pub struct CheckManifestArgs {
+    /// Manifest of the enumeration (see --write-manifest)
+    manifest: PathBuf,
+
+    /// Reports to check
+    #[arg(required = true)]
+    reports: Vec<PathBuf>,
+}
+
+impl CheckManifestArgs {
+    pub fn run(&self) -> Result<()> {
+        let manifest = manifest::Manifest::read(&self.manifest)?;
+        println!(
+            "Manifest of model {} ({}, enumeration version {}): {} algorithms",
+            manifest.model,
+            manifest.enumeration,
+            manifest.version,
+            manifest.hashes.len()
+        );
+        if manifest.version != manifest::ENUMERATION_VERSION {
+            anyhow::bail!(
+                "manifest of enumeration version {} instead of {}",
+                manifest.version,
+                manifest::ENUMERATION_VERSION
+            );
+        }
+        for path in self.reports.iter() {
+            let report = report::Report::read(path)?;
+            let n_checked = manifest
+                .check_report(&report)
+                .with_context(|| format!("report {} differs from the manifest", path.display()))?;
+            println!("{}: {n_checked} algorithms match", path.display());
+        }
+        Ok(())
+    }
+}
+
+/// Checks that the environment can run verifications (spin and clang in the PATH, working directory, build of the verifier)
+#[derive(Clone, Debug, Parser)]
+#[command(author, version, long_about = None)]
+pub struct DoctorArgs {
+    #[arg(short = 'r', long = "ramdisk")]
+    ramdisk: Option<String>,
+}
+
+impl DoctorArgs {
+    pub fn run(&self) -> Result<()> {
+        let checks = doctor::run_checks(self.ramdisk.clone());
+        for check in checks.iter() {
+            println!("{check}");
+        }
+        let n_failed = checks.iter().filter(|c| !c.is_ok()).count();
+        if n_failed > 0 {
+            anyhow::bail!("{n_failed} checks failed");
+        }
+        println!("The environment is ready");
+        Ok(())
+    }
+}
+
+/// Verifies the reference algorithms of the bundled corpus (see [selftest]) and compares the outcomes with the expected
+/// ones, to detect changes of the semantics of the Promela model
+#[derive(Clone, Debug, Parser)]
+#[command(author, version, long_about = None)]
+pub struct SelftestArgs {
+    /// Model checker (native: built-in model checker for the centralized, FSYNC and SSYNC schedulers, spin otherwise)
+    #[arg(long = "backend", value_enum, default_value = "spin")]
+    backend: synthesis::Backend,
+
+    /// Verifies only the reference algorithms whose name contains PATTERN
+    #[arg(long = "name", value_name = "PATTERN")]
+    name: Option<String>,
+
+    /// Lists the reference algorithms with their expected outcomes, without verifying them
+    #[arg(long = "list")]
+    list: bool,
+
+    #[command(flatten)]
+    pan_build: PanBuildOptions,
+
+    #[command(flatten)]
+    workdir: WorkdirArgs,
+}
+
+impl SelftestArgs {
+    pub fn run(&self) -> Result<()> {
+        let references = selftest::select(self.name.as_deref()).collect::<Vec<_>>();
+        if references.is_empty() {
+            anyhow::bail!("no reference algorithm matches {:?}", self.name);
+        }
+        if self.list {
+            for reference in references {
+                println!("{:<22} {}", reference.name, reference.code);
+                println!("{:<22} {}", "", reference.description);
+                for expected in reference.expected {
+                    let options = expected.options();
+                    println!(
+                        "{:<22} {} {}: {}",
+                        "",
+                        expected.scheduler,
+                        options.variant_name(),
+                        expected.outcome
+                    );
+                }
+            }
+            return Ok(());
+        }
+
+        let expectations = selftest::expectations(references).collect::<Vec<_>>();
+        let needs_spin = expectations
+            .iter()
+            .any(|(_, e)| self.backend.uses_spin(e.options()));
+        let workdir = if needs_spin {
+            self.workdir.create()?
+        } else {
+            runner::create_temp_workdir()?
+        };
+        let outcome = runner::create_enclosure(workdir.path()).map(|enclosure| {
+            let verifier = self.backend.verifier(&enclosure, &self.pan_build);
+            expectations
+                .into_iter()
+                .progress()
+                .map(|(reference, expected)| selftest::check(reference, expected, &verifier))
+                .collect::<Vec<_>>()
+        });
+        runner::close_workdir(workdir)?;
+
+        let checks = outcome?;
+        for check in checks.iter() {
+            println!("{check}");
+        }
+        let n_failed = checks.iter().filter(|c| !c.is_ok()).count();
+        if n_failed > 0 {
+            anyhow::bail!(
+                "{n_failed} of {} verifications differ from the expected outcomes",
+                checks.len()
+            );
+        }
+        println!(
+            "All {} verifications have the expected outcome",
+            checks.len()
+        );
+        Ok(())
+    }
+}
+
+/// Verifies the reference algorithms of the bundled corpus (see [selftest]) with each number of threads and each kind of
+/// working directory, and reports the verifications per minute of each, to choose `--jobs`, `--shm` or a ramdisk, and
+/// the memory limits of the runs on the host (see [bench])
+#[derive(Clone, Debug, Parser)]
+#[command(author, version, long_about = None)]
+pub struct BenchArgs {
+    /// Numbers of threads, comma-separated (default: powers of two up to the number of cores, and the number of cores)
+    #[arg(short = 'j', long = "threads", value_delimiter = ',', value_parser = clap::value_parser!(u16).range(1..))]
+    threads: Vec<u16>,
+
+    /// Kinds of working directory, comma-separated (a ramdisk needs sudo)
+    #[arg(
+        long = "workdir",
+        value_enum,
+        value_delimiter = ',',
+        default_value = "shm,temp"
+    )]
+    workdirs: Vec<bench::BenchWorkdir>,
+
+    /// Number of times the corpus is verified in each configuration
+    #[arg(long = "rounds", default_value_t = 2, value_parser = clap::value_parser!(u16).range(1..))]
+    rounds: u16,
+
+    /// Model checker (native: built-in model checker for the centralized, FSYNC and SSYNC schedulers, spin otherwise)
+    #[arg(long = "backend", value_enum, default_value = "spin")]
+    backend: synthesis::Backend,
+
+    #[command(flatten)]
+    pan_build: PanBuildOptions,
+}
+
+impl BenchArgs {
+    pub fn run(&self) -> Result<()> {
+        let workload = bench::workload(self.rounds as usize)?;
+        let threads = if self.threads.is_empty() {
+            bench::default_threads(num_cpus::get())
+        } else {
+            self.threads.iter().map(|&n| n as usize).collect()
+        };
+        println!(
+            "Benchmark: {} verifications per configuration, {} threads, {} working directories",
+            workload.len(),
+            threads
+                .iter()
+                .map(|n| n.to_string())
+                .collect::<Vec<_>>()
+                .join("/"),
+            self.workdirs
+                .iter()
+                .map(|w| w.to_string())
+                .collect::<Vec<_>>()
+                .join("/")
+        );
+
+        let mut measures = Vec::new();
+        for &kind in self.workdirs.iter() {
+            let workdir = kind.create()?;
+            let mut outcome = Ok(());
+            for &n in threads.iter() {
+                let measure = bench::measure(kind, workdir.path(), n, &workload, &|enclosure| {
+                    self.backend.verifier(enclosure, &self.pan_build)
+                });
+                match measure {
+                    Ok(measure) => {
+                        println!(
+                            "{kind} / {n} threads: {:.0} verifications/min",
+                            measure.per_minute()
+                        );
+                        measures.push(measure);
+                    }
+                    Err(e) => {
+                        outcome = Err(e);
+                        break;
+                    }
+                }
+            }
+            runner::close_workdir(workdir)?;
+            outcome?;
+        }
+        println!();
+        bench::write_measures(&mut std::io::stdout(), &measures)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ModelKind;
+
+    fn parse(args: &[&str]) -> clap::error::Result<Command> {
+        let args = args.iter().map(OsString::from).collect();
+        App::try_parse_from(with_default_command(args)).map(|app| app.command)
+    }
+
+    #[test]
+    fn test_synth() {
+        let command = parse(&["synth-lights", "-s", "ssync", "E3L"]).unwrap();
+        assert!(matches!(command, Command::Synth(_)));
+        let command = parse(&["synth-lights", "synth", "--list-only", "F2"]).unwrap();
+        assert!(matches!(command, Command::Synth(cli) if cli.config().unwrap().list_only));
+        assert!(parse(&["synth-lights"]).is_err());
+    }
+
+    #[test]
+    fn test_log_args() {
+        let command = parse(&["synth-lights", "-vv", "--no-log-file", "E3L"]).unwrap();
+        assert_eq!(command.log_args().verbose, 2);
+        assert_eq!(command.log_args().log_path(), None);
+        assert!(parse(&["synth-lights", "-q", "-v", "E3L"]).is_err());
+    }
+
+    #[test]
+    fn test_preset() {
+        let args = ["synth-lights", "--preset", "heatmap-full", "-s", "ssync"]
+            .map(OsString::from)
+            .to_vec();
+        let args = with_default_command(preset::expand_presets(args).unwrap());
+        let Command::Synth(cli) = App::try_parse_from(args).unwrap().command else {
+            panic!("expected synth subcommand")
+        };
+        let config = cli.config().unwrap();
+        assert_eq!(config.schedulers, vec![common::Scheduler::SSYNC]);
+        assert_eq!(config.models.len(), 3);
+        assert!(matches!(config.output, ReportOutput::Tee(_)));
+    }
+
+    #[test]
+    fn test_workdir_args() {
+        assert!(parse(&["synth-lights", "--shm", "-r", "MyRamDisk", "E3L"]).is_err());
+        let Command::Synth(cli) = parse(&["synth-lights", "--shm", "E3L"]).unwrap() else {
+            panic!("expected synth subcommand")
+        };
+        assert_eq!(cli.config().unwrap().workdir, WorkdirStrategy::Shm);
+        let Command::Synth(cli) =
+            parse(&["synth-lights", "--reuse-workdir", "-r", "X", "E3L"]).unwrap()
+        else {
+            panic!("expected synth subcommand")
+        };
+        assert_eq!(
+            cli.config().unwrap().workdir,
+            WorkdirStrategy::ReusedRamdisk(Some("X".into()))
+        );
+    }
+
+    #[test]
+    fn test_count() {
+        let Command::Count(args) =
+            parse(&["synth-lights", "count", "-L", "external", "3"]).unwrap()
+        else {
+            panic!("expected count subcommand")
+        };
+        assert_eq!(
+            args.model.model().unwrap(),
+            Model::from((ModelKind::External, 3, true))
+        );
+        assert!(parse(&["synth-lights", "count", "--json", "--latex", "E3L"]).is_err());
+        let Command::Count(args) = parse(&["synth-lights", "count", "--json", "E3L"]).unwrap()
+        else {
+            panic!("expected count subcommand")
+        };
+        assert!(args.as_json && args.run().is_ok());
+    }
+
+    #[test]
+    fn test_promela() {
+        let command = parse(&["synth-lights", "promela", "0_1_2__S2_H0_O1"]).unwrap();
+        assert!(command.run().is_err());
+        let Command::Promela(args) = parse(&[
+            "synth-lights",
+            "promela",
+            "--no-multiplicity",
+            "external",
+            "3",
+            "0_1_2__S2_H0_O1",
+        ])
+        .unwrap() else {
+            panic!("expected promela subcommand")
+        };
+        assert!(args.algorithm.algorithm().unwrap().class_L());
+    }
+
+    #[test]
+    fn test_dot() {
+        let Command::Dot(args) = parse(&["synth-lights", "dot", "E3L", "0_1_2__S2_H0_O1"]).unwrap()
+        else {
+            panic!("expected dot subcommand")
+        };
+        assert_eq!(args.algorithm.algorithm().unwrap().num_colors(), 3);
+    }
+
+    #[test]
+    fn test_simulate() {
+        let Command::Simulate(args) = parse(&[
+            "synth-lights",
+            "simulate",
+            "--colors",
+            "0,1",
+            "E3L:0_1_2__S2_H0_O1",
+        ])
+        .unwrap() else {
+            panic!("expected simulate subcommand")
+        };
+        assert_eq!(args.colors, ["0", "1"]);
+        assert_eq!(args.scheduler, common::Scheduler::SSYNC);
+    }
+
+    #[test]
+    fn test_check() {
+        assert!(parse(&["synth-lights", "check", "-s", "ssync", "-a", "algo.pml"]).is_ok());
+        assert!(parse(&[
+            "synth-lights",
+            "check",
+            "--reduce",
+            "--ma",
+            "64",
+            "--pan-define",
+            "BITSTATE"
+        ])
+        .is_ok());
+        let Command::Check(args) = parse(&["synth-lights", "check", "--no-multiplicity"]).unwrap()
+        else {
+            panic!("expected check subcommand")
+        };
+        assert!(args.no_multiplicity);
+    }
+
+    #[test]
+    fn test_minimize() {
+        let Command::Minimize(args) = parse(&[
+            "synth-lights",
+            "minimize",
+            "-s",
+            "ssync",
+            "E3L:0_1_2__S2_H0_O1",
+        ])
+        .unwrap() else {
+            panic!("expected minimize subcommand")
+        };
+        assert_eq!(args.scheduler, common::Scheduler::SSYNC);
+        assert!(args.algorithm.algorithm().is_ok());
+        let Command::Minimize(args) = parse(&[
+            "synth-lights",
+            "minimize",
+            "--no-multiplicity",
+            "E3L:0_1_2__S2_H0_O1",
+        ])
+        .unwrap() else {
+            panic!("expected minimize subcommand")
+        };
+        assert!(args.algorithm.no_multiplicity && args.algorithm.algorithm().is_ok());
+    }
+
+    #[test]
+    fn test_verify_batch() {
+        let Command::VerifyBatch(args) = parse(&[
+            "synth-lights",
+            "verify-batch",
+            "-i",
+            "results.txt",
+            "--variants",
+            "all",
+            "F2L",
+        ])
+        .unwrap() else {
+            panic!("expected verify-batch subcommand")
+        };
+        let config = args.config().unwrap();
+        assert_eq!(config.variants_to_check(args.scheduler).len(), 4);
+    }
+
+    #[test]
+    fn test_diff() {
+        assert!(matches!(
+            parse(&["synth-lights", "diff", "a.txt", "b.txt"]).unwrap(),
+            Command::Diff(_)
+        ));
+    }
+
+    #[test]
+    fn test_check_manifest() {
+        assert!(parse(&["synth-lights", "check-manifest", "manifest.txt"]).is_err());
+        assert!(matches!(
+            parse(&["synth-lights", "check-manifest", "manifest.txt", "a.txt", "b.txt"]).unwrap(),
+            Command::CheckManifest(args) if args.reports.len() == 2
+        ));
+    }
+
+    #[test]
+    fn test_doctor() {
+        assert!(matches!(
+            parse(&["synth-lights", "doctor", "-r", "MyRamDisk"]).unwrap(),
+            Command::Doctor(args) if args.ramdisk.as_deref() == Some("MyRamDisk")
+        ));
+    }
+
+    #[test]
+    fn test_selftest() {
+        let Command::Selftest(args) = parse(&[
+            "synth-lights",
+            "selftest",
+            "--backend",
+            "native",
+            "--name",
+            "to-",
+        ])
+        .unwrap() else {
+            panic!("expected selftest subcommand")
+        };
+        assert_eq!(args.backend, synthesis::Backend::Native);
+        assert!(args.clone().run().is_ok());
+        assert!(SelftestArgs {
+            list: true,
+            ..args.clone()
+        }
+        .run()
+        .is_ok());
+        let none = SelftestArgs {
+            name: Some("no-such-algorithm".into()),
+            ..args
+        };
+        assert!(none.run().is_err());
+    }
+
+    #[test]
+    fn test_bench() {
+        let Command::Bench(args) =
+            parse(&["synth-lights", "bench", "-j", "1,4", "--workdir", "temp"]).unwrap()
+        else {
+            panic!("expected bench subcommand")
+        };
+        assert_eq!(args.threads, [1, 4]);
+        assert_eq!(args.workdirs, [bench::BenchWorkdir::Temp]);
+        assert!(parse(&["synth-lights", "bench", "-j", "0"]).is_err());
+    }
+}
@@ -1,10 +1,14 @@
 use anyhow::Result;
 use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
 pub use strum::IntoEnumIterator;
 use strum::{Display, EnumIter, EnumString};
 
-#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord)]
+#[derive(
+    Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize,
+)]
 #[repr(transparent)]
+#[serde(transparent)]
 pub struct Color(pub u8);
 
 impl TryFrom<&str> for Color {
@@ -22,21 +26,52 @@ impl TryFrom<String> for Color {
     }
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, EnumIter)]
+#[derive(
+    Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, EnumIter, Serialize, Deserialize,
+)]
 pub enum Move {
+    #[default]
     Stay,
     ToHalf,
     ToOther,
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, EnumString, Display, EnumIter)]
+#[derive(
+    Debug,
+    Clone,
+    Copy,
+    Default,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Hash,
+    EnumString,
+    Display,
+    EnumIter,
+    Serialize,
+    Deserialize,
+)]
 pub enum Distance {
+    #[default]
     Same,
     Near,
     Far,
 }
 
-#[derive(ValueEnum, Debug, Display, Clone, Copy, PartialEq, Eq, EnumString, EnumIter)]
+#[derive(
+    ValueEnum,
+    Debug,
+    Display,
+    Clone,
+    Copy,
+    PartialEq,
+    Eq,
+    EnumString,
+    EnumIter,
+    Serialize,
+    Deserialize,
+)]
 #[allow(non_camel_case_types)]
 #[allow(clippy::upper_case_acronyms)]
 pub enum Scheduler {
@@ -101,15 +136,80 @@ impl std::fmt::Display for Move {
     }
 }
 
-impl Default for Move {
-    fn default() -> Self {
-        Move::Stay
+/// repertoire of moves admissible in the actions of the algorithms (all moves by default), which always includes
+/// [Move::Stay] since gathered robots must stay. It restricts the moves of the model ([Move]) and cannot add new
+/// ones: a new move would also need its semantics in the Promela model (`Robots.pml`) and in the native checker.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Moves(u8);
+
+impl Moves {
+    pub const ALL: Moves = Moves(0b111);
+
+    fn bit(movement: Move) -> u8 {
+        1 << movement as u8
+    }
+
+    pub fn new(moves: impl IntoIterator<Item = Move>) -> Result<Self> {
+        let moves = Moves(moves.into_iter().fold(0, |bits, m| bits | Self::bit(m)));
+        if !moves.contains(Move::Stay) {
+            anyhow::bail!("the moves must include STAY (gathered robots must stay)");
+        }
+        Ok(moves)
+    }
+
+    pub fn contains(&self, movement: Move) -> bool {
+        self.0 & Self::bit(movement) != 0
+    }
+
+    pub fn is_all(&self) -> bool {
+        *self == Self::ALL
+    }
+
+    /// admissible moves, in the order of [Move::iter].
+    pub fn iter(self) -> impl Iterator<Item = Move> + Clone {
+        Move::iter().filter(move |&m| self.contains(m))
+    }
+
+    /// number of admissible moves.
+    pub fn count(&self) -> usize {
+        self.0.count_ones() as usize
     }
 }
 
-impl Default for Distance {
+impl Default for Moves {
     fn default() -> Self {
-        Distance::Same
+        Self::ALL
+    }
+}
+
+impl TryFrom<&str> for Moves {
+    type Error = anyhow::Error;
+
+    /// comma-separated list of moves (e.g., `S,O` or `stay,to_other`).
+    fn try_from(value: &str) -> Result<Self> {
+        let moves = value
+            .split(',')
+            .map(|m| Move::try_from(m.trim()))
+            .collect::<Result<Vec<_>>>()?;
+        Self::new(moves)
+    }
+}
+
+impl std::str::FromStr for Moves {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        Self::try_from(s)
+    }
+}
+
+impl std::fmt::Display for Moves {
+    /// codes of the moves (e.g., `SO`).
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for movement in self.iter() {
+            write!(f, "{}", movement.as_code())?;
+        }
+        Ok(())
     }
 }
 
@@ -161,6 +261,27 @@ impl Scheduler {
     pub fn as_promela(&self) -> String {
         self.to_string().to_uppercase()
     }
+
+    /// the strongest schedulers below this one (e.g., SSYNC for ASYNC_LC_Strict), in the order of declaration.
+    pub fn next_weaker(&self) -> Vec<Scheduler> {
+        let weaker = Scheduler::iter().filter(|s| s < self).collect::<Vec<_>>();
+        weaker
+            .iter()
+            .copied()
+            .filter(|s| !weaker.iter().any(|w| s < w))
+            .collect()
+    }
+
+    /// chain of weaker schedulers up to this one (excluded), from the weakest: the first of the strongest schedulers
+    /// below each (see [Scheduler::next_weaker]). An algorithm failing under one of them also fails under this one.
+    pub fn escalation(&self) -> Vec<Scheduler> {
+        let mut chain = std::iter::successors(self.next_weaker().first().copied(), |s| {
+            s.next_weaker().first().copied()
+        })
+        .collect::<Vec<_>>();
+        chain.reverse();
+        chain
+    }
 }
 
 #[derive(Debug, PartialEq, Eq)]
@@ -208,6 +329,24 @@ mod tests {
         assert_eq!(iter.next(), None);
     }
 
+    #[test]
+    fn test_moves() {
+        assert_eq!(Moves::default(), Moves::ALL);
+        assert_eq!(
+            Moves::ALL.iter().collect::<Vec<_>>(),
+            Move::iter().collect::<Vec<_>>()
+        );
+        let moves = Moves::try_from("to_other, S").unwrap();
+        assert_eq!(
+            moves.iter().collect::<Vec<_>>(),
+            [Move::Stay, Move::ToOther]
+        );
+        assert_eq!((moves.count(), moves.to_string()), (2, "SO".to_string()));
+        assert!(!moves.contains(Move::ToHalf) && !moves.is_all());
+        assert!(Moves::try_from("H,O").is_err());
+        assert!(Moves::try_from("S,X").is_err());
+    }
+
     #[test]
     fn test_distance() {
         assert!(Distance::Same < Distance::Far);
@@ -250,13 +389,11 @@ mod tests {
         for lhs in Scheduler::iter() {
             for rhs in Scheduler::iter() {
                 for via in Scheduler::iter() {
-                    match lhs.partial_cmp(&via) {
-                        outcome @ (Some(Less) | Some(Greater)) => {
-                            if via.partial_cmp(&rhs) == outcome {
-                                assert_eq!(lhs.partial_cmp(&rhs), outcome);
-                            }
-                        }
-                        _ => {}
+                    let outcome = lhs.partial_cmp(&via);
+                    if matches!(outcome, Some(Less) | Some(Greater))
+                        && via.partial_cmp(&rhs) == outcome
+                    {
+                        assert_eq!(lhs.partial_cmp(&rhs), outcome);
                     }
                 }
             }
@@ -270,4 +407,31 @@ mod tests {
             assert_eq!(sched.partial_cmp(&sched), Some(Equal));
         }
     }
+
+    #[test]
+    fn test_next_weaker() {
+        use Scheduler::*;
+        assert_eq!(Centralized.next_weaker(), vec![]);
+        assert_eq!(SSYNC.next_weaker(), vec![Centralized, FSYNC]);
+        assert_eq!(ASYNC_LC_Strict.next_weaker(), vec![SSYNC]);
+        assert_eq!(
+            ASYNC.next_weaker(),
+            vec![ASYNC_LC_Atomic, ASYNC_CM_Atomic, ASYNC_Move_Safe]
+        );
+        assert_eq!(ASYNC_Safe.next_weaker(), vec![ASYNC_Regular]);
+    }
+
+    #[test]
+    fn test_escalation() {
+        use Scheduler::*;
+        assert_eq!(FSYNC.escalation(), vec![]);
+        assert_eq!(SSYNC.escalation(), vec![Centralized]);
+        assert_eq!(
+            ASYNC.escalation(),
+            vec![Centralized, SSYNC, ASYNC_LC_Strict, ASYNC_LC_Atomic]
+        );
+        for sched in Scheduler::iter() {
+            assert!(sched.escalation().iter().all(|s| s < &sched));
+        }
+    }
 }
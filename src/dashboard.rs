@@ -0,0 +1,441 @@
+//! Interactive dashboard of a synthesis run on the terminal (standard error), enabled with `--dashboard` (cargo feature
+//! `tui`).
+//!
+//! The dashboard is a [RunObserver] showing the current model and phase, the progress with rate and ETA, the outcome
+//! counts, and the PASS algorithms found so far, most recent first. It takes over the terminal (alternate screen) until
+//! the end of the run, in place of the bare progress bar of the parallel verification, and reads the keys:
+//! `q` (or ctrl-c) stops the run as an interruption does (see [interrupt]), the arrows scroll the PASS algorithms,
+//! and `r` redraws the screen.
+
+use anyhow::{Context, Result};
+use ratatui::backend::{Backend, CrosstermBackend};
+use ratatui::crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+use ratatui::crossterm::execute;
+use ratatui::crossterm::terminal::{
+    disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen,
+};
+use ratatui::layout::{Constraint, Layout};
+use ratatui::style::{Color, Style, Stylize};
+use ratatui::text::Line;
+use ratatui::widgets::{Block, Gauge, List, ListItem, Paragraph};
+use ratatui::{Frame, Terminal};
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread::JoinHandle;
+use std::time::{Duration, Instant};
+
+use crate::algorithm::Algorithm;
+use crate::common::Scheduler;
+use crate::interrupt;
+use crate::model::Model;
+use crate::observer::{Phase, RunObserver};
+use crate::runner::SpinOutcome;
+
+/// number of PASS algorithms kept for the list (the most recent ones).
+const MAX_PASS: usize = 1000;
+/// interval between two updates of the screen, during which the keys are read.
+const REFRESH_INTERVAL: Duration = Duration::from_millis(250);
+/// interval between two complete redraws of the screen, which erase the lines written by other means (e.g., logs).
+const FULL_REDRAW_INTERVAL: Duration = Duration::from_secs(5);
+/// exit code of the process when stopped at once (as for a second SIGINT, see [interrupt]).
+const EXIT_CODE: i32 = 130;
+
+/// state of the current run shown by the dashboard.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct DashboardState {
+    /// current model and scheduler, with the phase of the run
+    pub current: Option<(Model, Scheduler, Phase)>,
+    /// start of the verification
+    pub verify_start: Option<Instant>,
+    pub n_generated: usize,
+    pub n_pass: usize,
+    pub n_fail: usize,
+    pub n_incomplete: usize,
+    pub n_errors: usize,
+    /// most recent PASS algorithms (index, code), most recent last
+    pub recent_pass: VecDeque<(usize, String)>,
+}
+
+impl DashboardState {
+    /// number of algorithms verified (with an outcome or an error).
+    pub fn n_verified(&self) -> usize {
+        self.n_pass + self.n_fail + self.n_incomplete + self.n_errors
+    }
+
+    /// verifications per second since the start of the verification, if started.
+    pub fn rate(&self) -> Option<f64> {
+        let elapsed = self.verify_start?.elapsed().as_secs_f64();
+        (elapsed > 0.0).then(|| self.n_verified() as f64 / elapsed)
+    }
+
+    /// estimated time to verify the algorithms generated so far, at the current rate.
+    pub fn eta(&self) -> Option<Duration> {
+        let remaining = self.n_generated.saturating_sub(self.n_verified());
+        let rate = self.rate().filter(|&rate| rate > 0.0)?;
+        Duration::try_from_secs_f64(remaining as f64 / rate).ok()
+    }
+}
+
+/// action requested by a key.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum KeyAction {
+    None,
+    /// redraws the whole screen
+    Redraw,
+    /// stops the run as an interruption
+    Interrupt,
+    /// exits at once (second interruption)
+    Exit,
+}
+
+/// view of the dashboard: offset of the list of PASS algorithms, changed by the keys.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct View {
+    /// number of PASS algorithms skipped at the top of the list (the most recent ones)
+    pub scroll: usize,
+}
+
+impl View {
+    /// handles a key, given the number of PASS algorithms in the list.
+    pub fn on_key(&mut self, key: KeyEvent, n_pass: usize, interrupted: bool) -> KeyAction {
+        let ctrl = key.modifiers.contains(KeyModifiers::CONTROL);
+        match key.code {
+            KeyCode::Char('c') if ctrl && interrupted => KeyAction::Exit,
+            KeyCode::Char('c') if ctrl => KeyAction::Interrupt,
+            KeyCode::Char('l') if ctrl => KeyAction::Redraw,
+            KeyCode::Char('q') | KeyCode::Esc => KeyAction::Interrupt,
+            KeyCode::Char('r') => KeyAction::Redraw,
+            KeyCode::Down | KeyCode::Char('j') => {
+                self.scroll = (self.scroll + 1).min(n_pass.saturating_sub(1));
+                KeyAction::None
+            }
+            KeyCode::Up | KeyCode::Char('k') => {
+                self.scroll = self.scroll.saturating_sub(1);
+                KeyAction::None
+            }
+            KeyCode::Home => {
+                self.scroll = 0;
+                KeyAction::None
+            }
+            _ => KeyAction::None,
+        }
+    }
+}
+
+/// draws the state on the frame.
+pub fn render(frame: &mut Frame, state: &DashboardState, view: View, interrupted: bool) {
+    let [header, gauge, counts, list, footer] = Layout::vertical([
+        Constraint::Length(1),
+        Constraint::Length(3),
+        Constraint::Length(1),
+        Constraint::Min(3),
+        Constraint::Length(1),
+    ])
+    .areas(frame.area());
+
+    let title = match state.current {
+        Some((model, scheduler, phase)) => format!("{model} / {scheduler}: {phase}"),
+        None => "starting".to_string(),
+    };
+    let status = if interrupted { "  (stopping)" } else { "" };
+    frame.render_widget(
+        Paragraph::new(Line::from(vec![title.bold(), status.red()])),
+        header,
+    );
+
+    let n_verified = state.n_verified();
+    let ratio = match state.n_generated {
+        0 => 0.0,
+        n => (n_verified as f64 / n as f64).min(1.0),
+    };
+    let rate = state
+        .rate()
+        .map_or(String::new(), |rate| format!("  {rate:.1}/s"));
+    let eta = state.eta().map_or(String::new(), |eta| {
+        format!(
+            "  ETA {}",
+            interrupt::format_duration(Duration::from_secs(eta.as_secs()))
+        )
+    });
+    frame.render_widget(
+        Gauge::default()
+            .block(Block::bordered().title(" progress "))
+            .gauge_style(Style::default().fg(Color::Cyan))
+            .ratio(ratio)
+            .label(format!("{n_verified}/{}{rate}{eta}", state.n_generated)),
+        gauge,
+    );
+
+    frame.render_widget(
+        Paragraph::new(Line::from(vec![
+            format!("pass: {}", state.n_pass).green(),
+            format!("  fail: {}", state.n_fail).into(),
+            format!("  incomplete: {}", state.n_incomplete).yellow(),
+            format!("  errors: {}", state.n_errors).red(),
+        ])),
+        counts,
+    );
+
+    let items = state
+        .recent_pass
+        .iter()
+        .rev()
+        .skip(view.scroll)
+        .map(|(i, code)| ListItem::new(format!("{i:4} : PASS {code}")))
+        .collect::<Vec<_>>();
+    frame.render_widget(
+        List::new(items).block(Block::bordered().title(format!(
+            " PASS algorithms ({}, most recent first) ",
+            state.n_pass
+        ))),
+        list,
+    );
+
+    frame.render_widget(
+        Paragraph::new("q: stop the run (as ctrl-c)   \u{2191}/\u{2193}: scroll   r: redraw").dim(),
+        footer,
+    );
+}
+
+pub struct Dashboard {
+    state: Arc<Mutex<DashboardState>>,
+    /// drawing thread, with its stop flag (none if hidden)
+    screen: Option<(Arc<AtomicBool>, JoinHandle<Result<()>>)>,
+}
+
+impl Dashboard {
+    /// dashboard drawn on the standard error, which takes over the terminal until dropped.
+    pub fn new() -> Result<Self> {
+        let state = Arc::new(Mutex::new(DashboardState::default()));
+        enable_raw_mode().context("failed to set up the terminal for the dashboard")?;
+        let terminal = execute!(std::io::stderr(), EnterAlternateScreen)
+            .map_err(anyhow::Error::from)
+            .and_then(|_| Ok(Terminal::new(CrosstermBackend::new(std::io::stderr()))?));
+        let terminal = match terminal {
+            Ok(terminal) => terminal,
+            Err(e) => {
+                restore_terminal();
+                return Err(e.context("failed to set up the terminal for the dashboard"));
+            }
+        };
+        let stop = Arc::new(AtomicBool::new(false));
+        let thread = {
+            let state = state.clone();
+            let stop = stop.clone();
+            std::thread::spawn(move || {
+                let outcome = draw_until_stopped(terminal, &state, &stop);
+                restore_terminal();
+                outcome
+            })
+        };
+        Ok(Self {
+            state,
+            screen: Some((stop, thread)),
+        })
+    }
+
+    /// dashboard keeping the state of the run without drawing it.
+    pub fn hidden() -> Self {
+        Self {
+            state: Default::default(),
+            screen: None,
+        }
+    }
+
+    /// current state.
+    pub fn state(&self) -> DashboardState {
+        self.state.lock().expect("dashboard state").clone()
+    }
+
+    fn update<F: FnOnce(&mut DashboardState)>(&self, action: F) {
+        action(&mut self.state.lock().expect("dashboard state"));
+    }
+}
+
+impl Drop for Dashboard {
+    fn drop(&mut self) {
+        if let Some((stop, thread)) = self.screen.take() {
+            stop.store(true, Ordering::SeqCst);
+            match thread.join() {
+                Ok(Ok(())) => {}
+                Ok(Err(e)) => log::warn!("dashboard failed: {e:#}"),
+                Err(_) => log::warn!("dashboard failed"),
+            }
+        }
+    }
+}
+
+/// draws the state until the stop flag is set, and handles the keys.
+fn draw_until_stopped<B: Backend>(
+    mut terminal: Terminal<B>,
+    state: &Mutex<DashboardState>,
+    stop: &AtomicBool,
+) -> Result<()> {
+    let mut view = View::default();
+    let mut last_redraw = Instant::now();
+    terminal.clear()?;
+    while !stop.load(Ordering::SeqCst) {
+        if last_redraw.elapsed() >= FULL_REDRAW_INTERVAL {
+            terminal.clear()?;
+            last_redraw = Instant::now();
+        }
+        let current = state.lock().expect("dashboard state").clone();
+        let interrupted = interrupt::is_interrupted();
+        terminal.draw(|frame| render(frame, &current, view, interrupted))?;
+        if !event::poll(REFRESH_INTERVAL)? {
+            continue;
+        }
+        let Event::Key(key) = event::read()? else {
+            continue;
+        };
+        if key.kind != KeyEventKind::Press {
+            continue;
+        }
+        match view.on_key(key, current.recent_pass.len(), interrupted) {
+            KeyAction::None => {}
+            KeyAction::Redraw => {
+                terminal.clear()?;
+                last_redraw = Instant::now();
+            }
+            KeyAction::Interrupt => interrupt::interrupt(),
+            KeyAction::Exit => {
+                restore_terminal();
+                std::process::exit(EXIT_CODE);
+            }
+        }
+    }
+    Ok(())
+}
+
+/// leaves the alternate screen and the raw mode of the terminal.
+fn restore_terminal() {
+    let _ = execute!(std::io::stderr(), LeaveAlternateScreen);
+    let _ = disable_raw_mode();
+}
+
+impl RunObserver for Dashboard {
+    fn on_phase_change(&self, model: Model, scheduler: Scheduler, phase: Phase) {
+        self.update(|state| {
+            if phase == Phase::Prepare {
+                *state = DashboardState::default();
+            }
+            if phase == Phase::Verify {
+                state.verify_start = Some(Instant::now());
+            }
+            state.current = Some((model, scheduler, phase));
+        });
+    }
+
+    fn on_generated(&self, _index: usize, _algo: &Algorithm) {
+        self.update(|state| state.n_generated += 1);
+    }
+
+    fn on_outcome(
+        &self,
+        index: usize,
+        algo: &Algorithm,
+        outcome: SpinOutcome,
+        _variants: &[SpinOutcome],
+    ) {
+        self.update(|state| match outcome {
+            SpinOutcome::Pass => {
+                state.n_pass += 1;
+                state.recent_pass.push_back((index, algo.as_code()));
+                if state.recent_pass.len() > MAX_PASS {
+                    state.recent_pass.pop_front();
+                }
+            }
+            SpinOutcome::Fail => state.n_fail += 1,
+            // inconclusive either way
+            SpinOutcome::OutOfMemory | SpinOutcome::SearchIncomplete => state.n_incomplete += 1,
+        });
+    }
+
+    fn on_error(&self, _index: usize, _algo: &Algorithm, _error: &anyhow::Error) {
+        self.update(|state| state.n_errors += 1);
+    }
+
+    fn shows_progress(&self) -> bool {
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ModelKind;
+    use ratatui::backend::TestBackend;
+
+    #[test]
+    fn test_dashboard() {
+        let dashboard = Dashboard::hidden();
+        let model = Model::from((ModelKind::External, 3, true));
+        let algos = ["0_1_2__S2_H0_O1", "0_1_2__S2_H1_O0"]
+            .map(|code| Algorithm::try_parse(ModelKind::External, 3, true, code).unwrap());
+
+        dashboard.on_phase_change(model, Scheduler::ASYNC, Phase::Prepare);
+        algos
+            .iter()
+            .enumerate()
+            .for_each(|(i, a)| dashboard.on_generated(i, a));
+        dashboard.on_phase_change(model, Scheduler::ASYNC, Phase::Verify);
+        dashboard.on_outcome(0, &algos[0], SpinOutcome::Pass, &[SpinOutcome::Pass]);
+        dashboard.on_error(1, &algos[1], &anyhow::Error::msg("broken"));
+        let state = dashboard.state();
+        assert_eq!((state.n_generated, state.n_pass, state.n_errors), (2, 1, 1));
+        assert_eq!(
+            state.recent_pass,
+            VecDeque::from([(0, "0_1_2__S2_H0_O1".to_string())])
+        );
+
+        let mut terminal = Terminal::new(TestBackend::new(80, 12)).unwrap();
+        terminal
+            .draw(|frame| render(frame, &state, View::default(), false))
+            .unwrap();
+        let screen = terminal
+            .backend()
+            .buffer()
+            .content()
+            .iter()
+            .map(|cell| cell.symbol())
+            .collect::<String>();
+        assert!(screen.contains("E3L / ASYNC: Verify"));
+        assert!(screen.contains("2/2"));
+        assert!(screen.contains("pass: 1"));
+        assert!(screen.contains("0 : PASS 0_1_2__S2_H0_O1"));
+
+        dashboard.on_phase_change(model, Scheduler::SSYNC, Phase::Prepare);
+        let state = dashboard.state();
+        assert_eq!(state.n_generated, 0);
+        assert_eq!(
+            state.current,
+            Some((model, Scheduler::SSYNC, Phase::Prepare))
+        );
+    }
+
+    #[test]
+    fn test_view_keys() {
+        let key = |code| KeyEvent::new(code, KeyModifiers::NONE);
+        let ctrl_c = KeyEvent::new(KeyCode::Char('c'), KeyModifiers::CONTROL);
+        let mut view = View::default();
+        assert_eq!(view.on_key(key(KeyCode::Down), 3, false), KeyAction::None);
+        view.on_key(key(KeyCode::Down), 3, false);
+        view.on_key(key(KeyCode::Down), 3, false);
+        assert_eq!(view.scroll, 2);
+        view.on_key(key(KeyCode::Up), 3, false);
+        assert_eq!(view.scroll, 1);
+        view.on_key(key(KeyCode::Home), 3, false);
+        assert_eq!(view.scroll, 0);
+        assert_eq!(
+            view.on_key(key(KeyCode::Char('q')), 3, false),
+            KeyAction::Interrupt
+        );
+        assert_eq!(view.on_key(ctrl_c, 3, false), KeyAction::Interrupt);
+        assert_eq!(view.on_key(ctrl_c, 3, true), KeyAction::Exit);
+        assert_eq!(
+            view.on_key(key(KeyCode::Char('r')), 3, false),
+            KeyAction::Redraw
+        );
+    }
+}
@@ -0,0 +1,174 @@
+//! Adaptive search depth of the `pan` processes (`--adaptive-depth`): a search truncated by the maximal depth of pan
+//! (`-m`, see [PanBuildOptions::depth](crate::runner::PanBuildOptions::depth)) makes the verification incomplete, and
+//! re-running each incomplete algorithm by hand with a larger depth is tedious. With a [DepthTuner] set by
+//! [set_adaptive_depth], the verifications started by [crate::runner] use its depth, which is raised as soon as too
+//! many of the verifications at the current depth are truncated; the adjustments are listed in the report.
+
+use std::sync::Mutex;
+
+/// number of verifications at the same depth before deciding whether to raise it.
+pub const WINDOW: usize = 20;
+
+/// fraction of the verifications of a window truncated by the depth from which the depth is raised.
+pub const THRESHOLD: f64 = 0.1;
+
+/// factor by which the depth is raised.
+pub const FACTOR: usize = 4;
+
+/// depth beyond which the depth is not raised (pan allocates its stack for the whole depth).
+pub const MAX_DEPTH: usize = 10_000_000;
+
+/// raise of the search depth, with the verifications that caused it.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Adjustment {
+    pub from: usize,
+    pub to: usize,
+    /// verifications at the previous depth truncated by the depth
+    pub n_truncated: usize,
+    /// verifications at the previous depth
+    pub n_verified: usize,
+}
+
+impl std::fmt::Display for Adjustment {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "search depth raised from {} to {} ({} of {} verifications incomplete at depth {})",
+            self.from, self.to, self.n_truncated, self.n_verified, self.from
+        )
+    }
+}
+
+/// search depth tuned from the outcomes of the verifications.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DepthTuner {
+    depth: usize,
+    n_verified: usize,
+    n_truncated: usize,
+    adjustments: Vec<Adjustment>,
+}
+
+impl DepthTuner {
+    pub fn new(depth: usize) -> Self {
+        Self {
+            depth,
+            n_verified: 0,
+            n_truncated: 0,
+            adjustments: Vec::new(),
+        }
+    }
+
+    /// depth of the next verifications.
+    pub fn depth(&self) -> usize {
+        self.depth
+    }
+
+    /// adjustments made so far.
+    pub fn adjustments(&self) -> &[Adjustment] {
+        &self.adjustments
+    }
+
+    /// records a verification run at the given depth, truncated or not by the depth, and raises the depth at the end
+    /// of a window with too many truncated verifications. Verifications at an older depth (e.g., completed by
+    /// another thread after a raise) are ignored.
+    pub fn record(&mut self, depth: usize, truncated: bool) -> Option<Adjustment> {
+        if depth != self.depth {
+            return None;
+        }
+        self.n_verified += 1;
+        self.n_truncated += truncated as usize;
+        if self.n_verified < WINDOW {
+            return None;
+        }
+        let adjustment = Adjustment {
+            from: self.depth,
+            to: (self.depth * FACTOR).min(MAX_DEPTH),
+            n_truncated: self.n_truncated,
+            n_verified: self.n_verified,
+        };
+        let raise = adjustment.to > adjustment.from
+            && self.n_truncated as f64 >= THRESHOLD * self.n_verified as f64;
+        self.n_verified = 0;
+        self.n_truncated = 0;
+        if !raise {
+            return None;
+        }
+        self.depth = adjustment.to;
+        self.adjustments.push(adjustment);
+        Some(adjustment)
+    }
+}
+
+static TUNER: Mutex<Option<DepthTuner>> = Mutex::new(None);
+
+/// sets the tuner of the depth of the pan processes started from now on (none: the depth of their build options).
+pub fn set_adaptive_depth(tuner: Option<DepthTuner>) {
+    *TUNER.lock().unwrap() = tuner;
+}
+
+/// depth of the next verification: the depth of the tuner if set, or else the given depth.
+pub fn search_depth(depth: usize) -> usize {
+    TUNER.lock().unwrap().as_ref().map_or(depth, |t| t.depth())
+}
+
+/// records a verification in the tuner, if set (see [DepthTuner::record]).
+pub fn record(depth: usize, truncated: bool) {
+    if let Some(ref mut tuner) = *TUNER.lock().unwrap() {
+        if let Some(adjustment) = tuner.record(depth, truncated) {
+            log::info!("Adaptive depth: {adjustment}");
+        }
+    }
+}
+
+/// adjustments made by the tuner, if set.
+pub fn adjustments() -> Vec<Adjustment> {
+    TUNER
+        .lock()
+        .unwrap()
+        .as_ref()
+        .map_or_else(Vec::new, |t| t.adjustments().to_vec())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_depth_tuner() {
+        let mut tuner = DepthTuner::new(100_000);
+        // a window with few truncated verifications keeps the depth
+        for i in 0..WINDOW {
+            assert_eq!(tuner.record(100_000, i == 0), None);
+        }
+        assert_eq!(tuner.depth(), 100_000);
+        // too many truncated verifications raise it at the end of the window
+        for i in 0..WINDOW - 1 {
+            assert_eq!(tuner.record(100_000, i < 3), None);
+        }
+        let raised = Adjustment {
+            from: 100_000,
+            to: 400_000,
+            n_truncated: 4,
+            n_verified: WINDOW,
+        };
+        assert_eq!(tuner.record(100_000, true), Some(raised));
+        assert_eq!(tuner.depth(), 400_000);
+        // late verifications at the previous depth are ignored
+        for _ in 0..WINDOW {
+            assert_eq!(tuner.record(100_000, true), None);
+        }
+        assert_eq!(tuner.adjustments().len(), 1);
+        assert_eq!(
+            tuner.adjustments()[0].to_string(),
+            "search depth raised from 100000 to 400000 (4 of 20 verifications incomplete at depth 100000)"
+        );
+
+        // the depth is not raised beyond the maximum
+        let mut tuner = DepthTuner::new(MAX_DEPTH / 2);
+        for _ in 0..2 * WINDOW {
+            tuner.record(tuner.depth(), true);
+        }
+        assert_eq!(tuner.depth(), MAX_DEPTH);
+        assert_eq!(tuner.adjustments().len(), 1);
+    }
+}
@@ -0,0 +1,148 @@
+//! Checks of the environment needed by the verification (`synth-lights doctor`).
+//!
+//! Each [Check] runs one step of a verification in isolation (tools in the `PATH`, working directory, build of the verifier),
+//! so that environment problems are reported once with a hint, rather than as an error for each algorithm during a run.
+
+use anyhow::{Context, Result};
+
+use crate::algorithm::Algorithm;
+use crate::common::Scheduler;
+use crate::promela::ModelRunOptions;
+use crate::runner;
+use crate::ModelKind;
+
+/// algorithm compiled to check the toolchain (any valid algorithm would do).
+const PROBE_ALGORITHM: &str = "0_1_2__S2_H0_O1";
+
+/// outcome of one check of the environment.
+#[derive(Debug)]
+pub struct Check {
+    pub name: &'static str,
+    /// details (e.g., version of the tool) if the check succeeds
+    pub outcome: Result<String>,
+    /// how to fix the environment if the check fails
+    pub hint: &'static str,
+}
+
+impl Check {
+    fn new(name: &'static str, hint: &'static str, outcome: Result<String>) -> Self {
+        Self {
+            name,
+            outcome,
+            hint,
+        }
+    }
+
+    pub fn is_ok(&self) -> bool {
+        self.outcome.is_ok()
+    }
+}
+
+impl std::fmt::Display for Check {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.outcome {
+            Ok(details) => write!(f, "[ ok ] {}: {}", self.name, details),
+            Err(e) => write!(
+                f,
+                "[FAIL] {}: {:#}\n       hint: {}",
+                self.name, e, self.hint
+            ),
+        }
+    }
+}
+
+/// first line of the output of a tool called with the given arguments (e.g., its version).
+pub fn tool_version(tool: &str, args: &[&str]) -> Result<String> {
+    let output = duct::cmd(tool, args)
+        .stderr_to_stdout()
+        .unchecked()
+        .read()
+        .with_context(|| format!("{tool} not found in PATH"))?;
+    output
+        .lines()
+        .map(str::trim)
+        .find(|l| !l.is_empty())
+        .map(str::to_string)
+        .ok_or_else(|| anyhow::anyhow!("{tool} printed no version"))
+}
+
+/// runs all checks in order; the checks depending on a failed check are skipped.
+pub fn run_checks(ramdisk: Option<String>) -> Vec<Check> {
+    let mut checks = vec![
+        Check::new(
+            "spin",
+            "install the spin model checker (e.g., `brew install spin`) and add it to the PATH",
+            tool_version("spin", &["-V"]),
+        ),
+        Check::new(
+            "clang",
+            "install clang (e.g., the Xcode command-line tools on macOS, or `apt install clang`)",
+            tool_version("clang", &["--version"]),
+        ),
+    ];
+
+    let workdir = runner::create_root_workdir(ramdisk);
+    let workdir_hint = if cfg!(target_os = "linux") {
+        "check that `sudo mount` is allowed and that no stale volume is mounted under /mnt/tmp"
+    } else {
+        "eject any stale ramdisk (see `df`) and check that `hdiutil`/`diskutil` are available"
+    };
+    let workdir = match workdir {
+        Ok(workdir) => workdir,
+        Err(e) => {
+            checks.push(Check::new("working directory", workdir_hint, Err(e)));
+            return checks;
+        }
+    };
+    let enclosure = runner::create_enclosure(workdir.path());
+    checks.push(Check::new(
+        "working directory",
+        workdir_hint,
+        enclosure
+            .as_ref()
+            .map(|path| path.display().to_string())
+            .map_err(|e| anyhow::anyhow!("{e:#}")),
+    ));
+
+    if let Ok(ref enclosure) = enclosure {
+        if checks.iter().all(Check::is_ok) {
+            let algo = Algorithm::try_parse(ModelKind::External, 3, true, PROBE_ALGORITHM)
+                .expect("valid probe algorithm");
+            let options = ModelRunOptions::new(Scheduler::SSYNC);
+            checks.push(Check::new(
+                "verifier build",
+                "check that the spin and clang versions above work together (spin -a, then clang on pan.c)",
+                runner::compile_model(enclosure, &algo, options)
+                    .map(|_| format!("pan compiled for {PROBE_ALGORITHM}")),
+            ));
+        }
+    }
+
+    checks.push(Check::new(
+        "working directory cleanup",
+        workdir_hint,
+        runner::close_workdir(workdir).map(|_| "closed".to_string()),
+    ));
+    checks
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_checks() {
+        assert!(tool_version("sh", &["-c", "echo '\n  version 1.2\nmore'"])
+            .is_ok_and(|v| v == "version 1.2"));
+        let missing = tool_version("no-such-tool-synth-lights", &["-V"]);
+        assert!(missing.is_err());
+
+        let check = Check::new("tool", "install it", missing);
+        assert!(!check.is_ok());
+        let text = check.to_string();
+        assert!(text.starts_with("[FAIL] tool: no-such-tool-synth-lights not found in PATH"));
+        assert!(text.ends_with("hint: install it"));
+        let check = Check::new("tool", "install it", Ok("1.0".to_string()));
+        assert_eq!(check.to_string(), "[ ok ] tool: 1.0");
+    }
+}
@@ -0,0 +1,126 @@
+//! Graphviz (dot) representation of algorithms, as a graph of color changes labeled with the observation and move.
+
+use anyhow::{Context, Result};
+use dot_writer::{Attributes, Color, DotWriter, Style};
+use std::path::{Path, PathBuf};
+
+use crate::algorithm::{Algorithm, Rule};
+use crate::common::{Color as AlgoColor, Move};
+
+fn movement(mv: Move) -> String {
+    match mv {
+        Move::Stay => "Stay",
+        Move::ToHalf => "Half",
+        Move::ToOther => "Other",
+    }
+    .to_string()
+}
+
+/// generates the dot code of the color-change graph of an algorithm.
+pub fn algo_to_dot(algorithm: &Algorithm) -> String {
+    let mut output_bytes = Vec::new();
+    {
+        let mut writer = DotWriter::from(&mut output_bytes);
+        writer.set_pretty_print(true);
+
+        let mut digraph = writer.digraph();
+        digraph
+            .node_attributes()
+            .set_style(Style::Filled)
+            .set_color(Color::LightGrey);
+        digraph
+            .graph_attributes()
+            .set_label(&format!(
+                "{} {} {}\n{}",
+                algorithm.model_kind(),
+                algorithm.num_colors(),
+                if algorithm.class_L() { "L" } else { "" },
+                algorithm.as_code()
+            ))
+            .set_font("monospace");
+
+        for Rule(guard, action) in algorithm.rules() {
+            let current_states = if let Some(c) = guard.my_color() {
+                vec![c]
+            } else {
+                AlgoColor::iter_ncols(algorithm.num_colors()).collect()
+            };
+            let move_action = movement(action.movement());
+            let color_to = action.color();
+            let label = match (guard.other_color(), guard.is_gathered()) {
+                (Some(c), true) if !algorithm.class_L() => format!("({}G):{}", c, move_action),
+                (Some(c), _) => format!("({}):{}", c, move_action),
+                (None, true) if !algorithm.class_L() => format!("G:{}", move_action),
+                (None, _) => move_action.to_string(),
+            };
+
+            for color_from in current_states {
+                digraph
+                    .edge(color_from.to_string(), color_to.to_string())
+                    .attributes()
+                    .set_label(&label);
+            }
+        }
+    }
+    String::from_utf8(output_bytes).unwrap()
+}
+
+/// renders the color-change graph of an algorithm in svg with graphviz (`dot -Tsvg`, which must be in the PATH).
+pub fn algo_to_svg(algorithm: &Algorithm) -> Result<String> {
+    duct::cmd!("dot", "-Tsvg")
+        .stdin_bytes(algo_to_dot(algorithm))
+        .stdout_capture()
+        .read()
+        .context("failed to render the graph with graphviz (dot)")
+}
+
+/// writes the dot code of each algorithm in the directory (created if needed), in a file named after the code of
+/// the algorithm, and also renders it in svg with graphviz (`dot -Tsvg`) if `svg` is set.
+/// Returns the paths of the files written.
+pub fn export_all(algorithms: &[Algorithm], dir: &Path, svg: bool) -> Result<Vec<PathBuf>> {
+    std::fs::create_dir_all(dir)
+        .with_context(|| format!("failed to create directory {}", dir.display()))?;
+    let mut paths = Vec::new();
+    for algorithm in algorithms {
+        let dot_code = algo_to_dot(algorithm);
+        let path = dir.join(format!("{}.dot", algorithm.as_code()));
+        std::fs::write(&path, &dot_code)
+            .with_context(|| format!("failed to write {}", path.display()))?;
+        paths.push(path);
+        if svg {
+            let path = dir.join(format!("{}.svg", algorithm.as_code()));
+            duct::cmd!("dot", "-Tsvg", "-o", &path)
+                .stdin_bytes(dot_code)
+                .stdout_null()
+                .run()
+                .with_context(|| {
+                    format!("failed to render {} with graphviz (dot)", path.display())
+                })?;
+            paths.push(path);
+        }
+    }
+    Ok(paths)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_export_all() {
+        let algorithms = ["E3L:0_1_2__S2_H0_O1", "E3L:0_1_2__S2_H1_O0"]
+            .map(|code| Algorithm::try_parse_v2(code).unwrap());
+        let dir = std::env::temp_dir().join(format!("dot-{}", uuid::Uuid::new_v4()));
+        let paths = export_all(&algorithms, &dir, false).unwrap();
+        assert_eq!(
+            paths,
+            [
+                dir.join("0_1_2__S2_H0_O1.dot"),
+                dir.join("0_1_2__S2_H1_O0.dot")
+            ]
+        );
+        let dot_code = std::fs::read_to_string(&paths[1]).unwrap();
+        assert_eq!(dot_code, algo_to_dot(&algorithms[1]));
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
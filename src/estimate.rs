@@ -0,0 +1,163 @@
+//! Estimation of the cost of a run before committing to it, enabled with `--dry-run`.
+//!
+//! The viable algorithms are generated and counted, and a few of them (evenly spaced in the enumeration) are
+//! verified to calibrate the time per verification and the disk usage of an enclosure.
+//! The estimate assumes that verifications run in parallel without contention.
+
+use std::io::Write;
+use std::path::Path;
+use std::time::Duration;
+
+use anyhow::Result;
+
+/// estimated wall time and disk usage of the verification of all viable algorithms.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Estimate {
+    pub n_algos: usize,
+    /// number of verifications timed for the calibration
+    pub n_calibrated: usize,
+    /// mean time of a verification (all variants)
+    pub per_algo: Duration,
+    /// number of concurrent verifications
+    pub threads: usize,
+    /// largest size of an enclosure after the verification of an algorithm
+    pub enclosure_bytes: u64,
+}
+
+impl Estimate {
+    /// estimate from the durations of the calibration verifications.
+    pub fn new(
+        n_algos: usize,
+        calibration: &[Duration],
+        threads: usize,
+        enclosure_bytes: u64,
+    ) -> Self {
+        let total = calibration.iter().sum::<Duration>();
+        let per_algo = match calibration.len() {
+            0 => Duration::ZERO,
+            n => total / n as u32,
+        };
+        Self {
+            n_algos,
+            n_calibrated: calibration.len(),
+            per_algo,
+            threads: threads.max(1),
+            enclosure_bytes,
+        }
+    }
+
+    /// estimated wall time of the verification.
+    pub fn wall_time(&self) -> Duration {
+        let rounds = self.n_algos.div_ceil(self.threads);
+        self.per_algo * rounds as u32
+    }
+
+    /// estimated disk usage of the working directory (one enclosure per thread).
+    pub fn disk_bytes(&self) -> u64 {
+        self.enclosure_bytes * self.threads.min(self.n_algos.max(1)) as u64
+    }
+
+    /// writes the estimate in the report.
+    pub fn write(&self, output: &mut dyn Write) -> Result<()> {
+        writeln!(output, "Dry run: {} viable algorithms", self.n_algos)?;
+        writeln!(
+            output,
+            "Calibration: {} verifications, {} ms per algorithm",
+            self.n_calibrated,
+            self.per_algo.as_millis()
+        )?;
+        writeln!(
+            output,
+            "Estimated verification time: {} ({} threads)",
+            format_duration(self.wall_time()),
+            self.threads
+        )?;
+        writeln!(
+            output,
+            "Estimated disk usage: {} ({} per enclosure)",
+            format_bytes(self.disk_bytes()),
+            format_bytes(self.enclosure_bytes)
+        )?;
+        Ok(())
+    }
+}
+
+/// indices of `n` algorithms evenly spaced among `n_algos`.
+pub fn calibration_indices(n_algos: usize, n: usize) -> Vec<usize> {
+    let n = n.min(n_algos);
+    (0..n).map(|k| k * n_algos / n).collect()
+}
+
+/// total size of the files in a directory (recursively).
+pub fn dir_size(path: &Path) -> std::io::Result<u64> {
+    let mut size = 0;
+    for entry in std::fs::read_dir(path)? {
+        let entry = entry?;
+        let metadata = entry.metadata()?;
+        size += if metadata.is_dir() {
+            dir_size(&entry.path())?
+        } else {
+            metadata.len()
+        };
+    }
+    Ok(size)
+}
+
+/// duration in days, hours, minutes, seconds (e.g., `2d 03h 15m 00s`).
+fn format_duration(duration: Duration) -> String {
+    let secs = duration.as_secs();
+    let (days, hours, mins, secs) = (secs / 86400, secs / 3600 % 24, secs / 60 % 60, secs % 60);
+    if days > 0 {
+        format!("{days}d {hours:02}h {mins:02}m {secs:02}s")
+    } else {
+        format!("{hours:02}h {mins:02}m {secs:02}s")
+    }
+}
+
+/// size in bytes with a binary unit (e.g., `12.5 MiB`).
+fn format_bytes(bytes: u64) -> String {
+    const UNITS: [&str; 4] = ["KiB", "MiB", "GiB", "TiB"];
+    if bytes < 1024 {
+        return format!("{bytes} B");
+    }
+    let mut value = bytes as f64 / 1024.0;
+    let mut unit = 0;
+    while value >= 1024.0 && unit + 1 < UNITS.len() {
+        value /= 1024.0;
+        unit += 1;
+    }
+    format!("{value:.1} {}", UNITS[unit])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_estimate() {
+        let calibration = [Duration::from_millis(100), Duration::from_millis(300)];
+        let estimate = Estimate::new(1000, &calibration, 8, 3 << 20);
+        assert_eq!(estimate.per_algo, Duration::from_millis(200));
+        assert_eq!(estimate.wall_time(), Duration::from_millis(200 * 125));
+        assert_eq!(estimate.disk_bytes(), 24 << 20);
+
+        let mut output = Vec::new();
+        estimate.write(&mut output).unwrap();
+        let output = String::from_utf8(output).unwrap();
+        assert!(output.contains("Estimated verification time: 00h 00m 25s (8 threads)"));
+        assert!(output.contains("Estimated disk usage: 24.0 MiB (3.0 MiB per enclosure)"));
+
+        assert_eq!(Estimate::new(0, &[], 0, 0).wall_time(), Duration::ZERO);
+        assert_eq!(
+            format_duration(Duration::from_secs(2 * 86400 + 3600 + 61)),
+            "2d 01h 01m 01s"
+        );
+    }
+
+    #[test]
+    fn test_calibration_indices() {
+        assert_eq!(calibration_indices(100, 4), vec![0, 25, 50, 75]);
+        assert_eq!(calibration_indices(2, 5), vec![0, 1]);
+        assert!(calibration_indices(0, 5).is_empty());
+    }
+}
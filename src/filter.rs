@@ -0,0 +1,500 @@
+//! Viability filters applied to the generated algorithms before verification.
+//!
+//! Each [Filter] is a named structural predicate over an [Algorithm]. A [FilterSet] is an ordered
+//! selection of filters, built from command-line flags through [FilterArgs], and shared by all binaries.
+
+use anyhow::{bail, Context, Result};
+use clap::{Args, ValueEnum};
+use serde::Serialize;
+use strum::{Display, EnumIter, IntoEnumIterator};
+
+use crate::algorithm::{Algorithm, Guard};
+use crate::common::{Move, Moves};
+use crate::report::Entry;
+use crate::runner::SpinOutcome;
+use crate::ModelKind;
+
+/// largest number of colors considered when deducing the model of an algorithm code.
+const MAX_COLORS: u8 = 10;
+
+/// structural predicate deciding whether an algorithm is worth verifying.
+/// Library users can implement this trait to inject custom constraints into a run
+/// (see [crate::run_with_filters]), in addition to the built-in [Filter]s.
+pub trait ViabilityFilter {
+    /// name of the filter, used in reports.
+    fn name(&self) -> String;
+
+    /// returns `true` if the algorithm should be kept.
+    fn accept(&self, algo: &Algorithm) -> bool;
+}
+
+/// filter defined by a name and a closure.
+///
+/// # Examples
+///
+/// ```
+/// # use synth_lights::filter::{FnFilter, ViabilityFilter};
+/// let no_half = FnFilter::new("no-half", |algo| {
+///     algo.rules().all(|r| r.action().movement() != synth_lights::common::Move::ToHalf)
+/// });
+/// assert_eq!(no_half.name(), "no-half");
+/// ```
+pub struct FnFilter<F> {
+    name: String,
+    predicate: F,
+}
+
+impl<F> FnFilter<F>
+where
+    F: Fn(&Algorithm) -> bool,
+{
+    pub fn new(name: impl Into<String>, predicate: F) -> Self {
+        Self {
+            name: name.into(),
+            predicate,
+        }
+    }
+}
+
+impl<F> ViabilityFilter for FnFilter<F>
+where
+    F: Fn(&Algorithm) -> bool,
+{
+    fn name(&self) -> String {
+        self.name.clone()
+    }
+
+    fn accept(&self, algo: &Algorithm) -> bool {
+        (self.predicate)(algo)
+    }
+}
+
+/// filter rejecting the algorithms that embed a known solution with fewer colors (see [Algorithm::embeds]),
+/// typically read from the report of a run with fewer colors (see [ExcludeLower::from_report]).
+pub struct ExcludeLower {
+    lower: Vec<Algorithm>,
+}
+
+impl ExcludeLower {
+    pub fn new(lower: Vec<Algorithm>) -> Self {
+        Self { lower }
+    }
+
+    /// collects the PASS algorithms of a report (see [Entry::parse]) that belong to the given model.
+    /// The number of colors of each algorithm is given by the prefix of its code, or deduced from its number of rules.
+    #[allow(non_snake_case)]
+    pub fn from_report(report: &str, model: ModelKind, class_L: bool) -> Result<Self> {
+        let mut lower = Vec::new();
+        for line in report.lines() {
+            let Some(entry) = Entry::parse(line)? else {
+                continue;
+            };
+            if entry.outcome != SpinOutcome::Pass {
+                continue;
+            }
+            let code = entry.code.as_str();
+            if code.contains(':') {
+                let algo = Algorithm::try_parse_v2(code)?;
+                if algo.model_kind() != model || algo.class_L() != class_L {
+                    bail!("algorithm {code} does not belong to a {model} model");
+                }
+                lower.push(algo);
+                continue;
+            }
+            let n_guards = code
+                .split("__")
+                .next()
+                .unwrap_or_default()
+                .split('_')
+                .count();
+            let Some(n_colors) =
+                (1..=MAX_COLORS).find(|&n| Guard::number_for_model(model, n, class_L) == n_guards)
+            else {
+                bail!("algorithm {code} does not belong to a {model} model");
+            };
+            lower.push(Algorithm::try_parse(model, n_colors, class_L, code)?);
+        }
+        Ok(Self::new(lower))
+    }
+
+    pub fn lower(&self) -> &[Algorithm] {
+        &self.lower
+    }
+}
+
+impl ViabilityFilter for ExcludeLower {
+    fn name(&self) -> String {
+        format!("exclude-lower ({} algorithms)", self.lower.len())
+    }
+
+    fn accept(&self, algo: &Algorithm) -> bool {
+        !self.lower.iter().any(|l| algo.embeds(l))
+    }
+}
+
+/// filter accepting the algorithms whose code (see [Algorithm::as_code]) matches a regular expression, e.g.,
+/// `__S` to require a stationary first action, or `_O1_` for some rule moving to the other with color 1.
+pub struct MatchCode {
+    regex: lazy_regex::Regex,
+}
+
+impl MatchCode {
+    pub fn new(pattern: &str) -> Result<Self> {
+        let regex = lazy_regex::Regex::new(pattern)
+            .with_context(|| format!("invalid regular expression {pattern:?}"))?;
+        Ok(Self { regex })
+    }
+}
+
+impl ViabilityFilter for MatchCode {
+    fn name(&self) -> String {
+        format!("match ({})", self.regex.as_str())
+    }
+
+    fn accept(&self, algo: &Algorithm) -> bool {
+        self.regex.is_match(&algo.as_code())
+    }
+}
+
+#[derive(
+    ValueEnum, Display, EnumIter, Serialize, Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord,
+)]
+#[strum(serialize_all = "kebab-case")]
+#[serde(rename_all = "kebab-case")]
+pub enum Filter {
+    /// all gathered rules are stationary ([Algorithm::all_gathered_are_stay])
+    AllGatheredStay,
+    /// all colors are used in the actions ([Algorithm::all_colors_used_in_actions])
+    AllColorsInActions,
+    /// all colors are used in the non-gathered actions ([Algorithm::all_colors_used_in_non_gathered])
+    AllColorsInNonGathered,
+    /// best-effort reduction of color permutations ([Algorithm::is_pseudo_canonical])
+    PseudoCanonical,
+    /// some non-gathered rule is stationary ([Algorithm::some_non_gathered_is_stay])
+    NonGatheredStay,
+    /// some non-gathered rule moves to half ([Algorithm::some_non_gathered_is_to_half])
+    NonGatheredToHalf,
+    /// some non-gathered rule moves to the other ([Algorithm::some_non_gathered_is_to_other])
+    NonGatheredToOther,
+    /// Viglietta's retain rule ([Algorithm::retains_color_iif_other_color_different])
+    Retain,
+    /// symmetry under exchanging the colors observed by full lights ([Algorithm::is_swap_symmetric])
+    Symmetric,
+    /// strongly connected color transition graph ([Algorithm::has_strongly_connected_colors]); not a necessary
+    /// condition for gathering under all schedulers
+    ColorGraph,
+    /// exact reduction of color permutations ([Algorithm::is_canonical])
+    Canonical,
+}
+
+impl Filter {
+    pub fn accept(&self, algo: &Algorithm) -> bool {
+        match self {
+            Filter::AllGatheredStay => algo.all_gathered_are_stay(),
+            Filter::AllColorsInActions => algo.all_colors_used_in_actions(),
+            Filter::AllColorsInNonGathered => algo.all_colors_used_in_non_gathered(),
+            Filter::PseudoCanonical => algo.is_pseudo_canonical(),
+            Filter::NonGatheredStay => algo.some_non_gathered_is_stay(),
+            Filter::NonGatheredToHalf => algo.some_non_gathered_is_to_half(),
+            Filter::NonGatheredToOther => algo.some_non_gathered_is_to_other(),
+            Filter::Retain => algo.retains_color_iif_other_color_different(),
+            Filter::Symmetric => algo.is_swap_symmetric(),
+            Filter::ColorGraph => algo.has_strongly_connected_colors(),
+            Filter::Canonical => algo.is_canonical(),
+        }
+    }
+
+    /// short human-readable description of the filter, used in reports.
+    pub fn description(&self) -> &'static str {
+        match self {
+            Filter::AllGatheredStay => "all gathered are stay",
+            Filter::AllColorsInActions => "all colors used in actions",
+            Filter::AllColorsInNonGathered => "all colors used in non-gathered",
+            Filter::PseudoCanonical => "is pseudo-canonical",
+            Filter::NonGatheredStay => "some non-gathered is stay",
+            Filter::NonGatheredToHalf => "some non-gathered is to-half",
+            Filter::NonGatheredToOther => "some non-gathered is to-other",
+            Filter::Retain => "retains color iif other is different",
+            Filter::Symmetric => "is swap-symmetric",
+            Filter::ColorGraph => "color graph strongly connected",
+            Filter::Canonical => "is canonical",
+        }
+    }
+
+    /// move that the filter requires in some non-gathered rule, if any.
+    pub fn required_move(&self) -> Option<Move> {
+        match self {
+            Filter::NonGatheredStay => Some(Move::Stay),
+            Filter::NonGatheredToHalf => Some(Move::ToHalf),
+            Filter::NonGatheredToOther => Some(Move::ToOther),
+            _ => None,
+        }
+    }
+
+    /// filters enabled unless explicitly disabled.
+    pub fn is_default(&self) -> bool {
+        !matches!(
+            self,
+            Filter::Retain | Filter::Symmetric | Filter::ColorGraph | Filter::Canonical
+        )
+    }
+
+    /// filters that depend only on the colors and moves used by the rules, whose counts are computed without
+    /// enumerating the algorithms (see [crate::generator::count_filtered_algorithms_in_model]).
+    pub fn is_structural(&self) -> bool {
+        matches!(
+            self,
+            Filter::AllGatheredStay
+                | Filter::AllColorsInActions
+                | Filter::AllColorsInNonGathered
+                | Filter::NonGatheredStay
+                | Filter::NonGatheredToHalf
+                | Filter::NonGatheredToOther
+        )
+    }
+
+    /// filters disabled by weak filtering.
+    pub fn is_strong(&self) -> bool {
+        matches!(
+            self,
+            Filter::NonGatheredStay | Filter::NonGatheredToHalf | Filter::NonGatheredToOther
+        )
+    }
+}
+
+impl ViabilityFilter for Filter {
+    fn name(&self) -> String {
+        self.to_string()
+    }
+
+    fn accept(&self, algo: &Algorithm) -> bool {
+        Filter::accept(self, algo)
+    }
+}
+
+/// ordered set of filters; an algorithm is viable if it is accepted by all of them.
+/// Filters are always applied in the order of [Filter], regardless of the order in which they were enabled.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct FilterSet {
+    filters: Vec<Filter>,
+}
+
+impl Default for FilterSet {
+    fn default() -> Self {
+        Self {
+            filters: Filter::iter().filter(Filter::is_default).collect(),
+        }
+    }
+}
+
+impl FilterSet {
+    /// set without any filter.
+    pub fn empty() -> Self {
+        Self {
+            filters: Vec::new(),
+        }
+    }
+
+    pub fn enable(&mut self, filter: Filter) -> &mut Self {
+        if !self.filters.contains(&filter) {
+            self.filters.push(filter);
+            self.filters.sort();
+        }
+        self
+    }
+
+    pub fn disable(&mut self, filter: Filter) -> &mut Self {
+        self.filters.retain(|f| f != &filter);
+        self
+    }
+
+    pub fn contains(&self, filter: Filter) -> bool {
+        self.filters.contains(&filter)
+    }
+
+    /// disables the filters requiring a move outside of the repertoire, which no algorithm would pass.
+    pub fn restrict_to(&mut self, moves: Moves) -> &mut Self {
+        self.filters
+            .retain(|f| f.required_move().is_none_or(|m| moves.contains(m)));
+        self
+    }
+
+    pub fn filters(&self) -> &[Filter] {
+        &self.filters
+    }
+
+    pub fn accept(&self, algo: &Algorithm) -> bool {
+        self.filters.iter().all(|f| f.accept(algo))
+    }
+}
+
+/// command-line arguments selecting the filters, shared by all binaries.
+#[derive(Args, Debug, Clone, Default)]
+pub struct FilterArgs {
+    /// Enables weak filtering (disables the non-gathered-stay, non-gathered-to-half, and non-gathered-to-other filters)
+    #[arg(short = 'w')]
+    pub weak_filter: bool,
+
+    /// Enables Viglietta's retain rule filtering ("A robot retains its color if and only if it sees the other robot set to a different color.")
+    #[arg(short = 'R')]
+    pub retain_filter: bool,
+
+    /// Enables exact canonical filtering (only one algorithm per class of color permutations)
+    #[arg(short = 'C', long = "canonical")]
+    pub canonical_filter: bool,
+
+    /// Enables swap-symmetric filtering (a robot seeing colors (a, b) acts as one seeing (b, a), with a and b exchanged)
+    #[arg(long = "symmetric")]
+    pub symmetric_filter: bool,
+
+    /// Enables a filter (can be repeated)
+    #[arg(long = "filter", value_enum, value_name = "FILTER")]
+    pub enabled: Vec<Filter>,
+
+    /// Disables a filter (can be repeated; takes precedence over other flags)
+    #[arg(long = "no-filter", value_enum, value_name = "FILTER")]
+    pub disabled: Vec<Filter>,
+}
+
+impl FilterArgs {
+    pub fn filter_set(&self) -> FilterSet {
+        let mut set = FilterSet::default();
+        if self.weak_filter {
+            for f in Filter::iter().filter(Filter::is_strong) {
+                set.disable(f);
+            }
+        }
+        if self.retain_filter {
+            set.enable(Filter::Retain);
+        }
+        if self.canonical_filter {
+            set.enable(Filter::Canonical);
+        }
+        if self.symmetric_filter {
+            set.enable(Filter::Symmetric);
+        }
+        for &f in self.enabled.iter() {
+            set.enable(f);
+        }
+        for &f in self.disabled.iter() {
+            set.disable(f);
+        }
+        set
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_filter_args() {
+        let args = FilterArgs::default();
+        assert_eq!(args.filter_set(), FilterSet::default());
+        assert_eq!(args.filter_set().filters().len(), 7);
+
+        let args = FilterArgs {
+            weak_filter: true,
+            retain_filter: true,
+            symmetric_filter: true,
+            enabled: vec![Filter::NonGatheredStay],
+            disabled: vec![Filter::PseudoCanonical],
+            ..Default::default()
+        };
+        assert_eq!(
+            args.filter_set().filters(),
+            &[
+                Filter::AllGatheredStay,
+                Filter::AllColorsInActions,
+                Filter::AllColorsInNonGathered,
+                Filter::NonGatheredStay,
+                Filter::Retain,
+                Filter::Symmetric,
+            ]
+        );
+        assert_eq!(
+            Filter::NonGatheredToHalf.to_string(),
+            "non-gathered-to-half"
+        );
+    }
+
+    #[test]
+    fn test_viability_filter() {
+        let filters: Vec<Box<dyn ViabilityFilter>> = vec![
+            Box::new(Filter::AllGatheredStay),
+            Box::new(FnFilter::new("single-color", |a: &Algorithm| {
+                a.rules().all(|r| r.action().color().0 == 0)
+            })),
+        ];
+        assert_eq!(filters[0].name(), "all-gathered-stay");
+        assert_eq!(filters[1].name(), "single-color");
+        let n_accepted =
+            crate::generator::generate_algorithms_in_model(crate::ModelKind::External, 2, true)
+                .filter(|a| filters.iter().all(|f| f.accept(a)))
+                .count();
+        assert_eq!(n_accepted, 9);
+    }
+
+    #[test]
+    fn test_match_code() {
+        let filter = MatchCode::new("__S1_S0_").unwrap();
+        assert_eq!(filter.name(), "match (__S1_S0_)");
+        let matching =
+            crate::generator::generate_algorithms_in_model(crate::ModelKind::Full, 2, true)
+                .filter(|a| filter.accept(a))
+                .collect::<Vec<_>>();
+        assert_eq!(matching.len(), 3 * 2 * 3 * 2);
+        assert!(matching.iter().all(|a| a.as_code().contains("__S1_S0_")));
+        assert!(MatchCode::new("(").is_err());
+    }
+
+    #[test]
+    fn test_filter_set_matches_chain() {
+        let set = FilterSet::default();
+        let n_set = crate::generator::generate_algorithms_in_model(crate::ModelKind::Full, 2, true)
+            .filter(|a| set.accept(a))
+            .count();
+        assert_eq!(n_set, 294);
+    }
+
+    #[test]
+    fn test_restrict_to_moves() {
+        let mut set = FilterSet::default();
+        set.restrict_to(Moves::ALL);
+        assert_eq!(set, FilterSet::default());
+        set.restrict_to(Moves::try_from("S,O").unwrap());
+        assert!(!set.contains(Filter::NonGatheredToHalf));
+        assert!(set.contains(Filter::NonGatheredToOther) && set.contains(Filter::NonGatheredStay));
+        assert_eq!(Filter::AllGatheredStay.required_move(), None);
+    }
+
+    #[test]
+    fn test_exclude_lower() {
+        let report = "Run options: ...\n\
+                      \n   0 : PASS 0_1__S0_H1\n\
+                      CLASS >    0 : 0_1__S0_H1 (1 algorithms)\n\
+                      Verification Finished with 1 pass, 0 fail, 0 incomplete, 0 errors (1 algorithms)\n";
+        let filter = ExcludeLower::from_report(report, ModelKind::External, true).unwrap();
+        assert_eq!(filter.lower().len(), 1);
+        assert_eq!(filter.lower()[0].num_colors(), 2);
+
+        let upper = Algorithm::try_parse(ModelKind::External, 3, true, "0_1_2__S0_H1_H0").unwrap();
+        assert!(!filter.accept(&upper));
+        // embedding the lower algorithm requires two colors `a`, `b` with rules `a: S a` and `b: H b`
+        // (accepted: 8^3 algorithms without rule `a: S a`, plus 8^3 without `b: H b`, minus 7^3 with neither)
+        let n_accepted =
+            crate::generator::generate_algorithms_in_model(ModelKind::External, 3, true)
+                .filter(|a| filter.accept(a))
+                .count();
+        assert_eq!(n_accepted, 512 + 512 - 343);
+
+        assert!(ExcludeLower::from_report(report, ModelKind::Full, true).is_err());
+
+        // self-describing codes, and only the PASS algorithms
+        let report = "   0 : PASS E2L:0_1__S0_H1\nFAIL >    1 : FAIL E2L:0_1__S1_H0\n";
+        let filter = ExcludeLower::from_report(report, ModelKind::External, true).unwrap();
+        assert_eq!(filter.lower().len(), 1);
+        assert_eq!(filter.lower()[0].as_code(), "0_1__S0_H1");
+        assert!(ExcludeLower::from_report(report, ModelKind::Full, true).is_err());
+    }
+}
@@ -1,6 +1,67 @@
 use crate::algorithm::*;
 use crate::common::*;
+use crate::filter::{Filter, FilterSet};
+use crate::model::Model;
 use crate::ModelKind;
+use itertools::Itertools;
+use serde::Serialize;
+
+/// guards of the algorithms of a model, in the order used by the generator (and expected by [Algorithm::try_parse]),
+/// to build algorithms by hand with [Algorithm::new].
+///
+/// # Examples
+///
+/// ```
+/// # use synth_lights::algorithm::{Action, Algorithm};
+/// # use synth_lights::common::{Color, Move};
+/// # use synth_lights::generator::guards_for_model;
+/// # use synth_lights::model::Model;
+/// let model = Model::try_from("E3L").unwrap();
+/// let guards = guards_for_model(model);
+/// let actions = [(1, Move::ToHalf), (2, Move::Stay), (0, Move::ToOther)].map(|(c, m)| Action(Color(c), m));
+/// let algo = Algorithm::new(model.n_colors, &guards, &actions);
+/// assert_eq!(algo.as_code(), "0_1_2__H1_S2_O0");
+/// ```
+pub fn guards_for_model(model: Model) -> Vec<Guard> {
+    model_guards(model.category, model.n_colors, model.class_L)
+}
+
+/// builds the list of guards for a given model, in the order used by the generator.
+fn model_guards(model: ModelKind, n_colors: u8, class_l: bool) -> Vec<Guard> {
+    let colors = (0..n_colors).map(Color);
+    let dist = [Distance::Same, Distance::Near].into_iter();
+
+    match model {
+        ModelKind::Full if class_l => {
+            let my_cols = colors.clone();
+            let other_cols = colors;
+            itertools::iproduct!(my_cols, other_cols)
+                .map(|(c1, c2)| Guard::LFull(c1, c2))
+                .collect::<Vec<_>>()
+        }
+        ModelKind::Full => {
+            let my_cols = colors.clone();
+            let other_cols = colors;
+            itertools::iproduct!(dist, my_cols, other_cols)
+                .map(|(d, c1, c2)| Guard::Full(c1, c2, d))
+                .collect::<Vec<_>>()
+        }
+        ModelKind::External if class_l => colors.map(Guard::LExternal).collect::<Vec<_>>(),
+        ModelKind::External => {
+            let other_cols = colors;
+            itertools::iproduct!(dist, other_cols)
+                .map(|(d, c)| Guard::External(c, d))
+                .collect::<Vec<_>>()
+        }
+        ModelKind::Internal if class_l => colors.map(Guard::LInternal).collect::<Vec<_>>(),
+        ModelKind::Internal => {
+            let my_cols = colors;
+            itertools::iproduct!(dist, my_cols)
+                .map(|(d, c)| Guard::Internal(c, d))
+                .collect::<Vec<_>>()
+        }
+    }
+}
 
 /// generates all algorithms for a given model.
 ///
@@ -31,69 +92,383 @@ pub fn generate_algorithms_in_model(
     n_colors: u8,
     class_l: bool,
 ) -> impl Iterator<Item = Algorithm> {
-    let colors = (0..n_colors).map(Color);
-    let dist = [Distance::Same, Distance::Near].into_iter();
+    generate_algorithms_with_moves(model, n_colors, class_l, Moves::ALL)
+}
 
-    let guards = match model {
-        ModelKind::Full if class_l => {
-            let my_cols = colors.clone();
-            let other_cols = colors;
-            itertools::iproduct!(my_cols, other_cols)
-                .map(|(c1, c2)| Guard::LFull(c1, c2))
-                .collect::<Vec<_>>()
+/// generates all algorithms for a given model whose actions only use the given moves, in the order of
+/// [generate_algorithms_in_model] restricted to these algorithms (each guard ranges over fewer actions).
+pub fn generate_algorithms_with_moves(
+    model: ModelKind,
+    n_colors: u8,
+    class_l: bool,
+    moves: Moves,
+) -> impl Iterator<Item = Algorithm> {
+    let guards = model_guards(model, n_colors, class_l);
+
+    OdometerIter::new(n_colors, guards, moves)
+}
+
+/// enumerates all assignments of actions to the guards as a mixed-radix odometer:
+/// each guard is a digit ranging over the actions in the order `(Move, Color)`,
+/// the last guard being the least significant digit.
+/// The current actions are kept in a single buffer updated in place.
+struct OdometerIter {
+    n_colors: u8,
+    guards: Vec<Guard>,
+    digits: Vec<usize>,
+    actions: Vec<Action>,
+    all_actions: Vec<Action>,
+    started: bool,
+}
+
+impl OdometerIter {
+    fn new(n_colors: u8, guards: Vec<Guard>, moves: Moves) -> Self {
+        let all_actions = itertools::iproduct!(moves.iter(), Color::iter_ncols(n_colors))
+            .map(|(m, c)| Action(c, m))
+            .collect::<Vec<_>>();
+        let n_guards = guards.len();
+        OdometerIter {
+            n_colors,
+            guards,
+            digits: vec![0; n_guards],
+            actions: vec![all_actions[0]; n_guards],
+            all_actions,
+            started: false,
         }
-        ModelKind::Full => {
-            let my_cols = colors.clone();
-            let other_cols = colors;
-            itertools::iproduct!(dist, my_cols, other_cols)
-                .map(|(d, c1, c2)| Guard::Full(c1, c2, d))
-                .collect::<Vec<_>>()
+    }
+
+    /// increments the odometer; returns false when it wraps around.
+    fn increment(&mut self) -> bool {
+        for (digit, action) in self.digits.iter_mut().zip(self.actions.iter_mut()).rev() {
+            *digit += 1;
+            if *digit < self.all_actions.len() {
+                *action = self.all_actions[*digit];
+                return true;
+            }
+            *digit = 0;
+            *action = self.all_actions[0];
         }
-        ModelKind::External if class_l => colors.map(Guard::LExternal).collect::<Vec<_>>(),
-        ModelKind::External => {
-            let other_cols = colors;
-            itertools::iproduct!(dist, other_cols)
-                .map(|(d, c)| Guard::External(c, d))
-                .collect::<Vec<_>>()
+        false
+    }
+}
+
+impl Iterator for OdometerIter {
+    type Item = Algorithm;
+
+    fn next(&mut self) -> Option<Algorithm> {
+        if self.guards.is_empty() || self.all_actions.is_empty() {
+            return None;
         }
-        ModelKind::Internal if class_l => colors.map(Guard::LInternal).collect::<Vec<_>>(),
-        ModelKind::Internal => {
-            let my_cols = colors;
-            itertools::iproduct!(dist, my_cols)
-                .map(|(d, c)| Guard::Internal(c, d))
-                .collect::<Vec<_>>()
+        if self.started {
+            if !self.increment() {
+                self.guards.clear();
+                return None;
+            }
+        } else {
+            self.started = true;
         }
-    };
+        Some(Algorithm::new(self.n_colors, &self.guards, &self.actions))
+    }
+}
 
-    let n_guards = guards.len();
-
-    let all_actions_iter = (1..n_guards).fold::<Box<dyn Iterator<Item = Vec<_>>>, _>(
-        Box::new(
-            itertools::iproduct!(Move::iter(), Color::iter_ncols(n_colors))
-                .map(|(m, c)| vec![Action(c, m)]),
-        ),
-        |accum, _| {
-            Box::new(
-                itertools::iproduct!(
-                    accum,
-                    itertools::iproduct!(Move::iter(), Color::iter_ncols(n_colors))
-                        .map(|(m, c)| Action(c, m))
-                )
-                .map::<Vec<_>, _>(|(v, a)| {
-                    let mut v = v;
-                    v.push(a);
-                    v
-                }),
-            )
-        },
-    );
+/// generates only the canonical representatives (see [`Algorithm::is_canonical`]) of all algorithms
+/// for a given model, that is, exactly one algorithm per class of equivalence under color permutation.
+///
+/// Unlike filtering the output of [`generate_algorithms_in_model`], the enumeration is done directly
+/// in canonical form: the moves of the non-gathered guards with same colors are chosen first in
+/// sorted order, and the remaining actions are assigned guard by guard while pruning every prefix that
+/// cannot be completed into a canonical algorithm. Non-canonical candidates are thus never built.
+///
+/// The algorithms are produced in a different order than [`generate_algorithms_in_model`].
+///
+/// # Arguments
+///
+/// * `model`    - kind of model considered ([`ModelKind`]).
+/// * `n_colors` - number of colors
+/// * `class_l`  - flag whether the model is limited to class L algorithms (`true`) or not (`false`)
+///
+pub fn generate_canonical_algorithms_in_model(
+    model: ModelKind,
+    n_colors: u8,
+    class_l: bool,
+) -> impl Iterator<Item = Algorithm> {
+    generate_canonical_algorithms_with_moves(model, n_colors, class_l, Moves::ALL)
+}
+
+/// generates only the canonical representatives of the algorithms of a given model whose actions only use the given
+/// moves (see [generate_canonical_algorithms_in_model]): color permutations preserve the moves.
+pub fn generate_canonical_algorithms_with_moves(
+    model: ModelKind,
+    n_colors: u8,
+    class_l: bool,
+    moves: Moves,
+) -> impl Iterator<Item = Algorithm> {
+    CanonicalIter::new(n_colors, model_guards(model, n_colors, class_l), moves)
+}
+
+/// enumerates canonical algorithms by depth-first search over the actions with prefix pruning.
+struct CanonicalIter {
+    n_colors: u8,
+    guards: Vec<Guard>,
+    moves: Moves,
+    /// positions of the non-gathered guards with same colors (their moves are fixed first).
+    same_pos: Vec<usize>,
+    /// sorted assignments of moves to the positions in `same_pos`.
+    same_moves: Box<dyn Iterator<Item = Vec<Move>>>,
+    /// admissible actions at each position, for the current assignment of `same_moves`.
+    options: Vec<Vec<Action>>,
+    /// non-trivial color permutations preserving the current `same_moves`, together with,
+    /// for each position `j`, the position `i` such that the permutation maps guard `i` onto guard `j`.
+    perms: Vec<(Vec<Color>, Vec<usize>)>,
+    choice: Vec<usize>,
+    actions: Vec<Action>,
+    fresh: bool,
+}
+
+impl CanonicalIter {
+    fn new(n_colors: u8, guards: Vec<Guard>, moves: Moves) -> Self {
+        let same_pos = guards
+            .iter()
+            .positions(|g| !g.is_gathered() && g.same_colors())
+            .collect::<Vec<_>>();
+        let same_moves = Box::new(moves.iter().combinations_with_replacement(same_pos.len()));
+        let n_guards = guards.len();
+        CanonicalIter {
+            n_colors,
+            guards,
+            moves,
+            same_pos,
+            same_moves,
+            options: Vec::new(),
+            perms: Vec::new(),
+            choice: Vec::with_capacity(n_guards),
+            actions: vec![Action(Color(0), Move::Stay); n_guards],
+            fresh: false,
+        }
+    }
+
+    /// moves on to the next assignment of moves to the same-colors guards,
+    /// and prepares the options and the permutations accordingly.
+    fn next_same_moves(&mut self) -> bool {
+        let Some(moves) = self.same_moves.next() else {
+            return false;
+        };
+        let (n_colors, repertoire) = (self.n_colors, self.moves);
+        let mut fixed = vec![None; self.guards.len()];
+        for (&i, &m) in self.same_pos.iter().zip(moves.iter()) {
+            fixed[i] = Some(m);
+        }
+        self.options = fixed
+            .iter()
+            .map(|f| {
+                itertools::iproduct!(repertoire.iter(), Color::iter_ncols(n_colors))
+                    .filter(|(m, _)| f.is_none_or(|f| f == *m))
+                    .map(|(m, c)| Action(c, m))
+                    .collect()
+            })
+            .collect();
+        let guards = &self.guards;
+        self.perms = Color::iter_ncols(n_colors)
+            .permutations(n_colors as usize)
+            .filter(|perm| perm.iter().enumerate().any(|(i, c)| c.0 as usize != i))
+            .filter_map(|perm| {
+                let mut src = vec![0; guards.len()];
+                for (i, g) in guards.iter().enumerate() {
+                    let j = guards
+                        .iter()
+                        .position(|g2| g2 == &g.permute_colors(&perm))
+                        .expect("guard set is not closed under color permutation");
+                    if fixed[i] != fixed[j] {
+                        return None;
+                    }
+                    src[j] = i;
+                }
+                Some((perm, src))
+            })
+            .collect();
+        self.choice.clear();
+        self.fresh = true;
+        true
+    }
+
+    /// checks that no permutation makes the first `k` assigned actions smaller.
+    fn prefix_ok(&self, k: usize) -> bool {
+        self.perms.iter().all(|(perm, src)| {
+            for (j, &i) in src.iter().enumerate() {
+                if j >= k || i >= k {
+                    return true;
+                }
+                let Action(c, m) = self.actions[i];
+                let permuted = Action(perm[c.0 as usize], m);
+                match permuted.cmp(&self.actions[j]) {
+                    std::cmp::Ordering::Less => return false,
+                    std::cmp::Ordering::Greater => return true,
+                    std::cmp::Ordering::Equal => {}
+                }
+            }
+            true
+        })
+    }
+
+    /// advances to the next choice at the deepest level, backtracking as needed.
+    fn bump(&mut self) -> bool {
+        while let Some(c) = self.choice.last_mut() {
+            *c += 1;
+            if *c < self.options[self.choice.len() - 1].len() {
+                return true;
+            }
+            self.choice.pop();
+        }
+        false
+    }
+}
+
+impl Iterator for CanonicalIter {
+    type Item = Algorithm;
+
+    fn next(&mut self) -> Option<Algorithm> {
+        let n_guards = self.guards.len();
+        loop {
+            let mut valid = if self.fresh {
+                self.fresh = false;
+                self.choice.push(0);
+                true
+            } else {
+                self.bump()
+            };
+            while valid {
+                let k = self.choice.len();
+                self.actions[k - 1] = self.options[k - 1][self.choice[k - 1]];
+                if self.prefix_ok(k) {
+                    if k == n_guards {
+                        return Some(Algorithm::new(self.n_colors, &self.guards, &self.actions));
+                    }
+                    self.choice.push(0);
+                } else {
+                    valid = self.bump();
+                }
+            }
+            if !self.next_same_moves() {
+                return None;
+            }
+        }
+    }
+}
+
+/// builds the algorithm at position `index` in the order of [generate_algorithms_in_model],
+/// by decomposing the index in mixed radix: each guard is a digit (the first guard being the most
+/// significant) whose value enumerates the possible actions in the order `(Move, Color)`.
+fn algorithm_at(guards: &[Guard], n_colors: u8, moves: Moves, index: u64) -> Algorithm {
+    let radix = moves.count() as u64 * n_colors as u64;
+    let mut rest = index;
+    let mut actions = vec![Action(Color(0), Move::Stay); guards.len()];
+    for action in actions.iter_mut().rev() {
+        let digit = rest % radix;
+        rest /= radix;
+        let mv = moves
+            .iter()
+            .nth((digit / n_colors as u64) as usize)
+            .expect("digit out of range");
+        *action = Action(Color((digit % n_colors as u64) as u8), mv);
+    }
+    Algorithm::new(n_colors, guards, &actions)
+}
+
+/// returns the algorithm at position `index` in the order of [generate_algorithms_in_model],
+/// without iterating from the start, or `None` if the index is past the last algorithm of the model.
+/// This allows shards, resumes and sampling to jump directly to arbitrary positions.
+///
+/// # Arguments
+///
+/// * `model`    - kind of model considered ([`ModelKind`]).
+/// * `n_colors` - number of colors
+/// * `class_l`  - flag whether the model is limited to class L algorithms (`true`) or not (`false`)
+/// * `index`    - position of the algorithm in the enumeration (starting at 0)
+///
+pub fn nth_algorithm(
+    model: ModelKind,
+    n_colors: u8,
+    class_l: bool,
+    index: u64,
+) -> Option<Algorithm> {
+    if index >= count_algorithms_in_model(model, n_colors, class_l) {
+        return None;
+    }
+    let guards = model_guards(model, n_colors, class_l);
+    Some(algorithm_at(&guards, n_colors, Moves::ALL, index))
+}
+
+/// position of the algorithm in the order of [generate_algorithms_in_model] (the inverse of [nth_algorithm]).
+pub fn algorithm_index(algo: &Algorithm) -> u64 {
+    let n_colors = algo.num_colors() as u64;
+    let radix = Moves::ALL.count() as u64 * n_colors;
+    algo.rules().fold(0, |index, Rule(_, action)| {
+        index * radix + action.movement() as u64 * n_colors + action.color().0 as u64
+    })
+}
+
+/// samples algorithms of a given model uniformly at random, without replacement, each with its position in the
+/// order of [generate_algorithms_in_model] (see [nth_algorithm]).
+/// The iterator ends once every algorithm of the model has been drawn, each draw taking a constant time.
+/// For a given seed, the sequence of algorithms is reproducible.
+///
+/// # Arguments
+///
+/// * `model`    - kind of model considered ([`ModelKind`]).
+/// * `n_colors` - number of colors
+/// * `class_l`  - flag whether the model is limited to class L algorithms (`true`) or not (`false`)
+/// * `seed`     - seed of the random number generator
+///
+pub fn sample_algorithms_in_model(
+    model: ModelKind,
+    n_colors: u8,
+    class_l: bool,
+    seed: u64,
+) -> impl Iterator<Item = (u64, Algorithm)> {
+    sample_algorithms_with_moves(model, n_colors, class_l, Moves::ALL, seed)
+}
+
+/// samples the algorithms of a given model whose actions only use the given moves, as [sample_algorithms_in_model]
+/// (the positions are those in the enumeration of all the algorithms of the model, regardless of the moves).
+pub fn sample_algorithms_with_moves(
+    model: ModelKind,
+    n_colors: u8,
+    class_l: bool,
+    moves: Moves,
+    seed: u64,
+) -> impl Iterator<Item = (u64, Algorithm)> {
+    use rand::{Rng, SeedableRng};
+    use std::collections::HashMap;
 
-    all_actions_iter
-        .map::<Algorithm, _>(move |actions| Algorithm::new(n_colors, &guards, actions.as_slice()))
+    let guards = model_guards(model, n_colors, class_l);
+    let total = count_algorithms_with_moves(model, n_colors, class_l, moves);
+    let mut rng = rand::rngs::StdRng::seed_from_u64(seed);
+    // Fisher-Yates shuffle of 0..total, drawn lazily: only the displaced positions are stored
+    let mut displaced = HashMap::<u64, u64>::new();
+    (0..total).map(move |n| {
+        let k = rng.random_range(n..total);
+        let index = displaced.get(&k).copied().unwrap_or(k);
+        if k != n {
+            displaced.insert(k, displaced.get(&n).copied().unwrap_or(n));
+        }
+        displaced.remove(&n);
+        let algo = algorithm_at(&guards, n_colors, moves, index);
+        (algorithm_index(&algo), algo)
+    })
 }
 
 pub fn count_algorithms_in_model(model: ModelKind, n_colors: u8, class_l: bool) -> u64 {
-    let n_moves = 3;
+    count_algorithms_with_moves(model, n_colors, class_l, Moves::ALL)
+}
+
+/// number of algorithms of a given model whose actions only use the given moves.
+pub fn count_algorithms_with_moves(
+    model: ModelKind,
+    n_colors: u8,
+    class_l: bool,
+    moves: Moves,
+) -> u64 {
+    let n_moves = moves.count() as u64;
     match model {
         ModelKind::Full => {
             let num_guards = n_colors as u32 * n_colors as u32;
@@ -116,6 +491,189 @@ pub fn count_algorithms_in_model(model: ModelKind, n_colors: u8, class_l: bool)
     }
 }
 
+/// number of algorithms of the model accepted by all the filters, computed without enumerating them when all the
+/// filters are structural ([Filter::is_structural]); `None` otherwise, or if the number does not fit in 64 bits.
+///
+/// The colors and the moves of the actions are chosen independently, so the number of algorithms is the product of
+/// the number of color assignments (all colors used in the actions, or in the non-gathered actions) and the number of
+/// move assignments (gathered rules stationary, non-gathered rules using some moves), each counted by
+/// inclusion–exclusion over the colors or moves missing.
+pub fn count_filtered_algorithms_in_model(
+    model: ModelKind,
+    n_colors: u8,
+    class_l: bool,
+    filters: &[Filter],
+) -> Option<u64> {
+    if !filters.iter().all(Filter::is_structural) {
+        return None;
+    }
+    let has = |filter: Filter| filters.contains(&filter);
+    let guards = model_guards(model, n_colors, class_l);
+    let n_gathered = guards.iter().filter(|g| g.is_gathered()).count() as u32;
+    let n_other = guards.len() as u32 - n_gathered;
+    let n_colors = n_colors as u32;
+    let n_moves = 3;
+
+    let colors = if has(Filter::AllColorsInNonGathered) {
+        surjections(n_other, n_colors)?.checked_mul((n_colors as i128).checked_pow(n_gathered)?)?
+    } else if has(Filter::AllColorsInActions) {
+        surjections(n_gathered + n_other, n_colors)?
+    } else {
+        (n_colors as i128).checked_pow(n_gathered + n_other)?
+    };
+    let gathered_moves = if has(Filter::AllGatheredStay) {
+        1
+    } else {
+        (n_moves as i128).checked_pow(n_gathered)?
+    };
+    let required_moves = [
+        Filter::NonGatheredStay,
+        Filter::NonGatheredToHalf,
+        Filter::NonGatheredToOther,
+    ]
+    .into_iter()
+    .filter(|&f| has(f))
+    .count() as u32;
+    let other_moves = inclusion_exclusion(n_other, n_moves, required_moves)?;
+
+    let count = colors
+        .checked_mul(gathered_moves)?
+        .checked_mul(other_moves)?;
+    u64::try_from(count).ok()
+}
+
+/// options of [filter_statistics].
+#[derive(Clone, Debug)]
+pub struct FilterStatsOptions {
+    /// filters, applied in turn
+    pub filters: FilterSet,
+    /// enumerates the algorithms to count the stages that cannot be counted analytically (otherwise, they are omitted)
+    pub enumerate: bool,
+    /// shows a progress bar while enumerating the algorithms
+    pub progress: bool,
+}
+
+impl Default for FilterStatsOptions {
+    fn default() -> Self {
+        Self {
+            filters: FilterSet::default(),
+            enumerate: true,
+            progress: false,
+        }
+    }
+}
+
+/// numbers of algorithms of a model at each stage of filtering (see [filter_statistics]).
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct FilterStats {
+    /// model string (e.g., `E3L`)
+    pub model: String,
+    /// number of algorithms of the model, if it fits in 64 bits
+    pub total: Option<u64>,
+    /// number of algorithms remaining after each filter in turn
+    /// (only the first stages, counted analytically, if the algorithms are not enumerated)
+    pub stages: Vec<FilterStage>,
+}
+
+/// number of algorithms accepted by a filter and the filters before it.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct FilterStage {
+    pub filter: Filter,
+    pub description: &'static str,
+    pub remaining: u64,
+    /// counted without enumerating the algorithms (see [count_filtered_algorithms_in_model])
+    pub analytic: bool,
+}
+
+/// counts the algorithms of the model remaining after each filter in turn: analytically for the first stages, as long
+/// as the filters are structural ([Filter::is_structural]), then by enumerating the algorithms (if enabled).
+pub fn filter_statistics(model: Model, options: &FilterStatsOptions) -> FilterStats {
+    use indicatif::ProgressIterator;
+
+    let filters = options.filters.filters();
+    let count_stage = |k: usize| {
+        count_filtered_algorithms_in_model(
+            model.category,
+            model.n_colors,
+            model.class_L,
+            &filters[..k],
+        )
+    };
+    let total = count_stage(0);
+    let mut stages = filters
+        .iter()
+        .enumerate()
+        .map_while(|(k, &filter)| {
+            Some(FilterStage {
+                filter,
+                description: filter.description(),
+                remaining: count_stage(k + 1)?,
+                analytic: true,
+            })
+        })
+        .collect::<Vec<_>>();
+
+    let n_counted = stages.len();
+    if options.enumerate && n_counted < filters.len() {
+        let mut counts = vec![0u64; filters.len()];
+        let progress = match total {
+            Some(total) if options.progress => indicatif::ProgressBar::new(total),
+            _ => indicatif::ProgressBar::hidden(),
+        };
+        // only the algorithms accepted by the filters counted analytically are checked further
+        for algo in generate_algorithms_in_model(model.category, model.n_colors, model.class_L)
+            .progress_with(progress)
+            .filter(|algo| filters[..n_counted].iter().all(|f| f.accept(algo)))
+        {
+            for (k, f) in filters.iter().enumerate().skip(n_counted) {
+                if !f.accept(&algo) {
+                    break;
+                }
+                counts[k] += 1;
+            }
+        }
+        stages.extend(
+            filters
+                .iter()
+                .zip(counts)
+                .skip(n_counted)
+                .map(|(&filter, remaining)| FilterStage {
+                    filter,
+                    description: filter.description(),
+                    remaining,
+                    analytic: false,
+                }),
+        );
+    }
+    FilterStats {
+        model: model.to_string(),
+        total,
+        stages,
+    }
+}
+
+/// number of maps from `n` items onto `k` values.
+fn surjections(n: u32, k: u32) -> Option<i128> {
+    inclusion_exclusion(n, k, k)
+}
+
+/// number of maps from `n` items to `k` values that reach each of `r` given values
+/// (inclusion–exclusion over the given values missed).
+fn inclusion_exclusion(n: u32, k: u32, r: u32) -> Option<i128> {
+    (0..=r).try_fold(0i128, |sum, j| {
+        let term = binomial(r, j).checked_mul(((k - j) as i128).checked_pow(n)?)?;
+        if j % 2 == 0 {
+            sum.checked_add(term)
+        } else {
+            sum.checked_sub(term)
+        }
+    })
+}
+
+fn binomial(n: u32, k: u32) -> i128 {
+    (0..k).fold(1, |acc, i| acc * (n - i) as i128 / (i + 1) as i128)
+}
+
 #[cfg(test)]
 pub mod tests {
     use super::*;
@@ -146,6 +704,21 @@ pub mod tests {
         ]
     }
 
+    #[test]
+    fn test_guards_for_model() {
+        let model = |code| Model::try_from(code).unwrap();
+        assert_eq!(
+            guards_for_model(model("F2")),
+            guards_for_full_lights_2_cols()
+        );
+        assert_eq!(guards_for_model(model("E3")), guards_for_external_3_cols());
+        let guards = guards_for_model(model("I4L"));
+        assert_eq!(
+            guards,
+            (0..4).map(Color).map(Guard::LInternal).collect::<Vec<_>>()
+        );
+    }
+
     #[test]
     fn test_action_iter() {
         const FIRST_FIVE: [&str; 5] = [
@@ -198,6 +771,128 @@ pub mod tests {
         assert_eq!(count_7, 4704);
     }
 
+    #[test]
+    fn test_canonical_generator() {
+        for (model, n_colors, class_l) in [
+            (ModelKind::Full, 2, true),
+            (ModelKind::External, 3, true),
+            (ModelKind::Internal, 3, true),
+            (ModelKind::External, 3, false),
+        ] {
+            let expected = generate_algorithms_in_model(model, n_colors, class_l)
+                .filter(|a| a.is_canonical())
+                .collect::<std::collections::BTreeSet<_>>();
+            let generated = generate_canonical_algorithms_in_model(model, n_colors, class_l)
+                .collect::<Vec<_>>();
+            assert_eq!(generated.len(), expected.len());
+            assert_eq!(
+                generated
+                    .into_iter()
+                    .collect::<std::collections::BTreeSet<_>>(),
+                expected
+            );
+        }
+    }
+
+    #[test]
+    fn test_algorithm_at() {
+        let guards = guards_for_external_3_cols();
+        for (i, algo) in generate_algorithms_in_model(ModelKind::External, 3, false)
+            .enumerate()
+            .step_by(101)
+        {
+            assert_eq!(algorithm_at(&guards, 3, Moves::ALL, i as u64), algo);
+        }
+    }
+
+    #[test]
+    fn test_moves() {
+        let moves = Moves::try_from("S,O").unwrap();
+        let uses_moves = |a: &Algorithm| a.rules().all(|r| moves.contains(r.action().movement()));
+        let (model, n_colors, class_l) = (ModelKind::External, 3, true);
+        let expected = generate_algorithms_in_model(model, n_colors, class_l)
+            .filter(uses_moves)
+            .collect::<Vec<_>>();
+        let generated =
+            generate_algorithms_with_moves(model, n_colors, class_l, moves).collect::<Vec<_>>();
+        assert_eq!(generated, expected);
+        assert_eq!(
+            count_algorithms_with_moves(model, n_colors, class_l, moves),
+            expected.len() as u64
+        );
+        let guards = model_guards(model, n_colors, class_l);
+        assert_eq!(algorithm_at(&guards, n_colors, moves, 17), expected[17]);
+
+        let canonical = generate_canonical_algorithms_with_moves(model, n_colors, class_l, moves)
+            .collect::<std::collections::BTreeSet<_>>();
+        let expected = expected
+            .into_iter()
+            .filter(|a| a.is_canonical())
+            .collect::<std::collections::BTreeSet<_>>();
+        assert_eq!(canonical, expected);
+        assert!(
+            sample_algorithms_with_moves(model, n_colors, class_l, moves, 7)
+                .take(20)
+                .all(|(_, a)| uses_moves(&a))
+        );
+    }
+
+    #[test]
+    fn test_nth_algorithm() {
+        let all = generate_algorithms_in_model(ModelKind::Full, 2, true).collect::<Vec<_>>();
+        for index in [0, 1, 17, 500, all.len() - 1] {
+            assert_eq!(
+                nth_algorithm(ModelKind::Full, 2, true, index as u64).as_ref(),
+                Some(&all[index])
+            );
+        }
+        assert_eq!(
+            nth_algorithm(ModelKind::Full, 2, true, all.len() as u64),
+            None
+        );
+        assert_eq!(
+            nth_algorithm(ModelKind::Full, 2, false, 0).map(|a| a.as_code()),
+            Some("00s_01s_10s_11s_00d_01d_10d_11d__S0_S0_S0_S0_S0_S0_S0_S0".to_string())
+        );
+    }
+
+    #[test]
+    fn test_sample_algorithms() {
+        let sample = sample_algorithms_in_model(ModelKind::Full, 2, true, 42)
+            .take(100)
+            .collect::<Vec<_>>();
+        // each algorithm comes with its position in the whole enumeration
+        for (index, algo) in sample.iter() {
+            assert_eq!(
+                nth_algorithm(ModelKind::Full, 2, true, *index).as_ref(),
+                Some(algo)
+            );
+        }
+        let restricted = Moves::new([Move::Stay, Move::ToOther]).unwrap();
+        for (index, algo) in
+            sample_algorithms_with_moves(ModelKind::Full, 2, true, restricted, 1).take(100)
+        {
+            assert_eq!(nth_algorithm(ModelKind::Full, 2, true, index), Some(algo));
+        }
+        let again = sample_algorithms_in_model(ModelKind::Full, 2, true, 42)
+            .take(100)
+            .collect::<Vec<_>>();
+        assert_eq!(sample, again);
+
+        // each algorithm is drawn exactly once
+        let all = sample_algorithms_in_model(ModelKind::External, 2, true, 7)
+            .map(|(_, algo)| algo)
+            .collect::<Vec<_>>();
+        assert_eq!(
+            all.len() as u64,
+            count_algorithms_in_model(ModelKind::External, 2, true)
+        );
+        let all = all.into_iter().collect::<std::collections::BTreeSet<_>>();
+        let expected = generate_algorithms_in_model(ModelKind::External, 2, true)
+            .collect::<std::collections::BTreeSet<_>>();
+        assert_eq!(all, expected);
+    }
+
     #[test]
     fn test_count_algorithms() {
         let test_cases = [
@@ -216,4 +911,80 @@ pub mod tests {
             );
         }
     }
+
+    #[test]
+    fn test_count_filtered_algorithms() {
+        use strum::IntoEnumIterator;
+        let structural = Filter::iter()
+            .filter(Filter::is_structural)
+            .collect::<Vec<_>>();
+        for (model, n_colors, class_l) in [
+            (ModelKind::Full, 2, true),
+            (ModelKind::External, 3, true),
+            (ModelKind::External, 2, false),
+            (ModelKind::Internal, 2, false),
+        ] {
+            // bit i of the mask: accepted by the i-th structural filter
+            let masks = generate_algorithms_in_model(model, n_colors, class_l)
+                .map(|algo| {
+                    structural
+                        .iter()
+                        .enumerate()
+                        .filter(|(_, f)| f.accept(&algo))
+                        .fold(0usize, |mask, (i, _)| mask | 1 << i)
+                })
+                .collect::<Vec<_>>();
+            for subset in 0..1usize << structural.len() {
+                let filters = structural
+                    .iter()
+                    .enumerate()
+                    .filter(|(i, _)| subset & 1 << i != 0)
+                    .map(|(_, &f)| f)
+                    .collect::<Vec<_>>();
+                let expected = masks.iter().filter(|&&m| m & subset == subset).count() as u64;
+                assert_eq!(
+                    count_filtered_algorithms_in_model(model, n_colors, class_l, &filters),
+                    Some(expected),
+                    "{model:?} {n_colors} {class_l} {filters:?}"
+                );
+            }
+        }
+        assert_eq!(
+            count_filtered_algorithms_in_model(ModelKind::Full, 3, true, &[]),
+            Some(count_algorithms_in_model(ModelKind::Full, 3, true))
+        );
+        let filters = [Filter::AllGatheredStay, Filter::PseudoCanonical];
+        assert_eq!(
+            count_filtered_algorithms_in_model(ModelKind::Full, 2, true, &filters),
+            None
+        );
+        // 10^40 algorithms
+        assert_eq!(
+            count_filtered_algorithms_in_model(ModelKind::Full, 5, false, &[]),
+            None
+        );
+    }
+
+    #[test]
+    fn test_filter_statistics() {
+        let model = Model::try_from("F2L").unwrap();
+        let stats = filter_statistics(model, &FilterStatsOptions::default());
+        assert_eq!(stats.total, Some(1296));
+        let remaining = stats.stages.iter().map(|s| s.remaining).collect::<Vec<_>>();
+        assert_eq!(remaining, [1296, 1134, 1134, 756, 588, 434, 294]);
+        let analytic = stats.stages.iter().filter(|s| s.analytic).count();
+        assert_eq!(analytic, 3);
+
+        let options = FilterStatsOptions {
+            enumerate: false,
+            ..Default::default()
+        };
+        let partial = filter_statistics(model, &options);
+        assert_eq!(partial.stages[..], stats.stages[..3]);
+
+        let json = serde_json::to_value(&stats).unwrap();
+        assert_eq!(json["model"], "F2L");
+        assert_eq!(json["stages"][0]["filter"], "all-gathered-stay");
+        assert_eq!(json["stages"][6]["remaining"], 294);
+    }
 }
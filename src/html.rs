@@ -0,0 +1,153 @@
+//! Self-contained HTML report of a run (see `--report-html`): summary of each run, sortable table of the PASS
+//! algorithms, and their color-change graphs inlined in svg (see [dot::algo_to_svg]).
+
+use anyhow::{Context, Result};
+use log::warn;
+use std::io::Write;
+use std::path::Path;
+
+use crate::algorithm::Algorithm;
+use crate::classify;
+use crate::dot;
+use crate::synthesis::RunResult;
+
+/// sorts the rows of a table when clicking on the header of a column (numerically if both cells are numbers).
+const SORT_SCRIPT: &str = r#"
+document.querySelectorAll("table.sortable th").forEach((th, col) => th.addEventListener("click", () => {
+  const body = th.closest("table").tBodies[0];
+  const asc = th.dataset.order !== "asc";
+  th.dataset.order = asc ? "asc" : "desc";
+  const key = row => row.cells[col].dataset.key ?? row.cells[col].textContent;
+  const rows = Array.from(body.rows).sort((a, b) => {
+    const [x, y] = [key(a), key(b)];
+    const cmp = isNaN(x) || isNaN(y) ? x.localeCompare(y) : x - y;
+    return asc ? cmp : -cmp;
+  });
+  rows.forEach(row => body.appendChild(row));
+}));
+"#;
+
+const STYLE: &str = r#"
+body { font-family: sans-serif; margin: 2em; }
+table { border-collapse: collapse; margin-bottom: 2em; }
+th, td { border: 1px solid #ccc; padding: 0.2em 0.6em; text-align: right; }
+td.code { font-family: monospace; text-align: left; }
+table.sortable th { cursor: pointer; background: #eee; }
+pre { text-align: left; }
+"#;
+
+/// escapes the special characters of HTML in a text.
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// writes the HTML report of the runs to a file, with the diagrams rendered by graphviz.
+pub fn save_report(path: &Path, options: &str, runs: &[RunResult]) -> Result<()> {
+    let mut file = std::fs::File::create(path)
+        .with_context(|| format!("failed to create HTML report {}", path.display()))?;
+    write_report(&mut file, options, runs, &dot::algo_to_svg)
+}
+
+/// writes the HTML report of the runs, given the options of the run (as in the text report) and the rendering of
+/// the diagrams in svg; if the rendering fails (e.g., graphviz is not installed), the dot code is inlined instead.
+pub fn write_report(
+    output: &mut dyn Write,
+    options: &str,
+    runs: &[RunResult],
+    render: &dyn Fn(&Algorithm) -> Result<String>,
+) -> Result<()> {
+    writeln!(output, "<!DOCTYPE html>")?;
+    writeln!(output, "<html><head><meta charset=\"utf-8\">")?;
+    writeln!(output, "<title>synth-lights report</title>")?;
+    writeln!(output, "<style>{STYLE}</style></head><body>")?;
+    writeln!(output, "<h1>synth-lights report</h1>")?;
+    writeln!(
+        output,
+        "<details><summary>Run options</summary><pre>{}</pre></details>",
+        escape(options)
+    )?;
+
+    writeln!(output, "<h2>Summary</h2>")?;
+    writeln!(output, "<table class=\"sortable\"><thead><tr>")?;
+    for column in [
+        "model",
+        "scheduler",
+        "pass",
+        "fail",
+        "incomplete",
+        "errors",
+        "algorithms",
+        "enumerated",
+        "ms",
+    ] {
+        write!(output, "<th>{column}</th>")?;
+    }
+    writeln!(output, "</tr></thead><tbody>")?;
+    for run in runs {
+        writeln!(
+            output,
+            "<tr><td class=\"code\">{}</td><td class=\"code\">{}</td><td>{}</td><td>{}</td><td>{}</td><td>{}</td>\
+             <td>{}</td><td>{}</td><td>{}</td></tr>",
+            escape(&run.model.to_string()),
+            escape(&run.scheduler.to_string()),
+            run.n_pass,
+            run.n_fail,
+            run.n_incomplete,
+            run.n_errors,
+            run.n_algos,
+            run.n_enumerated,
+            run.duration.as_millis()
+        )?;
+    }
+    writeln!(output, "</tbody></table>")?;
+
+    let mut render_failed = false;
+    for run in runs {
+        writeln!(
+            output,
+            "<h2>PASS algorithms: {} / {} ({})</h2>",
+            escape(&run.model.to_string()),
+            escape(&run.scheduler.to_string()),
+            run.pass.len()
+        )?;
+        if run.pass.is_empty() {
+            continue;
+        }
+        writeln!(output, "<table class=\"sortable\"><thead><tr>")?;
+        writeln!(
+            output,
+            "<th>index</th><th>algorithm</th><th>features</th><th>diagram</th>"
+        )?;
+        writeln!(output, "</tr></thead><tbody>")?;
+        for (i, algo) in run.pass.iter() {
+            let diagram = match render(algo) {
+                Ok(svg) => svg[svg.find("<svg").unwrap_or(0)..].to_string(),
+                Err(e) => {
+                    if !render_failed {
+                        warn!(
+                            "HTML report: {e:#}; the dot code of the diagrams is inlined instead"
+                        );
+                        render_failed = true;
+                    }
+                    format!("<pre>{}</pre>", escape(&dot::algo_to_dot(algo)))
+                }
+            };
+            writeln!(
+                output,
+                "<tr><td>{i}</td><td class=\"code\">{}</td><td class=\"code\">{}</td>\
+                 <td><details><summary>show</summary>{diagram}</details></td></tr>",
+                escape(&algo.as_code()),
+                escape(&classify::feature_names(&classify::features(algo)))
+            )?;
+        }
+        writeln!(output, "</tbody></table>")?;
+    }
+
+    writeln!(output, "<script>{SORT_SCRIPT}</script>")?;
+    writeln!(output, "</body></html>")?;
+    output.flush()?;
+    Ok(())
+}
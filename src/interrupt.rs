@@ -0,0 +1,239 @@
+//! Graceful interruption of a run by a signal (SIGINT, e.g., ctrl-c, or SIGTERM), once [install]ed.
+//!
+//! On the first signal, no new verification is started (see [is_interrupted]) and the verifications in progress are
+//! completed; the run then writes the report of the algorithms verified so far, and removes its working directory
+//! (ejecting the ramdisk), instead of leaving them behind. The tools still running after the grace period are killed
+//! (see [track]), and a second signal exits at once. A run with a time budget is interrupted in the same way when the
+//! budget is exhausted (see [interrupt_after]).
+
+use anyhow::{anyhow, bail, Context, Result};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::Duration;
+
+/// default grace period of the tools running when the run is interrupted, in seconds.
+pub const DEFAULT_GRACE_SECS: u64 = 10;
+
+/// interval between two checks of the interruption by the thread killing the tools.
+const POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// exit code of the process on a second signal (as a shell does for SIGINT).
+const EXIT_CODE: i32 = 130;
+
+static INTERRUPTED: OnceLock<Arc<AtomicBool>> = OnceLock::new();
+/// cause of the interruption, if not a signal
+static REASON: OnceLock<&'static str> = OnceLock::new();
+static INSTALLED: OnceLock<()> = OnceLock::new();
+/// tools running, with their identifiers (see [track])
+static RUNNING: Mutex<Vec<(usize, Arc<duct::Handle>)>> = Mutex::new(Vec::new());
+static NEXT_ID: AtomicUsize = AtomicUsize::new(0);
+
+fn flag() -> &'static Arc<AtomicBool> {
+    INTERRUPTED.get_or_init(Default::default)
+}
+
+/// handling of the interruptions on the command line.
+#[derive(clap::Args, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct InterruptArgs {
+    /// Grace period of the verifications in progress when interrupted (ctrl-c), in seconds: the tools still running are then killed
+    #[arg(long = "grace-period", value_name = "SECS", default_value_t = DEFAULT_GRACE_SECS)]
+    pub grace_secs: u64,
+}
+
+impl Default for InterruptArgs {
+    fn default() -> Self {
+        Self {
+            grace_secs: DEFAULT_GRACE_SECS,
+        }
+    }
+}
+
+impl InterruptArgs {
+    /// handles the interruptions with the grace period (see [install]).
+    pub fn install(&self) -> Result<()> {
+        install(Duration::from_secs(self.grace_secs))
+    }
+}
+
+/// handles SIGINT and SIGTERM for the rest of the process (only the first call has an effect): the tools running
+/// when the signal is received are killed after the grace period.
+pub fn install(grace: Duration) -> Result<()> {
+    use signal_hook::consts::{SIGINT, SIGTERM};
+    if INSTALLED.set(()).is_err() {
+        return Ok(());
+    }
+    let flag = flag();
+    for signal in [SIGINT, SIGTERM] {
+        // registered first, so that the flag is only set by the first signal when it is checked
+        signal_hook::flag::register_conditional_shutdown(signal, EXIT_CODE, flag.clone())
+            .context("failed to install the signal handler")?;
+        signal_hook::flag::register(signal, flag.clone())
+            .context("failed to install the signal handler")?;
+    }
+    std::thread::spawn(move || {
+        while !is_interrupted() {
+            std::thread::sleep(POLL_INTERVAL);
+        }
+        log::warn!(
+            "interrupted: completing the verifications in progress ({}s at most, interrupt again to exit at once)",
+            grace.as_secs()
+        );
+        std::thread::sleep(grace);
+        kill_running();
+    });
+    Ok(())
+}
+
+/// checks if the run was interrupted.
+pub fn is_interrupted() -> bool {
+    flag().load(Ordering::SeqCst)
+}
+
+/// interrupts the run as if a signal was received (e.g., by an embedder with its own signal handling).
+pub fn interrupt() {
+    flag().store(true, Ordering::SeqCst);
+}
+
+/// cause of the interruption (signal or time budget).
+pub fn reason() -> &'static str {
+    REASON.get().copied().unwrap_or("signal")
+}
+
+/// interrupts the run once the time budget is exhausted, unless it was interrupted before.
+pub fn interrupt_after(budget: Duration) {
+    std::thread::spawn(move || {
+        std::thread::sleep(budget);
+        if !is_interrupted() {
+            log::warn!("time budget of {} exhausted", format_duration(budget));
+            let _ = REASON.set("time budget exhausted");
+            interrupt();
+        }
+    });
+}
+
+/// parses a duration in seconds (`90`, `90s`), minutes (`30m`), hours (`12h`) or days (`2d`), or a sum of them
+/// (`1h30m`).
+pub fn parse_duration(text: &str) -> Result<Duration> {
+    let mut secs: u64 = 0;
+    let mut rest = text.trim();
+    if rest.is_empty() {
+        bail!("empty duration");
+    }
+    while !rest.is_empty() {
+        let digits = rest
+            .find(|c: char| !c.is_ascii_digit())
+            .unwrap_or(rest.len());
+        if digits == 0 {
+            bail!("invalid duration '{text}' (expected e.g. 90s, 30m, 12h or 1h30m)");
+        }
+        let value = rest[..digits].parse::<u64>()?;
+        let (unit, tail) = match rest[digits..].chars().next() {
+            Some(unit) => (unit, &rest[digits + unit.len_utf8()..]),
+            None => ('s', ""),
+        };
+        let unit_secs = match unit {
+            's' => 1,
+            'm' => 60,
+            'h' => 3600,
+            'd' => 86400,
+            _ => bail!("invalid unit '{unit}' in duration '{text}' (expected s, m, h or d)"),
+        };
+        secs = value
+            .checked_mul(unit_secs)
+            .and_then(|value| secs.checked_add(value))
+            .ok_or_else(|| anyhow!("duration '{text}' is too large"))?;
+        rest = tail;
+    }
+    Ok(Duration::from_secs(secs))
+}
+
+/// duration in the format of [parse_duration] (e.g., `1h30m`).
+pub fn format_duration(duration: Duration) -> String {
+    let secs = duration.as_secs();
+    let parts = [
+        (secs / 86400, "d"),
+        (secs / 3600 % 24, "h"),
+        (secs / 60 % 60, "m"),
+        (secs % 60, "s"),
+    ];
+    let text = parts
+        .iter()
+        .filter(|(value, _)| *value > 0)
+        .map(|(value, unit)| format!("{value}{unit}"))
+        .collect::<String>();
+    if text.is_empty() {
+        "0s".to_string()
+    } else {
+        text
+    }
+}
+
+/// tool tracked while running, so that it is killed at the end of the grace period; untracked when dropped.
+pub struct Tracked(usize);
+
+impl Drop for Tracked {
+    fn drop(&mut self) {
+        RUNNING.lock().unwrap().retain(|(id, _)| *id != self.0);
+    }
+}
+
+/// tracks a running tool until the returned guard is dropped.
+pub fn track(handle: Arc<duct::Handle>) -> Tracked {
+    let id = NEXT_ID.fetch_add(1, Ordering::SeqCst);
+    RUNNING.lock().unwrap().push((id, handle));
+    Tracked(id)
+}
+
+fn kill_running() {
+    let running = RUNNING.lock().unwrap();
+    if !running.is_empty() {
+        log::warn!("killing {} tools still running", running.len());
+    }
+    for (_, handle) in running.iter() {
+        if let Err(e) = handle.kill() {
+            log::warn!("failed to kill tool: {e}");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_duration() {
+        assert_eq!(parse_duration("90").unwrap(), Duration::from_secs(90));
+        assert_eq!(parse_duration("30m").unwrap(), Duration::from_secs(1800));
+        assert_eq!(parse_duration("12h").unwrap(), Duration::from_secs(43200));
+        assert_eq!(parse_duration("1h30m").unwrap(), Duration::from_secs(5400));
+        assert_eq!(parse_duration("2d").unwrap(), Duration::from_secs(172800));
+        for invalid in [
+            "",
+            "h",
+            "12x",
+            "1.5h",
+            "-1h",
+            "999999999999999999d",
+            "18446744073709551615s1s",
+        ] {
+            assert!(parse_duration(invalid).is_err(), "{invalid}");
+        }
+        assert_eq!(format_duration(Duration::from_secs(5400)), "1h30m");
+        assert_eq!(format_duration(Duration::ZERO), "0s");
+    }
+
+    #[test]
+    fn test_track() {
+        let handle = Arc::new(duct::cmd!("true").unchecked().start().unwrap());
+        let tracked = track(handle.clone());
+        assert!(RUNNING
+            .lock()
+            .unwrap()
+            .iter()
+            .any(|(id, _)| *id == tracked.0));
+        handle.wait().unwrap();
+        let id = tracked.0;
+        drop(tracked);
+        assert!(RUNNING.lock().unwrap().iter().all(|(i, _)| *i != id));
+    }
+}
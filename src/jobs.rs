@@ -0,0 +1,134 @@
+//! Offline verification jobs (see `--emit-jobs`): instead of running spin, a synthesis run writes one directory per
+//! viable algorithm, with the bundle of each variant of its verification (see [runner::write_bundle]) and a script
+//! running them, together with a script running all the jobs and a SLURM array script. The verification can then run
+//! on machines where spin and clang are available, but not this tool.
+
+use anyhow::Result;
+use std::path::{Path, PathBuf};
+
+use crate::algorithm::Algorithm;
+use crate::promela::{self, ModelRunOptions};
+use crate::runner::{self, PanBuildOptions, BUNDLE_SCRIPT};
+
+/// script of a job, verifying all the variants of its algorithm.
+pub const JOB_SCRIPT: &str = "run.sh";
+
+/// file written by a job: the algorithm, then the outcome of each variant (see [write_job]).
+pub const RESULT_FILE: &str = "result.txt";
+
+/// file listing the directories of the jobs, one per line (the lines of the SLURM array).
+pub const JOBS_FILE: &str = "jobs.txt";
+
+/// script running all the jobs on the local machine.
+pub const RUN_ALL_SCRIPT: &str = "run_all.sh";
+
+/// SLURM array script running one job per task.
+pub const SLURM_SCRIPT: &str = "slurm.sh";
+
+/// directory of the job of the algorithm with the given index among the viable algorithms.
+pub fn job_name(index: usize) -> String {
+    format!("{index:06}")
+}
+
+/// writes the job of an algorithm in `dir/<index>` (see [job_name]): one bundle per variant, and a script writing
+/// the outcomes in [RESULT_FILE], as `<index> : <code>` followed by one line `<variant> <outcome>` per variant.
+/// Returns the name of the job directory.
+pub fn write_job(
+    dir: &Path,
+    index: usize,
+    algo: &Algorithm,
+    variants: &[ModelRunOptions],
+    build: &PanBuildOptions,
+) -> Result<String> {
+    let name = job_name(index);
+    let job = dir.join(&name);
+    let promela = promela::generate_promela(algo);
+    let mut script = format!(
+        "#!/bin/sh\n# job {index}: verification of {}\ncd \"$(dirname \"$0\")\"\n{{\necho \"{index} : {}\"\n",
+        algo.as_code_v2(),
+        algo.as_code()
+    );
+    for &options in variants {
+        let variant = options.variant_name();
+        let bundle = variant.replace('/', "-");
+        runner::write_bundle(
+            &job.join(&bundle),
+            &algo.as_code_v2(),
+            &promela,
+            options,
+            build,
+        )?;
+        script += &format!("echo \"{variant} $(sh {bundle}/{BUNDLE_SCRIPT})\"\n");
+    }
+    script += &format!("}} > {RESULT_FILE}.tmp && mv {RESULT_FILE}.tmp {RESULT_FILE}\n");
+    runner::write_script(&job.join(JOB_SCRIPT), &script)?;
+    Ok(name)
+}
+
+/// writes the list of the jobs of the directory, and the scripts running them (see [RUN_ALL_SCRIPT] and
+/// [SLURM_SCRIPT]); returns the paths of the scripts.
+pub fn write_scripts(dir: &Path, title: &str, jobs: &[String]) -> Result<(PathBuf, PathBuf)> {
+    std::fs::create_dir_all(dir)?;
+    let list = jobs
+        .iter()
+        .map(|job| format!("{job}\n"))
+        .collect::<String>();
+    std::fs::write(dir.join(JOBS_FILE), list)?;
+
+    let run_all = dir.join(RUN_ALL_SCRIPT);
+    runner::write_script(
+        &run_all,
+        &format!(
+            "#!/bin/sh\n# runs the {} jobs of {title}, JOBS at a time (1 by default)\ncd \"$(dirname \"$0\")\"\n\
+            xargs -P \"${{JOBS:-1}}\" -I {{}} sh {{}}/{JOB_SCRIPT} < {JOBS_FILE}\n",
+            jobs.len()
+        ),
+    )?;
+
+    let slurm = dir.join(SLURM_SCRIPT);
+    runner::write_script(
+        &slurm,
+        &format!(
+            "#!/bin/sh\n# runs the {n} jobs of {title}, one per task (submit from this directory: sbatch {SLURM_SCRIPT})\n\
+            #SBATCH --job-name=synth-lights-{name}\n#SBATCH --array=1-{n}\n#SBATCH --output=slurm-%A_%a.out\n\
+            cd \"${{SLURM_SUBMIT_DIR:-.}}\"\njob=$(sed -n \"${{SLURM_ARRAY_TASK_ID}}p\" {JOBS_FILE})\nsh \"$job/{JOB_SCRIPT}\"\n",
+            n = jobs.len(),
+            name = title.replace(' ', "-"),
+        ),
+    )?;
+    Ok((run_all, slurm))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::common::Scheduler;
+
+    #[test]
+    fn test_write_jobs() {
+        let dir = std::env::temp_dir().join(format!("synth-lights-jobs-{}", std::process::id()));
+        let algo = Algorithm::try_parse_v2("E3L:0_1_2__S2_H0_O1").unwrap();
+        let options = ModelRunOptions::new(Scheduler::SSYNC);
+        let build = PanBuildOptions::default();
+        let job = write_job(&dir, 42, &algo, &options.variants(), &build).unwrap();
+        assert_eq!(job, "000042");
+        for bundle in ["non-rigid-ss", "non-rigid-qss", "rigid-ss", "rigid-qss"] {
+            assert!(dir.join(&job).join(bundle).join(BUNDLE_SCRIPT).exists());
+        }
+        let script = std::fs::read_to_string(dir.join(&job).join(JOB_SCRIPT)).unwrap();
+        assert!(script.contains("echo \"42 : 0_1_2__S2_H0_O1\"\n"));
+        assert!(script.contains("echo \"rigid/qss $(sh rigid-qss/verify.sh)\"\n"));
+
+        let (run_all, slurm) = write_scripts(&dir, "E3L SSYNC", &[job]).unwrap();
+        assert_eq!(
+            std::fs::read_to_string(dir.join(JOBS_FILE)).unwrap(),
+            "000042\n"
+        );
+        assert!(std::fs::read_to_string(run_all)
+            .unwrap()
+            .contains("xargs -P"));
+        let slurm = std::fs::read_to_string(slurm).unwrap();
+        assert!(slurm.contains("#SBATCH --job-name=synth-lights-E3L-SSYNC\n#SBATCH --array=1-1\n"));
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
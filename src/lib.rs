@@ -1,60 +1,234 @@
 #![forbid(unsafe_code)]
 
 pub mod algorithm;
+pub mod analyze;
+pub mod bench;
+pub mod cluster;
+pub mod checker;
+pub mod classify;
+pub mod commands;
 pub mod common;
+#[cfg(feature = "tui")]
+pub mod dashboard;
+pub mod depth;
+pub mod doctor;
+pub mod dot;
+pub mod estimate;
+pub mod filter;
 pub mod generator;
+pub mod html;
+pub mod interrupt;
+pub mod jobs;
+pub mod logging;
+pub mod manifest;
+pub mod merge;
+pub mod metadata;
+pub mod observer;
+pub mod output;
+pub mod preset;
 pub mod promela;
+pub mod pruning;
+pub mod repl;
+pub mod report;
 pub mod runner;
+pub mod search;
+pub mod selftest;
+pub mod simulator;
 pub mod model;
+pub mod notify;
+pub mod stats;
+pub mod status;
+pub mod synthesis;
+pub mod throttle;
 
-use anyhow::{Context, Result};
+use anyhow::{bail, Context, Result};
 use clap::{Parser, ValueEnum};
-use std::path::Path;
+use serde::{Deserialize, Serialize};
+use std::io::IsTerminal;
 use std::path::PathBuf;
 use strum::Display;
 
 use convert_case::{Case, Casing};
 
-use log::info;
-
-use runner::{run_verification, SpinOutcome};
-
 const DEFAULT_OUTPUT_DIR: &str = "results";
 
 /// Algorithm synthesis for two robots gathering.
 /// Given a system model, the program generates all viable algorithms for that model
 /// and uses model checking to search for those that solve gathering (aka, rendez-vous).
-#[derive(Debug, Parser)]
-#[command(author, version, about, long_about = None)]
+#[derive(Clone, Debug, Parser)]
+#[command(author, version, about, long_about = None, args_override_self = true)]
 #[allow(non_snake_case)]
 pub struct Cli {
-    /// Category of algorithms
-    #[arg(value_enum)]
-    category: ModelKind,
-
-    /// Number of colors allowed in the model
-    #[arg()]
-    n_colors: u8,
+    #[command(flatten)]
+    model: model::ModelArgs,
 
-    /// Limits search to class L algorithms
-    #[arg(short = 'L')]
-    class_L: bool,
+    /// Expands to the options of a named preset, built-in or defined in the presets directory (see `synth-lights presets`); later options override them
+    #[arg(long = "preset", value_name = "NAME")]
+    preset: Option<String>,
 
     /// Enables sequential execution
     #[arg(short = 'S', long = "sequential")]
     sequential: bool,
 
-    /// Enables weak filtering
-    #[arg(short = 'w')]
-    weak_filter: bool,
+    #[command(flatten)]
+    filters: filter::FilterArgs,
+
+    /// Restricts the moves admissible in the actions, comma-separated (e.g., S,O without half-way moves; all by default, STAY required)
+    #[arg(long = "moves", value_name = "MOVES", conflicts_with = "search")]
+    moves: Option<common::Moves>,
+
+    /// Skips algorithms embedding a PASS algorithm with fewer colors, read from the report of a previous run
+    #[arg(long = "exclude-lower", value_name = "RESULTS_FILE")]
+    exclude_lower: Option<PathBuf>,
+
+    /// Only keeps algorithms whose code string matches the regular expression (e.g., `_O1_`)
+    #[arg(long = "match", value_name = "REGEX")]
+    code_match: Option<String>,
+
+    /// Groups PASS algorithms by equivalence class (color permutations) in the report
+    #[arg(short = 'G', long = "group-classes")]
+    group_classes: bool,
+
+    /// Appends statistics over the PASS algorithms (moves, colors written) to the report
+    #[arg(long = "stats")]
+    stats: bool,
+
+    /// Groups PASS algorithms by structural features (e.g., color cycle, swap symmetry) in the report
+    #[arg(long = "classify")]
+    classify: bool,
+
+    /// Lists the rules of each PASS algorithm in human-readable form in the report ("when I see X ... → set color C and move M")
+    #[arg(long = "pretty")]
+    pretty: bool,
+
+    /// Lists the failing algorithms too in the report, one line each prefixed by "FAIL >" (instead of a dot in sequential mode)
+    #[arg(long = "report-fails")]
+    report_fails: bool,
+
+    /// Verifies the failing algorithms again under the next weaker schedulers, and lists those passing there in the report, prefixed by "NEAR-MISS >"
+    #[arg(long = "near-misses", conflicts_with = "search")]
+    near_misses: bool,
+
+    /// Adds the raw output of spin, clang or pan to the errors ("ERROR >" lines) and incomplete verifications ("OUTPUT >" lines) in the report
+    #[arg(long = "verbose-errors")]
+    verbose_errors: bool,
+
+    /// Lists the codes of all viable algorithms without verifying them
+    #[arg(long = "list-only")]
+    list_only: bool,
+
+    /// Writes a verification job per viable algorithm in the directory (Promela bundles and shell scripts, with a SLURM array script) instead of running spin
+    #[arg(long = "emit-jobs", value_name = "DIR", conflicts_with_all = ["search", "list_only", "dry_run"])]
+    emit_jobs: Option<PathBuf>,
+
+    /// Only estimates the wall time and disk usage of the run, by counting the viable algorithms and timing a few verifications
+    #[arg(long = "dry-run")]
+    dry_run: bool,
+
+    /// Number of verifications timed to calibrate the estimate (see --dry-run)
+    #[arg(long = "calibration", value_name = "N", default_value_t = 5)]
+    calibration: usize,
+
+    /// Verifies only N viable algorithms sampled uniformly at random (the indices in the report are their positions in the sample)
+    #[arg(long = "sample", value_name = "N")]
+    sample: Option<usize>,
+
+    /// Maximal number of algorithms drawn to fill the sample, if the viable algorithms are too few (see --sample; 1000 per sampled algorithm by default)
+    #[arg(long = "sample-draws", value_name = "N", requires = "sample")]
+    sample_draws: Option<u64>,
+
+    /// Seed of the random sampling (see --sample)
+    #[arg(long = "seed", default_value_t = 0)]
+    seed: u64,
+
+    /// Verifies only the viable algorithms from index N (the indices in the report are those of the whole enumeration)
+    #[arg(long = "offset", value_name = "N", default_value_t = 0)]
+    offset: usize,
 
-    /// Enables Viglietta's retain rule filtering ("A robot retains its color if and only if it sees the other robot set to a different color.")
-    #[arg(short = 'R')]
-    retain_filter: bool,
+    /// Verifies at most N viable algorithms (from --offset)
+    #[arg(long = "limit", value_name = "N")]
+    limit: Option<usize>,
 
-    /// Scheduler of the model
-    #[arg(short = 's', long = "sched", value_enum, default_value = "async")]
-    scheduler: common::Scheduler,
+    /// Writes the manifest of the enumeration of the viable algorithms (index and hash of each algorithm) to FILE
+    #[arg(long = "write-manifest", value_name = "FILE")]
+    write_manifest: Option<PathBuf>,
+
+    /// Checks the enumeration of the viable algorithms against the manifest in FILE before verifying them
+    #[arg(long = "manifest", value_name = "FILE")]
+    manifest: Option<PathBuf>,
+
+    /// Also writes a self-contained HTML report to FILE (summary, sortable table of the PASS algorithms and their
+    /// diagrams, rendered in svg if graphviz is installed)
+    #[arg(long = "report-html", value_name = "FILE")]
+    report_html: Option<PathBuf>,
+
+    /// Runs a local search (hill-climbing with N restarts) instead of the exhaustive search
+    #[arg(long = "search", value_name = "N")]
+    search: Option<usize>,
+
+    /// Maximal number of mutations per restart of the local search (see --search)
+    #[arg(long = "search-iterations", default_value_t = 1000)]
+    search_iterations: usize,
+
+    /// Tries to falsify each algorithm by simulation before running the model checker, which is then skipped for the falsified algorithms
+    #[arg(long = "prefilter")]
+    prefilter: bool,
+
+    /// Verifies each algorithm first under weaker schedulers (from Centralized up to the one of the run), dropping it as soon as it fails
+    #[arg(long = "escalate", conflicts_with = "search")]
+    escalate: bool,
+
+    /// Model checker (native: built-in model checker for the centralized, FSYNC and SSYNC schedulers, spin otherwise)
+    #[arg(long = "backend", value_enum, default_value = "spin")]
+    backend: synthesis::Backend,
+
+    /// Stops the verification once N PASS algorithms are found (the verifications in progress are completed)
+    #[arg(long = "find", value_name = "N")]
+    find: Option<usize>,
+
+    /// Stops the verification at the first PASS algorithm (same as --find 1)
+    #[arg(long = "find-one", conflicts_with = "find")]
+    find_one: bool,
+
+    /// Stops the run cleanly after this wall time (e.g., 12h, 1h30m), as on ctrl-c: the report lists the algorithms verified so far and the offset to resume from
+    #[arg(long = "time-budget", value_name = "DURATION", value_parser = interrupt::parse_duration)]
+    time_budget: Option<std::time::Duration>,
+
+    /// Order of verification of the viable algorithms (promising: most likely to pass first; the indices are unchanged)
+    #[arg(long = "order", value_enum, default_value = "index")]
+    order: synthesis::Order,
+
+    /// Lists the outcomes in the report once the verification is complete, in this order (errors last), instead of as soon as they are obtained
+    #[arg(long = "sort", value_enum, value_name = "KEY", conflicts_with = "search")]
+    sort: Option<synthesis::ReportSort>,
+
+    #[command(flatten)]
+    pan_build: runner::PanBuildOptions,
+
+    /// Raises the search depth of pan (--depth) for the next verifications when too many are incomplete because of it
+    #[arg(long = "adaptive-depth", conflicts_with = "search")]
+    adaptive_depth: bool,
+
+    #[command(flatten)]
+    memory_limits: throttle::MemoryLimits,
+
+    #[command(flatten)]
+    interrupt: interrupt::InterruptArgs,
+
+    /// Minimal free space of the working directory in MB, under which the parallel verification moves its enclosures to a disk-backed temporary directory
+    #[arg(long = "min-free-space", value_name = "MB")]
+    min_free_space: Option<usize>,
+
+    /// Scheduler of the model (a comma-separated list runs a sweep over all schedulers)
+    #[arg(
+        short = 's',
+        long = "sched",
+        value_enum,
+        value_delimiter = ',',
+        action = clap::ArgAction::Set,
+        default_value = "async"
+    )]
+    schedulers: Vec<common::Scheduler>,
 
     /// Rigid moves restriction (otherwise non-rigid)
     #[arg(long = "rigid")]
@@ -64,6 +238,54 @@ pub struct Cli {
     #[arg(short = 'Q', long = "quasi-ss")]
     quasi_ss: bool,
 
+    /// Fairness assumption of the verification (none: pan without -f, fair-activation: weak fairness and each robot activated infinitely often)
+    #[arg(long = "fairness", value_enum, default_value = "weak")]
+    fairness: promela::Fairness,
+
+    /// Non-rigid moves: maximal number of moves stopped short before the robots are within reach (1 in the original model)
+    #[arg(long = "delta-steps", value_name = "K", default_value_t = promela::DEFAULT_DELTA_STEPS, value_parser = clap::value_parser!(u8).range(1..))]
+    delta_steps: u8,
+
+    #[command(flatten)]
+    initial: promela::InitialConditions,
+
+    /// Variants of the model checked for each algorithm (all: the 4 combinations of rigid and quasi-ss)
+    #[arg(long = "variants", value_enum, default_value = "selected")]
+    variants: Variants,
+
+    /// Shows an interactive dashboard (progress, rate, ETA, outcome counts, PASS algorithms) on the terminal: q stops
+    /// the run, the arrows scroll the PASS algorithms (the report then needs -f or -o if the output is a terminal)
+    #[arg(long = "dashboard")]
+    dashboard: bool,
+
+    /// Serves a JSON status page (progress, counts, ETA, host info) on the given port during the run
+    #[arg(long = "status-port", value_name = "PORT")]
+    status_port: Option<u16>,
+
+    /// Writes progress records (done, total, pass, fail, rate, ETA) as JSON lines to stderr (default) or to a file or named pipe
+    #[arg(long = "progress-json", value_name = "PATH", num_args = 0..=1, default_missing_value = "-")]
+    progress_json: Option<String>,
+
+    /// Interval between two progress records of --progress-json, in seconds
+    #[arg(long = "progress-interval", value_name = "SECS", default_value_t = status::PROGRESS_INTERVAL.as_secs_f64())]
+    progress_interval: f64,
+
+    /// Notifies the end of the run (or its error) with the summary line: webhook URL (posted as JSON) or shell command
+    #[arg(long = "notify", value_name = "URL_OR_COMMAND")]
+    notify: Option<String>,
+
+    /// Writes one log file per verification error in the given directory (named after the index and code of the algorithm)
+    #[arg(long = "error-logs", value_name = "DIR")]
+    error_logs: Option<PathBuf>,
+
+    /// Archives the trail of each failing verification with spin in the given directory, with the promela file of the algorithm (one subdirectory per algorithm code)
+    #[arg(long = "save-trails", value_name = "DIR")]
+    save_trails: Option<PathBuf>,
+
+    /// Saves the standard output and error of the tool failing in a verification error in the given directory, with the promela file of the algorithm (one subdirectory per algorithm code)
+    #[arg(long = "failures-dir", value_name = "DIR", default_value = "failures")]
+    failures_dir: PathBuf,
+
     /// Write output to a file (use default filename made from command line arguments if no name is specified with -o; stdout by default)
     #[arg(short = 'f', long = "file")]
     to_file: bool,
@@ -72,11 +294,51 @@ pub struct Cli {
     #[arg(short = 'o', long = "out")]
     output_dir: Option<PathBuf>,
 
-    #[arg(short = 'r', long = "ramdisk")]
-    ramdisk: Option<String>,
+    /// Makes the default output file name unique if the file already exists, with a timestamp or an incrementing counter
+    #[arg(long = "unique-name", value_enum, value_name = "SUFFIX")]
+    unique_name: Option<NameSuffix>,
+
+    /// Overwrites the output file if it already exists
+    #[arg(long = "force", conflicts_with = "append")]
+    force: bool,
+
+    /// Appends the report to the output file if it already exists
+    #[arg(long = "append")]
+    append: bool,
+
+    /// Additional output of the report: [text:|jsonl:](stdout|-|tcp://HOST:PORT|FILE), may be repeated
+    #[arg(long = "sink", value_name = "SPEC")]
+    sinks: Vec<String>,
+
+    #[command(flatten)]
+    workdir: runner::WorkdirArgs,
+
+    #[command(flatten)]
+    pub log: logging::LogArgs,
+}
+
+/// variants of the model (rigid / non-rigid moves, self-stabilizing / quasi self-stabilizing) checked for each algorithm.
+#[derive(Default, ValueEnum, Display, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Variants {
+    /// only the variant selected with `--rigid` and `--quasi-ss`
+    #[default]
+    Selected,
+    /// all four variants (see [promela::ModelRunOptions::variants])
+    All,
+}
+
+/// suffix making a default output file name unique (see [unique_path]).
+#[derive(ValueEnum, Display, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum NameSuffix {
+    /// local date and time (e.g., `_20240131-142500`)
+    Timestamp,
+    /// first free number (e.g., `_2`)
+    Counter,
 }
 
-#[derive(Default, ValueEnum, Display, Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[derive(
+    Default, ValueEnum, Display, Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize,
+)]
 pub enum ModelKind {
     #[default]
     Full,
@@ -109,333 +371,293 @@ impl TryFrom<String> for ModelKind {
     }
 }
 
-fn suggested_name(cli: &Cli) -> String {
-    let prefix = if cli.sequential { "output" } else { "parout" };
-    let class_l = if cli.class_L { "_L" } else { "" };
-    let kind = cli.category.to_string().to_lowercase();
-    let n_colors = cli.n_colors;
-    let scheduler = cli.scheduler.to_string().to_case(Case::Kebab);
-    let rigid = if cli.rigid { "_rigid" } else { "" };
-    let quasi_ss = if cli.quasi_ss { "_qss" } else { "" };
-    format!("{prefix}{class_l}_{kind}_{n_colors}_{scheduler}{rigid}{quasi_ss}.txt")
+fn suggested_name(cli: &Cli, model: &model::Model, scheduler: common::Scheduler) -> String {
+    let prefix = if cli.list_only {
+        "viable"
+    } else if cli.dry_run {
+        "dryrun"
+    } else if cli.emit_jobs.is_some() {
+        "jobs"
+    } else if cli.sequential {
+        "output"
+    } else {
+        "parout"
+    };
+    let class_l = if model.class_L { "_L" } else { "" };
+    let kind = model.category.to_string().to_lowercase();
+    let n_colors = model.n_colors;
+    let scheduler = scheduler.to_string().to_case(Case::Kebab);
+    let variants = variants_suffix(cli);
+    let sample = match cli.sample {
+        Some(n) => format!("_sample{n}-{}", cli.seed),
+        None => String::new(),
+    };
+    let slice = match (cli.offset, cli.limit) {
+        (0, None) => String::new(),
+        (offset, None) => format!("_slice{offset}-end"),
+        (offset, Some(n)) => format!("_slice{offset}-{}", offset + n),
+    };
+    format!("{prefix}{class_l}_{kind}_{n_colors}_{scheduler}{variants}{sample}{slice}.txt")
 }
 
-pub fn run(cli: &Cli) -> Result<()> {
-    use indicatif::ParallelProgressIterator;
-    use rayon::prelude::*;
-    use std::cell::RefCell;
-    use std::fs::File;
-    use std::io::Write;
-    use std::time::{Duration, Instant};
-
-    thread_local! {
-        static ENCLOSURE: RefCell<Option<PathBuf>> = RefCell::new(None);
-    }
-
-    fn with_enclosure_do<F>(work_dir: &Path, action: F) -> Result<(usize, String, SpinOutcome)>
-    where
-        F: Fn(&Path) -> Result<(usize, String, SpinOutcome)>,
-    {
-        ENCLOSURE.with(|cell| {
-            let mut enclosure = cell.borrow_mut();
-            if enclosure.is_none() {
-                let path = runner::create_enclosure(work_dir)?;
-                *enclosure = Some(path);
+/// path made unique with the given suffix if the file already exists (unchanged otherwise).
+/// A counter is added after the timestamp in the unlikely case where the timestamped file also exists.
+fn unique_path(path: PathBuf, suffix: Option<NameSuffix>) -> PathBuf {
+    let Some(suffix) = suffix.filter(|_| path.exists()) else {
+        return path;
+    };
+    let stem = path.file_stem().unwrap_or_default().to_string_lossy();
+    let extension = path
+        .extension()
+        .map(|e| format!(".{}", e.to_string_lossy()))
+        .unwrap_or_default();
+    let with_suffix = |suffix: &str| path.with_file_name(format!("{stem}_{suffix}{extension}"));
+    let stem = match suffix {
+        NameSuffix::Timestamp => {
+            let now = time::OffsetDateTime::now_local()
+                .unwrap_or_else(|_| time::OffsetDateTime::now_utc());
+            let format =
+                time::macros::format_description!("[year][month][day]-[hour][minute][second]");
+            let timestamp = now.format(&format).unwrap_or_default();
+            let timestamped = with_suffix(&timestamp);
+            if !timestamped.exists() {
+                return timestamped;
             }
-            let thread_enclosure = enclosure
-                .as_deref()
-                .ok_or_else(|| anyhow::Error::msg("Could not obtain enclosure"))?;
-            action(thread_enclosure)
-        })
-    }
-
-    let output_file_name = match cli.output_dir {
-        Some(ref path) => Some(path.to_owned()),
-        None if cli.to_file => {
-            let path: PathBuf = [DEFAULT_OUTPUT_DIR, &suggested_name(cli)].iter().collect();
-            Some(path)
+            format!("{stem}_{timestamp}")
         }
-        _ => None,
+        NameSuffix::Counter => stem.to_string(),
     };
+    (2..)
+        .map(|n| path.with_file_name(format!("{stem}_{n}{extension}")))
+        .find(|p| !p.exists())
+        .expect("some counter is free")
+}
 
-    if let Some(ref path) = output_file_name {
-        info!(
-            "Output to file: {}",
-            path.to_str().ok_or_else(|| anyhow::Error::msg(format!(
-                "cannot represent filename: {:?}",
-                path.as_os_str()
-            )))?
-        );
-    }
-
-    let mut output: Box<dyn Write> = match output_file_name {
-        Some(ref path) => Box::new(Tee::new(
-            File::options()
-                .write(true)
-                .create_new(true)
-                .open(path)
-                .context("failed to open output file (name provided)")?,
-            std::io::stdout(),
-        )),
-        None => Box::new(std::io::stdout()),
+/// part of the default output file name describing the variants of the model (e.g., `_rigid_qss`),
+/// the fairness assumption unless weak (e.g., `_rigid_nofair`), the granularity of the non-rigid moves unless
+/// the original one (e.g., `_delta3`), and the restrictions of the initial configurations (e.g., `_init0-distinct`).
+fn variants_suffix(cli: &Cli) -> String {
+    let variants = match (cli.variants, cli.rigid, cli.quasi_ss) {
+        (Variants::All, _, _) => "_variants",
+        (Variants::Selected, false, false) => "",
+        (Variants::Selected, true, false) => "_rigid",
+        (Variants::Selected, false, true) => "_qss",
+        (Variants::Selected, true, true) => "_rigid_qss",
     };
-
-    writeln!(output, "Run options: {:?}", cli)?;
-
-    info!("Preparing environment");
-
-    let model_run_options = promela::ModelRunOptions {
-        scheduler: cli.scheduler,
-        rigid: cli.rigid,
-        quasi_ss: cli.quasi_ss,
+    let fairness = match cli.fairness {
+        promela::Fairness::None => "_nofair",
+        promela::Fairness::Weak => "",
+        promela::Fairness::FairActivation => "_fairactivation",
     };
-    let t_start = Instant::now();
-    let workdir = runner::create_root_workdir(cli.ramdisk.clone())?;
-    let weak_filter = cli.weak_filter;
-    let retain_filter = cli.retain_filter;
-    let category = cli.category;
-    let n_colors = cli.n_colors;
-    #[allow(non_snake_case)]
-    let class_L = cli.class_L;
-
-    let t_prepare = Instant::now() - t_start;
-    let all_algos = generator::generate_algorithms_in_model(category, n_colors, class_L);
-    let all_viable_algos = all_algos
-        .filter(|a| a.all_gathered_are_stay())
-        .filter(|a| a.all_colors_used_in_actions())
-        .filter(|a| a.all_colors_used_in_non_gathered())
-        .filter(|a| a.is_pseudo_canonical())
-        .filter(|a| weak_filter || a.some_non_gathered_is_stay())
-        .filter(|a| weak_filter || a.some_non_gathered_is_to_half())
-        .filter(|a| weak_filter || a.some_non_gathered_is_to_other())
-        .filter(|a| !retain_filter || a.retains_color_iif_other_color_different())
-        .enumerate();
-
-    let mut n_algos: usize = 0;
-    let mut n_errors: usize = 0;
-    let mut n_pass: usize = 0;
-    let mut n_fail: usize = 0;
-    let mut n_incomplete: usize = 0;
-
-    let t_gen: Duration;
-    let t_verif: Duration;
-    let t_cleanup: Duration;
-
-    let cleanup_outcome: Result<_>; // used later
-
-    if cli.sequential {
-        //
-        // Sequential verification
-        //
-        let enclosure = runner::create_enclosure(workdir.path())?;
-
-        info!("Starting verification");
-        t_gen = Instant::now() - t_start;
-        for (i, algo) in all_viable_algos {
-            let outcome = run_verification(&enclosure, &algo, model_run_options)?;
-
-            n_algos += 1;
-            match outcome {
-                SpinOutcome::Fail => n_fail += 1,
-                SpinOutcome::Pass => n_pass += 1,
-                SpinOutcome::SearchIncomplete => n_incomplete += 1,
-            }
-            if !outcome.is_fail() {
-                writeln!(output)?;
-                writeln!(output, "{:4} : {} {}", i, outcome, &algo.as_code())?;
-            } else if (i + 1) % 100 == 0 {
-                write!(output, "\n.")?;
-            } else if (i + 1) % 10 == 0 {
-                write!(output, ". ")?;
-            } else {
-                write!(output, ".")?;
-            }
-            output.flush()?;
-        }
-        t_verif = Instant::now() - t_start;
-        t_cleanup = t_verif;
-        cleanup_outcome = Ok(());
-        // report and cleanup already done
-    } else {
-        //
-        // Parallel verification
-        //
-        let all_viable_algos = all_viable_algos.collect::<Vec<_>>();
-
-        let num_algos = all_viable_algos.len() as u64;
-
-        t_gen = Instant::now() - t_start;
-
-        // execute verification in parallel
-        info!("Starting verification (parallel)");
-        let outcomes = all_viable_algos
-            .into_par_iter()
-            .map(|(i, algo)| {
-                with_enclosure_do(workdir.path(), {
-                    |thread_enclosure| {
-                        run_verification(thread_enclosure, &algo, model_run_options)
-                            .map(|outcome| (i, algo.as_code(), outcome))
-                    }
-                })
-            })
-            .progress_count(num_algos)
-            .collect::<Vec<_>>();
+    let delta_steps = match cli.delta_steps {
+        promela::DEFAULT_DELTA_STEPS => String::new(),
+        k => format!("_delta{k}"),
+    };
+    let initial = match cli.initial.name() {
+        name if name.is_empty() => name,
+        name => format!("_{name}"),
+    };
+    format!("{variants}{fairness}{delta_steps}{initial}")
+}
 
-        info!("Cleaning up");
-        // eject ramdisk (if any)
-        t_verif = Instant::now() - t_start;
-        cleanup_outcome = runner::close_workdir(workdir);
-
-        // report PASS results / incomplete search / errors
-        t_cleanup = Instant::now() - t_start;
-        for res in outcomes.iter() {
-            match res {
-                Ok((i, algo_code, SpinOutcome::Pass)) => {
-                    writeln!(output, "{:4} : PASS {}", i, algo_code)?;
-                    output.flush()?;
-                }
-                Ok((i, algo_code, SpinOutcome::SearchIncomplete)) => {
-                    writeln!(
-                        output,
-                        "INCOMPLETE > {:4} : SearchIncomplete {}",
-                        i, algo_code
-                    )?;
-                    output.flush()?;
-                }
-                Ok(_) => { /* skip */ }
-                Err(e) => {
-                    writeln!(output, "ERROR : {:?}", e)?;
-                }
-            }
-        }
+pub fn run(cli: &Cli) -> Result<()> {
+    run_with_filters(cli, &[])
+}
 
-        // count for reporting
-        n_algos = num_algos as usize;
-        n_errors = outcomes.iter().filter(|res| res.is_err()).count();
-        n_pass = outcomes
-            .iter()
-            .filter_map(|res| res.as_ref().ok())
-            .filter(|(_, _, o)| *o == SpinOutcome::Pass)
-            .count();
-        n_fail = outcomes
-            .iter()
-            .filter_map(|res| res.as_ref().ok())
-            .filter(|(_, _, o)| *o == SpinOutcome::Fail)
-            .count();
-        n_incomplete = outcomes
-            .iter()
-            .filter_map(|res| res.as_ref().ok())
-            .filter(|(_, _, o)| *o == SpinOutcome::SearchIncomplete)
-            .count();
+/// same as [run], with additional filters provided by the caller (see [filter::ViabilityFilter]).
+/// The extra filters are applied after the filters selected on the command line.
+pub fn run_with_filters(cli: &Cli, extra_filters: &[&dyn filter::ViabilityFilter]) -> Result<()> {
+    cli.interrupt.install()?;
+    let outcome = execute_with_filters(cli, extra_filters);
+    if let Some(ref target) = cli.notify {
+        let notifier = notify::Notifier::from(target.as_str());
+        if let Err(e) = notifier.notify(&notify::summary_line(&outcome)) {
+            log::warn!("notification failed: {e:#}");
+        }
     }
-
-    let t_report = Instant::now() - t_start;
-
-    info!("Generating reports");
-    // output verification summary
-    writeln!(output, "Verification Finished with {n_pass} pass, {n_fail} fail, {n_incomplete} incomplete, {n_errors} errors ({n_algos} algorithms)")?;
-
-    // output time report:
-    // express all durations in millis
-    let t_prepare = t_prepare.as_millis();
-    let t_gen = t_gen.as_millis();
-    let t_verif = t_verif.as_millis();
-    let t_cleanup = t_cleanup.as_millis();
-    let t_report = t_report.as_millis();
-    // compute intervals
-    let delta_prepare = t_prepare;
-    let delta_gen = t_gen - t_prepare;
-    let delta_verif = t_verif - t_gen;
-    let delta_cleanup = t_cleanup - t_verif;
-    let delta_report = t_report - t_cleanup;
-    writeln!(output, "\nTiming report (Total: {} ms):", t_report)?;
-    writeln!(
-        output,
-        "| unit: ms       | prepare | generate | verify | cleanup | report |"
-    )?;
-    writeln!(
-        output,
-        "| -------------- | ------- | -------- | ------ | ------- | ------ |"
-    )?;
-    writeln!(
-        output,
-        "| **cumulative** | {} | {} | {} | {} | {} |",
-        t_prepare, t_gen, t_verif, t_cleanup, t_report
-    )?;
-    writeln!(
-        output,
-        "| **additive** | {} | {} | {} | {} | {} |",
-        delta_prepare, delta_gen, delta_verif, delta_cleanup, delta_report
-    )?;
-    writeln!(output)?;
-    writeln!(output, "Uname: {}", system_info())?;
-    writeln!(output, "Num cpus: {}", num_cpus::get())?;
-    writeln!(
-        output,
-        "OS/Arch: {} {}",
-        std::env::consts::OS,
-        std::env::consts::ARCH
-    )?;
-    output.flush()?;
-
-    drop(output); // just to make sure that the file is closed before unwinding due to other failures.
-
-    // now, the reporting file is closing:
-    // delayed reporting of the cleanup error
-    // this is to ensure that the reporting is saved before unrolling everything
-    cleanup_outcome
+    outcome.map(|_| ())
 }
 
-fn system_info() -> String {
-    duct::cmd!("uname", "-a")
-        .read()
-        .unwrap_or("<undetermined>".to_string())
-}
+fn execute_with_filters(
+    cli: &Cli,
+    extra_filters: &[&dyn filter::ViabilityFilter],
+) -> Result<synthesis::SynthesisResult> {
+    use std::io::Write;
 
-/// Provides "tee" functionality (as the `tee` command in shell)
-/// for any type implementing [std::io::Write].
-struct Tee<A, B>
-where
-    A: std::io::Write,
-    B: std::io::Write,
-{
-    writer_a: A,
-    writer_b: B,
-}
+    let config = cli.config()?;
+    let mut output = synthesis::open_output(&config.output, config.output_mode)?;
+    writeln!(output, "Run options: {:?}", cli)?;
+    writeln!(output, "Run id: {}", logging::run_id())?;
+    let metadata = metadata::RunMetadata::collect(&config).with_model_hash(config.model_hash());
+    metadata.write(&mut output)?;
+    for path in config.output.files() {
+        metadata.save_next_to(&path)?;
+    }
 
-impl<A, B> Tee<A, B>
-where
-    A: std::io::Write,
-    B: std::io::Write,
-{
-    pub fn new(writer_a: A, writer_b: B) -> Self {
-        Self { writer_a, writer_b }
+    #[cfg(feature = "tui")]
+    let dashboard = cli
+        .dashboard
+        .then(dashboard::Dashboard::new)
+        .transpose()?;
+    let status = cli
+        .status_port
+        .map(status::StatusServer::bind)
+        .transpose()?;
+    let progress = cli
+        .progress_json
+        .as_deref()
+        .map(|target| {
+            let interval = std::time::Duration::try_from_secs_f64(cli.progress_interval)
+                .ok()
+                .filter(|i| !i.is_zero())
+                .context("--progress-interval must be a positive number of seconds")?;
+            status::ProgressStream::open(target, interval)
+        })
+        .transpose()?;
+    let error_logs = cli
+        .error_logs
+        .as_ref()
+        .map(logging::ErrorLogs::new)
+        .transpose()?;
+    let mut run = extra_filters
+        .iter()
+        .fold(synthesis::SynthesisRun::new(config), |run, f| {
+            run.filter(*f)
+        });
+    #[cfg(feature = "tui")]
+    if let Some(ref dashboard) = dashboard {
+        run = run.observer(dashboard);
     }
+    if let Some(ref status) = status {
+        run = run.observer(status);
+    }
+    if let Some(ref progress) = progress {
+        run = run.observer(progress);
+    }
+    if let Some(ref error_logs) = error_logs {
+        run = run.observer(error_logs);
+    }
+    let outcome = run.execute_with_output(&mut output);
+
+    drop(output); // just to make sure that the file is closed before unwinding due to other failures.
+    outcome
 }
 
-impl<A, B> std::io::Write for Tee<A, B>
-where
-    A: std::io::Write,
-    B: std::io::Write,
-{
-    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
-        use std::io::{Error, ErrorKind};
-        let len_a = self.writer_a.write(buf)?;
-        let len_b = self.writer_b.write(buf)?;
-        if len_a == len_b {
-            Ok(len_a)
-        } else {
-            Err(Error::new(
-                ErrorKind::Other,
-                format!("different length: {len_a} vs. {len_b}"),
-            ))
+impl Cli {
+    /// builds the configuration of the synthesis (see [synthesis::SynthesisConfig]) from the command-line arguments.
+    pub fn config(&self) -> Result<synthesis::SynthesisConfig> {
+        use synthesis::*;
+
+        let models = self.model.models()?;
+        let config = SynthesisConfig::new(models[0])
+            .models(models)
+            .schedulers(self.schedulers.iter().copied())
+            .rigid(self.rigid)
+            .quasi_ss(self.quasi_ss)
+            .fairness(self.fairness)
+            .delta_steps(self.delta_steps)
+            .moves(self.moves.unwrap_or_default())
+            .initial_conditions(self.initial)
+            .no_multiplicity(self.model.no_multiplicity)
+            .variants(self.variants)
+            .filters(self.filters.filter_set())
+            .group_classes(self.group_classes)
+            .stats(self.stats)
+            .classify(self.classify)
+            .pretty(self.pretty)
+            .quiet(self.log.quiet)
+            .report_fails(self.report_fails)
+            .near_misses(self.near_misses)
+            .verbose_errors(self.verbose_errors)
+            .save_trails(self.save_trails.clone())
+            .save_failures(Some(self.failures_dir.clone()))
+            .list_only(self.list_only)
+            .emit_jobs(self.emit_jobs.clone())
+            .prefilter(self.prefilter)
+            .escalate(self.escalate)
+            .backend(self.backend)
+            .order(self.order)
+            .sort(self.sort)
+            .find(self.find.or(self.find_one.then_some(1)))
+            .time_budget(self.time_budget)
+            .pan_build(self.pan_build.clone())
+            .adaptive_depth(self.adaptive_depth)
+            .memory_limits(self.memory_limits)
+            .min_free_space(self.min_free_space)
+            .dry_run(self.dry_run.then_some(self.calibration))
+            .sample(self.sample, self.seed)
+            .sample_draws(self.sample_draws)
+            .slice(self.offset, self.limit)
+            .write_manifest(self.write_manifest.clone())
+            .manifest(self.manifest.clone())
+            .report_html(self.report_html.clone())
+            .exclude_lower(self.exclude_lower.clone())
+            .code_match(self.code_match.clone())
+            .search(self.search.map(|restarts| search::SearchOptions {
+                restarts,
+                iterations: self.search_iterations,
+                seed: self.seed,
+            }))
+            .workdir(WorkdirStrategy::from(&self.workdir))
+            .parallelism(if self.sequential {
+                Parallelism::Sequential
+            } else {
+                Parallelism::Parallel
+            });
+
+        let runs = config.runs();
+        let output = match self.output_dir {
+            Some(ref path) => ReportOutput::Tee(path.to_owned()),
+            None if self.to_file => {
+                let name = if runs.len() > 1 {
+                    sweep_name(self, &runs)
+                } else {
+                    let (model, scheduler) = runs[0];
+                    suggested_name(self, &model, scheduler)
+                };
+                let path = [DEFAULT_OUTPUT_DIR, &name].iter().collect();
+                ReportOutput::Tee(unique_path(path, self.unique_name))
+            }
+            None => ReportOutput::Stdout,
+        }
+        .with_sinks(
+            self.sinks
+                .iter()
+                .map(|s| output::SinkSpec::try_from(s.as_str()))
+                .collect::<Result<Vec<_>>>()?,
+        );
+        if self.dashboard && !cfg!(feature = "tui") {
+            bail!("--dashboard is not available: built without the `tui` feature");
         }
+        let output = if self.dashboard && std::io::stdout().is_terminal() {
+            // the dashboard takes over the terminal
+            let output = output.without_stdout();
+            if output.sinks().is_empty() {
+                bail!("--dashboard needs the report in a file (-f or -o) when the standard output is a terminal");
+            }
+            output
+        } else {
+            output
+        };
+        let config = config
+            .output(output)
+            .output_mode(synthesis::output_mode(self.force, self.append));
+        config.check_output()?;
+        Ok(config)
     }
+}
 
-    fn flush(&mut self) -> std::io::Result<()> {
-        self.writer_a.flush()?;
-        self.writer_b.flush()
-    }
+/// default name of the output file of a sweep (see [suggested_name]).
+fn sweep_name(cli: &Cli, runs: &[(model::Model, common::Scheduler)]) -> String {
+    use itertools::Itertools;
+    let models = runs.iter().map(|(m, _)| m.to_string()).unique().join("-");
+    let schedulers = cli
+        .schedulers
+        .iter()
+        .map(|s| s.to_string().to_case(Case::Kebab))
+        .join("-");
+    let variants = variants_suffix(cli);
+    format!("sweep_{models}_{schedulers}{variants}.txt")
 }
 
 #[cfg(test)]
@@ -444,7 +666,7 @@ mod tests {
     use crate::generator::tests::*;
     use algorithm::*;
     use common::*;
-    use runner::SpinOutcome;
+    use runner::{run_verification, SpinOutcome};
 
     #[test]
     fn test_try_outcomes() {
@@ -455,11 +677,7 @@ mod tests {
 
         let workdir = runner::create_root_workdir(Some(TEST_VOLUME.into())).unwrap();
         let enclosure = runner::create_enclosure(workdir.path()).unwrap();
-        let spin_options = promela::ModelRunOptions {
-            scheduler: Scheduler::Centralized,
-            rigid: false,
-            quasi_ss: false,
-        };
+        let spin_options = promela::ModelRunOptions::new(Scheduler::Centralized);
 
         let fail_algo = Algorithm::new(
             num_colors,
@@ -528,11 +746,7 @@ mod tests {
 
         let workdir = runner::create_root_workdir(Some(TEST_VOLUME.into())).unwrap();
         let enclosure = runner::create_enclosure(workdir.path()).unwrap();
-        let spin_options = promela::ModelRunOptions {
-            scheduler: Scheduler::ASYNC,
-            rigid: false,
-            quasi_ss: false,
-        };
+        let spin_options = promela::ModelRunOptions::new(Scheduler::ASYNC);
 
         let res = run_verification(&enclosure, &fail_algo, spin_options);
 
@@ -573,11 +787,7 @@ mod tests {
 
         let workdir = runner::create_root_workdir(Some(TEST_VOLUME.into())).unwrap();
         let enclosure = runner::create_enclosure(workdir.path()).unwrap();
-        let spin_options = promela::ModelRunOptions {
-            scheduler: Scheduler::ASYNC,
-            rigid: false,
-            quasi_ss: false,
-        };
+        let spin_options = promela::ModelRunOptions::new(Scheduler::ASYNC);
 
         let res = run_verification(&enclosure, &pass_algo, spin_options);
 
@@ -621,9 +831,9 @@ mod tests {
         let workdir = runner::create_root_workdir(Some(TEST_VOLUME.into())).unwrap();
         let enclosure = runner::create_enclosure(workdir.path()).unwrap();
         let mut spin_options = promela::ModelRunOptions {
-            scheduler: Scheduler::SSYNC,
             rigid: true,
             quasi_ss: true,
+            ..promela::ModelRunOptions::new(Scheduler::SSYNC)
         };
 
         let res_rigid_qss = run_verification(&enclosure, &pass_algo, spin_options);
@@ -638,24 +848,21 @@ mod tests {
         match &res_rigid_qss {
             Ok(outcome) => assert_eq!(outcome, &SpinOutcome::Pass),
             Err(e) => {
-                println!("{:?}", e);
-                assert!(false);
+                panic!("{:?}", e);
             }
         }
 
         match &res_rigid_ss {
             Ok(outcome) => assert_eq!(outcome, &SpinOutcome::Fail),
             Err(e) => {
-                println!("{:?}", e);
-                assert!(false);
+                panic!("{:?}", e);
             }
         }
 
         match &res_nrigid_ss {
             Ok(outcome) => assert_eq!(outcome, &SpinOutcome::Fail),
             Err(e) => {
-                println!("{:?}", e);
-                assert!(false);
+                panic!("{:?}", e);
             }
         }
     }
@@ -670,20 +877,75 @@ mod tests {
         quasi_ss: bool,
     ) -> Cli {
         #![allow(non_snake_case)]
-        Cli {
-            category,
-            n_colors,
-            class_L,
-            sequential,
-            scheduler,
-            to_file: false,
-            output_dir: None,
-            ramdisk: None,
-            weak_filter: false,
-            retain_filter: false,
-            rigid,
-            quasi_ss,
-        }
+        // the other options keep the defaults of the command line
+        let mut cli = Cli::try_parse_from(["synth-lights", "E3L"]).unwrap();
+        cli.model = model::Model::from((category, n_colors, class_L)).into();
+        cli.sequential = sequential;
+        cli.schedulers = vec![scheduler];
+        cli.rigid = rigid;
+        cli.quasi_ss = quasi_ss;
+        cli
+    }
+
+    #[test]
+    fn test_sweep_runs() {
+        let mut cli = make_test_cli(
+            ModelKind::Full,
+            2,
+            false,
+            false,
+            Scheduler::ASYNC,
+            false,
+            false,
+        );
+        cli.model.model = "F2,E3L".to_string();
+        cli.model.n_colors = None;
+        cli.schedulers = vec![Scheduler::SSYNC, Scheduler::ASYNC];
+        let runs = cli.config().unwrap().runs();
+        let f2 = model::Model::from((ModelKind::Full, 2, false));
+        let e3l = model::Model::from((ModelKind::External, 3, true));
+        assert_eq!(
+            runs,
+            vec![
+                (f2, Scheduler::SSYNC),
+                (f2, Scheduler::ASYNC),
+                (e3l, Scheduler::SSYNC),
+                (e3l, Scheduler::ASYNC),
+            ]
+        );
+        assert_eq!(sweep_name(&cli, &runs), "sweep_F2-E3L_ssync-async.txt");
+    }
+
+    #[test]
+    fn test_unique_path() {
+        let dir = std::env::temp_dir().join(format!("unique-path-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("parout_E3L.txt");
+        assert_eq!(unique_path(path.clone(), Some(NameSuffix::Counter)), path);
+        std::fs::write(&path, "").unwrap();
+        assert_eq!(unique_path(path.clone(), None), path);
+        let counted = unique_path(path.clone(), Some(NameSuffix::Counter));
+        assert_eq!(counted, dir.join("parout_E3L_2.txt"));
+        std::fs::write(&counted, "").unwrap();
+        assert_eq!(
+            unique_path(path.clone(), Some(NameSuffix::Counter)),
+            dir.join("parout_E3L_3.txt")
+        );
+        let timestamped = unique_path(path.clone(), Some(NameSuffix::Timestamp));
+        let name = timestamped
+            .file_name()
+            .unwrap()
+            .to_string_lossy()
+            .to_string();
+        assert!(lazy_regex::regex_is_match!(
+            r"^parout_E3L_\d{8}-\d{6}\.txt$",
+            &name
+        ));
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    fn default_name(cli: &Cli) -> String {
+        suggested_name(cli, &cli.model.model().unwrap(), cli.schedulers[0])
     }
 
     #[test]
@@ -697,7 +959,7 @@ mod tests {
             false,
             false,
         );
-        assert_eq!(suggested_name(&cli), "parout_L_full_2_async-lc-atomic.txt");
+        assert_eq!(default_name(&cli), "parout_L_full_2_async-lc-atomic.txt");
 
         let cli = make_test_cli(
             ModelKind::External,
@@ -709,7 +971,7 @@ mod tests {
             false,
         );
         assert_eq!(
-            suggested_name(&cli),
+            default_name(&cli),
             "output_external_3_async-move-regular.txt"
         );
 
@@ -723,7 +985,7 @@ mod tests {
             false,
         );
         assert_eq!(
-            suggested_name(&cli),
+            default_name(&cli),
             "parout_L_full_2_async-lc-atomic_rigid.txt"
         );
 
@@ -737,7 +999,7 @@ mod tests {
             true,
         );
         assert_eq!(
-            suggested_name(&cli),
+            default_name(&cli),
             "parout_L_full_2_async-lc-atomic_qss.txt"
         );
 
@@ -751,8 +1013,75 @@ mod tests {
             true,
         );
         assert_eq!(
-            suggested_name(&cli),
+            default_name(&cli),
             "parout_L_full_2_async-lc-atomic_rigid_qss.txt"
         );
+
+        let mut cli = make_test_cli(
+            ModelKind::Full,
+            2,
+            true,
+            false,
+            Scheduler::ASYNC_LC_Atomic,
+            false,
+            false,
+        );
+        cli.sample = Some(100);
+        cli.seed = 42;
+        assert_eq!(
+            default_name(&cli),
+            "parout_L_full_2_async-lc-atomic_sample100-42.txt"
+        );
+
+        cli.sample = None;
+        cli.offset = 10000;
+        cli.limit = Some(5000);
+        assert_eq!(
+            default_name(&cli),
+            "parout_L_full_2_async-lc-atomic_slice10000-15000.txt"
+        );
+        cli.offset = 0;
+        cli.limit = None;
+        cli.list_only = true;
+        assert_eq!(default_name(&cli), "viable_L_full_2_async-lc-atomic.txt");
+
+        cli.list_only = false;
+        cli.dry_run = true;
+        assert_eq!(default_name(&cli), "dryrun_L_full_2_async-lc-atomic.txt");
+
+        cli.dry_run = false;
+        cli.emit_jobs = Some(PathBuf::from("jobs"));
+        assert_eq!(default_name(&cli), "jobs_L_full_2_async-lc-atomic.txt");
+
+        cli.emit_jobs = None;
+        cli.rigid = true;
+        cli.variants = Variants::All;
+        assert_eq!(
+            default_name(&cli),
+            "parout_L_full_2_async-lc-atomic_variants.txt"
+        );
+
+        cli.variants = Variants::Selected;
+        cli.fairness = promela::Fairness::None;
+        assert_eq!(
+            default_name(&cli),
+            "parout_L_full_2_async-lc-atomic_rigid_nofair.txt"
+        );
+
+        cli.rigid = false;
+        cli.fairness = promela::Fairness::Weak;
+        cli.delta_steps = 3;
+        assert_eq!(
+            default_name(&cli),
+            "parout_L_full_2_async-lc-atomic_delta3.txt"
+        );
+        cli.initial.distinct = true;
+        assert_eq!(
+            default_name(&cli),
+            "parout_L_full_2_async-lc-atomic_delta3_distinct.txt"
+        );
+        let options = cli.config().unwrap().run_options(Scheduler::SSYNC);
+        assert_eq!(options.delta_steps, 3);
+        assert!(synthesis::Backend::Native.uses_spin(options));
     }
 }
@@ -0,0 +1,295 @@
+//! Structured logging: each log line is tagged with the identifier of the run and, during the verification
+//! of an algorithm, with the index and code of the algorithm (e.g., `run=5f0c… algo=12:0_1_2__S2_H0_O1 message`).
+//!
+//! The algorithm is recorded per thread by [with_algorithm], so that lines are tagged correctly in parallel runs.
+//! The [ErrorLogs] observer additionally writes one log file per verification error.
+
+use anyhow::{Context, Result};
+use log::{LevelFilter, Log, Metadata, Record};
+use simplelog::{CombinedLogger, SharedLogger};
+use std::cell::RefCell;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+use uuid::Uuid;
+
+use crate::algorithm::Algorithm;
+use crate::observer::RunObserver;
+use crate::runner::ToolError;
+
+thread_local! {
+    // (index, code) of the algorithm being verified by the current thread
+    static ALGORITHM: RefCell<Option<(usize, String)>> = const { RefCell::new(None) };
+}
+
+/// identifier of the current run (of the process), written in the report and in each log line.
+pub fn run_id() -> Uuid {
+    static RUN_ID: OnceLock<Uuid> = OnceLock::new();
+    *RUN_ID.get_or_init(Uuid::new_v4)
+}
+
+/// runs the action with the algorithm recorded as the one verified by the current thread.
+pub fn with_algorithm<T>(index: usize, algo: &Algorithm, action: impl FnOnce() -> T) -> T {
+    let previous = ALGORITHM.with(|cell| cell.replace(Some((index, algo.as_code()))));
+    let result = action();
+    ALGORITHM.with(|cell| *cell.borrow_mut() = previous);
+    result
+}
+
+/// tags of the current log line: run identifier, and algorithm being verified (if any).
+pub fn context() -> String {
+    ALGORITHM.with(|cell| match cell.borrow().as_ref() {
+        Some((index, code)) => format!("run={} algo={index}:{code}", run_id()),
+        None => format!("run={}", run_id()),
+    })
+}
+
+/// logger prefixing each record with the [context] before forwarding it.
+pub struct StructuredLogger<L: Log> {
+    inner: L,
+}
+
+impl<L: Log> StructuredLogger<L> {
+    pub fn new(inner: L) -> Self {
+        Self { inner }
+    }
+}
+
+impl<L: Log> Log for StructuredLogger<L> {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        self.inner.enabled(metadata)
+    }
+
+    fn log(&self, record: &Record) {
+        if !self.enabled(record.metadata()) {
+            return;
+        }
+        self.inner.log(
+            &Record::builder()
+                .args(format_args!("{} {}", context(), record.args()))
+                .metadata(record.metadata().clone())
+                .module_path(record.module_path())
+                .file(record.file())
+                .line(record.line())
+                .build(),
+        );
+    }
+
+    fn flush(&self) {
+        self.inner.flush()
+    }
+}
+
+/// installs the given loggers as the global logger, with structured lines.
+pub fn init(loggers: Vec<Box<dyn SharedLogger>>) -> Result<()> {
+    let combined = CombinedLogger::new(loggers);
+    log::set_max_level(combined.level());
+    log::set_boxed_logger(Box::new(StructuredLogger::new(combined)))
+        .context("failed to install the logger")
+}
+
+/// default path of the log file of the run: `logs/synth-lights-<run id>.log`.
+pub fn default_log_path() -> PathBuf {
+    PathBuf::from("logs").join(format!("synth-lights-{}.log", run_id()))
+}
+
+/// environment variable selecting the log level when no verbosity flag is given.
+pub const RUST_LOG: &str = "RUST_LOG";
+
+/// command-line arguments controlling the logging, shared by the long-running subcommands.
+#[derive(clap::Args, Clone, Debug, Default)]
+pub struct LogArgs {
+    /// Logs more (-v: info, -vv: debug, -vvv: trace); the level is otherwise read from RUST_LOG (off by default)
+    #[arg(short = 'v', long = "verbose", action = clap::ArgAction::Count)]
+    pub verbose: u8,
+
+    /// Logs nothing, regardless of RUST_LOG (the sequential mode does not print a dot per failing algorithm either)
+    #[arg(short = 'q', long = "quiet", conflicts_with = "verbose")]
+    pub quiet: bool,
+
+    /// Log file (logs/synth-lights-<run id>.log by default)
+    #[arg(long = "log-file", value_name = "PATH")]
+    pub log_file: Option<PathBuf>,
+
+    /// Disables the log file (the log is only written on the standard error)
+    #[arg(long = "no-log-file", conflicts_with = "log_file")]
+    pub no_log_file: bool,
+}
+
+impl LogArgs {
+    /// log level from the flags, or else from the value of `RUST_LOG` (with a warning if it is not recognized).
+    pub fn level(&self, rust_log: Option<&str>) -> (LevelFilter, Option<String>) {
+        match (self.quiet, self.verbose) {
+            (true, _) => (LevelFilter::Off, None),
+            (false, 0) => match rust_log.map(str::parse::<LevelFilter>) {
+                None => (LevelFilter::Off, None),
+                Some(Ok(level)) => (level, None),
+                Some(Err(_)) => (
+                    LevelFilter::Off,
+                    Some(format!(
+                        "unrecognized log level in {RUST_LOG}: {} (logging disabled)",
+                        rust_log.unwrap_or_default()
+                    )),
+                ),
+            },
+            (false, 1) => (LevelFilter::Info, None),
+            (false, 2) => (LevelFilter::Debug, None),
+            (false, _) => (LevelFilter::Trace, None),
+        }
+    }
+
+    /// path of the log file, if any.
+    pub fn log_path(&self) -> Option<PathBuf> {
+        if self.no_log_file {
+            None
+        } else {
+            Some(self.log_file.clone().unwrap_or_else(default_log_path))
+        }
+    }
+}
+
+/// observer writing one log file per verification error in a directory,
+/// named after the index and code of the algorithm, with the error and its causes.
+pub struct ErrorLogs {
+    dir: PathBuf,
+}
+
+impl ErrorLogs {
+    /// creates the directory if needed.
+    pub fn new(dir: impl Into<PathBuf>) -> Result<Self> {
+        let dir = dir.into();
+        std::fs::create_dir_all(&dir)
+            .with_context(|| format!("failed to create error log directory {}", dir.display()))?;
+        Ok(Self { dir })
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// path of the log file of the error of an algorithm.
+    pub fn path(&self, index: usize, algo: &Algorithm) -> PathBuf {
+        self.dir.join(format!("{index}_{}.log", algo.as_code()))
+    }
+}
+
+impl RunObserver for ErrorLogs {
+    fn on_error(&self, index: usize, algo: &Algorithm, error: &anyhow::Error) {
+        let path = self.path(index, algo);
+        let mut content = format!(
+            "run: {}\nindex: {index}\nalgorithm: {}\n\n{error:?}\n",
+            run_id(),
+            algo.as_code()
+        );
+        if let Some(tool_error) = error.chain().find_map(|e| e.downcast_ref::<ToolError>()) {
+            content.push_str(&format!(
+                "\n{} output:\n{}\n",
+                tool_error.tool,
+                tool_error.output()
+            ));
+        }
+        if let Err(e) = std::fs::write(&path, content) {
+            log::warn!("failed to write error log {}: {e}", path.display());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ModelKind;
+    use std::sync::Mutex;
+
+    #[derive(Default)]
+    struct Capture(Mutex<Vec<String>>);
+
+    impl Log for Capture {
+        fn enabled(&self, _metadata: &Metadata) -> bool {
+            true
+        }
+        fn log(&self, record: &Record) {
+            self.0.lock().unwrap().push(record.args().to_string());
+        }
+        fn flush(&self) {}
+    }
+
+    #[test]
+    fn test_structured_logger() {
+        let algo = Algorithm::try_parse(ModelKind::External, 3, true, "0_1_2__S2_H0_O1").unwrap();
+        let logger = StructuredLogger::new(Capture::default());
+        let log = |message: &str| {
+            logger.log(
+                &Record::builder()
+                    .args(format_args!("{message}"))
+                    .level(log::Level::Info)
+                    .build(),
+            )
+        };
+        log("before");
+        let n = with_algorithm(12, &algo, || {
+            log("during");
+            42
+        });
+        log("after");
+        assert_eq!(n, 42);
+        let run = run_id();
+        assert_eq!(
+            *logger.inner.0.lock().unwrap(),
+            [
+                format!("run={run} before"),
+                format!("run={run} algo=12:0_1_2__S2_H0_O1 during"),
+                format!("run={run} after"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_log_args() {
+        let args = LogArgs::default();
+        assert_eq!(args.level(None), (LevelFilter::Off, None));
+        assert_eq!(args.level(Some("DEBUG")), (LevelFilter::Debug, None));
+        let (level, warning) = args.level(Some("loud"));
+        assert_eq!(level, LevelFilter::Off);
+        assert!(warning.unwrap().contains("loud"));
+
+        let verbose = |verbose| LogArgs {
+            verbose,
+            ..LogArgs::default()
+        };
+        assert_eq!(verbose(1).level(Some("error")).0, LevelFilter::Info);
+        assert_eq!(verbose(2).level(None).0, LevelFilter::Debug);
+        assert_eq!(verbose(5).level(None).0, LevelFilter::Trace);
+        let quiet = LogArgs {
+            quiet: true,
+            ..LogArgs::default()
+        };
+        assert_eq!(quiet.level(Some("trace")).0, LevelFilter::Off);
+
+        assert_eq!(args.log_path(), Some(default_log_path()));
+        let no_file = LogArgs {
+            no_log_file: true,
+            ..LogArgs::default()
+        };
+        assert_eq!(no_file.log_path(), None);
+    }
+
+    #[test]
+    fn test_error_logs() {
+        let dir = std::env::temp_dir().join(format!("error-logs-{}", Uuid::new_v4()));
+        let logs = ErrorLogs::new(&dir).unwrap();
+        let algo = Algorithm::try_parse(ModelKind::External, 3, true, "0_1_2__S2_H0_O1").unwrap();
+        let error = anyhow::Error::new(ToolError {
+            tool: "clang".into(),
+            code: Some(1),
+            stdout: String::new(),
+            stderr: "pan.c:1: error: no space left on device\n".into(),
+        })
+        .context("verification failed");
+        logs.on_error(3, &algo, &error);
+        let content = std::fs::read_to_string(dir.join("3_0_1_2__S2_H0_O1.log")).unwrap();
+        assert!(content.contains("algorithm: 0_1_2__S2_H0_O1"));
+        assert!(content.contains("verification failed"));
+        assert!(content.contains("clang exited with code 1"));
+        assert!(content.contains("clang output:\npan.c:1: error: no space left on device\n"));
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
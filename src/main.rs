@@ -1,45 +1,38 @@
 use anyhow::Result;
-use clap::Parser;
-use synth_lights::Cli;
-
-use log::info;
+use synth_lights::commands::App;
+use synth_lights::logging::{self, RUST_LOG};
 
 use simplelog::*;
 
-const RUST_LOG: &str = "RUST_LOG";
+fn main() -> Result<()> {
+    let app = App::parse_args();
+    let log_args = app.command.log_args();
 
-fn env_loglevel() -> LevelFilter {
-    let log_level = std::env::var_os(RUST_LOG).map(|s| s.to_string_lossy().to_lowercase());
-    match log_level.as_deref() {
-        Some("off") | None => LevelFilter::Off,
-        Some("trace") => LevelFilter::Trace,
-        Some("debug") => LevelFilter::Debug,
-        Some("info") => LevelFilter::Info,
-        Some("warn") => LevelFilter::Warn,
-        Some("error") => LevelFilter::Error,
-        Some(s) => panic!("Unrecognized error level in RUST_LOG: {}", s),
+    let rust_log = std::env::var_os(RUST_LOG).map(|s| s.to_string_lossy().to_string());
+    let (log_level, warning) = log_args.level(rust_log.as_deref());
+    if let Some(warning) = warning {
+        eprintln!("Warning: {warning}");
     }
-}
-
-fn main() -> Result<()> {
-    CombinedLogger::init(vec![
-        TermLogger::new(
-            env_loglevel(),
+    let mut loggers: Vec<Box<dyn SharedLogger>> = vec![TermLogger::new(
+        log_level,
+        Config::default(),
+        TerminalMode::Stderr,
+        ColorChoice::Auto,
+    )];
+    if let Some(log_path) = log_args
+        .log_path()
+        .filter(|_| log_level != LevelFilter::Off)
+    {
+        if let Some(dir) = log_path.parent().filter(|d| !d.as_os_str().is_empty()) {
+            std::fs::create_dir_all(dir)?;
+        }
+        loggers.push(WriteLogger::new(
+            log_level,
             Config::default(),
-            TerminalMode::Stderr,
-            ColorChoice::Auto,
-        ),
-        WriteLogger::new(
-            env_loglevel(),
-            Config::default(),
-            std::fs::File::create("synth-lights.log").unwrap(),
-        ),
-    ])
-    .unwrap();
-
-    let cli = Cli::parse();
-
-    info!("Run options: {:?}", cli);
+            std::fs::File::create(log_path)?,
+        ));
+    }
+    logging::init(loggers)?;
 
-    synth_lights::run(&cli)
+    app.command.run()
 }
@@ -0,0 +1,204 @@
+//! Manifests of the enumeration of the viable algorithms, to combine results safely.
+//!
+//! The index of an algorithm in a report is its position in the enumeration of the viable algorithms of the run
+//! (see [crate::synthesis::generate_and_filter]), which depends on the generator, on the filters and on the version of
+//! the crate. A manifest records this order explicitly: the version of the enumeration ([ENUMERATION_VERSION]), the
+//! model, a description of the enumeration (filters, sampling), and a hash of the code of the algorithm at each index.
+//! Reports produced on different machines or with different versions (e.g., slices of a run, see `--offset`) can then
+//! be checked against the same manifest before they are combined.
+
+use anyhow::{bail, Context, Result};
+use lazy_regex::regex_captures;
+use std::collections::BTreeMap;
+use std::io::Write;
+use std::path::Path;
+
+use crate::algorithm::Algorithm;
+use crate::model::Model;
+use crate::report::Report;
+
+/// version of the order of enumeration of the viable algorithms, to be increased whenever the order changes.
+pub const ENUMERATION_VERSION: u32 = 1;
+
+const HEADER: &str = "# synth-lights enumeration manifest";
+
+/// hash of the code of an algorithm (64-bit FNV-1a, stable across platforms and versions).
+pub fn code_hash(code: &str) -> u64 {
+    code.bytes().fold(0xcbf2_9ce4_8422_2325, |hash, byte| {
+        (hash ^ byte as u64).wrapping_mul(0x0100_0000_01b3)
+    })
+}
+
+/// order of enumeration of the viable algorithms of a model: hash of the code of the algorithm at each index.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Manifest {
+    pub version: u32,
+    pub model: Model,
+    /// description of the enumeration (filters, sampling), which must match to compare indices
+    pub enumeration: String,
+    pub hashes: BTreeMap<usize, u64>,
+}
+
+impl Manifest {
+    /// manifest of the current version for the given algorithms with their indices.
+    pub fn new(
+        model: Model,
+        enumeration: impl Into<String>,
+        algos: impl IntoIterator<Item = (usize, Algorithm)>,
+    ) -> Self {
+        Self {
+            version: ENUMERATION_VERSION,
+            model,
+            enumeration: enumeration.into(),
+            hashes: algos
+                .into_iter()
+                .map(|(i, algo)| (i, code_hash(&algo.as_code())))
+                .collect(),
+        }
+    }
+
+    pub fn write(&self, output: &mut dyn Write) -> std::io::Result<()> {
+        writeln!(output, "{HEADER}")?;
+        writeln!(output, "version {}", self.version)?;
+        writeln!(output, "model {}", self.model)?;
+        writeln!(output, "enumeration {}", self.enumeration)?;
+        for (i, hash) in &self.hashes {
+            writeln!(output, "{i} {hash:016x}")?;
+        }
+        Ok(())
+    }
+
+    /// writes the manifest to a file.
+    pub fn save(&self, path: &Path) -> Result<()> {
+        let mut file = std::io::BufWriter::new(
+            std::fs::File::create(path)
+                .with_context(|| format!("failed to create manifest {}", path.display()))?,
+        );
+        self.write(&mut file)?;
+        file.flush()?;
+        Ok(())
+    }
+
+    pub fn parse(text: &str) -> Result<Self> {
+        let mut lines = text.lines().enumerate();
+        if lines.next().map(|(_, l)| l.trim_end()) != Some(HEADER) {
+            bail!("not an enumeration manifest (expected '{HEADER}')");
+        }
+        let mut field = |name: &str| -> Result<String> {
+            match lines.next() {
+                Some((_, line)) => {
+                    match line.strip_prefix(name).and_then(|v| v.strip_prefix(' ')) {
+                        Some(value) => Ok(value.to_string()),
+                        None => bail!("expected '{name}' in manifest, found: {line}"),
+                    }
+                }
+                None => bail!("missing '{name}' in manifest"),
+            }
+        };
+        let version = field("version")?
+            .parse()
+            .context("invalid version in manifest")?;
+        let model = Model::try_from(field("model")?.as_str())?;
+        let enumeration = field("enumeration")?;
+        let mut hashes = BTreeMap::new();
+        for (n, line) in lines {
+            let Some((_, index, hash)) = regex_captures!(r"^(\d+) ([0-9a-f]{16})\s*$", line) else {
+                bail!("invalid manifest line {}: {line}", n + 1);
+            };
+            hashes.insert(index.parse()?, u64::from_str_radix(hash, 16)?);
+        }
+        Ok(Self {
+            version,
+            model,
+            enumeration,
+            hashes,
+        })
+    }
+
+    pub fn read(path: &Path) -> Result<Self> {
+        let text = std::fs::read_to_string(path)
+            .with_context(|| format!("failed to read manifest {}", path.display()))?;
+        Self::parse(&text).with_context(|| format!("invalid manifest {}", path.display()))
+    }
+
+    /// checks that the manifest describes the same enumeration as the current version for the model.
+    pub fn check_enumeration(&self, model: Model, enumeration: &str) -> Result<()> {
+        if self.version != ENUMERATION_VERSION {
+            bail!(
+                "manifest of enumeration version {} instead of {ENUMERATION_VERSION}",
+                self.version
+            );
+        }
+        if self.model != model {
+            bail!("manifest of model {} instead of {model}", self.model);
+        }
+        if self.enumeration != enumeration {
+            bail!(
+                "manifest of enumeration '{}' instead of '{enumeration}'",
+                self.enumeration
+            );
+        }
+        Ok(())
+    }
+
+    /// checks that the algorithm with the given code has the given index in the manifest.
+    pub fn check(&self, index: usize, code: &str) -> Result<()> {
+        match self.hashes.get(&index) {
+            Some(&hash) if hash == code_hash(code) => Ok(()),
+            Some(_) => bail!("algorithm {index} ({code}) differs from the manifest"),
+            None => bail!("algorithm {index} ({code}) is not in the manifest"),
+        }
+    }
+
+    /// checks all the algorithms listed in the report; returns their number.
+    pub fn check_report(&self, report: &Report) -> Result<usize> {
+        let mut n_checked = 0;
+        for run in &report.runs {
+            for entry in &run.entries {
+                self.check(entry.index, &entry.code)?;
+                n_checked += 1;
+            }
+        }
+        Ok(n_checked)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::generator;
+
+    #[test]
+    fn test_manifest() {
+        // the hash is part of the format: it must not change
+        assert_eq!(code_hash(""), 0xcbf2_9ce4_8422_2325);
+        assert_eq!(code_hash("a"), 0xaf63_dc4c_8601_ec8c);
+
+        let model = Model::try_from("F2L").unwrap();
+        let algos = generator::generate_algorithms_in_model(model.category, model.n_colors, true)
+            .enumerate()
+            .skip(10)
+            .take(5)
+            .collect::<Vec<_>>();
+        let manifest = Manifest::new(model, "filters=none", algos.clone());
+        let mut text = Vec::new();
+        manifest.write(&mut text).unwrap();
+        let text = String::from_utf8(text).unwrap();
+        assert!(text.starts_with("# synth-lights enumeration manifest\nversion 1\nmodel F2L\n"));
+        let parsed = Manifest::parse(&text).unwrap();
+        assert_eq!(parsed, manifest);
+
+        assert!(parsed.check_enumeration(model, "filters=none").is_ok());
+        assert!(parsed
+            .check_enumeration(model, "filters=canonical")
+            .is_err());
+        let (i, algo) = &algos[0];
+        assert!(parsed.check(*i, &algo.as_code()).is_ok());
+        assert!(parsed.check(*i + 1, &algo.as_code()).is_err());
+        assert!(parsed.check(0, &algo.as_code()).is_err());
+
+        let report = Report::parse(&format!("  {i} : PASS {}\n", algo.as_code())).unwrap();
+        assert_eq!(parsed.check_report(&report).unwrap(), 1);
+        assert!(Manifest::parse("version 1\n").is_err());
+    }
+}
@@ -0,0 +1,314 @@
+//! Merging of the reports of the shards of a run (slices verified on several machines with `--offset` and `--limit`,
+//! or the shards of a cluster run, see [crate::cluster]) into one consolidated report.
+//!
+//! The shards must be single runs with the same effective options (except those of the slice, of the output and of
+//! the environment, see [comparable_options]), under the same model (see [crate::metadata::model_hash]), and with
+//! disjoint ranges of indices; the merged report lists their algorithms
+//! in the order of the indices, with the sum of their counts and durations.
+
+use anyhow::{bail, Context, Result};
+use std::io::Write;
+use std::ops::Range;
+
+use crate::report::{Entry, Report, Summary};
+
+/// fields of the effective options that may differ between the shards of a run.
+const SHARD_FIELDS: [&str; 10] = [
+    "offset",
+    "limit",
+    "output",
+    "output_mode",
+    "workdir",
+    "time_budget",
+    "parallelism",
+    "memory_limits",
+    "min_free_space",
+    "quiet",
+];
+
+/// report of a shard, with its name (e.g., its file).
+#[derive(Clone, Debug)]
+pub struct ShardReport {
+    pub name: String,
+    pub report: Report,
+}
+
+/// shard of a merged report.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MergedShard {
+    pub name: String,
+    /// indices of the viable algorithms verified by the shard
+    pub range: Range<usize>,
+    pub summary: Summary,
+    pub total_ms: Option<u64>,
+}
+
+/// consolidated report of the shards of a run.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Merged {
+    /// shards in the order of their indices
+    pub shards: Vec<MergedShard>,
+    /// algorithms listed in the shards, in the order of their indices
+    pub entries: Vec<Entry>,
+    pub summary: Summary,
+    /// effective options of the shards, if given
+    pub options: Option<String>,
+    /// hash of the model of the shards, if given
+    pub model_hash: Option<String>,
+}
+
+impl Merged {
+    /// sum of the durations of the shards in ms (those without a timing report are not counted).
+    pub fn total_ms(&self) -> u64 {
+        self.shards.iter().filter_map(|s| s.total_ms).sum()
+    }
+
+    /// duration of the longest shard in ms.
+    pub fn longest_ms(&self) -> u64 {
+        self.shards
+            .iter()
+            .filter_map(|s| s.total_ms)
+            .max()
+            .unwrap_or(0)
+    }
+
+    /// indices between the shards not verified by any of them.
+    pub fn gaps(&self) -> Vec<Range<usize>> {
+        self.shards
+            .windows(2)
+            .filter(|w| w[0].range.end < w[1].range.start)
+            .map(|w| w[0].range.end..w[1].range.start)
+            .collect()
+    }
+
+    /// writes the merged report, parsed as a single run by [Report::parse].
+    pub fn write(&self, output: &mut dyn Write) -> Result<()> {
+        writeln!(output, "Merged report of {} shards", self.shards.len())?;
+        if let Some(ref options) = self.options {
+            writeln!(output, "Effective options: {options}")?;
+        }
+        if let Some(ref hash) = self.model_hash {
+            writeln!(output, "Model hash: {hash}")?;
+        }
+        for shard in self.shards.iter() {
+            writeln!(
+                output,
+                "SHARD > {} : {}..{} : {}",
+                shard.name, shard.range.start, shard.range.end, shard.summary
+            )?;
+        }
+        for gap in self.gaps() {
+            writeln!(output, "GAP > {}..{} : not verified", gap.start, gap.end)?;
+        }
+        writeln!(output)?;
+        for entry in self.entries.iter() {
+            writeln!(output, "{entry}")?;
+        }
+        writeln!(output, "{}", self.summary)?;
+        writeln!(
+            output,
+            "\nTiming report (Total: {} ms): sum over {} shards, longest shard: {} ms",
+            self.total_ms(),
+            self.shards.len(),
+            self.longest_ms()
+        )?;
+        output.flush()?;
+        Ok(())
+    }
+}
+
+/// effective options without the fields that may differ between shards (see [SHARD_FIELDS]), from their debug
+/// representation (e.g., `SynthesisConfig { models: [..], offset: 0, .. }`).
+pub fn comparable_options(options: &str) -> String {
+    let (Some(open), Some(close)) = (options.find('{'), options.rfind('}')) else {
+        return options.to_string();
+    };
+    // top-level fields, split at the commas outside brackets and strings
+    let mut fields = Vec::new();
+    let (mut depth, mut in_string, mut escaped, mut start) = (0, false, false, open + 1);
+    for (i, c) in options[open + 1..close].char_indices() {
+        let i = open + 1 + i;
+        match c {
+            _ if escaped => escaped = false,
+            '\\' if in_string => escaped = true,
+            '"' => in_string = !in_string,
+            '(' | '[' | '{' if !in_string => depth += 1,
+            ')' | ']' | '}' if !in_string => depth -= 1,
+            ',' if !in_string && depth == 0 => {
+                fields.push(options[start..i].trim());
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    fields.push(options[start..close].trim());
+    let kept = fields
+        .into_iter()
+        .filter(|field| {
+            let name = field.split(':').next().unwrap_or_default().trim();
+            !field.is_empty() && !SHARD_FIELDS.contains(&name)
+        })
+        .collect::<Vec<_>>();
+    format!("{}{{ {} }}", &options[..open], kept.join(", "))
+}
+
+/// merges the reports of the shards of a run; fails if they are not single complete runs, have different options or
+/// models, or overlap.
+pub fn merge(reports: &[ShardReport]) -> Result<Merged> {
+    let mut merged = Merged::default();
+    let mut reference: Option<(&str, String)> = None;
+    let mut model: Option<(&str, &str)> = None;
+    for ShardReport { name, report } in reports {
+        let [run] = report.runs.as_slice() else {
+            bail!(
+                "{name} has {} runs (a shard is a single run)",
+                report.runs.len()
+            );
+        };
+        let summary = run
+            .summary
+            .with_context(|| format!("{name} has no summary (incomplete run?)"))?;
+        if let Some(ref options) = report.options {
+            let options = comparable_options(options);
+            match reference {
+                Some((first, ref expected)) if *expected != options => {
+                    bail!("{name} was run with other options than {first}:\n  {expected}\n  {options}")
+                }
+                Some(_) => {}
+                None => {
+                    merged.options = report.options.clone();
+                    reference = Some((name, options));
+                }
+            }
+        }
+        if let Some(ref hash) = report.model_hash {
+            match model {
+                Some((first, expected)) if expected != hash => bail!(
+                    "{name} was run under another model than {first} (hash {hash}, not {expected}): \
+                    the Promela templates or the options of the verification differ"
+                ),
+                Some(_) => {}
+                None => {
+                    merged.model_hash = Some(hash.clone());
+                    model = Some((name, hash));
+                }
+            }
+        }
+        merged.summary += summary;
+        merged.entries.extend(run.entries.iter().cloned());
+        merged.shards.push(MergedShard {
+            name: name.clone(),
+            range: run.offset..run.offset + summary.n_algos,
+            summary,
+            total_ms: run.total_ms,
+        });
+    }
+    merged.shards.sort_by_key(|s| (s.range.start, s.range.end));
+    if let Some(w) = merged
+        .shards
+        .windows(2)
+        .find(|w| w[0].range.end > w[1].range.start)
+    {
+        bail!(
+            "the shards {} ({}..{}) and {} ({}..{}) overlap",
+            w[0].name,
+            w[0].range.start,
+            w[0].range.end,
+            w[1].name,
+            w[1].range.start,
+            w[1].range.end
+        );
+    }
+    merged.entries.sort_by_key(|e| e.index);
+    Ok(merged)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn shard(name: &str, offset: usize, body: &str, options: &str) -> ShardReport {
+        let text = format!(
+            "Effective options: SynthesisConfig {{ models: [F2L], offset: {offset}, limit: Some(4), output: Tee(\"{name}, x\"), sort: None }}\n\
+            {body}Slice: viable algorithms from index {offset}, 4 at most\n\nTiming report (Total: {} ms):\n",
+            100 + offset
+        );
+        ShardReport {
+            name: name.to_string(),
+            report: Report::parse(&text.replace("sort: None", options)).unwrap(),
+        }
+    }
+
+    #[test]
+    fn test_merge() {
+        assert_eq!(
+            comparable_options("C { a: [1, 2], offset: 3, output: Tee(\"a, b\"), b: \"}, c\" }"),
+            "C { a: [1, 2], b: \"}, c\" }"
+        );
+
+        let a = shard(
+            "a.txt",
+            0,
+            "   3 : PASS 0_1_2__S2_H0_O1\nVerification Finished with 1 pass, 3 fail, 0 incomplete, 0 errors (4 algorithms)\n",
+            "sort: None",
+        );
+        let b = shard(
+            "b.txt",
+            8,
+            "INCOMPLETE >    9 : SearchIncomplete 0_1_2__S2_H1_O0\nVerification Finished with 0 pass, 3 fail, 1 incomplete, 0 errors (4 algorithms)\n",
+            "sort: None",
+        );
+        let merged = merge(&[b.clone(), a.clone()]).unwrap();
+        assert_eq!(merged.shards[0].range, 0..4);
+        assert_eq!(merged.gaps(), vec![4..8]);
+        assert_eq!(
+            (
+                merged.summary.n_algos,
+                merged.total_ms(),
+                merged.longest_ms()
+            ),
+            (8, 208, 108)
+        );
+        assert_eq!(
+            merged.entries.iter().map(|e| e.index).collect::<Vec<_>>(),
+            [3, 9]
+        );
+
+        let mut text = Vec::new();
+        merged.write(&mut text).unwrap();
+        let report = Report::parse(&String::from_utf8(text).unwrap()).unwrap();
+        assert_eq!(report.runs[0].entries, merged.entries);
+        assert_eq!(report.runs[0].summary, Some(merged.summary));
+        assert_eq!(report.runs[0].total_ms, Some(208));
+
+        let overlapping = shard(
+            "c.txt",
+            2,
+            "Verification Finished with 0 pass, 4 fail, 0 incomplete, 0 errors (4 algorithms)\n",
+            "sort: None",
+        );
+        assert!(merge(&[a.clone(), overlapping])
+            .unwrap_err()
+            .to_string()
+            .contains("overlap"));
+        let other = shard(
+            "d.txt",
+            4,
+            "Verification Finished with 0 pass, 4 fail, 0 incomplete, 0 errors (4 algorithms)\n",
+            "sort: Some(Code)",
+        );
+        let mut changed = b.clone();
+        changed.report.model_hash = Some("0123456789abcdef".into());
+        let mut a_hashed = a.clone();
+        a_hashed.report.model_hash = Some("fedcba9876543210".into());
+        assert!(merge(&[a_hashed.clone(), b.clone()]).is_ok());
+        assert!(merge(&[a_hashed, changed])
+            .unwrap_err()
+            .to_string()
+            .contains("another model"));
+        assert!(merge(&[a, other])
+            .unwrap_err()
+            .to_string()
+            .contains("other options"));
+    }
+}
@@ -0,0 +1,233 @@
+//! Metadata of a run, to trust and reproduce its results later: versions of the crate and of the tools, hashes of the
+//! Promela templates, and the effective configuration (after presets and defaults).
+//!
+//! The metadata are written in the header of the report (see [RunMetadata::write]), and saved as JSON next to each
+//! report file (`<report>.meta.json`, see [RunMetadata::save_next_to]). The hash of the model (see [model_hash]) keeps
+//! results obtained under different semantics from being mixed: a report is only appended to (e.g., when a run is
+//! resumed with `--offset`) or merged with reports of the same model.
+
+use anyhow::{bail, Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use crate::doctor;
+use crate::logging;
+use crate::manifest::code_hash;
+use crate::promela::{self, ModelRunOptions};
+use crate::report::Report;
+use crate::runner::PanBuildOptions;
+
+/// git commit of the build (`unknown` outside a git repository).
+pub const GIT_COMMIT: &str = env!("SYNTH_LIGHTS_GIT_COMMIT");
+
+/// version of rustc of the build.
+pub const RUSTC_VERSION: &str = env!("SYNTH_LIGHTS_RUSTC_VERSION");
+
+/// extension of the metadata file of a report.
+const EXTENSION: &str = "meta.json";
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RunMetadata {
+    pub version: String,
+    pub git_commit: String,
+    pub rustc: String,
+    /// version of spin, if found
+    pub spin: Option<String>,
+    /// version of clang (compiling pan), if found
+    pub clang: Option<String>,
+    /// hash of each Promela template (see [code_hash])
+    pub templates: BTreeMap<String, String>,
+    /// hash of the semantics of the model checked (see [model_hash])
+    #[serde(default)]
+    pub model_hash: Option<String>,
+    pub command_line: Vec<String>,
+    pub run_id: String,
+    /// effective options of the run
+    pub options: String,
+}
+
+impl RunMetadata {
+    /// metadata of the current process for a run with the given effective options (e.g., the debug representation of
+    /// its configuration).
+    pub fn collect(options: impl std::fmt::Debug) -> Self {
+        Self {
+            version: env!("CARGO_PKG_VERSION").to_string(),
+            git_commit: GIT_COMMIT.to_string(),
+            rustc: RUSTC_VERSION.to_string(),
+            spin: doctor::tool_version("spin", &["-V"]).ok(),
+            clang: doctor::tool_version("clang", &["--version"]).ok(),
+            templates: template_hashes(),
+            model_hash: None,
+            command_line: std::env::args().collect(),
+            run_id: logging::run_id().to_string(),
+            options: format!("{options:?}"),
+        }
+    }
+
+    /// same metadata, with the hash of the model checked by the run (see [model_hash]).
+    pub fn with_model_hash(self, model_hash: String) -> Self {
+        Self {
+            model_hash: Some(model_hash),
+            ..self
+        }
+    }
+
+    /// writes the metadata as lines of the header of a report.
+    pub fn write(&self, output: &mut dyn Write) -> std::io::Result<()> {
+        let or_not_found = |version: &Option<String>| version.clone().unwrap_or("not found".into());
+        writeln!(
+            output,
+            "Version: synth-lights {} (commit {}, {})",
+            self.version, self.git_commit, self.rustc
+        )?;
+        writeln!(
+            output,
+            "Tools: spin: {}; clang: {}",
+            or_not_found(&self.spin),
+            or_not_found(&self.clang)
+        )?;
+        let templates = self
+            .templates
+            .iter()
+            .map(|(name, hash)| format!("{name}={hash}"))
+            .collect::<Vec<_>>();
+        writeln!(output, "Templates: {}", templates.join(", "))?;
+        if let Some(ref hash) = self.model_hash {
+            writeln!(output, "Model hash: {hash}")?;
+        }
+        writeln!(output, "Command line: {}", self.command_line.join(" "))?;
+        writeln!(output, "Effective options: {}", self.options)
+    }
+
+    /// path of the metadata file of a report.
+    pub fn path_next_to(report: &Path) -> PathBuf {
+        let mut name = report.as_os_str().to_owned();
+        name.push(format!(".{EXTENSION}"));
+        PathBuf::from(name)
+    }
+
+    /// saves the metadata as JSON next to the report (see [RunMetadata::path_next_to]).
+    pub fn save_next_to(&self, report: &Path) -> Result<()> {
+        let path = Self::path_next_to(report);
+        let json = serde_json::to_string_pretty(self)?;
+        std::fs::write(&path, json + "\n")
+            .with_context(|| format!("failed to write metadata {}", path.display()))
+    }
+
+    pub fn read(path: &Path) -> Result<Self> {
+        let text = std::fs::read_to_string(path)
+            .with_context(|| format!("failed to read metadata {}", path.display()))?;
+        serde_json::from_str(&text).with_context(|| format!("invalid metadata {}", path.display()))
+    }
+}
+
+/// hash of each embedded Promela template, by file name.
+pub fn template_hashes() -> BTreeMap<String, String> {
+    promela::PML_FILES
+        .iter()
+        .map(|(name, content)| (name.to_string(), format!("{:016x}", code_hash(content))))
+        .collect()
+}
+
+/// hash of the semantics of a model: the Promela templates, and the options of its verifications (defines of spin, and
+/// build and arguments of pan), in the order of the variants.
+pub fn model_hash(
+    variants: impl IntoIterator<Item = ModelRunOptions>,
+    build: &PanBuildOptions,
+) -> String {
+    let mut text = String::new();
+    for (name, content) in promela::PML_FILES {
+        text += &format!("{name}\n{content}\n");
+    }
+    text += &build.clang_defines().join(" ");
+    text += if build.safety_first {
+        " safety-first\n"
+    } else {
+        "\n"
+    };
+    for options in variants {
+        // the arguments of Fairness::pan_args with the default depth, so that the hash is unchanged
+        let pan_args =
+            PanBuildOptions::pan_args_with_depth(options.fairness.pan_args(), build.depth())
+                .join(" ");
+        text += &format!(
+            "{} / {pan_args}\n",
+            options.into_iter().collect::<Vec<_>>().join(" ")
+        );
+    }
+    format!("{:016x}", code_hash(&text))
+}
+
+/// checks that the results of a report (e.g., the one a run is appended to) were obtained under the model with the
+/// given hash; a report without hash (written by an older version) is accepted.
+pub fn check_model_hash(report: &Path, hash: &str) -> Result<()> {
+    let previous = Report::read(report)?.model_hash;
+    match previous {
+        Some(previous) if previous != hash => bail!(
+            "the results of {} were obtained under another model (hash {previous}, now {hash}): \
+            the Promela templates or the options of the verification differ",
+            report.display()
+        ),
+        _ => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_metadata() {
+        let metadata = RunMetadata::collect(("F2L", 3));
+        assert_eq!(metadata.version, env!("CARGO_PKG_VERSION"));
+        assert_eq!(metadata.templates.len(), promela::PML_FILES.len());
+        assert_eq!(metadata.options, "(\"F2L\", 3)");
+
+        let mut header = Vec::new();
+        metadata.write(&mut header).unwrap();
+        let header = String::from_utf8(header).unwrap();
+        assert!(header.starts_with(&format!(
+            "Version: synth-lights {} (commit ",
+            metadata.version
+        )));
+        assert!(header.contains("\nTemplates: MainGathering.pml="));
+        assert!(header.ends_with("Effective options: (\"F2L\", 3)\n"));
+
+        let dir = std::env::temp_dir().join(format!("synth-lights-meta-{}", metadata.run_id));
+        std::fs::create_dir_all(&dir).unwrap();
+        let report = dir.join("report.txt");
+        metadata.save_next_to(&report).unwrap();
+        let path = RunMetadata::path_next_to(&report);
+        assert_eq!(path, dir.join("report.txt.meta.json"));
+        assert_eq!(RunMetadata::read(&path).unwrap(), metadata);
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_model_hash() {
+        use crate::common::Scheduler;
+        use crate::synthesis::SynthesisConfig;
+
+        let config = SynthesisConfig::new("F2L".try_into().unwrap()).scheduler(Scheduler::SSYNC);
+        let hash = config.model_hash();
+        assert_eq!(hash, config.clone().model_hash());
+        assert_ne!(hash, config.clone().rigid(true).model_hash());
+        let build = PanBuildOptions {
+            collapse: true,
+            ..Default::default()
+        };
+        assert_ne!(hash, config.clone().pan_build(build).model_hash());
+
+        let dir = std::env::temp_dir().join(format!("synth-lights-hash-{}", logging::run_id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let report = dir.join("report.txt");
+        std::fs::write(&report, format!("Model hash: {hash}\n")).unwrap();
+        assert!(check_model_hash(&report, &hash).is_ok());
+        assert!(check_model_hash(&report, "0000000000000000").is_err());
+        std::fs::write(&report, "   3 : PASS 0_1_2__S2_H0_O1\n").unwrap();
+        assert!(check_model_hash(&report, "0000000000000000").is_ok());
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
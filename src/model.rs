@@ -1,8 +1,9 @@
 use super::*;
 use lazy_regex::regex_captures;
+use serde::{Deserialize, Serialize};
 
 
-#[derive(Default, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 #[allow(non_snake_case)]
 pub struct Model {
     pub category: ModelKind,
@@ -48,6 +49,129 @@ impl TryFrom<String> for Model {
     }
 }
 
+impl std::fmt::Display for Model {
+    /// compact model string (e.g., `F3L`).
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let kind = match self.category {
+            ModelKind::Full => "F",
+            ModelKind::Internal => "I",
+            ModelKind::External => "E",
+        };
+        let class_l = if self.class_L { "L" } else { "" };
+        write!(f, "{kind}{}{class_l}", self.n_colors)
+    }
+}
+
+impl Model {
+    /// resolves a model given on the command line, either as a compact model string (e.g., `F3L`),
+    /// or as a category (e.g., `full`) followed by a number of colors.
+    /// Class L is selected if either the model string ends with `L` or `class_l` is set.
+    #[allow(non_snake_case)]
+    pub fn from_args(model: &str, n_colors: Option<u8>, class_L: bool) -> Result<Self> {
+        if let Ok(parsed) = Model::try_from(model) {
+            if n_colors.is_some() {
+                anyhow::bail!("number of colors given twice (model string: {model})");
+            }
+            return Ok(Model {
+                class_L: parsed.class_L || class_L,
+                ..parsed
+            });
+        }
+        let category = ModelKind::from_str(model, true).map_err(|_| {
+            anyhow::anyhow!(
+                "invalid model: {model} (expected e.g., full, external, internal, or F3L)"
+            )
+        })?;
+        let n_colors = n_colors
+            .ok_or_else(|| anyhow::anyhow!("missing number of colors for model {model}"))?;
+        Ok(Model::from((category, n_colors, class_L)))
+    }
+
+    /// resolves a model given as one or two positional arguments (see [Model::from_args]).
+    #[allow(non_snake_case)]
+    pub fn from_positionals(args: &[String], class_L: bool) -> Result<Self> {
+        match args {
+            [model] => Model::from_args(model, None, class_L),
+            [model, n_colors] => {
+                let n_colors = n_colors
+                    .parse()
+                    .with_context(|| format!("invalid number of colors: {n_colors}"))?;
+                Model::from_args(model, Some(n_colors), class_L)
+            }
+            _ => anyhow::bail!("invalid model arguments: {}", args.join(" ")),
+        }
+    }
+}
+
+/// command-line arguments selecting the model, shared by all binaries.
+/// The model is given either as a category followed by the number of colors (e.g., `full 3 -L`),
+/// or as a compact model string (e.g., `F3L`).
+#[derive(clap::Args, Debug, Clone)]
+#[allow(non_snake_case)]
+pub struct ModelArgs {
+    /// Category of algorithms (full, internal, external), compact model string (e.g., F3L),
+    /// or comma-separated list of compact model strings for a sweep (e.g., F2,E3L,E4L)
+    #[arg(value_name = "MODEL")]
+    pub model: String,
+
+    /// Number of colors allowed in the model (omitted with a compact model string)
+    #[arg()]
+    pub n_colors: Option<u8>,
+
+    /// Limits search to class L algorithms
+    #[arg(short = 'L')]
+    pub class_L: bool,
+
+    /// Robots cannot detect co-location (no `same_position` observation): only the guards of class L remain
+    #[arg(long = "no-multiplicity")]
+    pub no_multiplicity: bool,
+}
+
+impl ModelArgs {
+    pub fn model(&self) -> Result<Model> {
+        Model::from_args(&self.model, self.n_colors, self.observes_class_L())
+    }
+
+    /// robots without multiplicity detection cannot tell whether the other robot is at the same position,
+    /// so that their guards cannot depend on the distance, which leaves exactly the guards of class L.
+    #[allow(non_snake_case)]
+    fn observes_class_L(&self) -> bool {
+        self.class_L || self.no_multiplicity
+    }
+
+    /// all models selected on the command line, in order.
+    /// Several models can only be given as a comma-separated list of compact model strings.
+    pub fn models(&self) -> Result<Vec<Model>> {
+        if !self.model.contains(',') {
+            return Ok(vec![self.model()?]);
+        }
+        if self.n_colors.is_some() {
+            anyhow::bail!("number of colors cannot be given with a list of models");
+        }
+        self.model
+            .split(',')
+            .map(|m| {
+                let model = Model::try_from(m.trim())?;
+                Ok(Model {
+                    class_L: model.class_L || self.observes_class_L(),
+                    ..model
+                })
+            })
+            .collect()
+    }
+}
+
+impl From<Model> for ModelArgs {
+    fn from(model: Model) -> Self {
+        Self {
+            model: model.category.to_string().to_lowercase(),
+            n_colors: Some(model.n_colors),
+            class_L: model.class_L,
+            no_multiplicity: false,
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -71,4 +195,82 @@ mod tests {
             assert_eq!(Model::try_from(*model).unwrap(), *expected);
         }
     }
+
+    #[test]
+    fn test_model_from_args() {
+        let f3l = Model::from((ModelKind::Full, 3, true));
+        assert_eq!(Model::from_args("F3L", None, false).unwrap(), f3l);
+        assert_eq!(Model::from_args("F3", None, true).unwrap(), f3l);
+        assert_eq!(Model::from_args("full", Some(3), true).unwrap(), f3l);
+        assert_eq!(Model::from_args("Full", Some(3), true).unwrap(), f3l);
+        assert_eq!(
+            Model::from_args("external", Some(4), false).unwrap(),
+            Model::from((ModelKind::External, 4, false))
+        );
+        assert!(Model::from_args("F3L", Some(3), false).is_err());
+        assert!(Model::from_args("full", None, false).is_err());
+        assert!(Model::from_args("X3", None, false).is_err());
+
+        let args = ["full".to_string(), "3".to_string()];
+        assert_eq!(Model::from_positionals(&args, true).unwrap(), f3l);
+        assert!(Model::from_positionals(&args[..1], true).is_err());
+        assert_eq!(
+            Model::from_positionals(&["F3L".to_string()], false).unwrap(),
+            f3l
+        );
+
+        let args = ModelArgs::from(f3l);
+        assert_eq!(args.model().unwrap(), f3l);
+        assert_eq!(args.models().unwrap(), vec![f3l]);
+    }
+
+    #[test]
+    fn test_model_list() {
+        let args = ModelArgs {
+            model: "F2,E3L, I4".to_string(),
+            n_colors: None,
+            class_L: false,
+            no_multiplicity: false,
+        };
+        let models = args.models().unwrap();
+        assert_eq!(
+            models,
+            vec![
+                Model::from((ModelKind::Full, 2, false)),
+                Model::from((ModelKind::External, 3, true)),
+                Model::from((ModelKind::Internal, 4, false)),
+            ]
+        );
+        let names = models.iter().map(|m| m.to_string()).collect::<Vec<_>>();
+        assert_eq!(names, vec!["F2", "E3L", "I4"]);
+        assert!(args.model().is_err());
+
+        let args = ModelArgs {
+            model: "F2,full".to_string(),
+            n_colors: None,
+            class_L: true,
+            no_multiplicity: false,
+        };
+        assert!(args.models().is_err());
+    }
+
+    #[test]
+    fn test_no_multiplicity() {
+        let args = ModelArgs {
+            model: "F2".to_string(),
+            n_colors: None,
+            class_L: false,
+            no_multiplicity: true,
+        };
+        let model = args.model().unwrap();
+        assert_eq!(model, Model::from((ModelKind::Full, 2, true)));
+        assert!(crate::generator::guards_for_model(model)
+            .iter()
+            .all(|g| g.distance().is_none()));
+        let args = ModelArgs {
+            model: "E3,I2L".to_string(),
+            ..args
+        };
+        assert!(args.models().unwrap().iter().all(|m| m.class_L));
+    }
 }
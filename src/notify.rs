@@ -0,0 +1,134 @@
+//! Notification of the end of a run (success or error), enabled with `--notify`.
+//!
+//! The target is either a webhook URL, to which the summary line is posted as JSON (`{"text": ...}`) with `curl`,
+//! or a shell command, run with the summary line on its standard input and in the `SYNTH_LIGHTS_SUMMARY` variable.
+
+use anyhow::{Context, Result};
+use duct::cmd;
+
+use crate::synthesis::SynthesisResult;
+
+/// environment variable holding the summary line for a notification command.
+pub const SUMMARY_VAR: &str = "SYNTH_LIGHTS_SUMMARY";
+
+/// where the end of a run is notified.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Notifier {
+    /// URL to which the summary is posted
+    Webhook(String),
+    /// shell command
+    Command(String),
+}
+
+impl From<&str> for Notifier {
+    fn from(target: &str) -> Self {
+        if target.starts_with("http://") || target.starts_with("https://") {
+            Notifier::Webhook(target.to_string())
+        } else {
+            Notifier::Command(target.to_string())
+        }
+    }
+}
+
+impl Notifier {
+    /// sends the summary line.
+    pub fn notify(&self, summary: &str) -> Result<()> {
+        match self {
+            Notifier::Webhook(url) => {
+                let payload = serde_json::json!({ "text": summary }).to_string();
+                cmd!(
+                    "curl",
+                    "--silent",
+                    "--show-error",
+                    "--fail",
+                    "-X",
+                    "POST",
+                    "-H",
+                    "Content-Type: application/json",
+                    "--data-binary",
+                    "@-",
+                    url
+                )
+                .stdin_bytes(payload)
+                .stdout_null()
+                .run()
+                .with_context(|| format!("failed to post notification to {url}"))?;
+            }
+            Notifier::Command(command) => {
+                cmd!("sh", "-c", command)
+                    .env(SUMMARY_VAR, summary)
+                    .stdin_bytes(summary)
+                    .run()
+                    .with_context(|| format!("failed to run notification command: {command}"))?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// summary line of a run: the outcome counts over all (model, scheduler) runs, or the error.
+pub fn summary_line(outcome: &Result<SynthesisResult>) -> String {
+    let host = duct::cmd!("uname", "-n")
+        .read()
+        .unwrap_or("<undetermined>".to_string());
+    match outcome {
+        Ok(result) => {
+            let runs = result
+                .runs
+                .iter()
+                .map(|r| format!("{} / {}", r.model, r.scheduler))
+                .collect::<Vec<_>>();
+            let sum = |count: fn(&crate::synthesis::RunResult) -> usize| {
+                result.runs.iter().map(count).sum::<usize>()
+            };
+            let duration = result
+                .runs
+                .iter()
+                .map(|r| r.duration)
+                .sum::<std::time::Duration>();
+            format!(
+                "synth-lights on {host}: {} finished with {} pass, {} fail, {} incomplete, {} errors ({} algorithms) in {} s",
+                runs.join(", "),
+                sum(|r| r.n_pass),
+                sum(|r| r.n_fail),
+                sum(|r| r.n_incomplete),
+                sum(|r| r.n_errors),
+                sum(|r| r.n_algos),
+                duration.as_secs()
+            )
+        }
+        Err(e) => format!("synth-lights on {host}: run failed: {e:#}"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_notifier() {
+        assert_eq!(
+            Notifier::from("https://hooks.example.org/abc"),
+            Notifier::Webhook("https://hooks.example.org/abc".to_string())
+        );
+        assert_eq!(
+            Notifier::from("mail -s done me"),
+            Notifier::Command("mail -s done me".to_string())
+        );
+
+        let outcome: Result<SynthesisResult> = Err(anyhow::Error::msg("ramdisk exists"));
+        let summary = summary_line(&outcome);
+        assert!(summary.ends_with("run failed: ramdisk exists"));
+        assert!(summary_line(&Ok(SynthesisResult::default())).contains("0 pass, 0 fail"));
+
+        let dir = std::env::temp_dir().join(format!("notify-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir(&dir).unwrap();
+        let file = dir.join("summary.txt");
+        Notifier::from(format!("cat > {}", file.display()).as_str())
+            .notify("all done")
+            .unwrap();
+        assert_eq!(std::fs::read_to_string(&file).unwrap(), "all done");
+        std::fs::remove_dir_all(dir).unwrap();
+        assert!(Notifier::from("exit 3").notify("failed").is_err());
+    }
+}
@@ -0,0 +1,92 @@
+//! Callbacks invoked during a synthesis run (see [crate::synthesis::SynthesisRun::observer]).
+//!
+//! Observers receive the generated algorithms and verification outcomes as they are obtained,
+//! so that embedders can stream results to their own interface or storage instead of parsing the textual report.
+//! During a parallel verification, callbacks are invoked concurrently from the worker threads
+//! and in no particular order; hence observers must be [Sync] and use interior mutability to record state.
+
+use strum::Display;
+
+use crate::algorithm::Algorithm;
+use crate::common::Scheduler;
+use crate::model::Model;
+use crate::runner::SpinOutcome;
+
+/// phase of the synthesis for one model and scheduler (matching the timing report).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Display)]
+pub enum Phase {
+    /// creation of the working directory
+    Prepare,
+    /// generation and filtering of the viable algorithms
+    Generate,
+    /// verification of the algorithms (or local search)
+    Verify,
+    /// removal of the working directory
+    Cleanup,
+    /// writing of the report
+    Report,
+    /// the run for the model and scheduler is complete
+    Done,
+}
+
+/// callbacks of a synthesis run. All methods do nothing by default.
+pub trait RunObserver: Sync {
+    /// the run for the given model and scheduler enters a new phase.
+    fn on_phase_change(&self, _model: Model, _scheduler: Scheduler, _phase: Phase) {}
+
+    /// a viable algorithm has been generated, with its index among the viable algorithms.
+    fn on_generated(&self, _index: usize, _algo: &Algorithm) {}
+
+    /// an algorithm has been verified, with its best outcome and the outcome for each checked variant.
+    fn on_outcome(
+        &self,
+        _index: usize,
+        _algo: &Algorithm,
+        _outcome: SpinOutcome,
+        _variants: &[SpinOutcome],
+    ) {
+    }
+
+    /// the verification of an algorithm failed with an error.
+    fn on_error(&self, _index: usize, _algo: &Algorithm, _error: &anyhow::Error) {}
+
+    /// whether the observer displays the progress of the verification itself,
+    /// in which case the default progress bar is not shown.
+    fn shows_progress(&self) -> bool {
+        false
+    }
+}
+
+/// forwards the callbacks to each observer in turn.
+impl RunObserver for Vec<&dyn RunObserver> {
+    fn on_phase_change(&self, model: Model, scheduler: Scheduler, phase: Phase) {
+        self.iter()
+            .for_each(|o| o.on_phase_change(model, scheduler, phase));
+    }
+
+    fn on_generated(&self, index: usize, algo: &Algorithm) {
+        self.iter().for_each(|o| o.on_generated(index, algo));
+    }
+
+    fn on_outcome(
+        &self,
+        index: usize,
+        algo: &Algorithm,
+        outcome: SpinOutcome,
+        variants: &[SpinOutcome],
+    ) {
+        self.iter()
+            .for_each(|o| o.on_outcome(index, algo, outcome, variants));
+    }
+
+    fn on_error(&self, index: usize, algo: &Algorithm, error: &anyhow::Error) {
+        self.iter().for_each(|o| o.on_error(index, algo, error));
+    }
+
+    fn shows_progress(&self) -> bool {
+        self.iter().any(|o| o.shows_progress())
+    }
+}
+
+/// no observer.
+impl RunObserver for () {}
@@ -0,0 +1,256 @@
+//! Outputs of the report of a run: any combination of the standard output, files and network sockets (`--sink`).
+//!
+//! Each sink has its own format: the text of the report as is, or JSON lines with one object per line of the report
+//! (`{"line": ...}`), with the parsed algorithm (`"entry"`) for the lines listing an algorithm (see [Entry::parse]).
+//! An [OutputSink] writes everything it receives to all its sinks, so that they all get the complete report.
+
+use anyhow::{bail, Context, Result};
+use serde::Serialize;
+use std::io::Write;
+use std::path::PathBuf;
+
+use crate::report::Entry;
+
+/// format of the report written to a sink.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum SinkFormat {
+    /// text of the report
+    #[default]
+    Text,
+    /// one JSON object per line of the report
+    Jsonl,
+}
+
+/// destination of a sink.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum SinkTarget {
+    Stdout,
+    /// file (opened according to the [OutputMode](crate::synthesis::OutputMode) of the run)
+    File(PathBuf),
+    /// TCP connection to `host:port`
+    Tcp(String),
+}
+
+/// sink of a report, written `[text:|jsonl:](stdout|-|tcp://HOST:PORT|PATH)` on the command line.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SinkSpec {
+    pub target: SinkTarget,
+    pub format: SinkFormat,
+}
+
+impl SinkSpec {
+    pub fn new(target: SinkTarget, format: SinkFormat) -> Self {
+        Self { target, format }
+    }
+}
+
+impl TryFrom<&str> for SinkSpec {
+    type Error = anyhow::Error;
+
+    fn try_from(spec: &str) -> Result<Self> {
+        let (format, target) = match spec.split_once(':') {
+            Some(("text", target)) => (SinkFormat::Text, target),
+            Some(("jsonl", target)) => (SinkFormat::Jsonl, target),
+            _ => (SinkFormat::Text, spec),
+        };
+        let target = match target {
+            "" => bail!("missing target in sink '{spec}'"),
+            "stdout" | "-" => SinkTarget::Stdout,
+            _ => match target.strip_prefix("tcp://") {
+                Some(address) if address.contains(':') => SinkTarget::Tcp(address.to_string()),
+                Some(_) => bail!("invalid sink '{spec}' (expected tcp://HOST:PORT)"),
+                None => SinkTarget::File(PathBuf::from(target)),
+            },
+        };
+        Ok(Self { target, format })
+    }
+}
+
+/// writer to several sinks.
+#[derive(Default)]
+pub struct OutputSink {
+    sinks: Vec<Box<dyn Write>>,
+}
+
+impl OutputSink {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// adds a sink, wrapped according to its format.
+    pub fn add(&mut self, writer: impl Write + 'static, format: SinkFormat) {
+        self.sinks.push(match format {
+            SinkFormat::Text => Box::new(writer),
+            SinkFormat::Jsonl => Box::new(JsonLines::new(writer)),
+        });
+    }
+
+    pub fn with(mut self, writer: impl Write + 'static, format: SinkFormat) -> Self {
+        self.add(writer, format);
+        self
+    }
+
+    /// connects to a TCP sink.
+    pub fn connect(address: &str) -> Result<std::net::TcpStream> {
+        std::net::TcpStream::connect(address)
+            .with_context(|| format!("failed to connect to output sink {address}"))
+    }
+}
+
+impl Write for OutputSink {
+    /// writes the whole buffer to every sink: a sink accepting only part of it (e.g., a pipe or a socket) is not an
+    /// error, the rest is written again until it is accepted.
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        for sink in self.sinks.iter_mut() {
+            sink.write_all(buf)?;
+        }
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        for sink in self.sinks.iter_mut() {
+            sink.flush()?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Serialize)]
+struct JsonLine<'a> {
+    line: &'a str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    entry: Option<Entry>,
+}
+
+/// writer converting the lines of a report to JSON lines; an unterminated last line is converted when dropped.
+pub struct JsonLines<W: Write> {
+    writer: W,
+    pending: Vec<u8>,
+}
+
+impl<W: Write> JsonLines<W> {
+    pub fn new(writer: W) -> Self {
+        Self {
+            writer,
+            pending: Vec::new(),
+        }
+    }
+
+    fn write_line(&mut self, line: &[u8]) -> std::io::Result<()> {
+        let line = String::from_utf8_lossy(line);
+        let line = line.trim_end_matches('\r');
+        let json = JsonLine {
+            line,
+            entry: Entry::parse(line).ok().flatten(),
+        };
+        serde_json::to_writer(&mut self.writer, &json)?;
+        self.writer.write_all(b"\n")
+    }
+}
+
+impl<W: Write> Write for JsonLines<W> {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.pending.extend_from_slice(buf);
+        while let Some(end) = self.pending.iter().position(|&b| b == b'\n') {
+            let line = self.pending.drain(..=end).collect::<Vec<_>>();
+            self.write_line(&line[..end])?;
+        }
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.writer.flush()
+    }
+}
+
+impl<W: Write> Drop for JsonLines<W> {
+    fn drop(&mut self) {
+        if !self.pending.is_empty() {
+            let line = std::mem::take(&mut self.pending);
+            let _ = self.write_line(&line).and_then(|_| self.writer.flush());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    /// writer accepting at most 3 bytes per write, sharing what it received.
+    #[derive(Clone, Default)]
+    struct Short(Rc<RefCell<Vec<u8>>>);
+
+    impl Write for Short {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            let n = buf.len().min(3);
+            self.0.borrow_mut().extend_from_slice(&buf[..n]);
+            Ok(n)
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    impl Short {
+        fn text(&self) -> String {
+            String::from_utf8(self.0.borrow().clone()).unwrap()
+        }
+    }
+
+    #[test]
+    fn test_sink_spec() {
+        let spec = |s| SinkSpec::try_from(s).unwrap();
+        assert_eq!(
+            spec("-"),
+            SinkSpec::new(SinkTarget::Stdout, SinkFormat::Text)
+        );
+        assert_eq!(
+            spec("jsonl:stdout"),
+            SinkSpec::new(SinkTarget::Stdout, SinkFormat::Jsonl)
+        );
+        assert_eq!(
+            spec("out/report.txt"),
+            SinkSpec::new(SinkTarget::File("out/report.txt".into()), SinkFormat::Text)
+        );
+        assert_eq!(
+            spec("jsonl:tcp://localhost:9000"),
+            SinkSpec::new(SinkTarget::Tcp("localhost:9000".into()), SinkFormat::Jsonl)
+        );
+        assert!(SinkSpec::try_from("tcp://localhost").is_err());
+        assert!(SinkSpec::try_from("jsonl:").is_err());
+    }
+
+    #[test]
+    fn test_output_sink() {
+        let (text, json) = (Short::default(), Short::default());
+        let mut sink = OutputSink::new()
+            .with(text.clone(), SinkFormat::Text)
+            .with(json.clone(), SinkFormat::Jsonl);
+        write!(
+            sink,
+            "Viable algorithms: 2\n   12 : PASS 0_1_2__S2_H0_O1\nend"
+        )
+        .unwrap();
+        sink.flush().unwrap();
+        drop(sink);
+
+        assert!(text
+            .text()
+            .starts_with("Viable algorithms: 2\n   12 : PASS 0_1"));
+        assert!(text.text().ends_with("_O1\nend"));
+        let lines = json
+            .text()
+            .lines()
+            .map(|l| serde_json::from_str::<serde_json::Value>(l).unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(lines.len(), 3);
+        assert_eq!(lines[0]["line"], "Viable algorithms: 2");
+        assert!(lines[0].get("entry").is_none());
+        assert_eq!(lines[1]["entry"]["index"], 12);
+        assert_eq!(lines[1]["entry"]["outcome"], "Pass");
+        assert_eq!(lines[2]["line"], "end");
+    }
+}
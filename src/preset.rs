@@ -0,0 +1,155 @@
+//! Named presets of run options (`--preset NAME`), to reproduce the configurations of past experiments exactly.
+//!
+//! A preset expands to command-line arguments inserted in place of `--preset NAME`, before parsing;
+//! options given after it override those of the preset. Presets are either built in ([BUILTIN_PRESETS]),
+//! or defined by the user as files named after the preset in the presets directory ([presets_dir]),
+//! holding the arguments separated by whitespace (`#` starts a comment). User presets take precedence.
+
+use anyhow::{Context, Result};
+use std::ffi::OsString;
+use std::path::PathBuf;
+
+/// option expanded to a preset.
+const PRESET_OPTION: &str = "--preset";
+
+/// built-in presets: (name, description, arguments).
+pub const BUILTIN_PRESETS: [(&str, &str, &str); 2] = [
+    (
+        "heatmap-full",
+        "full lights models and schedulers of the heatmap (scripts/make_heatmap.py)",
+        "-f -s centralized,fsync,ssync,async-lc-atomic,async-cm-atomic,async-move-atomic,async F2,F2L,F3L",
+    ),
+    (
+        "heatmap-external",
+        "external lights models and schedulers of the heatmap (scripts/make_heatmap.py)",
+        "-f -s centralized,fsync,ssync,async-lc-atomic,async-cm-atomic,async-move-atomic,async E3,E4,E3L,E4L,E5L,E6L,E7L",
+    ),
+];
+
+/// directory of the user presets: `$XDG_CONFIG_HOME/synth-lights/presets`, or `~/.config/synth-lights/presets`.
+pub fn presets_dir() -> Option<PathBuf> {
+    let config = std::env::var_os("XDG_CONFIG_HOME")
+        .map(PathBuf::from)
+        .or_else(|| std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))?;
+    Some(config.join("synth-lights").join("presets"))
+}
+
+/// splits the text of a preset into arguments.
+pub fn parse_preset(text: &str) -> Vec<String> {
+    text.lines()
+        .map(|line| line.split('#').next().unwrap_or_default())
+        .flat_map(str::split_whitespace)
+        .map(str::to_string)
+        .collect()
+}
+
+/// arguments of the preset with the given name.
+pub fn resolve(name: &str) -> Result<Vec<String>> {
+    if let Some(path) = presets_dir().map(|dir| dir.join(name)) {
+        if path.is_file() {
+            let text = std::fs::read_to_string(&path)
+                .with_context(|| format!("failed to read preset {}", path.display()))?;
+            return Ok(parse_preset(&text));
+        }
+    }
+    BUILTIN_PRESETS
+        .iter()
+        .find(|(n, _, _)| *n == name)
+        .map(|(_, _, args)| parse_preset(args))
+        .ok_or_else(|| {
+            let known = list().into_iter().map(|(n, _)| n).collect::<Vec<_>>();
+            anyhow::anyhow!(
+                "unknown preset: {name} (known presets: {})",
+                known.join(", ")
+            )
+        })
+}
+
+/// names and descriptions of the available presets (user presets first).
+pub fn list() -> Vec<(String, String)> {
+    let mut presets = Vec::new();
+    if let Some(Ok(entries)) = presets_dir().map(std::fs::read_dir) {
+        let mut names = entries
+            .filter_map(|e| e.ok())
+            .filter(|e| e.path().is_file())
+            .map(|e| e.file_name().to_string_lossy().to_string())
+            .collect::<Vec<_>>();
+        names.sort();
+        presets.extend(names.into_iter().map(|n| (n, "user preset".to_string())));
+    }
+    for (name, description, _) in BUILTIN_PRESETS {
+        if presets.iter().all(|(n, _)| n != name) {
+            presets.push((name.to_string(), description.to_string()));
+        }
+    }
+    presets
+}
+
+/// inserts the arguments of each preset after the `--preset NAME` (or `--preset=NAME`) option.
+pub fn expand_presets(args: Vec<OsString>) -> Result<Vec<OsString>> {
+    let mut expanded = Vec::with_capacity(args.len());
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        let name = match arg.to_str() {
+            Some(PRESET_OPTION) => {
+                expanded.push(arg);
+                match args.next() {
+                    Some(name) => {
+                        expanded.push(name.clone());
+                        name.to_string_lossy().to_string()
+                    }
+                    None => continue, // reported as missing value by the parser
+                }
+            }
+            Some(a) if a.starts_with("--preset=") => {
+                expanded.push(arg.clone());
+                a["--preset=".len()..].to_string()
+            }
+            _ => {
+                expanded.push(arg);
+                continue;
+            }
+        };
+        expanded.extend(resolve(&name)?.into_iter().map(OsString::from));
+    }
+    Ok(expanded)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_expand_presets() {
+        assert_eq!(
+            parse_preset("-f -s ssync  # comment\n\n--rigid E3L\n"),
+            ["-f", "-s", "ssync", "--rigid", "E3L"]
+        );
+
+        let args = ["synth-lights", "--preset", "heatmap-full", "--rigid"]
+            .map(OsString::from)
+            .to_vec();
+        let expanded = expand_presets(args).unwrap();
+        assert_eq!(
+            expanded[..4],
+            ["synth-lights", "--preset", "heatmap-full", "-f"]
+        );
+        assert_eq!(
+            expanded[5],
+            "centralized,fsync,ssync,async-lc-atomic,async-cm-atomic,async-move-atomic,async"
+        );
+        assert_eq!(expanded[expanded.len() - 2..], ["F2,F2L,F3L", "--rigid"]);
+
+        let args = ["synth-lights", "--preset=heatmap-external"]
+            .map(OsString::from)
+            .to_vec();
+        assert_eq!(expand_presets(args).unwrap().len(), 2 + 4);
+
+        let args = ["synth-lights", "--preset", "no-such-preset"]
+            .map(OsString::from)
+            .to_vec();
+        let error = expand_presets(args).unwrap_err().to_string();
+        assert!(error.starts_with("unknown preset: no-such-preset"));
+        assert!(error.contains("heatmap-full"));
+    }
+}
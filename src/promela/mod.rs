@@ -1,13 +1,15 @@
-use anyhow::Result;
+use anyhow::{anyhow, bail, Context, Result};
 use itertools::{self, Itertools};
+use lazy_regex::regex_captures;
 use std::fs;
 use std::include_str;
 use std::path::{Path, PathBuf};
 
-use crate::algorithm::{Action, Algorithm, Guard};
+use crate::algorithm::{Action, Algorithm, Guard, Rule};
 use crate::common::*;
 
-const ALGORITHM_FILE: &str = "Algorithms.pml";
+/// promela file holding the algorithm, replaced by [install_algorithm].
+pub const ALGORITHM_FILE: &str = "Algorithms.pml";
 
 const MAIN_PML: &str = include_str!("MainGathering.pml");
 const ROBOTS_PML: &str = include_str!("Robots.pml");
@@ -21,11 +23,134 @@ pub const PML_FILES: [(&str, &str); 4] = [
     ("Types.pml", TYPES_PML),
 ];
 
+/// fairness assumption of the verification.
+#[derive(clap::ValueEnum, strum::Display, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Fairness {
+    /// no fairness: pan is run without `-f`
+    None,
+    /// weak fairness of the processes (`pan -f`), as in the original model
+    #[default]
+    Weak,
+    /// fair activation, on top of weak fairness (`pan -f`): the property only has to hold in the executions where
+    /// each robot performs LOOK infinitely often (premise of the LTL formula, with `-DFAIR_ACTIVATION`).
+    /// This is not strong fairness of the transitions, and the bits recording the LOOKs double the state space.
+    FairActivation,
+}
+
+impl Fairness {
+    /// arguments of the `pan` verifier for the safety stage (see
+    /// [PanBuildOptions::safety_first](crate::runner::PanBuildOptions::safety_first)): without `-a`, pan only looks for
+    /// assertion violations and completions of the never claim, so that fairness does not apply.
+    pub fn safety_pan_args() -> Vec<&'static str> {
+        vec!["-m100000", "-E", "-n", "gathering"]
+    }
+
+    /// arguments of the `pan` verifier.
+    pub fn pan_args(self) -> Vec<&'static str> {
+        match self {
+            Fairness::None => vec!["-m100000", "-a", "-E", "-n", "gathering"],
+            Fairness::Weak | Fairness::FairActivation => {
+                vec!["-m100000", "-a", "-f", "-E", "-n", "gathering"]
+            }
+        }
+    }
+}
+
+/// restrictions of the initial configurations of the model, in addition to the choice between self-stabilizing and
+/// quasi self-stabilizing executions (all configurations allowed by the latter by default).
+#[derive(clap::Args, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct InitialConditions {
+    /// Both robots start with color 0 (otherwise any colors allowed by --quasi-ss)
+    #[arg(long = "init-color-zero")]
+    pub color_zero: bool,
+
+    /// Robots start at distinct positions (otherwise possibly gathered)
+    #[arg(long = "init-distinct")]
+    pub distinct: bool,
+}
+
+impl InitialConditions {
+    /// definitions passed to spin (`-DINIT_COLOR_ZERO`, `-DINIT_DISTINCT`).
+    pub fn defines(&self) -> Vec<String> {
+        let mut defines = Vec::new();
+        if self.color_zero {
+            defines.push("-DINIT_COLOR_ZERO".to_string());
+        }
+        if self.distinct {
+            defines.push("-DINIT_DISTINCT".to_string());
+        }
+        defines
+    }
+
+    /// checks if the initial colors and position are allowed.
+    pub fn admits(&self, colors: [Color; 2], position: Distance) -> bool {
+        (!self.color_zero || colors == [Color(0); 2])
+            && (!self.distinct || position != Distance::Same)
+    }
+
+    /// short name of the restrictions (e.g., `init0-distinct`), empty if none.
+    pub fn name(&self) -> String {
+        let mut names = Vec::new();
+        if self.color_zero {
+            names.push("init0");
+        }
+        if self.distinct {
+            names.push("distinct");
+        }
+        names.join("-")
+    }
+}
+
+/// granularity of the non-rigid moves in the original model: robots far apart are within reach after one move.
+pub const DEFAULT_DELTA_STEPS: u8 = 1;
+
 #[derive(Clone, Copy, Debug)]
 pub struct ModelRunOptions {
     pub scheduler: Scheduler,
     pub rigid: bool,
     pub quasi_ss: bool,
+    pub fairness: Fairness,
+    /// with non-rigid moves, maximal number of moves stopped short before the robots are within reach of each other
+    /// (`-DDELTA_STEPS`, initially chosen non-deterministically in `1..=delta_steps`); ignored with rigid moves
+    pub delta_steps: u8,
+    pub initial: InitialConditions,
+    /// robots cannot detect co-location (`-DNO_MULTIPLICITY`: the observation has no `same_position` field)
+    pub no_multiplicity: bool,
+}
+
+impl ModelRunOptions {
+    /// options of the original model with the given scheduler: non-rigid moves, self-stabilizing executions, weak
+    /// fairness, and no restriction of the initial configurations.
+    pub fn new(scheduler: Scheduler) -> Self {
+        ModelRunOptions {
+            scheduler,
+            rigid: false,
+            quasi_ss: false,
+            fairness: Fairness::Weak,
+            delta_steps: DEFAULT_DELTA_STEPS,
+            initial: InitialConditions::default(),
+            no_multiplicity: false,
+        }
+    }
+
+    /// the four combinations of rigid / non-rigid moves and self-stabilizing / quasi self-stabilizing
+    /// executions, with the same scheduler; from the weakest to the strongest assumptions.
+    pub fn variants(self) -> [ModelRunOptions; 4] {
+        [(false, false), (false, true), (true, false), (true, true)].map(|(rigid, quasi_ss)| {
+            ModelRunOptions {
+                rigid,
+                quasi_ss,
+                ..self
+            }
+        })
+    }
+
+    /// short name of the variant, regardless of the scheduler (e.g., `rigid/qss`).
+    pub fn variant_name(&self) -> String {
+        let rigid = if self.rigid { "rigid" } else { "non-rigid" };
+        let quasi_ss = if self.quasi_ss { "qss" } else { "ss" };
+        format!("{rigid}/{quasi_ss}")
+    }
 }
 
 impl IntoIterator for ModelRunOptions {
@@ -41,6 +166,16 @@ impl IntoIterator for ModelRunOptions {
         if self.quasi_ss {
             args.push("-DQUASISS".to_string());
         }
+        if self.fairness == Fairness::FairActivation {
+            args.push("-DFAIR_ACTIVATION".to_string());
+        }
+        if !self.rigid && self.delta_steps != DEFAULT_DELTA_STEPS {
+            args.push(format!("-DDELTA_STEPS={}", self.delta_steps));
+        }
+        args.extend(self.initial.defines());
+        if self.no_multiplicity {
+            args.push("-DNO_MULTIPLICITY".to_string());
+        }
         args.into_iter()
     }
 }
@@ -81,8 +216,8 @@ pub fn install_algorithm_from_code(path: &Path, promela: &str) -> Result<()> {
     Ok(())
 }
 
-fn promela_rule(rule: (&Guard, &Action)) -> String {
-    match rule {
+fn promela_rule(Rule(guard, action): Rule) -> String {
+    match (guard, action) {
         (Guard::Full(s,o,Distance::Same), Action(c,m)) =>
             format!("    :: (obs.color.me == {s}) && (obs.color.other == {o}) && (obs.same_position) -> command.move = {m}; command.new_color = {c};"),
         (Guard::Full(s,o,_), Action(c,m)) =>
@@ -141,6 +276,92 @@ inline Alg_Synth(obs, command)
     )
 }
 
+/// parses the algorithm back from the Promela code produced by [generate_promela].
+/// The model (kind, class L) is inferred from the conditions of the rules, and the number
+/// of colors from the `NUM_COLORS` definition.
+///
+/// # Arguments
+///
+/// * `code` - content of an `Algorithms.pml` file generated by this tool
+///
+pub fn parse_algorithm(code: &str) -> Result<Algorithm> {
+    let num_colors = code
+        .lines()
+        .find_map(|l| regex_captures!(r"^#\s*define\s+NUM_COLORS\s+\(?(\d+)\)?", l.trim()))
+        .map(|(_, n)| n.parse::<u8>())
+        .ok_or_else(|| anyhow!("missing NUM_COLORS definition"))??;
+
+    let mut guards = Vec::new();
+    let mut actions = Vec::new();
+    for line in code.lines() {
+        let Some((_, cond, mv, col)) = regex_captures!(
+            r"^::\s*(.*?)\s*->\s*command\.move\s*=\s*(\w+);\s*command\.new_color\s*=\s*(\d+);",
+            line.trim()
+        ) else {
+            continue;
+        };
+        let guard = parse_guard(cond).with_context(|| format!("parsing rule: {}", line.trim()))?;
+        let action = Action(Color::try_from(col)?, Move::try_from(mv)?);
+        if action.color().0 >= num_colors {
+            bail!(
+                "color {} out of range ({} colors)",
+                action.color(),
+                num_colors
+            );
+        }
+        guards.push(guard);
+        actions.push(action);
+    }
+
+    let Some(first) = guards.first() else {
+        bail!("no rule found in promela code");
+    };
+    let (model, class_l) = (first.model_kind(), first.class_L());
+    if guards
+        .iter()
+        .any(|g| g.model_kind() != model || g.class_L() != class_l)
+    {
+        bail!("rules belong to different models");
+    }
+    let expected = Guard::number_for_model(model, num_colors, class_l);
+    if guards.len() != expected {
+        bail!(
+            "number of rules ({}) does not match model ({})",
+            guards.len(),
+            expected
+        );
+    }
+    Ok(Algorithm::new(num_colors, &guards, &actions))
+}
+
+fn parse_guard(cond: &str) -> Result<Guard> {
+    let mut me = None;
+    let mut other = None;
+    let mut dist = None;
+    for term in cond.split("&&").map(str::trim) {
+        if let Some((_, c)) = regex_captures!(r"^\(obs\.color\.me == (\d+)\)$", term) {
+            me = Some(Color::try_from(c)?);
+        } else if let Some((_, c)) = regex_captures!(r"^\(obs\.color\.other == (\d+)\)$", term) {
+            other = Some(Color::try_from(c)?);
+        } else if term == "(obs.same_position)" {
+            dist = Some(Distance::Same);
+        } else if regex_captures!(r"^!\s*\(obs\.same_position\)$", term).is_some() {
+            dist = Some(Distance::Near);
+        } else {
+            bail!("unrecognized condition: {term}");
+        }
+    }
+    match (me, other, dist) {
+        (Some(s), Some(o), Some(d)) => Ok(Guard::Full(s, o, d)),
+        (Some(s), Some(o), None) => Ok(Guard::LFull(s, o)),
+        (Some(s), None, Some(d)) => Ok(Guard::Internal(s, d)),
+        (Some(s), None, None) => Ok(Guard::LInternal(s)),
+        (None, Some(o), Some(d)) => Ok(Guard::External(o, d)),
+        (None, Some(o), None) => Ok(Guard::LExternal(o)),
+        (None, None, _) => bail!("condition without colors: {cond}"),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -167,6 +388,31 @@ mod tests {
         println!("{}", generate_promela(&algo));
     }
 
+    #[test]
+    fn test_parse_algorithm() {
+        let algos = [
+            (crate::ModelKind::Full, 2, false),
+            (crate::ModelKind::Full, 2, true),
+            (crate::ModelKind::External, 3, false),
+            (crate::ModelKind::External, 4, true),
+            (crate::ModelKind::Internal, 3, false),
+            (crate::ModelKind::Internal, 3, true),
+        ]
+        .into_iter()
+        .flat_map(|(model, n_colors, class_l)| {
+            crate::generator::generate_algorithms_in_model(model, n_colors, class_l)
+                .step_by(997)
+                .take(5)
+        });
+        for algo in algos {
+            let promela = generate_promela(&algo);
+            assert_eq!(parse_algorithm(&promela).unwrap(), algo);
+        }
+
+        assert!(parse_algorithm("").is_err());
+        assert!(parse_algorithm("#  define NUM_COLORS     (2)").is_err());
+    }
+
     #[test]
     fn test_promela_gen() {
         let num_colors = 2;
@@ -215,7 +461,7 @@ mod tests {
 
         let external_algo = Algorithm::new(
             num_colors,
-            &guards,
+            guards,
             &[
                 Action(Color(0), Move::Stay),
                 Action(Color(0), Move::Stay),
@@ -230,4 +476,80 @@ mod tests {
         println!("External Algo: {}", external_algo.as_code());
         println!("{}", external_code);
     }
+
+    #[test]
+    fn test_fairness_args() {
+        let options = |fairness| ModelRunOptions {
+            rigid: true,
+            fairness,
+            ..ModelRunOptions::new(Scheduler::SSYNC)
+        };
+        let weak = options(Fairness::Weak).into_iter().collect::<Vec<_>>();
+        assert_eq!(weak, ["-DSCHEDULER=SSYNC", "-DMOVEMENT=RIGID"]);
+        let none = options(Fairness::None).into_iter().collect::<Vec<_>>();
+        assert_eq!(none, weak);
+        let fair = options(Fairness::FairActivation)
+            .into_iter()
+            .collect::<Vec<_>>();
+        assert_eq!(fair.last().unwrap(), "-DFAIR_ACTIVATION");
+        assert!(Fairness::Weak.pan_args().contains(&"-f"));
+        assert!(Fairness::FairActivation.pan_args().contains(&"-f"));
+        assert!(!Fairness::None.pan_args().contains(&"-f"));
+        assert!(!Fairness::safety_pan_args().contains(&"-a"));
+        assert!(MAIN_PML.contains("#ifdef FAIR_ACTIVATION"));
+    }
+
+    #[test]
+    fn test_delta_steps_args() {
+        let options = |rigid, delta_steps| ModelRunOptions {
+            rigid,
+            delta_steps,
+            ..ModelRunOptions::new(Scheduler::ASYNC)
+        };
+        let args = |options: ModelRunOptions| options.into_iter().collect::<Vec<_>>();
+        assert_eq!(args(options(false, 1)), ["-DSCHEDULER=ASYNC"]);
+        assert_eq!(
+            args(options(false, 3)),
+            ["-DSCHEDULER=ASYNC", "-DDELTA_STEPS=3"]
+        );
+        assert_eq!(
+            args(options(true, 3)),
+            ["-DSCHEDULER=ASYNC", "-DMOVEMENT=RIGID"]
+        );
+        assert!(ROBOTS_PML.contains("#if DELTA_STEPS > 1"));
+    }
+
+    #[test]
+    fn test_initial_conditions() {
+        let initial = InitialConditions {
+            color_zero: true,
+            distinct: true,
+        };
+        let options = ModelRunOptions {
+            initial,
+            ..ModelRunOptions::new(Scheduler::SSYNC)
+        };
+        let args = options.into_iter().collect::<Vec<_>>();
+        assert_eq!(
+            args,
+            ["-DSCHEDULER=SSYNC", "-DINIT_COLOR_ZERO", "-DINIT_DISTINCT"]
+        );
+        assert_eq!(initial.name(), "init0-distinct");
+        assert!(initial.admits([Color(0); 2], Distance::Far));
+        assert!(!initial.admits([Color(0); 2], Distance::Same));
+        assert!(!initial.admits([Color(0), Color(1)], Distance::Near));
+        assert!(InitialConditions::default().name().is_empty());
+        assert!(MAIN_PML.contains("#  ifdef INIT_DISTINCT"));
+    }
+
+    #[test]
+    fn test_no_multiplicity_args() {
+        let options = ModelRunOptions {
+            no_multiplicity: true,
+            ..ModelRunOptions::new(Scheduler::SSYNC)
+        };
+        let args = options.into_iter().collect::<Vec<_>>();
+        assert_eq!(args, ["-DSCHEDULER=SSYNC", "-DNO_MULTIPLICITY"]);
+        assert!(ROBOTS_PML.contains("#ifndef NO_MULTIPLICITY"));
+    }
 }
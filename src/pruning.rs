@@ -0,0 +1,240 @@
+//! Experimental synthesis by pruned enumeration with counterexample learning, for the schedulers handled by the native
+//! model checker ([checker]), selected with `synth-lights prune`.
+//!
+//! The rule tables (the action of each guard) are enumerated in lexicographic order, as by the generator, but every
+//! candidate is verified with the native model checker as soon as it is produced. A counterexample (an execution that
+//! never gathers) only depends on the actions of the rules applied along it, so every rule table that agrees with the
+//! candidate on these rules fails in the same way: the enumeration learns a clause excluding them all, as well as their
+//! color permutations, and skips every prefix of the rule table that violates a learnt clause.
+//!
+//! This is not a SAT or SMT encoding: the clauses are only checked on the prefixes of the enumeration, with no
+//! propagation nor conflict analysis. The enumeration is complete and yields exactly the correct canonical algorithms
+//! of the model (see [Algorithm::is_canonical]).
+
+use anyhow::{bail, Result};
+use itertools::Itertools;
+
+use crate::algorithm::{Action, Algorithm, Guard};
+use crate::checker;
+use crate::common::{Color, Move};
+use crate::generator;
+use crate::model::Model;
+use crate::promela::{Fairness, ModelRunOptions};
+use crate::simulator::{self, Counterexample};
+use strum::IntoEnumIterator;
+
+/// clause over the rule table: the listed actions (guard, index of the action) are not all taken together.
+type Clause = Vec<(usize, usize)>;
+
+/// pruned enumeration of the correct canonical algorithms of a model, produced in lexicographic order of their actions.
+pub struct PrunedEnumeration {
+    num_colors: u8,
+    guards: Vec<Guard>,
+    options: ModelRunOptions,
+    /// domain of the action of each guard, in the order of the generator
+    actions: Vec<Action>,
+    /// clauses indexed by their last guard
+    clauses: Vec<Vec<Clause>>,
+    /// next rule table to consider (indices in `actions`), or `None` once the search is over
+    next: Option<Vec<usize>>,
+    candidates: usize,
+    counterexamples: usize,
+}
+
+impl PrunedEnumeration {
+    /// fails if the scheduler of the options is not supported by the native model checker.
+    pub fn new(model: Model, options: ModelRunOptions) -> Result<Self> {
+        if !checker::supports(options.scheduler) {
+            bail!(
+                "scheduler {} is not supported by the pruned enumeration (only centralized, FSYNC and SSYNC)",
+                options.scheduler
+            );
+        }
+        if options.fairness == Fairness::None {
+            bail!("verifications without fairness are not supported by the pruned enumeration");
+        }
+        if !checker::supports_options(options) {
+            bail!(
+                "non-rigid moves with {} steps are not supported by the pruned enumeration",
+                options.delta_steps
+            );
+        }
+        let guards = generator::guards_for_model(model);
+        let actions = itertools::iproduct!(Move::iter(), Color::iter_ncols(model.n_colors))
+            .map(|(m, c)| Action(c, m))
+            .collect::<Vec<_>>();
+        Ok(PrunedEnumeration {
+            num_colors: model.n_colors,
+            clauses: vec![Vec::new(); guards.len()],
+            next: Some(vec![0; guards.len()]),
+            guards,
+            options,
+            actions,
+            candidates: 0,
+            counterexamples: 0,
+        })
+    }
+
+    /// number of candidates verified so far.
+    pub fn candidates(&self) -> usize {
+        self.candidates
+    }
+
+    /// number of counterexamples found so far (one per failed candidate).
+    pub fn counterexamples(&self) -> usize {
+        self.counterexamples
+    }
+
+    /// number of rule tables of the model, that is, of algorithms enumerated without counterexamples.
+    pub fn space(&self) -> u64 {
+        (self.actions.len() as u64).saturating_pow(self.guards.len() as u32)
+    }
+
+    fn algorithm(&self, values: &[usize]) -> Algorithm {
+        let actions = values.iter().map(|&v| self.actions[v]).collect::<Vec<_>>();
+        Algorithm::new(self.num_colors, &self.guards, &actions)
+    }
+
+    /// excludes the rule tables that agree with the candidate on the rules applied in the counterexample,
+    /// and their color permutations.
+    fn learn(&mut self, values: &[usize], counterexample: &Counterexample) {
+        let algo = self.algorithm(values);
+        let used = counterexample
+            .configs
+            .iter()
+            .zip(&counterexample.rounds)
+            .flat_map(|(&config, &round)| simulator::rules_used(&algo, config, round))
+            .unique()
+            .collect::<Vec<_>>();
+        for perm in Color::iter_ncols(self.num_colors).permutations(self.num_colors as usize) {
+            let clause = used
+                .iter()
+                .map(|&i| {
+                    let guard = self.guards[i].permute_colors(&perm);
+                    let Action(c, m) = self.actions[values[i]];
+                    let action = Action(perm[c.0 as usize], m);
+                    (
+                        self.guards.iter().position(|g| *g == guard).unwrap(),
+                        self.actions.iter().position(|a| *a == action).unwrap(),
+                    )
+                })
+                .sorted()
+                .collect::<Clause>();
+            if let Some(&(last, _)) = clause.last() {
+                self.clauses[last].push(clause);
+            }
+        }
+    }
+
+    fn violates(&self, guard: usize, values: &[usize]) -> bool {
+        self.clauses[guard]
+            .iter()
+            .any(|clause| clause.iter().all(|&(i, v)| values[i] == v))
+    }
+
+    /// first rule table from `values` (included), in lexicographic order, that satisfies all the clauses.
+    fn solve(&self, mut values: Vec<usize>) -> Option<Vec<usize>> {
+        let mut guard = 0;
+        while guard < values.len() {
+            if values[guard] == self.actions.len() {
+                if guard == 0 {
+                    return None;
+                }
+                guard -= 1;
+                values[guard] += 1;
+                values[guard + 1..].fill(0);
+            } else if self.violates(guard, &values) {
+                values[guard] += 1;
+                values[guard + 1..].fill(0);
+            } else {
+                guard += 1;
+            }
+        }
+        Some(values)
+    }
+
+    /// rule table following `values` in lexicographic order, if any.
+    fn successor(&self, mut values: Vec<usize>) -> Option<Vec<usize>> {
+        for value in values.iter_mut().rev() {
+            *value += 1;
+            if *value < self.actions.len() {
+                return Some(values);
+            }
+            *value = 0;
+        }
+        None
+    }
+}
+
+impl Iterator for PrunedEnumeration {
+    type Item = Result<Algorithm>;
+
+    fn next(&mut self) -> Option<Result<Algorithm>> {
+        loop {
+            let next = self.next.take()?;
+            let values = self.solve(next)?;
+            self.next = self.successor(values.clone());
+            let algo = self.algorithm(&values);
+            // the other algorithms of the class are equivalent
+            if !algo.is_canonical() {
+                continue;
+            }
+            self.candidates += 1;
+            match checker::counterexample(&algo, self.options) {
+                Err(e) => return Some(Err(e)),
+                Ok(None) => return Some(Ok(algo)),
+                Ok(Some(counterexample)) => {
+                    log::debug!("{} fails: {counterexample}", algo.as_code());
+                    self.counterexamples += 1;
+                    self.learn(&values, &counterexample);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::common::Scheduler;
+    use crate::promela::Fairness;
+    use crate::runner::SpinOutcome;
+
+    #[test]
+    fn test_pruned_enumeration() {
+        let model = Model::try_from("F2L").unwrap();
+        for (scheduler, rigid, quasi_ss) in [
+            (Scheduler::Centralized, false, false),
+            (Scheduler::FSYNC, true, false),
+            (Scheduler::SSYNC, true, true),
+        ] {
+            let options = ModelRunOptions {
+                rigid,
+                quasi_ss,
+                ..ModelRunOptions::new(scheduler)
+            };
+            let expected =
+                generator::generate_algorithms_in_model(model.category, model.n_colors, true)
+                    .filter(|algo| algo.is_canonical())
+                    .filter(|algo| checker::verify(algo, options).unwrap() == SpinOutcome::Pass)
+                    .collect::<Vec<_>>();
+            let mut search = PrunedEnumeration::new(model, options).unwrap();
+            let found = search.by_ref().collect::<Result<Vec<_>>>().unwrap();
+            assert_eq!(found, expected, "{scheduler}");
+            assert!(
+                search.candidates()
+                    < generator::count_algorithms_in_model(model.category, 2, true) as usize / 2
+            );
+            assert_eq!(search.candidates(), search.counterexamples() + found.len());
+        }
+
+        let async_options = ModelRunOptions::new(Scheduler::ASYNC);
+        assert!(PrunedEnumeration::new(model, async_options).is_err());
+        let unfair = ModelRunOptions {
+            scheduler: Scheduler::SSYNC,
+            fairness: Fairness::None,
+            ..async_options
+        };
+        assert!(PrunedEnumeration::new(model, unfair).is_err());
+    }
+}
@@ -0,0 +1,248 @@
+//! Interactive simulation of an algorithm (`synth-lights simulate`): the user activates the robots round by round,
+//! or lets a random or an adversarial scheduler choose the rounds, and the observations of the robots, the rules they
+//! apply and the resulting configurations are printed.
+//!
+//! The rounds are those of the [simulator] (complete Look-Compute-Move cycles allowed by the scheduler), so the
+//! executions of the ASYNC schedulers where the phases of the robots interleave are not covered.
+
+use anyhow::{anyhow, bail, Result};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use std::io::{BufRead, Write};
+
+use crate::algorithm::{Algorithm, Rule};
+use crate::common::{Color, Distance, Scheduler};
+use crate::simulator::{self, Config, Round};
+
+/// commands of the interactive simulation.
+pub const HELP: &str = "\
+commands:
+  a, b, ab         activate robot A, robot B, or both robots for a round
+  r [N]            let a random scheduler play N rounds (1 by default), stopping once gathered
+  x                let an adversarial scheduler play an execution that never gathers, if any
+  init POS CA CB   restart from position POS (s, n or f) with colors CA and CB
+  h                print this help
+  q                quit";
+
+/// state of an interactive simulation.
+pub struct Session<'a> {
+    algo: &'a Algorithm,
+    scheduler: Scheduler,
+    config: Config,
+    /// previous round ([Round::Both] initially)
+    last: Round,
+    n_rounds: usize,
+    rng: StdRng,
+}
+
+impl<'a> Session<'a> {
+    pub fn new(algo: &'a Algorithm, scheduler: Scheduler, config: Config, seed: u64) -> Self {
+        Self {
+            algo,
+            scheduler,
+            config,
+            last: Round::Both,
+            n_rounds: 0,
+            rng: StdRng::seed_from_u64(seed),
+        }
+    }
+
+    pub fn config(&self) -> Config {
+        self.config
+    }
+
+    /// restarts from the given configuration.
+    pub fn reset(&mut self, config: Config) {
+        self.config = config;
+        self.last = Round::Both;
+        self.n_rounds = 0;
+    }
+
+    /// plays a round, and describes the observations, the rules applied and the resulting configuration.
+    pub fn play(&mut self, round: Round) -> Result<String> {
+        if !Round::for_scheduler(self.scheduler).contains(&round) {
+            bail!(
+                "round {round} is not allowed by the {} scheduler",
+                self.scheduler
+            );
+        }
+        let config = self.config;
+        let mut lines = Vec::new();
+        for &robot in round.robots() {
+            let (me, other) = (config.colors[robot], config.colors[1 - robot]);
+            let name = robot_name(robot);
+            let rule = self
+                .algo
+                .rule_for(me, other, config.is_gathered())
+                .ok_or_else(|| anyhow!("no rule applies to robot {name} in {config}"))?;
+            let Rule(guard, action) = self.algo.rules().nth(rule).unwrap();
+            lines.push(format!(
+                "  robot {name} sees (me: {me}, other: {other}, {}) and applies rule {} -> {}",
+                if config.is_gathered() {
+                    "gathered"
+                } else {
+                    "apart"
+                },
+                guard.as_code(),
+                action.as_code()
+            ));
+        }
+        let next = simulator::step(self.algo, config, round)
+            .ok_or_else(|| anyhow!("no rule applies in {config}"))?;
+        self.config = next;
+        self.last = round;
+        self.n_rounds += 1;
+        lines.push(format!(
+            "round {} ({round}): {config} -> {next}{}",
+            self.n_rounds,
+            if next.is_gathered() { " gathered" } else { "" }
+        ));
+        Ok(lines.join("\n"))
+    }
+
+    /// plays a round chosen at random among those of the scheduler
+    /// (never the same robot alone twice in a row, as in the [simulator]).
+    pub fn play_random(&mut self) -> Result<String> {
+        let rounds = Round::for_scheduler(self.scheduler)
+            .iter()
+            .filter(|&&round| round == Round::Both || round != self.last)
+            .collect::<Vec<_>>();
+        let round = *rounds[self.rng.random_range(0..rounds.len())];
+        self.play(round)
+    }
+
+    /// plays an execution that never gathers from the current configuration (up to the first repetition of a
+    /// configuration), as found by [simulator::falsify_from].
+    pub fn play_adversary(&mut self) -> Result<String> {
+        let start = self.config;
+        let Some(counterexample) =
+            simulator::falsify_from(self.algo, self.scheduler, [(start, self.last)])
+        else {
+            return Ok(format!(
+                "every execution from {start} gathers (with the rounds of the simulator)"
+            ));
+        };
+        let mut lines = Vec::new();
+        for &round in &counterexample.rounds {
+            lines.push(self.play(round)?);
+        }
+        lines.push(format!(
+            "the execution cycles from {} and never gathers",
+            counterexample.configs[counterexample.cycle_start]
+        ));
+        Ok(lines.join("\n"))
+    }
+
+    /// executes a command (see [HELP]); returns `None` to quit.
+    pub fn execute(&mut self, command: &str) -> Option<Result<String>> {
+        let words = command.split_whitespace().collect::<Vec<_>>();
+        let result = match words.as_slice() {
+            [] => Ok(String::new()),
+            ["q" | "quit"] => return None,
+            ["h" | "help" | "?"] => Ok(HELP.to_string()),
+            ["a"] => self.play(Round::A),
+            ["b"] => self.play(Round::B),
+            ["ab" | "both"] => self.play(Round::Both),
+            ["r" | "random"] => self.play_random(),
+            ["r" | "random", n] => n
+                .parse::<usize>()
+                .map_err(|_| anyhow!("invalid number of rounds: {n}"))
+                .and_then(|n| {
+                    let mut lines = Vec::new();
+                    for _ in 0..n {
+                        if self.config.is_gathered() {
+                            break;
+                        }
+                        lines.push(self.play_random()?);
+                    }
+                    Ok(lines.join("\n"))
+                }),
+            ["x" | "adversary"] => self.play_adversary(),
+            ["init", position, a, b] => {
+                parse_config(self.algo.num_colors(), position, a, b).map(|config| {
+                    self.reset(config);
+                    format!("configuration: {config}")
+                })
+            }
+            _ => Err(anyhow!("unknown command: {command} (h for help)")),
+        };
+        Some(result)
+    }
+
+    /// reads and executes commands until the end of the input or `q`, with a prompt if `prompt` is set.
+    pub fn run(&mut self, input: impl BufRead, output: &mut dyn Write, prompt: bool) -> Result<()> {
+        writeln!(output, "configuration: {}", self.config)?;
+        let mut lines = input.lines();
+        loop {
+            if prompt {
+                write!(output, "> ")?;
+                output.flush()?;
+            }
+            let Some(line) = lines.next() else {
+                break;
+            };
+            match self.execute(&line?) {
+                None => break,
+                Some(Ok(text)) if text.is_empty() => {}
+                Some(Ok(text)) => writeln!(output, "{text}")?,
+                Some(Err(e)) => writeln!(output, "error: {e}")?,
+            }
+        }
+        Ok(())
+    }
+}
+
+fn robot_name(robot: usize) -> &'static str {
+    ["A", "B"][robot]
+}
+
+/// configuration from a position code (`s`, `n` or `f`) and the colors of robots A and B.
+pub fn parse_config(num_colors: u8, position: &str, a: &str, b: &str) -> Result<Config> {
+    let position = Distance::try_parse(position)?;
+    let colors = [Color::try_from(a)?, Color::try_from(b)?];
+    if let Some(c) = colors.iter().find(|c| c.0 >= num_colors) {
+        bail!("color {c} out of range ({num_colors} colors)");
+    }
+    Ok(Config { position, colors })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_session() {
+        // passes under SSYNC with rigid moves and quasi self-stabilization only
+        let algo = Algorithm::try_parse_v2("E4L:0_1_2_3__H1_S2_O3_S0").unwrap();
+        let config = parse_config(4, "n", "0", "0").unwrap();
+        let mut session = Session::new(&algo, Scheduler::SSYNC, config, 0);
+        let text = session.play(Round::Both).unwrap();
+        assert!(text.contains("robot A sees (me: 0, other: 0, apart) and applies rule 0 -> H1"));
+        assert!(text.ends_with("round 1 (Both): Near (0,0) -> Same (1,1) gathered"));
+        assert!(Session::new(&algo, Scheduler::FSYNC, config, 0)
+            .play(Round::A)
+            .is_err());
+        session.reset(config);
+        let text = session.execute("r 100").unwrap().unwrap();
+        assert!(session.config().is_gathered());
+        assert!(text.lines().count() <= 300);
+        assert!(parse_config(4, "n", "0", "4").is_err());
+
+        // fails under the centralized scheduler: the robots move half-way in turn
+        let algo =
+            Algorithm::try_parse_v2("F2:00s_01s_10s_11s_00d_01d_10d_11d__S0_S0_S0_S0_H0_H0_H0_H0")
+                .unwrap();
+        let mut session = Session::new(&algo, Scheduler::Centralized, config, 0);
+        let script = "a\nab\nfoo\ninit f 0 1\nx\nq\nb\n";
+        let mut output = Vec::new();
+        session.run(script.as_bytes(), &mut output, false).unwrap();
+        let output = String::from_utf8(output).unwrap();
+        assert!(output.starts_with("configuration: Near (0,0)\n"));
+        assert!(output.contains("round 1 (A): Near (0,0) -> Near (0,0)"));
+        assert!(output.contains("error: round Both is not allowed by the Centralized scheduler"));
+        assert!(output.contains("error: unknown command: foo"));
+        assert!(output.contains("configuration: Far (0,1)"));
+        // the last command is not executed after `q`
+        assert!(output.ends_with("never gathers\n"));
+    }
+}
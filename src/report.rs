@@ -0,0 +1,354 @@
+//! Parsing of the reports written by the synthesis, and comparison of the outcomes of two reports.
+//!
+//! Only the algorithms listed in a report (PASS and incomplete, and failing with `--report-fails`) are known;
+//! the other failing algorithms are not listed and appear as missing.
+
+use anyhow::{Context, Result};
+use lazy_regex::regex_captures;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use crate::runner::SpinOutcome;
+
+/// algorithm listed in a report with its outcome (the only parser of these lines, see [Entry::parse]).
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct Entry {
+    /// index among the viable algorithms of the run
+    pub index: usize,
+    pub outcome: SpinOutcome,
+    /// code string, as listed (possibly prefixed by the model, see [crate::algorithm::Algorithm::as_code_v2])
+    pub code: String,
+}
+
+impl Entry {
+    /// parses a line of a report listing an algorithm (PASS, incomplete, out of memory or failing); `None` for the
+    /// other lines.
+    pub fn parse(line: &str) -> Result<Option<Self>> {
+        let Some((_, index, outcome, code)) = regex_captures!(
+            r"^(?:INCOMPLETE > |OUT-OF-MEMORY > |FAIL > )?\s*(\d+) : (PASS|SearchIncomplete|Incomplete|OutOfMemory|FAIL) ((?:[FEI]\d+L?:)?[0-9sdSOH_]+)\s*$",
+            line
+        ) else {
+            return Ok(None);
+        };
+        Ok(Some(Entry {
+            index: index.parse()?,
+            outcome: match outcome {
+                "PASS" => SpinOutcome::Pass,
+                "OutOfMemory" => SpinOutcome::OutOfMemory,
+                "FAIL" => SpinOutcome::Fail,
+                _ => SpinOutcome::SearchIncomplete,
+            },
+            code: code.to_string(),
+        }))
+    }
+}
+
+impl std::fmt::Display for Entry {
+    /// line of the entry, as in the report.
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.outcome {
+            SpinOutcome::SearchIncomplete => write!(
+                f,
+                "INCOMPLETE > {:4} : SearchIncomplete {}",
+                self.index, self.code
+            ),
+            SpinOutcome::OutOfMemory => write!(
+                f,
+                "OUT-OF-MEMORY > {:4} : OutOfMemory {}",
+                self.index, self.code
+            ),
+            SpinOutcome::Fail => write!(f, "FAIL > {:4} : FAIL {}", self.index, self.code),
+            outcome => write!(f, "{:4} : {outcome} {}", self.index, self.code),
+        }
+    }
+}
+
+/// counts of the line "Verification Finished with ...".
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Summary {
+    pub n_pass: usize,
+    pub n_fail: usize,
+    pub n_incomplete: usize,
+    pub n_errors: usize,
+    pub n_algos: usize,
+}
+
+impl std::ops::AddAssign for Summary {
+    fn add_assign(&mut self, other: Self) {
+        self.n_pass += other.n_pass;
+        self.n_fail += other.n_fail;
+        self.n_incomplete += other.n_incomplete;
+        self.n_errors += other.n_errors;
+        self.n_algos += other.n_algos;
+    }
+}
+
+impl std::fmt::Display for Summary {
+    /// line of the summary, as in the report.
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Verification Finished with {} pass, {} fail, {} incomplete, {} errors ({} algorithms)",
+            self.n_pass, self.n_fail, self.n_incomplete, self.n_errors, self.n_algos
+        )
+    }
+}
+
+/// results of one run of a report (a report has several runs in a sweep).
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct RunReport {
+    /// model and scheduler of the run (e.g., "E3L / SSYNC"), only given in a sweep
+    pub label: Option<String>,
+    pub entries: Vec<Entry>,
+    /// number of ERROR lines
+    pub n_errors: usize,
+    pub summary: Option<Summary>,
+    /// index of the first viable algorithm verified (line "Slice: ...", 0 without it)
+    pub offset: usize,
+    /// total duration of the run in ms (line "Timing report (Total: ... ms)")
+    pub total_ms: Option<u64>,
+}
+
+impl RunReport {
+    /// outcome of each listed algorithm, by code string.
+    pub fn outcomes(&self) -> BTreeMap<&str, SpinOutcome> {
+        self.entries
+            .iter()
+            .map(|e| (e.code.as_str(), e.outcome))
+            .collect()
+    }
+}
+
+/// results parsed from a report.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Report {
+    pub runs: Vec<RunReport>,
+    /// effective options of the run (line "Effective options: ...", see [crate::metadata])
+    pub options: Option<String>,
+    /// hash of the model of the run (line "Model hash: ...", see [crate::metadata::model_hash])
+    pub model_hash: Option<String>,
+}
+
+impl Report {
+    /// parses the text of a report (of a single run or a sweep).
+    pub fn parse(text: &str) -> Result<Self> {
+        let mut runs = vec![RunReport::default()];
+        let mut options = None;
+        let mut model_hash = None;
+        for (n, line) in text.lines().enumerate() {
+            let context = || format!("line {}: {}", n + 1, line);
+            let run = runs.last_mut().expect("at least one run");
+            if let Some((_, model, scheduler)) =
+                regex_captures!(r"^SWEEP > Model (\S+) / (\S+)\s*$", line)
+            {
+                let label = Some(format!("{model} / {scheduler}"));
+                if run.label.is_none() && run.entries.is_empty() && run.summary.is_none() {
+                    run.label = label;
+                } else {
+                    runs.push(RunReport {
+                        label,
+                        ..Default::default()
+                    });
+                }
+            } else if let Some(entry) = Entry::parse(line).with_context(context)? {
+                run.entries.push(entry);
+            } else if line.starts_with("ERROR : ") {
+                run.n_errors += 1;
+            } else if let Some(effective) = line.strip_prefix("Effective options: ") {
+                options = Some(effective.to_string());
+            } else if let Some(hash) = line.strip_prefix("Model hash: ") {
+                model_hash = Some(hash.trim().to_string());
+            } else if let Some((_, offset)) =
+                regex_captures!(r"^Slice: viable algorithms from index (\d+)", line)
+            {
+                run.offset = offset.parse().with_context(context)?;
+            } else if let Some((_, total)) =
+                regex_captures!(r"^Timing report \(Total: (\d+) ms\)", line)
+            {
+                run.total_ms = Some(total.parse().with_context(context)?);
+            } else if let Some((_, pass, fail, incomplete, errors, algos)) = regex_captures!(
+                r"^Verification Finished with (\d+) pass, (\d+) fail, (\d+) incomplete, (\d+) errors \((\d+) algorithms\)",
+                line
+            ) {
+                run.summary = Some(Summary {
+                    n_pass: pass.parse().with_context(context)?,
+                    n_fail: fail.parse().with_context(context)?,
+                    n_incomplete: incomplete.parse().with_context(context)?,
+                    n_errors: errors.parse().with_context(context)?,
+                    n_algos: algos.parse().with_context(context)?,
+                });
+            }
+        }
+        Ok(Self {
+            runs,
+            options,
+            model_hash,
+        })
+    }
+
+    /// parses a report written in JSON lines (see [crate::output]).
+    pub fn parse_jsonl(text: &str) -> Result<Self> {
+        #[derive(Deserialize)]
+        struct JsonLine {
+            line: String,
+        }
+        let mut lines = String::new();
+        for (n, json) in text
+            .lines()
+            .enumerate()
+            .filter(|(_, l)| !l.trim().is_empty())
+        {
+            let json: JsonLine = serde_json::from_str(json)
+                .with_context(|| format!("line {}: invalid JSON line of a report", n + 1))?;
+            lines += &json.line;
+            lines.push('\n');
+        }
+        Self::parse(&lines)
+    }
+
+    /// reads and parses a report file, in JSON lines if its extension is `jsonl` or `json`.
+    pub fn read(path: &Path) -> Result<Self> {
+        let text = std::fs::read_to_string(path)
+            .with_context(|| format!("failed to read report {}", path.display()))?;
+        match path.extension().and_then(|e| e.to_str()) {
+            Some("jsonl" | "json") => Self::parse_jsonl(&text),
+            _ => Self::parse(&text),
+        }
+        .with_context(|| format!("invalid report {}", path.display()))
+    }
+}
+
+/// algorithm whose outcome differs between two reports (`None` if not listed, i.e., failing without `--report-fails`
+/// or not verified).
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Change {
+    pub label: Option<String>,
+    pub code: String,
+    pub before: Option<SpinOutcome>,
+    pub after: Option<SpinOutcome>,
+}
+
+impl std::fmt::Display for Change {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let outcome = |o: Option<SpinOutcome>| o.map_or("-".to_string(), |o| o.to_string());
+        if let Some(ref label) = self.label {
+            write!(f, "[{label}] ")?;
+        }
+        write!(
+            f,
+            "{} : {} -> {}",
+            self.code,
+            outcome(self.before),
+            outcome(self.after)
+        )
+    }
+}
+
+/// returns the algorithms whose outcome changed from report `a` to report `b`.
+/// Runs are matched by label, except that two single-run reports are always compared.
+pub fn diff(a: &Report, b: &Report) -> Vec<Change> {
+    let pairs: Vec<(&RunReport, Option<&RunReport>)> = if a.runs.len() == 1 && b.runs.len() == 1 {
+        vec![(&a.runs[0], Some(&b.runs[0]))]
+    } else {
+        a.runs
+            .iter()
+            .map(|ra| (ra, b.runs.iter().find(|rb| rb.label == ra.label)))
+            .collect()
+    };
+    let empty = RunReport::default();
+    let mut changes = Vec::new();
+    for (ra, rb) in pairs {
+        let before = ra.outcomes();
+        let after = rb.unwrap_or(&empty).outcomes();
+        let codes = before
+            .keys()
+            .chain(after.keys())
+            .collect::<std::collections::BTreeSet<_>>();
+        for code in codes {
+            let (o_a, o_b) = (before.get(code).copied(), after.get(code).copied());
+            if o_a != o_b {
+                changes.push(Change {
+                    label: ra.label.clone(),
+                    code: code.to_string(),
+                    before: o_a,
+                    after: o_b,
+                });
+            }
+        }
+    }
+    changes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SWEEP: &str = "\
+Run options: Cli { .. }
+
+SWEEP > Model E3L / SSYNC
+   0 : PASS 0_1_2__S2_H0_O1
+INCOMPLETE >    5 : SearchIncomplete 0_1_2__S2_H1_O0
+Verification Finished with 1 pass, 4 fail, 1 incomplete, 0 errors (6 algorithms)
+
+SWEEP > Model E3L / ASYNC
+.....
+   3 : PASS 0_1_2__S2_H0_O1
+   4 : Incomplete 0_1_2__S1_H0_O2
+ERROR : spin failed
+Verification Finished with 1 pass, 3 fail, 1 incomplete, 1 errors (6 algorithms)
+";
+
+    #[test]
+    fn test_parse() {
+        let report = Report::parse(SWEEP).unwrap();
+        assert_eq!(report.runs.len(), 2);
+        let run = &report.runs[1];
+        assert_eq!(run.label.as_deref(), Some("E3L / ASYNC"));
+        assert_eq!(
+            run.entries[1],
+            Entry {
+                index: 4,
+                outcome: SpinOutcome::SearchIncomplete,
+                code: "0_1_2__S1_H0_O2".to_string()
+            }
+        );
+        assert_eq!(run.n_errors, 1);
+        assert_eq!(run.summary.unwrap().n_fail, 3);
+        assert_eq!(
+            run.summary.unwrap().to_string(),
+            "Verification Finished with 1 pass, 3 fail, 1 incomplete, 1 errors (6 algorithms)"
+        );
+        assert_eq!(
+            report.runs[0].entries[1].to_string(),
+            "INCOMPLETE >    5 : SearchIncomplete 0_1_2__S2_H1_O0"
+        );
+        assert_eq!(report.runs[0].entries.len(), 2);
+
+        let line = "OUT-OF-MEMORY >   12 : OutOfMemory 0_1_2__S2_H1_O0";
+        let entry = Entry::parse(line).unwrap().unwrap();
+        assert_eq!(entry.outcome, SpinOutcome::OutOfMemory);
+        assert_eq!(entry.to_string(), line);
+    }
+
+    #[test]
+    fn test_diff() {
+        let a = Report::parse(SWEEP).unwrap();
+        let b = Report::parse(
+            &SWEEP
+                .replace("SearchIncomplete 0_1_2__S2_H1_O0", "PASS 0_1_2__S2_H1_O0")
+                .replace("   4 : Incomplete 0_1_2__S1_H0_O2\n", ""),
+        )
+        .unwrap();
+        assert!(diff(&a, &a).is_empty());
+        let changes = diff(&a, &b);
+        assert_eq!(changes.len(), 2);
+        assert_eq!(
+            changes[0].to_string(),
+            "[E3L / SSYNC] 0_1_2__S2_H1_O0 : Incomplete -> PASS"
+        );
+        assert_eq!(changes[1].before, Some(SpinOutcome::SearchIncomplete));
+        assert_eq!(changes[1].after, None);
+    }
+}
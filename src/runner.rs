@@ -1,23 +1,42 @@
-use anyhow::Result;
+use anyhow::{bail, Context, Result};
 use duct::cmd;
+use lazy_regex::regex_captures;
+use std::hash::{DefaultHasher, Hash, Hasher};
 use std::path::{Path, PathBuf};
+use std::sync::Arc;
 
 use crate::algorithm::Algorithm;
-use crate::promela::{self, prepare_promela_code};
+use crate::depth;
+use crate::interrupt;
+use crate::promela::{self, prepare_promela_code, Fairness, ModelRunOptions};
+use crate::throttle;
 
-use log::{debug, trace};
+use log::{debug, trace, warn};
+use serde::{Deserialize, Serialize};
 
 const TRAIL_FILENAME: &str = "MainGathering.pml.trail";
+/// file of the directory holding the output of the last run of `pan` (see [read_pan_output]).
+const PAN_OUTPUT_FILENAME: &str = "pan.out";
+/// file of the directory recording the hash of the sources of the last `pan` built in it.
+const PAN_HASH_FILENAME: &str = "pan.hash";
+/// sources generated by `spin -a` and compiled into `pan` (`pan.c` includes the others).
+const PAN_SOURCES: [&str; 6] = ["pan.c", "pan.h", "pan.b", "pan.m", "pan.p", "pan.t"];
 const VOLUME: &str = "SynthLightsRamDisk";
+/// memory-backed directories in which a working directory can be created without privileges (see [create_shm_workdir]).
+const SHM_DIR: &str = "/dev/shm";
+const RUNTIME_DIR_VAR: &str = "XDG_RUNTIME_DIR";
 
 #[derive(Debug)]
 pub enum Workdir {
     Ramdisk(String, PathBuf),
+    /// temporary directory, for runs that do not call spin (see [create_temp_workdir]),
+    /// or in a memory-backed directory (see [create_shm_workdir]); removed when closed
+    Temp(PathBuf),
 }
 impl Workdir {
     pub fn path(&self) -> &Path {
         match self {
-            Workdir::Ramdisk(_, path) => path,
+            Workdir::Ramdisk(_, path) | Workdir::Temp(path) => path,
         }
     }
 }
@@ -46,19 +65,197 @@ pub fn create_root_workdir(ramdisk: Option<String>) -> Result<Workdir> {
     let ramdisk = ramdisk.unwrap_or_else(|| VOLUME.into());
     const SIZE: u16 = 512;
 
-    let (dev, path) = ramdisk::create_ramdisk(SIZE, ramdisk.as_str())?;
+    let (dev, path) = ramdisk::create_ramdisk(SIZE, ramdisk.as_str()).map_err(|e| {
+        let already_exists = e.kind() == std::io::ErrorKind::AlreadyExists;
+        let error = anyhow::Error::new(e);
+        if already_exists {
+            error.context("the ramdisk is already mounted (use --reuse-workdir to reuse it)")
+        } else {
+            error
+        }
+    })?;
 
     Ok(Workdir::Ramdisk(dev, path))
 }
 
+/// same as [create_root_workdir], but reuses the ramdisk if it is already mounted (e.g., left by a crashed run),
+/// after removing the enclosures left in it: the ramdisk must not be in use by another run.
+pub fn reuse_root_workdir(ramdisk: Option<String>) -> Result<Workdir> {
+    let volume = ramdisk.clone().unwrap_or_else(|| VOLUME.into());
+    let path = ramdisk::volume_path(&volume);
+    if !ramdisk::is_mounted(&path) {
+        return create_root_workdir(ramdisk);
+    }
+    let n_removed = remove_stale_enclosures(&path)?;
+    log::info!(
+        "Reusing ramdisk {} ({n_removed} stale enclosures removed)",
+        path.display()
+    );
+    Ok(Workdir::Ramdisk("reused".to_string(), path))
+}
+
+/// removes the enclosures left in a working directory (see [create_enclosure]); returns their number.
+pub fn remove_stale_enclosures(path: &Path) -> Result<usize> {
+    let mut n_removed = 0;
+    for entry in std::fs::read_dir(path)? {
+        let entry = entry?;
+        if entry
+            .file_name()
+            .to_string_lossy()
+            .starts_with("enclosure-")
+            && entry.path().is_dir()
+        {
+            std::fs::remove_dir_all(entry.path()).with_context(|| {
+                format!(
+                    "failed to remove stale enclosure {}",
+                    entry.path().display()
+                )
+            })?;
+            n_removed += 1;
+        }
+    }
+    Ok(n_removed)
+}
+
+/// creates a root working directory in the temporary directory of the system, without a ramdisk
+/// (the verifications that do not call spin write little to the disk).
+pub fn create_temp_workdir() -> Result<Workdir> {
+    let path = std::env::temp_dir().join(format!("synth-lights-{:x}", uuid::Uuid::new_v4()));
+    trace!("create_temp_workdir() -> {:?}", path);
+    std::fs::create_dir_all(&path)?;
+    Ok(Workdir::Temp(path))
+}
+
+/// creates a root working directory in `/dev/shm`, or else in `$XDG_RUNTIME_DIR`: both are tmpfs on Linux, so that
+/// the verifications get the speed of a ramdisk without mounting one (which needs `sudo`, often unavailable on clusters).
+pub fn create_shm_workdir() -> Result<Workdir> {
+    let candidates = std::iter::once(PathBuf::from(SHM_DIR))
+        .chain(std::env::var_os(RUNTIME_DIR_VAR).map(PathBuf::from))
+        .filter(|dir| dir.is_dir());
+    for dir in candidates {
+        let path = dir.join(format!("synth-lights-{:x}", uuid::Uuid::new_v4()));
+        match std::fs::create_dir(&path) {
+            Ok(()) => {
+                trace!("create_shm_workdir() -> {:?}", path);
+                return Ok(Workdir::Temp(path));
+            }
+            Err(e) => debug!(
+                "cannot create a working directory in {}: {e}",
+                dir.display()
+            ),
+        }
+    }
+    bail!("no writable memory-backed directory ({SHM_DIR} or ${RUNTIME_DIR_VAR}) for the working directory")
+}
+
+/// where the root working directory of the verifications is created, shared by the subcommands running spin.
+#[derive(clap::Args, Clone, Debug, Default, PartialEq, Eq)]
+pub struct WorkdirArgs {
+    /// Volume name of the ramdisk holding the working directory
+    #[arg(short = 'r', long = "ramdisk")]
+    pub ramdisk: Option<String>,
+
+    /// Creates the working directory in /dev/shm (or $XDG_RUNTIME_DIR) instead of mounting a ramdisk (no sudo needed; Linux)
+    #[arg(long = "shm", conflicts_with = "ramdisk")]
+    pub shm: bool,
+
+    /// Reuses the ramdisk if it is already mounted (e.g., after a crash), removing the enclosures left in it
+    #[arg(long = "reuse-workdir", conflicts_with = "shm")]
+    pub reuse: bool,
+}
+
+impl WorkdirArgs {
+    /// creates the root working directory (see [create_root_workdir] and [create_shm_workdir]).
+    pub fn create(&self) -> Result<Workdir> {
+        if self.shm {
+            create_shm_workdir()
+        } else if self.reuse {
+            reuse_root_workdir(self.ramdisk.clone())
+        } else {
+            create_root_workdir(self.ramdisk.clone())
+        }
+    }
+}
+
+/// space available in the file system of the path, in MB (from `df -Pk`), if known.
+pub fn available_space_mb(path: &Path) -> Option<usize> {
+    let df = cmd!("df", "-Pk", path)
+        .stderr_null()
+        .unchecked()
+        .read()
+        .ok()?;
+    let kb = df.lines().nth(1)?.split_whitespace().nth(3)?;
+    kb.parse::<usize>().ok().map(|kb| kb / 1024)
+}
+
+/// removes the files left in an enclosure by the last verification (pan and its sources, trail), keeping the
+/// Promela files: the next verification rebuilds pan.
+pub fn clean_enclosure(path: &Path) -> Result<()> {
+    let files = PAN_SOURCES.iter().chain(&[
+        "pan",
+        PAN_HASH_FILENAME,
+        PAN_OUTPUT_FILENAME,
+        TRAIL_FILENAME,
+    ]);
+    for file in files {
+        match std::fs::remove_file(path.join(file)) {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => {
+                return Err(e).with_context(|| format!("failed to clean {}", path.display()))
+            }
+            _ => (),
+        }
+    }
+    Ok(())
+}
+
 /// closes a working directory (e.g, unmount the ramdisk).
 pub fn close_workdir(workdir: Workdir) -> Result<()> {
     trace!("close_workdir({:?})", workdir);
-    ramdisk::eject_ramdisk(workdir.path())?;
+    match workdir {
+        Workdir::Ramdisk(_, ref path) => ramdisk::eject_ramdisk(path)?,
+        Workdir::Temp(ref path) => std::fs::remove_dir_all(path)?,
+    }
 
     Ok(())
 }
 
+/// working directory closed when dropped (e.g., on an early return with an error or after an interruption), unless
+/// it was closed before with [WorkdirGuard::close], which returns the outcome of the cleanup.
+#[derive(Debug)]
+pub struct WorkdirGuard(Option<Workdir>);
+
+impl WorkdirGuard {
+    pub fn new(workdir: Workdir) -> Self {
+        Self(Some(workdir))
+    }
+
+    /// closes the working directory (see [close_workdir]).
+    pub fn close(mut self) -> Result<()> {
+        match self.0.take() {
+            Some(workdir) => close_workdir(workdir),
+            None => Ok(()),
+        }
+    }
+}
+
+impl std::ops::Deref for WorkdirGuard {
+    type Target = Workdir;
+
+    fn deref(&self) -> &Workdir {
+        self.0.as_ref().expect("working directory already closed")
+    }
+}
+
+impl Drop for WorkdirGuard {
+    fn drop(&mut self) {
+        if let Some(workdir) = self.0.take() {
+            if let Err(e) = close_workdir(workdir) {
+                log::error!("failed to close the working directory: {e:#}");
+            }
+        }
+    }
+}
+
 /// creates a subdirectory (enclosure) as a working space for a thread,
 /// and returns a path to the newly created directory.
 /// The call prepares the Promela code by calling [prepare_promela_code()]
@@ -82,9 +279,10 @@ pub fn create_enclosure(path: &Path) -> Result<PathBuf> {
     Ok(path)
 }
 
-#[derive(Debug, PartialEq, Eq, PartialOrd, Ord)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
 pub enum SpinOutcome {
     Fail, //< the verification fails. Details or counter-example should be obtained via regular verification.
+    OutOfMemory, //< the verification process is unconclusive because pan ran out of memory (see `--memlim`).
     SearchIncomplete, //< the verification process is unconclusive because the search was incomplete.
     Pass,             //< the algorithms passes the check.
 }
@@ -98,11 +296,136 @@ impl std::fmt::Display for SpinOutcome {
         match self {
             Self::Fail => write!(f, "fail"),
             Self::Pass => write!(f, "PASS"),
+            Self::OutOfMemory => write!(f, "OutOfMemory"),
             Self::SearchIncomplete => write!(f, "Incomplete"),
         }
     }
 }
 
+/// model checker verifying an algorithm under the given options of the model,
+/// so that the synthesis can use spin ([SpinBackend]) or other checkers without depending on them.
+pub trait VerificationBackend: Sync {
+    fn verify(&self, algo: &Algorithm, options: &ModelRunOptions) -> Result<SpinOutcome>;
+}
+
+/// defines passed to clang when building the `pan` verifier; the defaults are those of the original setting
+/// (`-DMEMLIM=16384 -DXUSAFE -DNOREDUCE`). The right set differs between the scheduler models: e.g., partial-order
+/// reduction or state compression can make a large model feasible. Also selects the stages of the runs of pan.
+#[derive(clap::Args, Clone, Debug, Default, PartialEq, Eq)]
+pub struct PanBuildOptions {
+    /// Enables the partial-order reduction of pan (omits -DNOREDUCE)
+    #[arg(long = "reduce")]
+    pub reduce: bool,
+
+    /// Keeps the checks of the exclusive use of channels in pan (omits -DXUSAFE)
+    #[arg(long = "no-xusafe")]
+    pub no_xusafe: bool,
+
+    /// Compresses the states with -DCOLLAPSE
+    #[arg(long = "collapse")]
+    pub collapse: bool,
+
+    /// Stores the states in a minimized automaton with -DMA=N (N: size of the state vector)
+    #[arg(long = "ma", value_name = "N")]
+    pub ma: Option<usize>,
+
+    /// Memory limit of pan in MB (-DMEMLIM, 16384 by default)
+    #[arg(long = "memlim", value_name = "MB")]
+    pub memlim: Option<usize>,
+
+    /// Additional define passed to clang for pan (e.g., --pan-define BITSTATE or --pan-define VECTORSZ=2048; can be repeated)
+    #[arg(long = "pan-define", value_name = "NAME[=VALUE]")]
+    pub defines: Vec<String>,
+
+    /// Runs pan for safety first (without -a), and for liveness only if no violation is found
+    #[arg(long = "safety-first")]
+    pub safety_first: bool,
+
+    /// Maximal search depth of pan (-m, 100000 by default)
+    #[arg(long = "depth", value_name = "N", value_parser = clap::value_parser!(u64).range(1..))]
+    pub depth: Option<u64>,
+}
+
+impl PanBuildOptions {
+    /// memory limit of pan in MB when not given.
+    pub const DEFAULT_MEMLIM: usize = 16384;
+
+    /// maximal search depth of pan when not given (see [Fairness::pan_args]).
+    pub const DEFAULT_DEPTH: usize = 100_000;
+
+    /// memory limit of pan in MB.
+    pub fn memlim_mb(&self) -> usize {
+        self.memlim.unwrap_or(Self::DEFAULT_MEMLIM)
+    }
+
+    /// maximal search depth of pan.
+    pub fn depth(&self) -> usize {
+        self.depth
+            .map_or(Self::DEFAULT_DEPTH, |depth| depth as usize)
+    }
+
+    /// arguments of pan (e.g., [Fairness::pan_args]) with the given search depth instead of the default one.
+    pub fn pan_args_with_depth(args: Vec<&str>, depth: usize) -> Vec<String> {
+        args.into_iter()
+            .map(|arg| match arg.starts_with("-m") {
+                true => format!("-m{depth}"),
+                false => arg.to_string(),
+            })
+            .collect()
+    }
+
+    /// defines passed to clang (`-D...`).
+    pub fn clang_defines(&self) -> Vec<String> {
+        let mut defines = vec![format!("-DMEMLIM={}", self.memlim_mb())];
+        if !self.no_xusafe {
+            defines.push("-DXUSAFE".to_string());
+        }
+        if !self.reduce {
+            defines.push("-DNOREDUCE".to_string());
+        }
+        if self.collapse {
+            defines.push("-DCOLLAPSE".to_string());
+        }
+        if let Some(n) = self.ma {
+            defines.push(format!("-DMA={n}"));
+        }
+        defines.extend(self.defines.iter().map(|d| format!("-D{d}")));
+        defines
+    }
+}
+
+/// spin pipeline ([run_verification]) in a directory holding the promela files (e.g., an enclosure).
+#[derive(Clone, Debug)]
+pub struct SpinBackend {
+    dir: PathBuf,
+    build: PanBuildOptions,
+}
+
+impl SpinBackend {
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self {
+            dir: dir.into(),
+            build: PanBuildOptions::default(),
+        }
+    }
+
+    /// builds pan with the given options.
+    pub fn build_options(mut self, build: PanBuildOptions) -> Self {
+        self.build = build;
+        self
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+}
+
+impl VerificationBackend for SpinBackend {
+    fn verify(&self, algo: &Algorithm, options: &ModelRunOptions) -> Result<SpinOutcome> {
+        run_verification_with_build(&self.dir, algo, *options, &self.build)
+    }
+}
+
 /// runs the verification proper on the given algorithm,
 /// assuming that all promela files are already installed at the given path.
 /// This includes the following:
@@ -115,7 +438,7 @@ impl std::fmt::Display for SpinOutcome {
 ///
 /// * `dir`   - path to the directory holding the promela files
 /// * `algo`  - algorithm to verify
-/// * `sched` - name of the scheduler to be used for the verification (check promela files or see below).
+/// * `options` - options of the model (scheduler, see below, and variant) passed to spin, and fairness of pan.
 ///
 /// # Outputs
 ///
@@ -129,12 +452,22 @@ impl std::fmt::Display for SpinOutcome {
 /// * SSYNC
 /// * FSYNC
 /// * ... _see [`Scheduler`]_
-pub fn run_verification<T>(dir: &Path, algo: &Algorithm, spin_args: T) -> Result<SpinOutcome>
-where
-    T: IntoIterator,
-    T::Item: Into<String>,
-{
-    debug!("run_verification({:?}, {:?}, spin_args)", dir, algo);
+pub fn run_verification(
+    dir: &Path,
+    algo: &Algorithm,
+    options: ModelRunOptions,
+) -> Result<SpinOutcome> {
+    run_verification_with_build(dir, algo, options, &PanBuildOptions::default())
+}
+
+/// same as [run_verification], with pan built with the given options.
+pub fn run_verification_with_build(
+    dir: &Path,
+    algo: &Algorithm,
+    options: ModelRunOptions,
+    build: &PanBuildOptions,
+) -> Result<SpinOutcome> {
+    debug!("run_verification({:?}, {:?}, {:?})", dir, algo, options);
     let mut trail_file: PathBuf = dir.to_path_buf();
     trail_file.push(TRAIL_FILENAME);
     let trail_file = trail_file.as_path();
@@ -146,16 +479,31 @@ where
         eprintln!("ERROR: trail file was not deleted");
     }
 
-    let _ = promela::install_algorithm(dir, algo)?;
-    run_spin_and_model(dir, trail_file, spin_args)
+    promela::install_algorithm(dir, algo)?;
+    run_spin_and_model(dir, trail_file, options, build)
 }
 
-pub fn run_verification_from_code<T>(dir: &Path, algo: &str, spin_args: T) -> Result<SpinOutcome>
-where
-    T: IntoIterator,
-    T::Item: Into<String>,
-{
-    debug!("run_verification({:?}, {:?}, spin_args)", dir, algo);
+/// runs the verification of the algorithm once for each of the given options (e.g., [ModelRunOptions::variants]),
+/// reusing the same directory. Returns the outcomes in the same order as the options.
+pub fn run_verification_variants(
+    dir: &Path,
+    algo: &Algorithm,
+    variants: &[ModelRunOptions],
+) -> Result<Vec<SpinOutcome>> {
+    let backend = SpinBackend::new(dir);
+    variants
+        .iter()
+        .map(|options| backend.verify(algo, options))
+        .collect()
+}
+
+pub fn run_verification_from_code(
+    dir: &Path,
+    algo: &str,
+    options: ModelRunOptions,
+    build: &PanBuildOptions,
+) -> Result<SpinOutcome> {
+    debug!("run_verification({:?}, {:?}, {:?})", dir, algo, options);
     let mut trail_file: PathBuf = dir.to_path_buf();
     trail_file.push(TRAIL_FILENAME);
     let trail_file = trail_file.as_path();
@@ -167,8 +515,138 @@ where
         eprintln!("ERROR: trail file was not deleted");
     }
 
-    let _ = promela::install_algorithm_from_code(dir, algo)?;
-    run_spin_and_model(dir, trail_file, spin_args)
+    promela::install_algorithm_from_code(dir, algo)?;
+    run_spin_and_model(dir, trail_file, options, build)
+}
+
+/// simplifies an algorithm by re-verification: each rule is in turn replaced by its default action
+/// (see [crate::algorithm::Guard::default_action]), and the replacement is kept if the resulting
+/// algorithm still passes the verification.
+/// Rules found redundant by static analysis ([Algorithm::redundant_rules]) are replaced without verification.
+///
+/// # Arguments
+///
+/// * `dir`       - path to the directory holding the promela files
+/// * `algo`      - algorithm to simplify (expected to pass the verification)
+/// * `options`   - options of the model, as in [run_verification]
+/// * `build`     - options of the pan build, the same as for the verification that the algorithm passed
+///
+pub fn minimize_algorithm(
+    dir: &Path,
+    algo: &Algorithm,
+    options: ModelRunOptions,
+    build: &PanBuildOptions,
+) -> Result<Algorithm> {
+    debug!("minimize_algorithm({:?}, {:?}, {:?})", dir, algo, options);
+    let mut current = algo.clone();
+    for i in current.redundant_rules() {
+        if let Some(simplified) = current.with_default_rule(i) {
+            current = simplified;
+        }
+    }
+    for i in 0..current.rules().count() {
+        let Some(candidate) = current.with_default_rule(i) else {
+            continue;
+        };
+        if candidate == current {
+            continue;
+        }
+        if run_verification_with_build(dir, &candidate, options, build)? == SpinOutcome::Pass {
+            current = candidate;
+        }
+    }
+    Ok(current)
+}
+
+/// installs the algorithm and builds the verifier (spin and clang) without running it,
+/// assuming that all promela files are already installed at the given path.
+pub fn compile_model<T>(dir: &Path, algo: &Algorithm, spin_args: T) -> Result<()>
+where
+    T: IntoIterator,
+    T::Item: Into<String>,
+{
+    debug!("compile_model({:?}, {:?}, spin_args)", dir, algo);
+    promela::install_algorithm(dir, algo)?;
+    run_spin(dir, spin_args)?;
+    build_pan(dir, &PanBuildOptions::default())?;
+    Ok(())
+}
+
+/// installs the algorithm and runs `spin -a` on the model without building the verifier, to check that the promela
+/// files and the options are consistent (e.g., no unknown scheduler) before running the verifications.
+/// Fails with the messages of spin if it rejects the model.
+pub fn lint_model<T>(dir: &Path, algo: &Algorithm, spin_args: T) -> Result<()>
+where
+    T: IntoIterator,
+    T::Item: Into<String>,
+{
+    debug!("lint_model({:?}, {:?}, spin_args)", dir, algo);
+    promela::install_algorithm(dir, algo)?;
+    let args = full_spin_args(spin_args);
+    let output = cmd("spin", &args)
+        .dir(dir)
+        .stderr_to_stdout()
+        .stdout_capture()
+        .unchecked()
+        .run()
+        .context("failed to run spin")?;
+    let messages = String::from_utf8_lossy(&output.stdout);
+    if !output.status.success() || messages.lines().any(|l| l.contains("Error:")) {
+        bail!(
+            "spin rejects the model (spin {}):\n{}",
+            args.join(" "),
+            messages.trim_end()
+        );
+    }
+    Ok(())
+}
+
+/// output of the last run of pan in the directory (e.g., the statistics of an incomplete search), if any.
+pub fn read_pan_output(dir: &Path) -> Result<Option<String>> {
+    let path = dir.join(PAN_OUTPUT_FILENAME);
+    if path.exists() {
+        Ok(Some(std::fs::read_to_string(path)?))
+    } else {
+        Ok(None)
+    }
+}
+
+/// copies the trail of the last verification in the directory, if any, to `<archive>/<name>.trail`, together with
+/// the promela file of the algorithm (see [promela::ALGORITHM_FILE]). Returns whether there was a trail.
+pub fn save_trail(dir: &Path, archive: &Path, name: &str) -> Result<bool> {
+    let trail_file = dir.join(TRAIL_FILENAME);
+    if !trail_file.exists() {
+        return Ok(false);
+    }
+    std::fs::create_dir_all(archive)
+        .with_context(|| format!("failed to create trail archive {}", archive.display()))?;
+    std::fs::copy(&trail_file, archive.join(format!("{name}.trail")))?;
+    std::fs::copy(
+        dir.join(promela::ALGORITHM_FILE),
+        archive.join(promela::ALGORITHM_FILE),
+    )?;
+    Ok(true)
+}
+
+/// saves a failed verification in the directory to `<archive>/<name>.error` (the error and its causes), with the
+/// standard output and error of the tool that failed, if any (see [ToolError]), in `<name>.stdout` and
+/// `<name>.stderr`, and the promela file of the algorithm (see [promela::ALGORITHM_FILE]) if it was generated.
+pub fn save_failure(dir: &Path, archive: &Path, name: &str, error: &anyhow::Error) -> Result<()> {
+    std::fs::create_dir_all(archive)
+        .with_context(|| format!("failed to create failure archive {}", archive.display()))?;
+    std::fs::write(
+        archive.join(format!("{name}.error")),
+        format!("{error:?}\n"),
+    )?;
+    if let Some(tool_error) = error.chain().find_map(|e| e.downcast_ref::<ToolError>()) {
+        std::fs::write(archive.join(format!("{name}.stdout")), &tool_error.stdout)?;
+        std::fs::write(archive.join(format!("{name}.stderr")), &tool_error.stderr)?;
+    }
+    let algorithm_file = dir.join(promela::ALGORITHM_FILE);
+    if algorithm_file.exists() {
+        std::fs::copy(&algorithm_file, archive.join(promela::ALGORITHM_FILE))?;
+    }
+    Ok(())
 }
 
 pub fn read_trail_file(dir: &Path) -> Result<Option<String>> {
@@ -177,41 +655,303 @@ pub fn read_trail_file(dir: &Path) -> Result<Option<String>> {
     let trail_file = trail_file.as_path();
 
     if trail_file.exists() {
-        return Ok(Some(std::fs::read_to_string(trail_file)?));
+        Ok(Some(std::fs::read_to_string(trail_file)?))
     } else {
         Ok(None)
     }
 }
 
-fn run_spin_and_model<T>(dir: &Path, trail_file: &Path, spin_args: T) -> Result<SpinOutcome>
-where
-    T: IntoIterator,
-    T::Item: Into<String>,
-{
-    debug!("run_spin_and_model({:?}, {:?}, spin_args)", dir, trail_file);
-    let _s = run_spin(dir, spin_args)?;
-    let _c = run_clang(dir)?;
-    let check_result = run_pan(dir)?;
+fn run_spin_and_model(
+    dir: &Path,
+    trail_file: &Path,
+    options: ModelRunOptions,
+    build: &PanBuildOptions,
+) -> Result<SpinOutcome> {
+    debug!(
+        "run_spin_and_model({:?}, {:?}, {:?})",
+        dir, trail_file, options
+    );
+    let pan_output = dir.join(PAN_OUTPUT_FILENAME);
+    if pan_output.exists() {
+        std::fs::remove_file(&pan_output)?;
+    }
+    let _s = run_spin(dir, options)?;
+    let _c = build_pan(dir, build)?;
+    let depth = depth::search_depth(build.depth());
+    let check_result = {
+        let _permit = throttle::acquire(build.memlim_mb());
+        match build.safety_first {
+            true => {
+                let safety_result = run_pan_safety(dir, depth)?;
+                let safety_summary = PanSummary::parse(&safety_result);
+                let safety_failed = is_fail(dir, &safety_summary, trail_file);
+                if safety_failed || safety_summary.out_of_memory {
+                    // violation of a safety property: the liveness stage would also report it
+                    // (or the liveness stage, which stores more states, would also run out of memory)
+                    std::fs::write(pan_output, &safety_result)?;
+                    depth::record(depth, false);
+                    return Ok(match safety_failed {
+                        true => SpinOutcome::Fail,
+                        false => SpinOutcome::OutOfMemory,
+                    });
+                }
+                run_pan(dir, options.fairness, depth)?
+            }
+            false => run_pan(dir, options.fairness, depth)?,
+        }
+    };
+    std::fs::write(pan_output, &check_result)?;
 
-    if trail_file.exists() {
-        return Ok(SpinOutcome::Fail);
+    let summary = PanSummary::parse(&check_result);
+    let outcome = match is_fail(dir, &summary, trail_file) {
+        true => SpinOutcome::Fail,
+        false => summary.outcome(),
+    };
+    depth::record(
+        depth,
+        outcome == SpinOutcome::SearchIncomplete && summary.depth_truncated,
+    );
+    Ok(outcome)
+}
+
+/// checks whether a run of pan found a violation: from the `errors: N` of its summary (see [PanSummary]), or from
+/// the trail file if pan stopped before its summary (e.g., out of memory). The trail file is also checked when the
+/// summary is found, and a disagreement (e.g., a trail left by a previous run, or a trail that could not be written)
+/// is reported loudly, the summary being trusted.
+fn is_fail(dir: &Path, summary: &PanSummary, trail_file: &Path) -> bool {
+    let has_trail = trail_file.exists();
+    match summary.errors {
+        Some(errors) => {
+            if (errors > 0) != has_trail {
+                warn!(
+                    "pan reported {errors} errors in {} but the trail file {} {}; the outcome follows pan's errors",
+                    dir.display(),
+                    trail_file.display(),
+                    if has_trail { "exists" } else { "is missing" }
+                );
+            }
+            errors > 0
+        }
+        None => has_trail,
     }
-    Ok(outcome_from_output(&check_result))
 }
 
-fn outcome_from_output(check_result: &str) -> SpinOutcome {
-    trace!("outcome_from_output({})", check_result);
-    let found_warning = check_result
-        .lines()
-        .any(|l| l.starts_with("Warning: Search not completed"));
-    if found_warning {
-        SpinOutcome::SearchIncomplete
+/// summary of a run of pan, from its output: the `errors: N` of the line it prints at the end of the search
+/// (`State-vector 88 byte, depth reached 1234, errors: 0`), `None` if pan stopped before it, and the warnings and
+/// violation it reports on the way.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct PanSummary {
+    /// number of errors found (pan stops at the first one by default)
+    pub errors: Option<usize>,
+    /// first violation reported (`pan:1: ...`), with the name of the ltl formula checked, if any
+    pub violation: Option<Violation>,
+    /// the search was not completed (`Warning: Search not completed`)
+    pub incomplete: bool,
+    /// the search was truncated by the maximal depth (`-m`)
+    pub depth_truncated: bool,
+    /// pan ran out of memory (the system refused an allocation, or the memory limit of `-DMEMLIM` was reached): the
+    /// search stops at once, possibly without the warning of an incomplete search
+    pub out_of_memory: bool,
+}
+
+impl PanSummary {
+    pub fn parse(output: &str) -> Self {
+        trace!("PanSummary::parse({})", output);
+        let mut summary = Self::default();
+        let mut claim = None;
+        for line in output.lines() {
+            if let Some((_, errors)) =
+                regex_captures!(r"depth reached \d+, errors: (\d+)\s*$", line)
+            {
+                summary.errors = errors.parse().ok();
+            } else if let Some(name) = line.trim().strip_prefix("pan: ltl formula ") {
+                claim = Some(name.trim().to_string());
+            } else if summary.violation.is_none() {
+                summary.violation = parse_violation(line);
+            }
+            summary.incomplete |= line.starts_with("Warning: Search not completed");
+            summary.depth_truncated |= line.contains("max search depth too small");
+            summary.out_of_memory |= line.contains("out of memory")
+                || line.contains("max memory reached")
+                || line.contains("reached -DMEMLIM bound");
+        }
+        if let Some(Violation::AcceptanceCycle(name) | Violation::Claim(name)) =
+            &mut summary.violation
+        {
+            *name = claim;
+        }
+        summary
+    }
+
+    /// outcome of the run, unless it found a violation (see [is_fail]).
+    pub fn outcome(&self) -> SpinOutcome {
+        if self.out_of_memory {
+            SpinOutcome::OutOfMemory
+        } else if self.incomplete {
+            SpinOutcome::SearchIncomplete
+        } else {
+            SpinOutcome::Pass
+        }
+    }
+}
+
+/// property violated by a failing verification, as reported by pan (see [violation_from_output]).
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Violation {
+    /// assertion, with its expression
+    Assertion(String),
+    /// acceptance cycle of the never claim (e.g., of the ltl formula `gathering`), with its name if known
+    AcceptanceCycle(Option<String>),
+    /// end of the never claim reached (a safety property), with its name if known
+    Claim(Option<String>),
+    NonProgressCycle,
+    InvalidEndState,
+}
+
+impl std::fmt::Display for Violation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let claim = |name: &Option<String>| match name {
+            Some(name) => format!(" of claim {name}"),
+            None => String::new(),
+        };
+        match self {
+            Violation::Assertion(expr) => write!(f, "assertion violated: {expr}"),
+            Violation::AcceptanceCycle(name) => write!(f, "acceptance cycle{}", claim(name)),
+            Violation::Claim(name) => write!(f, "claim violated{}", claim(name)),
+            Violation::NonProgressCycle => write!(f, "non-progress cycle"),
+            Violation::InvalidEndState => write!(f, "invalid end state"),
+        }
+    }
+}
+
+/// first violation reported in the output of pan (see [PanSummary]).
+pub fn violation_from_output(output: &str) -> Option<Violation> {
+    PanSummary::parse(output).violation
+}
+
+/// violation reported by a line of the output of pan (`pan:1: ...`), without the name of its claim.
+fn parse_violation(line: &str) -> Option<Violation> {
+    let (_, message) = regex_captures!(r"^pan:\d+: (.*?)\s*(?:\(at depth \d+\))?\s*$", line)?;
+    Some(
+        if let Some(expr) = message.strip_prefix("assertion violated") {
+            Violation::Assertion(expr.trim().to_string())
+        } else if message.starts_with("acceptance cycle") {
+            Violation::AcceptanceCycle(None)
+        } else if message.starts_with("claim violated") {
+            Violation::Claim(None)
+        } else if message.starts_with("non-progress cycle") {
+            Violation::NonProgressCycle
+        } else if message.starts_with("invalid end state") {
+            Violation::InvalidEndState
+        } else {
+            return None;
+        },
+    )
+}
+
+/// property violated by the last verification in the directory, from the saved output of pan (see [read_pan_output]).
+pub fn read_violation(dir: &Path) -> Result<Option<Violation>> {
+    Ok(read_pan_output(dir)?
+        .as_deref()
+        .and_then(violation_from_output))
+}
+
+/// name of the README of a bundle (see [write_bundle]).
+pub const BUNDLE_README: &str = "README.md";
+
+/// name of the script of a bundle running the verification and printing its outcome (see [write_bundle]).
+pub const BUNDLE_SCRIPT: &str = "verify.sh";
+
+/// writes a self-contained bundle of the verification of an algorithm (given by its Promela code) in the directory
+/// (created if needed): the Promela files of the model, `Algorithms.pml`, a README of the exact commands of spin,
+/// clang and pan run by this tool with these options, so that the verification can be reproduced without it, and a
+/// script running them and printing the outcome (`PASS`, `fail` or `Incomplete`, as in the reports).
+pub fn write_bundle(
+    dir: &Path,
+    title: &str,
+    promela: &str,
+    options: ModelRunOptions,
+    build: &PanBuildOptions,
+) -> Result<()> {
+    debug!("write_bundle({:?}, {title})", dir);
+    std::fs::create_dir_all(dir)
+        .with_context(|| format!("failed to create bundle directory {}", dir.display()))?;
+    prepare_promela_code(dir)?;
+    promela::install_algorithm_from_code(dir, promela)?;
+
+    let spin = format!("spin {}", full_spin_args(options).join(" "));
+    let clang = format!("clang {}", clang_args(build).join(" "));
+    let safety = PanBuildOptions::pan_args_with_depth(Fairness::safety_pan_args(), build.depth());
+    let safety = format!("./pan {}", safety.join(" "));
+    let pan = PanBuildOptions::pan_args_with_depth(options.fairness.pan_args(), build.depth());
+    let pan = format!("./pan {}", pan.join(" "));
+
+    let mut readme = format!("# Verification of {title}\n\n");
+    readme += &format!(
+        "Model: scheduler {}, {}, {} fairness (generated by synth-lights {}).\n\n",
+        options.scheduler,
+        options.variant_name(),
+        options.fairness,
+        env!("CARGO_PKG_VERSION")
+    );
+    readme += "Run in this directory, with spin and clang:\n\n";
+    readme += &format!("    {spin}\n    {clang}\n");
+    if build.safety_first {
+        readme += &format!("    {safety}\n\nIf the safety stage above reports `errors: 0`, check the liveness:\n\n");
+    }
+    readme += &format!("    {pan}\n");
+    readme += &format!(
+        "\nThe algorithm fails if pan reports `errors: N` with N > 0 at the end of its search, or writes the trail \
+        `{TRAIL_FILENAME}` if it stops before (replay it with `spin -t -p {}`), and the verification is inconclusive if pan warns `Search not completed`; otherwise the algorithm passes.\n\
+        The script `{BUNDLE_SCRIPT}` runs these commands and prints the outcome.\n",
+        full_spin_args(options)[1..].join(" ")
+    );
+    std::fs::write(dir.join(BUNDLE_README), readme)
+        .with_context(|| format!("failed to write the README of bundle {}", dir.display()))?;
+
+    let mut script = format!(
+        "#!/bin/sh\n# verification of {title} (see {BUNDLE_README}): prints PASS, fail or Incomplete\n\
+        set -e\ncd \"$(dirname \"$0\")\"\nrm -f {TRAIL_FILENAME} {PAN_OUTPUT_FILENAME}\n\
+        # errors reported at the end of the search, or the trail if pan stopped before\n\
+        failed() {{\n    if grep -q ', errors: [0-9]' {PAN_OUTPUT_FILENAME}; then\n        \
+        grep -q ', errors: [1-9]' {PAN_OUTPUT_FILENAME}\n    else\n        [ -f {TRAIL_FILENAME} ]\n    fi\n}}\n\
+        {spin} > spin.out\n{clang}\n"
+    );
+    if build.safety_first {
+        script += &format!(
+            "{safety} > {PAN_OUTPUT_FILENAME} || true\nif ! failed; then\n    \
+            {pan} > {PAN_OUTPUT_FILENAME} || true\nfi\n"
+        );
     } else {
+        script += &format!("{pan} > {PAN_OUTPUT_FILENAME} || true\n");
+    }
+    script += &format!(
+        "if failed; then\n    echo {}\n\
+        elif grep -qE 'out of memory|max memory reached|reached -DMEMLIM bound' {PAN_OUTPUT_FILENAME}; then\n    echo {}\n\
+        elif grep -q '^Warning: Search not completed' {PAN_OUTPUT_FILENAME}; then\n    \
+        echo {}\nelse\n    echo {}\nfi\n",
+        SpinOutcome::Fail,
+        SpinOutcome::OutOfMemory,
+        SpinOutcome::SearchIncomplete,
         SpinOutcome::Pass
+    );
+    write_script(&dir.join(BUNDLE_SCRIPT), &script)
+}
+
+/// writes an executable shell script.
+pub fn write_script(path: &Path, script: &str) -> Result<()> {
+    std::fs::write(path, script)
+        .with_context(|| format!("failed to write script {}", path.display()))?;
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        std::fs::set_permissions(path, std::fs::Permissions::from_mode(0o755))?;
     }
+    Ok(())
 }
 
-fn run_spin<T>(dir: &Path, spin_args: T) -> Result<String>
+/// arguments of `spin -a` on the main promela file, with the given arguments.
+fn full_spin_args<T>(spin_args: T) -> Vec<String>
 where
     T: IntoIterator,
     T::Item: Into<String>,
@@ -221,43 +961,167 @@ where
         args.push(x.into());
     }
     args.push("MainGathering.pml".to_string());
+    args
+}
+
+fn run_spin<T>(dir: &Path, spin_args: T) -> Result<String>
+where
+    T: IntoIterator,
+    T::Item: Into<String>,
+{
+    let args = full_spin_args(spin_args);
 
     trace!("run_spin({:?}, {:?})", dir, args);
 
-    cmd("spin", args)
+    run_tool(dir, "spin", args)
+}
+
+/// failure of an external tool (spin, clang or pan), with its raw output.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ToolError {
+    pub tool: String,
+    /// exit code (none if terminated by a signal)
+    pub code: Option<i32>,
+    pub stdout: String,
+    pub stderr: String,
+}
+
+impl ToolError {
+    /// standard output followed by the standard error of the tool.
+    pub fn output(&self) -> String {
+        [self.stdout.trim_end(), self.stderr.trim_end()]
+            .into_iter()
+            .filter(|s| !s.is_empty())
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+}
+
+impl std::fmt::Display for ToolError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.code {
+            Some(code) => write!(f, "{} exited with code {code}", self.tool),
+            None => write!(f, "{} was terminated by a signal", self.tool),
+        }
+    }
+}
+
+impl std::error::Error for ToolError {}
+
+/// runs a tool in the directory and returns its standard output; fails with a [ToolError] holding its output if the
+/// tool exits with an error.
+fn run_tool<T>(dir: &Path, tool: &str, args: T) -> Result<String>
+where
+    T: IntoIterator,
+    T::Item: Into<std::ffi::OsString>,
+{
+    let handle = cmd(tool, args)
         .dir(dir)
-        .read()
-        .map_err(anyhow::Error::new)
-}
-
-fn run_clang(dir: &Path) -> Result<String> {
-    trace!("run_clang({:?})", dir);
-    cmd!(
-        "clang",
-        "-DMEMLIM=16384",
-        "-DXUSAFE",
-        "-DNOREDUCE",
-        "-O2",
-        "-w",
-        "-o",
-        "pan",
-        "pan.c"
-    )
-    .dir(dir)
-    .read()
-    .map_err(anyhow::Error::new)
+        .stdout_capture()
+        .stderr_capture()
+        .unchecked()
+        .start()
+        .map(Arc::new)
+        .with_context(|| format!("failed to run {tool}"))?;
+    // killed at the end of the grace period of an interruption
+    let _tracked = interrupt::track(handle.clone());
+    let output = handle
+        .wait()
+        .with_context(|| format!("failed to run {tool}"))?;
+    let stdout = String::from_utf8_lossy(&output.stdout).into_owned();
+    if !output.status.success() {
+        let name = Path::new(tool)
+            .file_name()
+            .map_or(tool.into(), |n| n.to_string_lossy());
+        return Err(ToolError {
+            tool: name.into_owned(),
+            code: output.status.code(),
+            stdout,
+            stderr: String::from_utf8_lossy(&output.stderr).into_owned(),
+        }
+        .into());
+    }
+    Ok(stdout)
+}
+
+fn clang_args(build: &PanBuildOptions) -> Vec<String> {
+    let mut args = build.clang_defines();
+    args.extend(["-O2", "-w", "-o", "pan", "pan.c"].map(String::from));
+    args
+}
+
+/// hash of the sources of pan generated by spin in the directory, and of the arguments of clang.
+fn pan_sources_hash(dir: &Path, clang_args: &[String]) -> Result<u64> {
+    let mut hasher = DefaultHasher::new();
+    clang_args.hash(&mut hasher);
+    for name in PAN_SOURCES {
+        let path = dir.join(name);
+        if path.exists() {
+            name.hash(&mut hasher);
+            std::fs::read(path)?.hash(&mut hasher);
+        }
+    }
+    Ok(hasher.finish())
+}
+
+/// builds pan with clang, unless the pan of the directory was built from the same sources with the same arguments
+/// (e.g., when an algorithm is verified again with the same options in the same enclosure).
+/// Returns whether clang was run.
+fn build_pan(dir: &Path, build: &PanBuildOptions) -> Result<bool> {
+    let args = clang_args(build);
+    let hash = pan_sources_hash(dir, &args)?.to_string();
+    let hash_file = dir.join(PAN_HASH_FILENAME);
+    if dir.join("pan").exists()
+        && std::fs::read_to_string(&hash_file).is_ok_and(|previous| previous == hash)
+    {
+        trace!("build_pan({:?}): pan is up to date", dir);
+        return Ok(false);
+    }
+    // a failed build must not be taken for the previous one
+    if hash_file.exists() {
+        std::fs::remove_file(&hash_file)?;
+    }
+    run_clang(dir, args)?;
+    std::fs::write(hash_file, hash)?;
+    Ok(true)
+}
+
+fn run_clang(dir: &Path, args: Vec<String>) -> Result<String> {
+    trace!("run_clang({:?}, {:?})", dir, args);
+    run_tool(dir, "clang", args)
+}
+
+fn run_pan(dir: &Path, fairness: Fairness, depth: usize) -> Result<String> {
+    trace!("run_pan({:?}, {fairness}, {depth})", dir);
+    let args = PanBuildOptions::pan_args_with_depth(fairness.pan_args(), depth);
+    run_pan_tool(dir, args)
+}
+
+/// safety stage of the verification (see [PanBuildOptions::safety_first]), with the same pan verifier.
+fn run_pan_safety(dir: &Path, depth: usize) -> Result<String> {
+    trace!("run_pan_safety({:?}, {depth})", dir);
+    let args = PanBuildOptions::pan_args_with_depth(Fairness::safety_pan_args(), depth);
+    run_pan_tool(dir, args)
 }
 
-fn run_pan(dir: &Path) -> Result<String> {
-    trace!("run_pan({:?})", dir);
+/// runs pan with the arguments; pan exits with an error when it runs out of memory, but its output is returned as
+/// for a complete run, so that the outcome is [SpinOutcome::OutOfMemory] rather than an error.
+fn run_pan_tool(dir: &Path, args: Vec<String>) -> Result<String> {
+    match run_tool(dir, &pan_path(dir)?, args) {
+        Err(e) => match e.downcast_ref::<ToolError>() {
+            Some(error) if PanSummary::parse(&error.output()).out_of_memory => Ok(error.output()),
+            _ => Err(e),
+        },
+        result => result,
+    }
+}
+
+fn pan_path(dir: &Path) -> Result<String> {
     let full_pan = dir.join("pan");
-    let full_pan = full_pan
+    full_pan
         .to_str()
-        .ok_or_else(|| anyhow::Error::msg("Cannot convert path to str"))?;
-    cmd!(full_pan, "-m100000", "-a", "-f", "-E", "-n", "gathering")
-        .dir(dir)
-        .read()
-        .map_err(anyhow::Error::new)
+        .map(str::to_string)
+        .ok_or_else(|| anyhow::Error::msg("Cannot convert path to str"))
 }
 
 mod ramdisk {
@@ -347,11 +1211,44 @@ mod ramdisk {
             .run()
     }
 
+    /// path at which the ramdisk with the given volume name is mounted.
+    pub fn volume_path(volume: &str) -> PathBuf {
+        #[cfg(target_os = "macos")]
+        {
+            ["/Volumes", volume].into_iter().collect()
+        }
+        #[cfg(target_os = "linux")]
+        {
+            ["/", "mnt", "tmp", volume].iter().collect()
+        }
+        #[cfg(not(any(target_os = "linux", target_os = "macos")))]
+        {
+            PathBuf::from(volume)
+        }
+    }
+
+    /// whether a ramdisk is mounted at the path (on Linux, a file system mounted there according to `/proc/mounts`).
+    pub fn is_mounted(path: &Path) -> bool {
+        #[cfg(target_os = "linux")]
+        {
+            std::fs::read_to_string("/proc/mounts").is_ok_and(|mounts| {
+                mounts
+                    .lines()
+                    .filter_map(|line| line.split_whitespace().nth(1))
+                    .any(|mount_point| Path::new(mount_point) == path)
+            })
+        }
+        #[cfg(not(target_os = "linux"))]
+        {
+            path.is_dir()
+        }
+    }
+
     #[allow(unused_variables)]
     pub fn create_ramdisk(size_mb: u16, volume: &str) -> std::io::Result<(String, PathBuf)> {
         #[cfg(target_os = "macos")]
         {
-            let path: PathBuf = ["/Volumes", volume].into_iter().collect();
+            let path = volume_path(volume);
 
             if size_mb < 2 {
                 return Err(io::Error::new(
@@ -389,7 +1286,14 @@ mod ramdisk {
         }
         #[cfg(target_os = "linux")]
         {
-            let path: PathBuf = ["/", "mnt", "tmp", volume].iter().collect();
+            let path = volume_path(volume);
+            // mounting again would hide the files of the mounted ramdisk
+            if is_mounted(&path) {
+                return Err(io::Error::new(
+                    ErrorKind::AlreadyExists,
+                    format!("Volume already mounted: {:?}", path),
+                ));
+            }
             // create the enclosure directory
             create_mount_point(&path)?;
             mount_filesystem(&path)?;
@@ -461,6 +1365,47 @@ mod tests {
     use super::*;
     use crate::promela;
 
+    #[test]
+    fn test_write_bundle() {
+        let dir = std::env::temp_dir().join(format!("synth-lights-bundle-{}", std::process::id()));
+        let algo = Algorithm::try_parse_v2("E3L:0_1_2__S2_H0_O1").unwrap();
+        let options = ModelRunOptions {
+            rigid: true,
+            ..ModelRunOptions::new(crate::common::Scheduler::SSYNC)
+        };
+        let build = PanBuildOptions {
+            safety_first: true,
+            ..Default::default()
+        };
+        write_bundle(
+            &dir,
+            &algo.as_code_v2(),
+            &promela::generate_promela(&algo),
+            options,
+            &build,
+        )
+        .unwrap();
+        for (name, _) in promela::PML_FILES {
+            assert!(dir.join(name).exists(), "{name}");
+        }
+        assert!(dir.join(promela::ALGORITHM_FILE).exists());
+        let readme = std::fs::read_to_string(dir.join(BUNDLE_README)).unwrap();
+        assert!(readme.starts_with("# Verification of E3L:0_1_2__S2_H0_O1\n"));
+        assert!(readme.contains(
+            "    spin -a -DALGO=SYNTH -DSCHEDULER=SSYNC -DMOVEMENT=RIGID MainGathering.pml\n"
+        ));
+        assert!(
+            readme.contains("    clang -DMEMLIM=16384 -DXUSAFE -DNOREDUCE -O2 -w -o pan pan.c\n")
+        );
+        assert!(readme.contains("    ./pan -m100000 -E -n gathering\n"));
+        assert!(readme.contains("    ./pan -m100000 -a -f -E -n gathering\n"));
+        let script = std::fs::read_to_string(dir.join(BUNDLE_SCRIPT)).unwrap();
+        assert!(script.starts_with("#!/bin/sh\n"));
+        assert!(script.contains("\nclang -DMEMLIM=16384 -DXUSAFE -DNOREDUCE -O2 -w -o pan pan.c\n"));
+        assert!(script.contains("\nif failed; then\n"));
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
     #[test]
     fn test_enclosure() {
         const TEST_VOLUME: &str = "TestRamDisk_enclosure";
@@ -480,4 +1425,249 @@ mod tests {
         eprintln!("workdir: {:?}", workdir);
         close_workdir(workdir).unwrap();
     }
+
+    #[test]
+    fn test_temp_workdir() {
+        let workdir = create_temp_workdir().unwrap();
+        let path = workdir.path().to_path_buf();
+        let enclosure = create_enclosure(&path).unwrap();
+        assert!(enclosure.join("MainGathering.pml").is_file());
+        close_workdir(workdir).unwrap();
+        assert!(!path.exists());
+    }
+
+    #[test]
+    fn test_workdir_guard() {
+        let workdir = WorkdirGuard::new(create_temp_workdir().unwrap());
+        let path = workdir.path().to_path_buf();
+        drop(workdir);
+        assert!(!path.exists());
+    }
+
+    #[test]
+    fn test_shm_workdir() {
+        let args = WorkdirArgs {
+            shm: true,
+            ..Default::default()
+        };
+        if !Path::new(SHM_DIR).is_dir() && std::env::var_os(RUNTIME_DIR_VAR).is_none() {
+            assert!(args.create().is_err());
+            return;
+        }
+        let workdir = args.create().unwrap();
+        let path = workdir.path().to_path_buf();
+        assert!(path.is_dir() && matches!(workdir, Workdir::Temp(_)));
+        close_workdir(workdir).unwrap();
+        assert!(!path.exists());
+    }
+
+    #[test]
+    fn test_clean_enclosure() {
+        let workdir = create_temp_workdir().unwrap();
+        let enclosure = create_enclosure(workdir.path()).unwrap();
+        std::fs::write(enclosure.join("pan.c"), "").unwrap();
+        std::fs::write(enclosure.join(TRAIL_FILENAME), "").unwrap();
+        clean_enclosure(&enclosure).unwrap();
+        assert!(!enclosure.join("pan.c").exists() && !enclosure.join(TRAIL_FILENAME).exists());
+        assert!(enclosure.join("MainGathering.pml").is_file());
+        assert!(available_space_mb(workdir.path()).is_some());
+        close_workdir(workdir).unwrap();
+    }
+
+    #[test]
+    fn test_violation() {
+        let acceptance =
+            "warning: for p.o. reduction to be valid the never claim must be stutter-invariant\n\
+            pan: ltl formula gathering\n\
+            pan:1: acceptance cycle (at depth 38)\n\
+            pan: wrote MainGathering.pml.trail\n";
+        assert_eq!(
+            violation_from_output(acceptance),
+            Some(Violation::AcceptanceCycle(Some("gathering".into())))
+        );
+        assert_eq!(
+            violation_from_output(acceptance).unwrap().to_string(),
+            "acceptance cycle of claim gathering"
+        );
+        let assertion = "pan:1: assertion violated (robot[me].pending!=0) (at depth 12)\n";
+        assert_eq!(
+            violation_from_output(assertion),
+            Some(Violation::Assertion("(robot[me].pending!=0)".into()))
+        );
+        assert_eq!(
+            violation_from_output("pan:1: claim violated! (at depth 3)"),
+            Some(Violation::Claim(None))
+        );
+        assert_eq!(
+            violation_from_output("State-vector 88 byte, depth reached 41, errors: 0"),
+            None
+        );
+    }
+
+    #[test]
+    fn test_save_trail() {
+        let workdir = create_temp_workdir().unwrap();
+        let enclosure = create_enclosure(workdir.path()).unwrap();
+        let archive = workdir.path().join("trails").join("0_1_2__S2_H0_O1");
+        assert!(!save_trail(&enclosure, &archive, "ASYNC_rigid-ss").unwrap());
+        assert!(!archive.exists());
+
+        let algo =
+            Algorithm::try_parse(crate::ModelKind::External, 3, true, "0_1_2__S2_H0_O1").unwrap();
+        promela::install_algorithm(&enclosure, &algo).unwrap();
+        std::fs::write(enclosure.join(TRAIL_FILENAME), "-4:-4:-4\n").unwrap();
+        assert!(save_trail(&enclosure, &archive, "ASYNC_rigid-ss").unwrap());
+        assert_eq!(
+            std::fs::read_to_string(archive.join("ASYNC_rigid-ss.trail")).unwrap(),
+            "-4:-4:-4\n"
+        );
+        assert!(archive.join(promela::ALGORITHM_FILE).is_file());
+        close_workdir(workdir).unwrap();
+    }
+
+    #[test]
+    fn test_summary_outcome() {
+        let complete = "State-vector 88 byte, depth reached 1234, errors: 0\n";
+        assert_eq!(PanSummary::parse(complete).outcome(), SpinOutcome::Pass);
+        let incomplete = "Warning: Search not completed\n\t+ Partial Order Reduction\n";
+        assert_eq!(
+            PanSummary::parse(incomplete).outcome(),
+            SpinOutcome::SearchIncomplete
+        );
+        let oom = "pan: reached -DMEMLIM bound\n\t1.07374e+09 bytes used\n";
+        assert!(PanSummary::parse(oom).out_of_memory);
+        assert_eq!(PanSummary::parse(oom).outcome(), SpinOutcome::OutOfMemory);
+        let oom = format!("{incomplete}pan: out of memory\n");
+        assert_eq!(PanSummary::parse(&oom).outcome(), SpinOutcome::OutOfMemory);
+        assert!(!PanSummary::parse(complete).out_of_memory);
+    }
+
+    #[test]
+    fn test_pan_summary() {
+        let output = "pan:1: assertion violated (x==1) (at depth 12)\npan: wrote model.trail\n\
+                      State-vector 88 byte, depth reached 12, errors: 1\n       13 states, stored\n";
+        assert_eq!(
+            PanSummary::parse(output),
+            PanSummary {
+                errors: Some(1),
+                violation: Some(Violation::Assertion("(x==1)".into())),
+                ..PanSummary::default()
+            }
+        );
+        assert_eq!(
+            PanSummary::parse("pan: out of memory\n"),
+            PanSummary {
+                out_of_memory: true,
+                ..PanSummary::default()
+            }
+        );
+
+        let dir = std::env::temp_dir().join(format!("synth-lights-summary-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let trail_file = dir.join(TRAIL_FILENAME);
+        let fail = PanSummary::parse(output);
+        let pass = PanSummary::parse("State-vector 88 byte, depth reached 12, errors: 0\n");
+        let oom = PanSummary::parse("pan: out of memory\n");
+        assert!(is_fail(&dir, &fail, &trail_file));
+        assert!(!is_fail(&dir, &pass, &trail_file));
+        std::fs::write(&trail_file, "").unwrap();
+        // the summary is trusted over a stale trail, the trail is used without summary
+        assert!(!is_fail(&dir, &pass, &trail_file));
+        assert!(is_fail(&dir, &oom, &trail_file));
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_tool_error() {
+        let dir = std::env::temp_dir();
+        assert_eq!(run_tool(&dir, "sh", ["-c", "echo ok"]).unwrap(), "ok\n");
+        let error = run_tool(&dir, "sh", ["-c", "echo out; echo err >&2; exit 3"]).unwrap_err();
+        let tool_error = error.downcast_ref::<ToolError>().unwrap();
+        assert_eq!(error.to_string(), "sh exited with code 3");
+        assert_eq!(tool_error.output(), "out\nerr");
+        assert!(run_tool(&dir, "/nonexistent/tool", ["-h"]).is_err());
+    }
+
+    #[test]
+    fn test_stale_enclosures() {
+        let workdir = create_temp_workdir().unwrap();
+        let path = workdir.path().to_path_buf();
+        create_enclosure(&path).unwrap();
+        create_enclosure(&path).unwrap();
+        std::fs::write(path.join("other.txt"), "kept").unwrap();
+        assert_eq!(remove_stale_enclosures(&path).unwrap(), 2);
+        assert_eq!(std::fs::read_dir(&path).unwrap().count(), 1);
+        // a temporary directory is not a mounted ramdisk
+        assert!(!ramdisk::is_mounted(&path));
+        close_workdir(workdir).unwrap();
+    }
+
+    #[test]
+    fn test_pan_build_options() {
+        assert_eq!(
+            PanBuildOptions::default().clang_defines(),
+            ["-DMEMLIM=16384", "-DXUSAFE", "-DNOREDUCE"]
+        );
+        let build = PanBuildOptions {
+            reduce: true,
+            collapse: true,
+            ma: Some(64),
+            memlim: Some(4096),
+            defines: vec!["VECTORSZ=2048".to_string()],
+            ..PanBuildOptions::default()
+        };
+        assert_eq!(
+            build.clang_defines(),
+            [
+                "-DMEMLIM=4096",
+                "-DXUSAFE",
+                "-DCOLLAPSE",
+                "-DMA=64",
+                "-DVECTORSZ=2048"
+            ]
+        );
+
+        assert_eq!(build.depth(), PanBuildOptions::DEFAULT_DEPTH);
+        assert_eq!(
+            PanBuildOptions::pan_args_with_depth(Fairness::Weak.pan_args(), build.depth()),
+            Fairness::Weak.pan_args()
+        );
+        assert_eq!(
+            PanBuildOptions::pan_args_with_depth(Fairness::safety_pan_args(), 400_000),
+            ["-m400000", "-E", "-n", "gathering"]
+        );
+        assert!(
+            PanSummary::parse("error: max search depth too small\nWarning: Search not completed\n")
+                .depth_truncated
+        );
+        assert!(!PanSummary::parse("Warning: Search not completed\n").depth_truncated);
+    }
+
+    #[test]
+    fn test_build_pan_cache() {
+        let dir = std::env::temp_dir().join(format!("pan-cache-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir(&dir).unwrap();
+        let build = PanBuildOptions::default();
+        std::fs::write(dir.join("pan.c"), "int main() { return 0; }").unwrap();
+        std::fs::write(dir.join("pan.h"), "#define X 1").unwrap();
+        let hash = pan_sources_hash(&dir, &clang_args(&build)).unwrap();
+
+        // pan built from the same sources and arguments: clang is not run
+        std::fs::write(dir.join("pan"), "").unwrap();
+        std::fs::write(dir.join(PAN_HASH_FILENAME), hash.to_string()).unwrap();
+        assert!(!build_pan(&dir, &build).unwrap());
+
+        // other sources or arguments
+        std::fs::write(dir.join("pan.h"), "#define X 2").unwrap();
+        assert_ne!(pan_sources_hash(&dir, &clang_args(&build)).unwrap(), hash);
+        std::fs::write(dir.join("pan.h"), "#define X 1").unwrap();
+        let reduce = PanBuildOptions {
+            reduce: true,
+            ..PanBuildOptions::default()
+        };
+        assert_ne!(pan_sources_hash(&dir, &clang_args(&reduce)).unwrap(), hash);
+        assert_eq!(pan_sources_hash(&dir, &clang_args(&build)).unwrap(), hash);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
 }
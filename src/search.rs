@@ -0,0 +1,208 @@
+//! Local search over the space of algorithms, for models where exhaustive search is infeasible.
+//!
+//! The search is a hill-climbing with random restarts: starting from a random viable algorithm,
+//! it repeatedly mutates one action and keeps the mutant if its score does not decrease.
+//! Candidates are scored by verifying them against a ladder of increasingly weaker schedulers
+//! (see [scheduler_ladder]), so that only the most promising ones are verified under the target scheduler.
+
+use anyhow::Result;
+use log::{debug, info};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use std::collections::BTreeMap;
+
+use crate::algorithm::{Action, Algorithm};
+use crate::common::*;
+use crate::generator;
+use crate::promela::ModelRunOptions;
+use crate::runner::{SpinBackend, SpinOutcome, VerificationBackend};
+use crate::ModelKind;
+
+/// parameters of the local search.
+#[derive(Clone, Copy, Debug)]
+pub struct SearchOptions {
+    /// number of independent climbs, each from a new random algorithm.
+    pub restarts: usize,
+    /// maximal number of mutations tried during each climb.
+    pub iterations: usize,
+    /// seed of the random number generator.
+    pub seed: u64,
+}
+
+/// returns the schedulers used for scoring candidates, from the strongest to the target scheduler.
+/// Each scheduler of the ladder is weaker than the previous ones, so that an algorithm failing
+/// at some step is known to fail at all further steps.
+pub fn scheduler_ladder(target: Scheduler) -> Vec<Scheduler> {
+    let mut ladder = [Scheduler::Centralized, Scheduler::SSYNC]
+        .into_iter()
+        .filter(|s| s < &target)
+        .collect::<Vec<_>>();
+    ladder.push(target);
+    ladder
+}
+
+/// checks the structural conditions that any candidate of the search must satisfy.
+pub fn is_viable(algo: &Algorithm) -> bool {
+    algo.all_gathered_are_stay()
+        && algo.all_colors_used_in_actions()
+        && algo.all_colors_used_in_non_gathered()
+        && algo.some_non_gathered_is_stay()
+        && algo.some_non_gathered_is_to_half()
+        && algo.some_non_gathered_is_to_other()
+}
+
+/// returns a copy of the algorithm where the action of one non-gathered rule, chosen at random,
+/// is replaced by a different random action.
+pub fn mutate<R: Rng>(algo: &Algorithm, rng: &mut R) -> Algorithm {
+    let candidates = algo
+        .rules()
+        .enumerate()
+        .filter(|(_, r)| !r.guard().is_gathered())
+        .map(|(i, _)| i)
+        .collect::<Vec<_>>();
+    let index = candidates[rng.random_range(0..candidates.len())];
+    let current = algo
+        .rules()
+        .nth(index)
+        .expect("index out of range")
+        .action();
+    loop {
+        let mv = Move::iter()
+            .nth(rng.random_range(0..3))
+            .expect("move out of range");
+        let action = Action(Color(rng.random_range(0..algo.num_colors())), mv);
+        if action != current {
+            return algo.with_action(index, action);
+        }
+    }
+}
+
+/// scores an algorithm as the number of consecutive schedulers of the ladder under which it passes.
+/// The algorithm solves gathering under the target scheduler iff the score equals the length of the ladder.
+pub fn spin_score(
+    spin: &SpinBackend,
+    algo: &Algorithm,
+    ladder: &[Scheduler],
+    options: ModelRunOptions,
+) -> Result<usize> {
+    let mut score = 0;
+    for &scheduler in ladder {
+        let options = ModelRunOptions {
+            scheduler,
+            ..options
+        };
+        if spin.verify(algo, &options)? != SpinOutcome::Pass {
+            break;
+        }
+        score += 1;
+    }
+    Ok(score)
+}
+
+/// runs the local search and returns all algorithms reaching `max_score` (without duplicates).
+///
+/// # Arguments
+///
+/// * `model`, `n_colors`, `class_l` - the model in which the search is conducted
+/// * `options`   - parameters of the search
+/// * `max_score` - score of a solution; a climb stops as soon as it is reached
+/// * `score`     - scoring function (e.g., [spin_score]); scores are memoized within the search
+///
+pub fn hill_climb<F>(
+    model: ModelKind,
+    n_colors: u8,
+    class_l: bool,
+    options: SearchOptions,
+    max_score: usize,
+    mut score: F,
+) -> Result<Vec<Algorithm>>
+where
+    F: FnMut(&Algorithm) -> Result<usize>,
+{
+    let mut rng = StdRng::seed_from_u64(options.seed);
+    let mut memo: BTreeMap<Algorithm, usize> = BTreeMap::new();
+    let mut eval = |algo: &Algorithm| -> Result<usize> {
+        if let Some(s) = memo.get(algo) {
+            return Ok(*s);
+        }
+        let s = score(algo)?;
+        memo.insert(algo.clone(), s);
+        Ok(s)
+    };
+
+    let mut found: Vec<Algorithm> = Vec::new();
+    for restart in 0..options.restarts {
+        let Some(mut current) =
+            generator::sample_algorithms_in_model(model, n_colors, class_l, rng.random())
+                .map(|(_, algo)| algo)
+                .find(is_viable)
+        else {
+            break;
+        };
+        let mut current_score = eval(&current)?;
+        for _ in 0..options.iterations {
+            if current_score >= max_score {
+                break;
+            }
+            let candidate = mutate(&current, &mut rng);
+            if !is_viable(&candidate) {
+                continue;
+            }
+            let candidate_score = eval(&candidate)?;
+            if candidate_score >= current_score {
+                debug!(
+                    "climb {restart}: {} -> {} ({})",
+                    current_score,
+                    candidate_score,
+                    candidate.as_code()
+                );
+                current = candidate;
+                current_score = candidate_score;
+            }
+        }
+        info!("climb {restart} ended with score {current_score}/{max_score}");
+        if current_score >= max_score && !found.contains(&current) {
+            found.push(current);
+        }
+    }
+    Ok(found)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_scheduler_ladder() {
+        assert_eq!(
+            scheduler_ladder(Scheduler::ASYNC),
+            vec![Scheduler::Centralized, Scheduler::SSYNC, Scheduler::ASYNC]
+        );
+        assert_eq!(
+            scheduler_ladder(Scheduler::SSYNC),
+            vec![Scheduler::Centralized, Scheduler::SSYNC]
+        );
+        assert_eq!(scheduler_ladder(Scheduler::FSYNC), vec![Scheduler::FSYNC]);
+    }
+
+    #[test]
+    fn test_hill_climb() {
+        // toy score: number of non-gathered rules moving to the other robot
+        let score = |a: &Algorithm| -> Result<usize> {
+            Ok(a.rules()
+                .filter(|r| !r.guard().is_gathered() && r.action().movement() == Move::ToOther)
+                .count())
+        };
+        let options = SearchOptions {
+            restarts: 3,
+            iterations: 500,
+            seed: 1,
+        };
+        let found = hill_climb(ModelKind::Full, 2, true, options, 2, score).unwrap();
+        assert!(!found.is_empty());
+        for algo in found {
+            assert!(is_viable(&algo));
+            assert!(score(&algo).unwrap() >= 2);
+        }
+    }
+}
@@ -0,0 +1,218 @@
+//! Corpus of reference algorithms with their expected outcomes (`synth-lights selftest`).
+//!
+//! Each [Reference] is an algorithm from the literature or a simple baseline, with the outcome expected under some
+//! schedulers and restrictions. Verifying them again after a change of the Promela model (or of the native model
+//! checker) detects silent changes of the semantics, which would otherwise only show as different synthesis results.
+//! The expected outcomes were obtained with spin (ASYNC) and with the native model checker, which agree on the others.
+
+use anyhow::Result;
+
+use crate::algorithm::Algorithm;
+use crate::common::Scheduler;
+use crate::promela::ModelRunOptions;
+use crate::runner::{SpinOutcome, VerificationBackend};
+
+/// outcome expected for a reference algorithm under a scheduler and restrictions (with weak fairness).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Expected {
+    pub scheduler: Scheduler,
+    pub rigid: bool,
+    pub quasi_ss: bool,
+    pub outcome: SpinOutcome,
+}
+
+impl Expected {
+    const fn new(scheduler: Scheduler, rigid: bool, quasi_ss: bool, outcome: SpinOutcome) -> Self {
+        Self {
+            scheduler,
+            rigid,
+            quasi_ss,
+            outcome,
+        }
+    }
+
+    pub fn options(&self) -> ModelRunOptions {
+        ModelRunOptions {
+            rigid: self.rigid,
+            quasi_ss: self.quasi_ss,
+            ..ModelRunOptions::new(self.scheduler)
+        }
+    }
+}
+
+/// reference algorithm, given by its code string in the self-describing format (see [Algorithm::as_code_v2]).
+#[derive(Clone, Copy, Debug)]
+pub struct Reference {
+    pub name: &'static str,
+    pub description: &'static str,
+    pub code: &'static str,
+    pub expected: &'static [Expected],
+}
+
+impl Reference {
+    pub fn algorithm(&self) -> Result<Algorithm> {
+        Algorithm::try_parse_v2(self.code)
+    }
+}
+
+const PASS: SpinOutcome = SpinOutcome::Pass;
+const FAIL: SpinOutcome = SpinOutcome::Fail;
+
+/// reference algorithms with their expected outcomes.
+pub const CORPUS: &[Reference] = &[
+    Reference {
+        name: "full-2-async",
+        description: "2-color full-lights algorithm solving gathering under ASYNC (Viglietta, ALGOSENSOR 2013)",
+        code: "F2:00s_01s_10s_11s_00d_01d_10d_11d__S0_S0_S1_S1_S1_S0_O1_H0",
+        expected: &[
+            Expected::new(Scheduler::ASYNC, false, false, PASS),
+            Expected::new(Scheduler::SSYNC, false, false, PASS),
+            Expected::new(Scheduler::FSYNC, false, false, PASS),
+            Expected::new(Scheduler::Centralized, false, false, PASS),
+        ],
+    },
+    Reference {
+        name: "external-4-rigid-qss",
+        description: "4-color class L external-lights algorithm for rigid moves from quasi self-stabilizing starts (Okumura et al.)",
+        code: "E4L:0_1_2_3__H1_S2_O3_S0",
+        expected: &[
+            Expected::new(Scheduler::SSYNC, true, true, PASS),
+            Expected::new(Scheduler::SSYNC, true, false, FAIL),
+            Expected::new(Scheduler::SSYNC, false, false, FAIL),
+            Expected::new(Scheduler::FSYNC, true, true, PASS),
+            Expected::new(Scheduler::FSYNC, false, false, FAIL),
+            Expected::new(Scheduler::Centralized, false, false, PASS),
+        ],
+    },
+    Reference {
+        name: "external-3-ssync",
+        description: "3-color class L external-lights algorithm solving gathering under SSYNC",
+        code: "E3L:0_1_2__S2_H0_O1",
+        expected: &[
+            Expected::new(Scheduler::SSYNC, false, false, PASS),
+            Expected::new(Scheduler::FSYNC, false, false, PASS),
+            Expected::new(Scheduler::Centralized, false, false, PASS),
+        ],
+    },
+    Reference {
+        name: "full-2L-ssync",
+        description: "2-color class L full-lights algorithm solving gathering under SSYNC",
+        code: "F2L:00_01_10_11__H1_S0_O0_H0",
+        expected: &[
+            Expected::new(Scheduler::SSYNC, false, false, PASS),
+            Expected::new(Scheduler::FSYNC, false, false, PASS),
+        ],
+    },
+    Reference {
+        name: "to-other",
+        description: "baseline: always move to the other robot (solves only the centralized scheduler)",
+        code: "F2:00s_01s_10s_11s_00d_01d_10d_11d__S0_S0_S0_S0_O0_O0_O0_O0",
+        expected: &[
+            Expected::new(Scheduler::Centralized, false, false, PASS),
+            Expected::new(Scheduler::FSYNC, false, false, FAIL),
+            Expected::new(Scheduler::SSYNC, false, false, FAIL),
+        ],
+    },
+    Reference {
+        name: "to-half",
+        description: "baseline: always move half-way (solves only the FSYNC scheduler)",
+        code: "F2:00s_01s_10s_11s_00d_01d_10d_11d__S0_S0_S0_S0_H0_H0_H0_H0",
+        expected: &[
+            Expected::new(Scheduler::Centralized, false, false, FAIL),
+            Expected::new(Scheduler::FSYNC, false, false, PASS),
+            Expected::new(Scheduler::SSYNC, false, false, FAIL),
+        ],
+    },
+];
+
+/// references whose name contains the pattern (all if none).
+pub fn select(pattern: Option<&str>) -> impl Iterator<Item = &'static Reference> + '_ {
+    CORPUS
+        .iter()
+        .filter(move |r| pattern.is_none_or(|p| r.name.contains(p)))
+}
+
+/// outcome of the verification of a reference algorithm against one expectation.
+#[derive(Debug)]
+pub struct Check {
+    pub reference: &'static Reference,
+    pub expected: Expected,
+    pub outcome: Result<SpinOutcome>,
+}
+
+impl Check {
+    pub fn is_ok(&self) -> bool {
+        matches!(self.outcome, Ok(outcome) if outcome == self.expected.outcome)
+    }
+}
+
+impl std::fmt::Display for Check {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let options = self.expected.options();
+        let label = format!("{} {}", self.expected.scheduler, options.variant_name());
+        let status = match self.outcome {
+            _ if self.is_ok() => "ok".to_string(),
+            Ok(outcome) => format!("MISMATCH (got {outcome})"),
+            Err(ref e) => format!("ERROR ({e:#})"),
+        };
+        write!(
+            f,
+            "{:<22} {:<26} expected {:<5} {status}",
+            self.reference.name,
+            label,
+            self.expected.outcome.to_string()
+        )
+    }
+}
+
+/// expectations of the references, one per verification.
+pub fn expectations(
+    references: impl IntoIterator<Item = &'static Reference>,
+) -> impl Iterator<Item = (&'static Reference, Expected)> {
+    references
+        .into_iter()
+        .flat_map(|r| r.expected.iter().map(move |&e| (r, e)))
+}
+
+/// verifies a reference algorithm with the backend against one of its expectations.
+pub fn check(
+    reference: &'static Reference,
+    expected: Expected,
+    backend: &dyn VerificationBackend,
+) -> Check {
+    Check {
+        reference,
+        expected,
+        outcome: reference
+            .algorithm()
+            .and_then(|algo| backend.verify(&algo, &expected.options())),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::checker::{self, NativeBackend};
+
+    #[test]
+    fn test_corpus() {
+        for reference in CORPUS {
+            let algo = reference.algorithm().unwrap();
+            assert_eq!(algo.as_code_v2(), reference.code);
+            assert!(!reference.expected.is_empty());
+        }
+        assert_eq!(select(Some("external")).count(), 2);
+        assert_eq!(select(None).count(), CORPUS.len());
+
+        // expectations that do not need spin
+        let checks = expectations(select(None))
+            .filter(|(_, e)| checker::supports(e.scheduler))
+            .map(|(r, e)| check(r, e, &NativeBackend))
+            .collect::<Vec<_>>();
+        assert!(checks.len() > 10);
+        for check in checks.iter() {
+            assert!(check.is_ok(), "{check}");
+        }
+        assert!(checks[0].to_string().ends_with(" ok"));
+    }
+}
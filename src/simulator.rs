@@ -0,0 +1,497 @@
+//! Lightweight simulator of the two-robot system, used to falsify algorithms before their verification with spin.
+//!
+//! The simulator replays the steps of the Promela model (`Robots.pml`) on the same abstract domain
+//! (distance [Distance::Far], [Distance::Near] or [Distance::Same], and colors of the robots), restricted to
+//! executions made of complete Look-Compute-Move rounds that the schedulers of the model allow ([Round]):
+//! both robots activated synchronously, or one robot activated alone, but never the same robot alone twice in a row,
+//! so that the fairness limits of the schedulers are respected. All such executions from all initial configurations
+//! are explored exhaustively (there are a few hundred configurations at most).
+//!
+//! An execution that cycles without gathering is also an execution of the Promela model, for which spin reports
+//! a failure: the simulator never rejects an algorithm that passes the verification. The converse does not hold
+//! (many failing executions interleave the phases of the robots), so the algorithms that are not falsified
+//! are still verified with spin.
+//!
+//! The simulator models the non-rigid moves with their original granularity. The executions it explores are still
+//! executions of the model with coarser non-rigid moves ([ModelRunOptions::delta_steps]), where the robots may start
+//! a single move away from each other, so that its counterexamples remain valid there.
+
+use std::collections::{HashMap, HashSet};
+use std::hash::Hash;
+use strum::Display;
+
+use crate::algorithm::{Action, Algorithm};
+use crate::common::{Color, Distance, Move, Scheduler};
+use crate::promela::ModelRunOptions;
+
+/// robots activated in a round, for a complete Look-Compute-Move cycle.
+#[derive(Clone, Copy, Debug, Display, PartialEq, Eq, Hash)]
+pub enum Round {
+    /// both robots look at the same time, then compute and move (robot A first)
+    Both,
+    /// robot A alone
+    A,
+    /// robot B alone
+    B,
+}
+
+impl Round {
+    /// rounds that the scheduler allows.
+    pub fn for_scheduler(scheduler: Scheduler) -> &'static [Round] {
+        match scheduler {
+            Scheduler::FSYNC => &[Round::Both],
+            // look and compute are atomic, so the robots never look at the same time
+            Scheduler::Centralized | Scheduler::ASYNC_LC_Strict => &[Round::A, Round::B],
+            _ => &[Round::Both, Round::A, Round::B],
+        }
+    }
+
+    /// robots activated in the round (0 for A, 1 for B).
+    pub fn robots(self) -> &'static [usize] {
+        match self {
+            Round::Both => &[0, 1],
+            Round::A => &[0],
+            Round::B => &[1],
+        }
+    }
+}
+
+/// configuration of the system between two rounds (no robot is computing or moving).
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Config {
+    pub position: Distance,
+    /// colors of robots A and B
+    pub colors: [Color; 2],
+}
+
+impl Config {
+    pub fn is_gathered(&self) -> bool {
+        self.position == Distance::Same
+    }
+}
+
+impl<T> From<(Config, T)> for Config {
+    fn from((config, _): (Config, T)) -> Self {
+        config
+    }
+}
+
+impl std::fmt::Display for Config {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} ({},{})",
+            self.position, self.colors[0], self.colors[1]
+        )
+    }
+}
+
+/// execution of the system that cycles forever without gathering.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Counterexample {
+    /// configurations from an initial one; the last one also occurs at index `cycle_start`
+    pub configs: Vec<Config>,
+    /// rounds between consecutive configurations
+    pub rounds: Vec<Round>,
+    pub cycle_start: usize,
+}
+
+impl std::fmt::Display for Counterexample {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.configs[0])?;
+        for (round, config) in self.rounds.iter().zip(&self.configs[1..]) {
+            write!(f, " -{round}-> {config}")?;
+        }
+        write!(f, " (cycle from {})", self.configs[self.cycle_start])
+    }
+}
+
+/// pending move of a robot (`move_t` in the Promela model); `Miss` is a move aimed at a moving robot.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Pending {
+    Stay,
+    ToHalf,
+    ToOther,
+    Miss,
+}
+
+impl From<Move> for Pending {
+    fn from(movement: Move) -> Self {
+        match movement {
+            Move::Stay => Pending::Stay,
+            Move::ToHalf => Pending::ToHalf,
+            Move::ToOther => Pending::ToOther,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+struct Robot {
+    color: Color,
+    is_moving: bool,
+    pending: Pending,
+    command: Action,
+}
+
+/// state of the system within a round, updated as in the steps of `Robots.pml`.
+struct System {
+    position: Distance,
+    robots: [Robot; 2],
+}
+
+impl System {
+    fn new(config: Config) -> Self {
+        let robot = |color| Robot {
+            color,
+            is_moving: false,
+            pending: Pending::Stay,
+            command: Action(color, Move::Stay),
+        };
+        Self {
+            position: config.position,
+            robots: config.colors.map(robot),
+        }
+    }
+
+    fn config(&self) -> Config {
+        Config {
+            position: self.position,
+            colors: self.robots.map(|r| r.color),
+        }
+    }
+
+    /// Look (`endLOOK`); `None` if no rule of the algorithm applies (the Promela model blocks).
+    fn look(&mut self, me: usize, algo: &Algorithm) -> Option<()> {
+        let other = &self.robots[1 - me];
+        let same_position = self.position == Distance::Same;
+        let command = algo.action_for(self.robots[me].color, other.color, same_position)?;
+        let other_is_moving = other.is_moving;
+        let robot = &mut self.robots[me];
+        robot.command = command;
+        robot.pending = if same_position && !other_is_moving {
+            Pending::Stay
+        } else if other_is_moving && !command.is_stationary() {
+            Pending::Miss
+        } else {
+            command.movement().into()
+        };
+        Some(())
+    }
+
+    /// Compute (`endBCOMPUTE` and `endECOMPUTE`).
+    fn compute(&mut self, me: usize) {
+        self.robots[me].color = self.robots[me].command.color();
+    }
+
+    /// beginning of the Move (`endBMOVE`).
+    fn begin_move(&mut self, me: usize) {
+        let robot = &mut self.robots[me];
+        robot.is_moving = robot.pending != Pending::Stay;
+    }
+
+    /// end of the Move (`endEMOVE`), which may alter the pending move of the other robot.
+    fn end_move(&mut self, me: usize) {
+        let mine = self.robots[me].pending;
+        let theirs = self.robots[1 - me].pending;
+        let missed = if theirs == Pending::Stay {
+            Pending::Stay
+        } else {
+            Pending::Miss
+        };
+        if self.robots[me].is_moving {
+            let (position, theirs) = match (self.position, mine) {
+                (Distance::Far, _) | (_, Pending::Miss) => (Distance::Near, missed),
+                (Distance::Same, Pending::ToOther) => (Distance::Same, theirs),
+                (_, Pending::ToOther) => (Distance::Same, missed),
+                (position, Pending::ToHalf) => match theirs {
+                    Pending::ToHalf => (position, Pending::ToOther),
+                    _ => (position, missed),
+                },
+                // not reachable: the model asserts that a moving robot has a pending move
+                (position, Pending::Stay) => (position, theirs),
+            };
+            self.position = position;
+            self.robots[1 - me].pending = theirs;
+        }
+        self.robots[me].is_moving = false;
+        self.robots[me].pending = Pending::Stay;
+    }
+}
+
+/// configuration after a round, or `None` if no rule of the algorithm applies to an activated robot.
+pub fn step(algo: &Algorithm, config: Config, round: Round) -> Option<Config> {
+    let mut system = System::new(config);
+    for &robot in round.robots() {
+        system.look(robot, algo)?;
+    }
+    for &robot in round.robots() {
+        system.compute(robot);
+        system.begin_move(robot);
+        system.end_move(robot);
+    }
+    Some(system.config())
+}
+
+/// indices of the rules of the algorithm applied by the robots activated in a round (all robots look at the
+/// configuration before any of them computes or moves).
+pub fn rules_used(algo: &Algorithm, config: Config, round: Round) -> Vec<usize> {
+    round
+        .robots()
+        .iter()
+        .filter_map(|&robot| {
+            algo.rule_for(
+                config.colors[robot],
+                config.colors[1 - robot],
+                config.is_gathered(),
+            )
+        })
+        .collect()
+}
+
+/// initial configurations of the model (see `MainGathering.pml`), including the gathered ones.
+pub fn initial_configs(num_colors: u8, options: ModelRunOptions) -> Vec<Config> {
+    let positions: &[Distance] = if options.rigid {
+        &[Distance::Near, Distance::Same]
+    } else {
+        &[Distance::Far, Distance::Near, Distance::Same]
+    };
+    let mut configs = Vec::new();
+    for &position in positions {
+        for a in Color::iter_ncols(num_colors) {
+            for b in Color::iter_ncols(num_colors) {
+                if (!options.quasi_ss || a == b) && options.initial.admits([a, b], position) {
+                    configs.push(Config {
+                        position,
+                        colors: [a, b],
+                    });
+                }
+            }
+        }
+    }
+    configs
+}
+
+/// searches an execution of the algorithm that never gathers, among the executions made of the rounds
+/// allowed by the scheduler of the options (see the module documentation).
+pub fn falsify(algo: &Algorithm, options: ModelRunOptions) -> Option<Counterexample> {
+    let roots = initial_configs(algo.num_colors(), options)
+        .into_iter()
+        .map(|config| (config, Round::Both));
+    falsify_from(algo, options.scheduler, roots)
+}
+
+/// searches an execution of the algorithm that never gathers as in [falsify], from the given configurations,
+/// each with the round that led to it ([Round::Both] if none).
+pub fn falsify_from(
+    algo: &Algorithm,
+    scheduler: Scheduler,
+    roots: impl IntoIterator<Item = (Config, Round)>,
+) -> Option<Counterexample> {
+    // configuration, and previous round (a robot is not activated alone twice in a row)
+    type Node = (Config, Round);
+
+    let rounds = Round::for_scheduler(scheduler);
+    find_cycle(roots, |(config, last): Node| {
+        rounds
+            .iter()
+            .filter(|&&round| round == Round::Both || round != last)
+            .filter_map(|&round| step(algo, config, round).map(|next| (round, (next, round))))
+            .collect()
+    })
+}
+
+/// searches depth-first a cycle among the non-gathered configurations reachable from the roots, where each node
+/// of the state space holds a configuration (and possibly the state of the scheduler). The successors of a node
+/// are given with the round leading to them; the gathered nodes are final (the scheduler stops).
+pub fn find_cycle<N, F>(roots: impl IntoIterator<Item = N>, successors: F) -> Option<Counterexample>
+where
+    N: Copy + Eq + Hash + Into<Config>,
+    F: Fn(N) -> Vec<(Round, N)>,
+{
+    let is_gathered = |node: N| node.into().is_gathered();
+    let successors = |node: N| {
+        let mut next_nodes = successors(node);
+        next_nodes.retain(|&(_, next)| !is_gathered(next));
+        next_nodes
+    };
+    let mut explored = HashSet::new();
+    for root in roots {
+        if is_gathered(root) || explored.contains(&root) {
+            continue;
+        }
+        let mut stack = vec![(root, successors(root))];
+        let mut on_stack = HashMap::from([(root, 0)]);
+        let mut path = Vec::new();
+        while let Some((node, next_nodes)) = stack.last_mut() {
+            match next_nodes.pop() {
+                Some((round, next)) => {
+                    if let Some(&cycle_start) = on_stack.get(&next) {
+                        path.push(round);
+                        let mut configs =
+                            stack.iter().map(|(n, _)| (*n).into()).collect::<Vec<_>>();
+                        configs.push(next.into());
+                        return Some(Counterexample {
+                            configs,
+                            rounds: path,
+                            cycle_start,
+                        });
+                    }
+                    if !explored.contains(&next) {
+                        on_stack.insert(next, stack.len());
+                        path.push(round);
+                        stack.push((next, successors(next)));
+                    }
+                }
+                None => {
+                    let node = *node;
+                    on_stack.remove(&node);
+                    explored.insert(node);
+                    stack.pop();
+                    path.pop();
+                }
+            }
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::algorithm::Guard;
+    use crate::common::IntoEnumIterator;
+    use crate::generator::tests::*;
+    use crate::promela::InitialConditions;
+
+    fn options(scheduler: Scheduler) -> ModelRunOptions {
+        ModelRunOptions::new(scheduler)
+    }
+
+    fn full_2(actions: [(u8, Move); 8]) -> Algorithm {
+        let actions = actions.map(|(c, m)| Action(Color(c), m));
+        Algorithm::new(2, &guards_for_full_lights_2_cols(), &actions)
+    }
+
+    #[test]
+    fn test_step() {
+        use Move::*;
+        let algo = full_2([
+            (0, Stay),
+            (0, Stay),
+            (0, Stay),
+            (0, Stay),
+            (0, ToHalf),
+            (0, ToHalf),
+            (0, ToHalf),
+            (0, ToHalf),
+        ]);
+        let near = Config {
+            position: Distance::Near,
+            colors: [Color(1), Color(0)],
+        };
+        let far = Config {
+            position: Distance::Far,
+            ..near
+        };
+        let after = |config, round| step(&algo, config, round).unwrap();
+        // both robots move to the half-way point and meet
+        assert_eq!(after(near, Round::Both).position, Distance::Same);
+        assert_eq!(after(near, Round::Both).colors, [Color(0), Color(0)]);
+        // a robot moving alone to the half-way point remains near the other
+        assert_eq!(after(near, Round::A).position, Distance::Near);
+        assert_eq!(after(near, Round::A).colors, [Color(0), Color(0)]);
+        assert_eq!(after(far, Round::B).position, Distance::Near);
+    }
+
+    #[test]
+    fn test_initial_configs() {
+        let options = options(Scheduler::SSYNC);
+        assert_eq!(initial_configs(2, options).len(), 3 * 4);
+        let restricted = |color_zero, distinct| {
+            let initial = InitialConditions {
+                color_zero,
+                distinct,
+            };
+            initial_configs(2, ModelRunOptions { initial, ..options })
+        };
+        assert_eq!(restricted(false, true).len(), 2 * 4);
+        let zero = restricted(true, true);
+        assert_eq!(zero.len(), 2);
+        assert!(zero
+            .iter()
+            .all(|c| c.colors == [Color(0); 2] && !c.is_gathered()));
+    }
+
+    #[test]
+    fn test_falsify() {
+        use Move::*;
+        // outcomes verified with spin (see the tests of the crate)
+        let fail_centralized = full_2([
+            (0, Stay),
+            (0, Stay),
+            (0, Stay),
+            (0, Stay),
+            (0, ToHalf),
+            (0, ToHalf),
+            (0, ToHalf),
+            (0, ToHalf),
+        ]);
+        let counterexample = falsify(&fail_centralized, options(Scheduler::Centralized)).unwrap();
+        assert!(counterexample.configs.iter().all(|c| !c.is_gathered()));
+        assert_eq!(
+            counterexample.configs.len(),
+            counterexample.rounds.len() + 1
+        );
+        assert_eq!(
+            counterexample.configs.last(),
+            counterexample.configs.get(counterexample.cycle_start)
+        );
+        // rounds of one robot alone alternate
+        assert!(counterexample.rounds.windows(2).all(|w| w[0] != w[1]));
+        // the robots meet half-way when both move
+        assert!(falsify(&fail_centralized, options(Scheduler::FSYNC)).is_none());
+
+        let pass_centralized = full_2([
+            (0, Stay),
+            (0, Stay),
+            (0, Stay),
+            (0, Stay),
+            (0, ToOther),
+            (0, ToOther),
+            (0, ToOther),
+            (0, ToOther),
+        ]);
+        assert!(falsify(&pass_centralized, options(Scheduler::Centralized)).is_none());
+
+        // passes under ASYNC, hence under all schedulers and with the weakest assumptions
+        let pass_async = full_2([
+            (0, Stay),
+            (0, Stay),
+            (1, Stay),
+            (1, Stay),
+            (1, Stay),
+            (0, Stay),
+            (1, ToOther),
+            (0, ToHalf),
+        ]);
+        for scheduler in Scheduler::iter() {
+            assert_eq!(
+                falsify(&pass_async, options(scheduler)),
+                None,
+                "{scheduler}"
+            );
+        }
+
+        // passes under SSYNC with rigid moves and quasi self-stabilization only
+        let guards = (0..4).map(Color).map(Guard::LExternal).collect::<Vec<_>>();
+        let actions =
+            [(1, ToHalf), (2, Stay), (3, ToOther), (0, Stay)].map(|(c, m)| Action(Color(c), m));
+        let pass_rigid_qss = Algorithm::new(4, &guards, &actions);
+        let rigid_qss = ModelRunOptions {
+            rigid: true,
+            quasi_ss: true,
+            ..options(Scheduler::SSYNC)
+        };
+        assert!(falsify(&pass_rigid_qss, rigid_qss).is_none());
+    }
+}
@@ -0,0 +1,127 @@
+//! Statistics over a set of algorithms, typically the algorithms passing the verification.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::io::Write;
+
+use crate::algorithm::{Algorithm, Rule};
+use crate::common::{IntoEnumIterator, Move};
+
+/// aggregate statistics over the rules of a set of algorithms.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct AlgorithmStatistics {
+    pub n_algos: usize,
+    /// total number of rules over all algorithms
+    pub n_rules: usize,
+    /// number of rules with each move
+    pub rules_by_move: BTreeMap<Move, usize>,
+    /// number of algorithms with at least one rule with each move
+    pub algos_by_move: BTreeMap<Move, usize>,
+    /// number of algorithms by number of distinct colors written by their actions
+    pub algos_by_colors_written: BTreeMap<usize, usize>,
+    /// number of rules where the robot changes its own color (only counted for rules observing the own color)
+    pub n_color_changes: usize,
+}
+
+impl AlgorithmStatistics {
+    pub fn new<'a>(algos: impl IntoIterator<Item = &'a Algorithm>) -> Self {
+        let mut stats = Self::default();
+        for m in Move::iter() {
+            stats.rules_by_move.insert(m, 0);
+            stats.algos_by_move.insert(m, 0);
+        }
+        for algo in algos {
+            stats.n_algos += 1;
+            let mut moves = BTreeSet::new();
+            let mut colors = BTreeSet::new();
+            for Rule(guard, action) in algo.rules() {
+                stats.n_rules += 1;
+                *stats.rules_by_move.entry(action.movement()).or_default() += 1;
+                moves.insert(action.movement());
+                colors.insert(action.color());
+                if guard.my_color().is_some_and(|c| c != action.color()) {
+                    stats.n_color_changes += 1;
+                }
+            }
+            for m in moves {
+                *stats.algos_by_move.entry(m).or_default() += 1;
+            }
+            *stats
+                .algos_by_colors_written
+                .entry(colors.len())
+                .or_default() += 1;
+        }
+        stats
+    }
+
+    /// fraction of the rules (over all algorithms) with a [Move::Stay] action.
+    pub fn stay_fraction(&self) -> f64 {
+        self.fraction_of_rules(Move::Stay)
+    }
+
+    fn fraction_of_rules(&self, m: Move) -> f64 {
+        if self.n_rules == 0 {
+            0.0
+        } else {
+            self.rules_by_move.get(&m).copied().unwrap_or_default() as f64 / self.n_rules as f64
+        }
+    }
+
+    /// writes the statistics as markdown tables.
+    pub fn write(&self, output: &mut dyn Write) -> std::io::Result<()> {
+        writeln!(output, "| move | rules | % rules | algorithms |")?;
+        writeln!(output, "| ---- | ----- | ------- | ---------- |")?;
+        for (m, n) in self.rules_by_move.iter() {
+            writeln!(
+                output,
+                "| {} | {} | {:.1} | {} |",
+                m,
+                n,
+                100.0 * self.fraction_of_rules(*m),
+                self.algos_by_move.get(m).copied().unwrap_or_default()
+            )?;
+        }
+        writeln!(output)?;
+        writeln!(output, "| colors written | algorithms |")?;
+        writeln!(output, "| -------------- | ---------- |")?;
+        for (n_colors, n) in self.algos_by_colors_written.iter() {
+            writeln!(output, "| {} | {} |", n_colors, n)?;
+        }
+        writeln!(output)?;
+        writeln!(
+            output,
+            "Stay rules: {:.1}%, color changes: {} of {} rules",
+            100.0 * self.stay_fraction(),
+            self.n_color_changes,
+            self.n_rules
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ModelKind;
+
+    #[test]
+    fn test_statistics() {
+        let algos = ["0_1_2__S2_H0_O1", "0_1_2__S2_H1_O0", "0_1_2__S0_S0_O0"]
+            .iter()
+            .map(|code| Algorithm::try_parse(ModelKind::External, 3, true, code).unwrap())
+            .collect::<Vec<_>>();
+        let stats = AlgorithmStatistics::new(&algos);
+        assert_eq!(stats.n_algos, 3);
+        assert_eq!(stats.n_rules, 9);
+        assert_eq!(stats.rules_by_move[&Move::Stay], 4);
+        assert_eq!(stats.algos_by_move[&Move::ToHalf], 2);
+        assert_eq!(
+            stats.algos_by_colors_written,
+            BTreeMap::from([(1, 1), (3, 2)])
+        );
+        assert!((stats.stay_fraction() - 4.0 / 9.0).abs() < 1e-9);
+
+        let mut output = Vec::new();
+        stats.write(&mut output).unwrap();
+        let output = String::from_utf8(output).unwrap();
+        assert!(output.contains("| STAY | 4 | 44.4 | 3 |"));
+    }
+}
@@ -0,0 +1,507 @@
+//! JSON status page of a synthesis run served over HTTP, enabled with `--status-port`, and stream of progress
+//! records, enabled with `--progress-json`.
+//!
+//! The [StatusServer] is a [RunObserver] recording the progress of the run (current model, scheduler and phase,
+//! outcome counts, ETA) together with host information, and answering any HTTP request on the port with
+//! the current [Status] as JSON. It is meant for monitoring long runs remotely (e.g., `curl host:8080`).
+//! The [ProgressStream] records the same progress, but writes it periodically as JSON lines ([ProgressRecord]) to the
+//! standard error or to a file (e.g., a named pipe read by an orchestration script).
+
+use anyhow::{Context, Result};
+use serde::Serialize;
+use std::io::{Read, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use crate::algorithm::Algorithm;
+use crate::common::Scheduler;
+use crate::model::Model;
+use crate::observer::{Phase, RunObserver};
+use crate::runner::SpinOutcome;
+
+/// delay between two polls of the listening socket.
+const POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// default delay between two progress records.
+pub const PROGRESS_INTERVAL: Duration = Duration::from_secs(2);
+
+/// information on the host running the synthesis.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct HostInfo {
+    pub hostname: String,
+    pub os: String,
+    pub arch: String,
+    pub num_cpus: usize,
+    pub pid: u32,
+}
+
+impl HostInfo {
+    pub fn current() -> Self {
+        Self {
+            hostname: duct::cmd!("uname", "-n")
+                .read()
+                .unwrap_or("<undetermined>".to_string()),
+            os: std::env::consts::OS.to_string(),
+            arch: std::env::consts::ARCH.to_string(),
+            num_cpus: num_cpus::get(),
+            pid: std::process::id(),
+        }
+    }
+}
+
+/// progress of the run for the current model and scheduler, as served by the status page.
+#[derive(Clone, Debug, Default, PartialEq, Serialize)]
+pub struct Status {
+    /// current model and scheduler (none before the run starts)
+    pub model: Option<String>,
+    pub scheduler: Option<String>,
+    pub phase: Option<String>,
+    /// number of (model, scheduler) runs completed, e.g., in a sweep
+    pub runs_done: usize,
+    pub n_generated: usize,
+    /// number of algorithms verified (including errors)
+    pub n_done: usize,
+    pub n_pass: usize,
+    pub n_fail: usize,
+    pub n_incomplete: usize,
+    pub n_errors: usize,
+    /// seconds since the start of the run for the current model and scheduler
+    pub elapsed_secs: f64,
+    /// algorithms verified per second since the start of the verification (only during the verification)
+    pub rate: Option<f64>,
+    /// estimated seconds until the end of the verification (only during the verification)
+    pub eta_secs: Option<f64>,
+}
+
+/// state shared with the serving thread.
+#[derive(Debug)]
+struct Shared {
+    status: Status,
+    host: HostInfo,
+    run_start: Instant,
+    verify_start: Option<Instant>,
+}
+
+impl Shared {
+    /// status with the times computed now.
+    fn snapshot(&self) -> Status {
+        let mut status = self.status.clone();
+        status.elapsed_secs = self.run_start.elapsed().as_secs_f64();
+        status.rate = self
+            .verify_start
+            .filter(|_| status.n_done > 0)
+            .map(|start| status.n_done as f64 / start.elapsed().as_secs_f64().max(1e-9));
+        status.eta_secs = status
+            .rate
+            .map(|rate| status.n_generated.saturating_sub(status.n_done) as f64 / rate);
+        status
+    }
+
+    fn to_json(&self) -> String {
+        serde_json::json!({
+            "status": self.snapshot(),
+            "host": self.host,
+        })
+        .to_string()
+    }
+}
+
+/// state shared between an observer and its thread, updated by the callbacks of the run.
+#[derive(Clone)]
+struct Tracker(Arc<Mutex<Shared>>);
+
+impl Tracker {
+    fn new() -> Self {
+        Self(Arc::new(Mutex::new(Shared {
+            status: Status::default(),
+            host: HostInfo::current(),
+            run_start: Instant::now(),
+            verify_start: None,
+        })))
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, Shared> {
+        self.0.lock().expect("status state")
+    }
+}
+
+pub struct StatusServer {
+    shared: Tracker,
+    local_addr: SocketAddr,
+    stop: Arc<AtomicBool>,
+}
+
+impl StatusServer {
+    /// starts serving the status page on the given port of all interfaces (port 0 picks a free port).
+    pub fn bind(port: u16) -> Result<Self> {
+        let listener = TcpListener::bind(("0.0.0.0", port))
+            .with_context(|| format!("failed to listen on status port {port}"))?;
+        listener.set_nonblocking(true)?;
+        let local_addr = listener.local_addr()?;
+        log::info!("Serving status on {local_addr}");
+
+        let shared = Tracker::new();
+        let stop = Arc::new(AtomicBool::new(false));
+        {
+            let shared = shared.clone();
+            let stop = Arc::clone(&stop);
+            std::thread::spawn(move || serve(listener, &shared, &stop));
+        }
+        Ok(Self {
+            shared,
+            local_addr,
+            stop,
+        })
+    }
+
+    /// address on which the status page is served.
+    pub fn local_addr(&self) -> SocketAddr {
+        self.local_addr
+    }
+
+    /// current status.
+    pub fn status(&self) -> Status {
+        self.shared.lock().snapshot()
+    }
+}
+
+impl Drop for StatusServer {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+    }
+}
+
+/// answers the requests until stopped.
+fn serve(listener: TcpListener, shared: &Tracker, stop: &AtomicBool) {
+    while !stop.load(Ordering::Relaxed) {
+        match listener.accept() {
+            Ok((stream, _)) => {
+                let body = shared.lock().to_json();
+                if let Err(e) = respond(stream, &body) {
+                    log::warn!("status request failed: {e}");
+                }
+            }
+            Err(e) if e.kind() == std::io::ErrorKind::WouldBlock => {
+                std::thread::sleep(POLL_INTERVAL)
+            }
+            Err(e) => log::warn!("status connection failed: {e}"),
+        }
+    }
+}
+
+/// reads the request (whatever it is) and writes the JSON body as response.
+fn respond(mut stream: TcpStream, body: &str) -> std::io::Result<()> {
+    stream.set_nonblocking(false)?;
+    stream.set_read_timeout(Some(Duration::from_secs(1)))?;
+    let mut request = [0u8; 1024];
+    let _ = stream.read(&mut request)?;
+    write!(
+        stream,
+        "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+        body.len()
+    )?;
+    stream.flush()
+}
+
+impl RunObserver for Tracker {
+    fn on_phase_change(&self, model: Model, scheduler: Scheduler, phase: Phase) {
+        {
+            let shared = &mut *self.lock();
+            if phase == Phase::Prepare {
+                shared.status = Status {
+                    runs_done: shared.status.runs_done,
+                    ..Status::default()
+                };
+                shared.run_start = Instant::now();
+                shared.verify_start = None;
+            }
+            if phase == Phase::Verify {
+                shared.verify_start = Some(Instant::now());
+            }
+            if phase == Phase::Done {
+                shared.status.runs_done += 1;
+            }
+            shared.status.model = Some(model.to_string());
+            shared.status.scheduler = Some(scheduler.to_string());
+            shared.status.phase = Some(phase.to_string());
+        }
+    }
+
+    fn on_generated(&self, _index: usize, _algo: &Algorithm) {
+        self.lock().status.n_generated += 1;
+    }
+
+    fn on_outcome(
+        &self,
+        _index: usize,
+        _algo: &Algorithm,
+        outcome: SpinOutcome,
+        _variants: &[SpinOutcome],
+    ) {
+        let status = &mut self.lock().status;
+        status.n_done += 1;
+        match outcome {
+            SpinOutcome::Pass => status.n_pass += 1,
+            SpinOutcome::Fail => status.n_fail += 1,
+            // inconclusive either way
+            SpinOutcome::OutOfMemory | SpinOutcome::SearchIncomplete => status.n_incomplete += 1,
+        }
+    }
+
+    fn on_error(&self, _index: usize, _algo: &Algorithm, _error: &anyhow::Error) {
+        let status = &mut self.lock().status;
+        status.n_done += 1;
+        status.n_errors += 1;
+    }
+}
+
+impl RunObserver for StatusServer {
+    fn on_phase_change(&self, model: Model, scheduler: Scheduler, phase: Phase) {
+        self.shared.on_phase_change(model, scheduler, phase);
+    }
+
+    fn on_generated(&self, index: usize, algo: &Algorithm) {
+        self.shared.on_generated(index, algo);
+    }
+
+    fn on_outcome(
+        &self,
+        index: usize,
+        algo: &Algorithm,
+        outcome: SpinOutcome,
+        variants: &[SpinOutcome],
+    ) {
+        self.shared.on_outcome(index, algo, outcome, variants);
+    }
+
+    fn on_error(&self, index: usize, algo: &Algorithm, error: &anyhow::Error) {
+        self.shared.on_error(index, algo, error);
+    }
+}
+
+/// progress record written by a [ProgressStream] (one JSON object per line).
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct ProgressRecord {
+    pub model: Option<String>,
+    pub scheduler: Option<String>,
+    pub phase: Option<String>,
+    /// number of algorithms verified (including errors)
+    pub done: usize,
+    /// number of algorithms to verify (generated so far)
+    pub total: usize,
+    pub pass: usize,
+    pub fail: usize,
+    pub incomplete: usize,
+    pub errors: usize,
+    pub elapsed_secs: f64,
+    /// algorithms verified per second
+    pub rate: Option<f64>,
+    pub eta_secs: Option<f64>,
+}
+
+impl From<Status> for ProgressRecord {
+    fn from(status: Status) -> Self {
+        Self {
+            model: status.model,
+            scheduler: status.scheduler,
+            phase: status.phase,
+            done: status.n_done,
+            total: status.n_generated,
+            pass: status.n_pass,
+            fail: status.n_fail,
+            incomplete: status.n_incomplete,
+            errors: status.n_errors,
+            elapsed_secs: status.elapsed_secs,
+            rate: status.rate,
+            eta_secs: status.eta_secs,
+        }
+    }
+}
+
+type SharedWriter = Arc<Mutex<Box<dyn Write + Send>>>;
+
+/// writes a progress record periodically, at each change of phase, and when dropped.
+pub struct ProgressStream {
+    shared: Tracker,
+    writer: SharedWriter,
+    stop: Arc<AtomicBool>,
+}
+
+impl ProgressStream {
+    /// progress records written to the standard error (target `-` or `stderr`) or to a file (e.g., a named pipe,
+    /// in which case this waits for a reader).
+    pub fn open(target: &str, interval: Duration) -> Result<Self> {
+        let writer: Box<dyn Write + Send> = match target {
+            "-" | "stderr" => Box::new(std::io::stderr()),
+            path => Box::new(
+                std::fs::OpenOptions::new()
+                    .write(true)
+                    .create(true)
+                    .truncate(true)
+                    .open(path)
+                    .with_context(|| format!("failed to open progress output {path}"))?,
+            ),
+        };
+        Ok(Self::new(writer, interval))
+    }
+
+    pub fn new(writer: Box<dyn Write + Send>, interval: Duration) -> Self {
+        let stream = Self {
+            shared: Tracker::new(),
+            writer: Arc::new(Mutex::new(writer)),
+            stop: Arc::new(AtomicBool::new(false)),
+        };
+        {
+            let (shared, writer, stop) = (
+                stream.shared.clone(),
+                Arc::clone(&stream.writer),
+                Arc::clone(&stream.stop),
+            );
+            std::thread::spawn(move || {
+                let mut last = Instant::now();
+                while !stop.load(Ordering::Relaxed) {
+                    std::thread::sleep(POLL_INTERVAL.min(interval));
+                    if last.elapsed() >= interval {
+                        write_record(&shared, &writer);
+                        last = Instant::now();
+                    }
+                }
+            });
+        }
+        stream
+    }
+
+    /// current progress.
+    pub fn record(&self) -> ProgressRecord {
+        self.shared.lock().snapshot().into()
+    }
+}
+
+/// writes the current progress (errors are only logged: the run goes on without progress records).
+fn write_record(shared: &Tracker, writer: &SharedWriter) {
+    let record = ProgressRecord::from(shared.lock().snapshot());
+    let mut writer = writer.lock().expect("progress output");
+    let result = serde_json::to_writer(&mut *writer, &record)
+        .map_err(std::io::Error::from)
+        .and_then(|_| writeln!(writer))
+        .and_then(|_| writer.flush());
+    if let Err(e) = result {
+        log::warn!("failed to write progress: {e}");
+    }
+}
+
+impl Drop for ProgressStream {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+        write_record(&self.shared, &self.writer);
+    }
+}
+
+impl RunObserver for ProgressStream {
+    fn on_phase_change(&self, model: Model, scheduler: Scheduler, phase: Phase) {
+        self.shared.on_phase_change(model, scheduler, phase);
+        write_record(&self.shared, &self.writer);
+    }
+
+    fn on_generated(&self, index: usize, algo: &Algorithm) {
+        self.shared.on_generated(index, algo);
+    }
+
+    fn on_outcome(
+        &self,
+        index: usize,
+        algo: &Algorithm,
+        outcome: SpinOutcome,
+        variants: &[SpinOutcome],
+    ) {
+        self.shared.on_outcome(index, algo, outcome, variants);
+    }
+
+    fn on_error(&self, index: usize, algo: &Algorithm, error: &anyhow::Error) {
+        self.shared.on_error(index, algo, error);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ModelKind;
+
+    #[test]
+    fn test_status_server() {
+        let server = StatusServer::bind(0).unwrap();
+        let model = Model::from((ModelKind::External, 3, true));
+        let algo = Algorithm::try_parse(ModelKind::External, 3, true, "0_1_2__S2_H0_O1").unwrap();
+
+        server.on_phase_change(model, Scheduler::SSYNC, Phase::Prepare);
+        server.on_generated(0, &algo);
+        server.on_generated(1, &algo);
+        server.on_phase_change(model, Scheduler::SSYNC, Phase::Verify);
+        server.on_outcome(0, &algo, SpinOutcome::Pass, &[SpinOutcome::Pass]);
+        let status = server.status();
+        assert_eq!(
+            (status.n_generated, status.n_done, status.n_pass),
+            (2, 1, 1)
+        );
+        assert_eq!(status.model.as_deref(), Some("E3L"));
+        assert!(status.eta_secs.is_some());
+
+        let port = server.local_addr().port();
+        let mut stream = TcpStream::connect(("127.0.0.1", port)).unwrap();
+        stream.write_all(b"GET / HTTP/1.1\r\n\r\n").unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).unwrap();
+        assert!(response.starts_with("HTTP/1.1 200 OK"));
+        let body = response.split("\r\n\r\n").nth(1).unwrap();
+        let json: serde_json::Value = serde_json::from_str(body).unwrap();
+        assert_eq!(json["status"]["n_pass"], 1);
+        assert_eq!(json["status"]["phase"], "Verify");
+        assert_eq!(json["host"]["num_cpus"], num_cpus::get());
+    }
+
+    #[test]
+    fn test_progress_stream() {
+        #[derive(Clone, Default)]
+        struct Buffer(Arc<Mutex<Vec<u8>>>);
+        impl Write for Buffer {
+            fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+                self.0.lock().unwrap().write(buf)
+            }
+            fn flush(&mut self) -> std::io::Result<()> {
+                Ok(())
+            }
+        }
+
+        let buffer = Buffer::default();
+        let stream = ProgressStream::new(Box::new(buffer.clone()), Duration::from_millis(10));
+        let model = Model::from((ModelKind::External, 3, true));
+        let algo = Algorithm::try_parse(ModelKind::External, 3, true, "0_1_2__S2_H0_O1").unwrap();
+        stream.on_phase_change(model, Scheduler::SSYNC, Phase::Prepare);
+        stream.on_generated(0, &algo);
+        stream.on_generated(1, &algo);
+        stream.on_phase_change(model, Scheduler::SSYNC, Phase::Verify);
+        stream.on_outcome(0, &algo, SpinOutcome::Fail, &[SpinOutcome::Fail]);
+        let record = stream.record();
+        assert_eq!((record.done, record.total, record.fail), (1, 2, 1));
+        assert!(record.rate.is_some() && record.eta_secs.is_some());
+        std::thread::sleep(Duration::from_millis(250));
+        drop(stream);
+
+        let text = String::from_utf8(buffer.0.lock().unwrap().clone()).unwrap();
+        let records = text
+            .lines()
+            .map(|l| serde_json::from_str::<serde_json::Value>(l).unwrap())
+            .collect::<Vec<_>>();
+        // one per change of phase, some periodic ones, and the last one
+        assert!(records.len() > 3);
+        assert_eq!(records[0]["phase"], "Prepare");
+        let last = records.last().unwrap();
+        assert_eq!(
+            (last["done"].as_u64(), last["total"].as_u64()),
+            (Some(1), Some(2))
+        );
+        assert_eq!(last["model"], "E3L");
+    }
+}
@@ -0,0 +1,3946 @@
+//! Library interface to the synthesis.
+//!
+//! A [SynthesisConfig] describes a run (models, schedulers, filters, execution, report output, working directory),
+//! and a [SynthesisRun] executes it and returns typed results ([SynthesisResult]), in addition to the textual report.
+//! The command-line interface ([crate::run]) is a thin wrapper building a configuration from its arguments.
+//!
+//! The phases of a run are also available separately ([prepare_workdirs], [generate_and_filter], [verify_all], [report]),
+//! for instance to verify a list of algorithms obtained otherwise.
+//!
+//! # Examples
+//!
+//! ```no_run
+//! # use synth_lights::synthesis::*;
+//! # use synth_lights::model::Model;
+//! # use synth_lights::common::Scheduler;
+//! # fn main() -> anyhow::Result<()> {
+//! let config = SynthesisConfig::new(Model::try_from("E3L")?)
+//!     .scheduler(Scheduler::SSYNC)
+//!     .output(ReportOutput::Discard);
+//! let result = SynthesisRun::new(config).execute()?;
+//! for (i, algo) in result.runs[0].pass.iter() {
+//!     println!("{i}: {}", algo.as_code());
+//! }
+//! # Ok(())
+//! # }
+//! ```
+
+use anyhow::{Context, Result};
+use convert_case::{Case, Casing};
+use log::info;
+use serde::{Deserialize, Serialize};
+use std::cell::Cell;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+use crate::algorithm::Algorithm;
+use crate::checker;
+use crate::classify;
+use crate::common::{IntoEnumIterator, Moves, Scheduler};
+use crate::depth::{self, DepthTuner};
+use crate::estimate::{self, Estimate};
+use crate::filter::{ExcludeLower, Filter, FilterSet, MatchCode, ViabilityFilter};
+use crate::generator;
+use crate::html;
+use crate::interrupt;
+use crate::jobs;
+use crate::logging;
+use crate::manifest::Manifest;
+use crate::metadata;
+use crate::model::Model;
+use crate::observer::{Phase, RunObserver};
+use crate::output::{OutputSink, SinkFormat, SinkSpec, SinkTarget};
+use crate::promela::{Fairness, InitialConditions, ModelRunOptions, DEFAULT_DELTA_STEPS};
+use crate::runner::{
+    self, PanBuildOptions, SpinOutcome, ToolError, VerificationBackend, Violation,
+};
+use crate::search::{self, SearchOptions};
+use crate::simulator;
+use crate::stats::AlgorithmStatistics;
+use crate::throttle::{self, MemoryLimits};
+use crate::Variants;
+
+/// how the verification of the algorithms is executed.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Parallelism {
+    /// one algorithm at a time, reporting outcomes as soon as they are obtained
+    Sequential,
+    /// in parallel over all available CPU cores
+    #[default]
+    Parallel,
+    /// in parallel over the given number of threads
+    Threads(usize),
+}
+
+/// order in which the viable algorithms are verified (the indices and the report are unchanged).
+#[derive(clap::ValueEnum, strum::Display, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Order {
+    /// order of the enumeration
+    #[default]
+    Index,
+    /// most promising algorithms first (see [classify::promise])
+    Promising,
+}
+
+impl Order {
+    /// sorts the items by their algorithms in the order (stable).
+    pub fn sort<T>(&self, items: &mut [T], algo: impl Fn(&T) -> &Algorithm) {
+        if *self == Order::Promising {
+            items.sort_by_cached_key(|item| std::cmp::Reverse(classify::promise(algo(item))));
+        }
+    }
+}
+
+/// order of the listing of the outcomes in the report, written once the verification is complete (instead of
+/// writing each outcome as soon as it is obtained). Errors are listed last.
+#[derive(clap::ValueEnum, strum::Display, Clone, Copy, Debug, PartialEq, Eq)]
+#[strum(serialize_all = "lowercase")]
+pub enum ReportSort {
+    /// index among the viable algorithms
+    Index,
+    /// PASS, then incomplete, then FAIL algorithms (by index)
+    Outcome,
+    /// slowest verifications first
+    Time,
+    /// code of the algorithms
+    Code,
+}
+
+impl ReportSort {
+    /// sorts the results in the order (stable).
+    pub fn sort(&self, results: &mut [&Result<Verified>]) {
+        use std::cmp::Reverse;
+        // errors last, in their original order
+        fn key<K>(res: &Result<Verified>, key: impl Fn(&Verified) -> K) -> (bool, Option<K>) {
+            (res.is_err(), res.as_ref().ok().map(key))
+        }
+        match self {
+            ReportSort::Index => results.sort_by_key(|res| key(res, |v| v.index)),
+            ReportSort::Outcome => {
+                results.sort_by_key(|res| key(res, |v| (Reverse(v.outcome), v.index)))
+            }
+            ReportSort::Time => results.sort_by_key(|res| key(res, |v| Reverse(v.duration))),
+            ReportSort::Code => results.sort_by_cached_key(|res| key(res, |v| v.algo.as_code())),
+        }
+    }
+}
+
+/// model checker verifying the algorithms.
+#[derive(clap::ValueEnum, strum::Display, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Backend {
+    /// spin (with clang) for all schedulers
+    #[default]
+    Spin,
+    /// native model checker for the centralized, FSYNC and SSYNC schedulers (see [checker]), spin for the others
+    Native,
+}
+
+impl Backend {
+    /// checks if spin is needed to verify the algorithms with the options: the native model checker relies on the
+    /// fairness limits of the schedulers (see [checker]), which subsume weak fairness and fair activation but not
+    /// their absence, and only models the non-rigid moves of the original model.
+    pub fn uses_spin(&self, options: ModelRunOptions) -> bool {
+        *self == Backend::Spin || !checker::supports_options(options)
+    }
+
+    /// verification backend running spin (if needed) in the given enclosure, with pan built with the given options.
+    pub fn verifier(&self, enclosure: &Path, build: &PanBuildOptions) -> BackendVerifier {
+        BackendVerifier {
+            backend: *self,
+            spin: runner::SpinBackend::new(enclosure).build_options(build.clone()),
+        }
+    }
+}
+
+/// verification backend selected by a [Backend], dispatching each verification to spin
+/// or to the native model checker according to the scheduler.
+#[derive(Clone, Debug)]
+pub struct BackendVerifier {
+    backend: Backend,
+    spin: runner::SpinBackend,
+}
+
+impl VerificationBackend for BackendVerifier {
+    fn verify(&self, algo: &Algorithm, options: &ModelRunOptions) -> Result<SpinOutcome> {
+        if self.backend.uses_spin(*options) {
+            self.spin.verify(algo, options)
+        } else {
+            checker::NativeBackend.verify(algo, options)
+        }
+    }
+}
+
+/// where the textual report of a run is written.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub enum ReportOutput {
+    /// standard output
+    #[default]
+    Stdout,
+    /// file (opened according to the [OutputMode]), echoed on the standard output
+    Tee(PathBuf),
+    /// file (opened according to the [OutputMode])
+    File(PathBuf),
+    /// no report (results are only returned)
+    Discard,
+    /// any combination of sinks, each with its own format (see [crate::output])
+    Sinks(Vec<SinkSpec>),
+}
+
+impl ReportOutput {
+    /// sinks equivalent to the output.
+    pub fn sinks(&self) -> Vec<SinkSpec> {
+        let file = |path: &PathBuf| SinkSpec::new(SinkTarget::File(path.clone()), SinkFormat::Text);
+        let stdout = SinkSpec::new(SinkTarget::Stdout, SinkFormat::Text);
+        match self {
+            ReportOutput::Stdout => vec![stdout],
+            ReportOutput::Tee(path) => vec![file(path), stdout],
+            ReportOutput::File(path) => vec![file(path)],
+            ReportOutput::Discard => vec![],
+            ReportOutput::Sinks(sinks) => sinks.clone(),
+        }
+    }
+
+    /// files written by the output.
+    pub fn files(&self) -> Vec<PathBuf> {
+        self.sinks()
+            .into_iter()
+            .filter_map(|sink| match sink.target {
+                SinkTarget::File(path) => Some(path),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// output to the additional sinks as well (unchanged if there are none).
+    pub fn with_sinks(self, sinks: impl IntoIterator<Item = SinkSpec>) -> Self {
+        let mut sinks = sinks.into_iter().peekable();
+        if sinks.peek().is_none() {
+            return self;
+        }
+        let mut all = self.sinks();
+        all.extend(sinks);
+        ReportOutput::Sinks(all)
+    }
+
+    /// output without the standard output (e.g., taken by the dashboard).
+    pub fn without_stdout(self) -> Self {
+        match self {
+            ReportOutput::Stdout => ReportOutput::Discard,
+            ReportOutput::Tee(path) => ReportOutput::File(path),
+            ReportOutput::Sinks(sinks) => ReportOutput::Sinks(
+                sinks
+                    .into_iter()
+                    .filter(|sink| sink.target != SinkTarget::Stdout)
+                    .collect(),
+            ),
+            output => output,
+        }
+    }
+}
+
+/// how an output file is opened when it already exists.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum OutputMode {
+    /// fails if the file exists
+    #[default]
+    CreateNew,
+    /// truncates the file
+    Truncate,
+    /// appends the report to the file
+    Append,
+}
+
+/// where the root working directory of the verification is created (see [runner::create_root_workdir]).
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub enum WorkdirStrategy {
+    /// ramdisk with the default volume name
+    #[default]
+    Ramdisk,
+    /// ramdisk with the given volume name
+    NamedRamdisk(String),
+    /// directory in `/dev/shm` or `$XDG_RUNTIME_DIR`, without mounting a ramdisk (see [runner::create_shm_workdir])
+    Shm,
+    /// ramdisk with the given volume name (default if none), reused if already mounted
+    /// (see [runner::reuse_root_workdir])
+    ReusedRamdisk(Option<String>),
+}
+
+impl WorkdirStrategy {
+    fn ramdisk(&self) -> Option<String> {
+        match self {
+            WorkdirStrategy::Ramdisk | WorkdirStrategy::Shm => None,
+            WorkdirStrategy::NamedRamdisk(name) => Some(name.clone()),
+            WorkdirStrategy::ReusedRamdisk(name) => name.clone(),
+        }
+    }
+}
+
+impl From<&runner::WorkdirArgs> for WorkdirStrategy {
+    fn from(args: &runner::WorkdirArgs) -> Self {
+        match args.ramdisk {
+            _ if args.shm => WorkdirStrategy::Shm,
+            ref name if args.reuse => WorkdirStrategy::ReusedRamdisk(name.clone()),
+            Some(ref name) => WorkdirStrategy::NamedRamdisk(name.clone()),
+            None => WorkdirStrategy::Ramdisk,
+        }
+    }
+}
+
+/// configuration of a synthesis run, built from a model with the builder methods.
+/// Several models and schedulers make a sweep: all schedulers are run for each model in turn.
+#[derive(Clone, Debug)]
+pub struct SynthesisConfig {
+    pub models: Vec<Model>,
+    pub schedulers: Vec<Scheduler>,
+    pub rigid: bool,
+    pub quasi_ss: bool,
+    pub fairness: Fairness,
+    /// granularity of the non-rigid moves (see [ModelRunOptions::delta_steps])
+    pub delta_steps: u8,
+    /// moves admissible in the actions of the algorithms
+    pub moves: Moves,
+    pub initial: InitialConditions,
+    /// robots cannot detect co-location (see [ModelRunOptions::no_multiplicity])
+    pub no_multiplicity: bool,
+    pub variants: Variants,
+    pub filters: FilterSet,
+    /// results file whose PASS algorithms are excluded when embedded (see [ExcludeLower])
+    pub exclude_lower: Option<PathBuf>,
+    /// regular expression that the codes of the algorithms must match (see [MatchCode])
+    pub code_match: Option<String>,
+    pub group_classes: bool,
+    /// appends statistics over the PASS algorithms to the report (see [AlgorithmStatistics])
+    pub stats: bool,
+    /// groups the PASS algorithms by structural features in the report (see [classify])
+    pub classify: bool,
+    /// lists the rules of each PASS algorithm in human-readable form (see [Algorithm::to_pretty_table])
+    pub pretty: bool,
+    /// no dot for each failing algorithm in sequential mode
+    pub quiet: bool,
+    /// lists the failing algorithms too in the report (prefixed by `FAIL >`)
+    pub report_fails: bool,
+    /// verifies the failing algorithms again under the next weaker schedulers (see [NearMiss])
+    pub near_misses: bool,
+    /// adds the raw output of the tools to the errors and incomplete verifications in the report (see [ToolError])
+    pub verbose_errors: bool,
+    /// directory in which the trails of the failing verifications with spin are archived (see [runner::save_trail])
+    pub save_trails: Option<PathBuf>,
+    /// directory in which the output of the tools is saved when a verification fails with an error (see
+    /// [runner::save_failure] and [FailureSaved])
+    pub save_failures: Option<PathBuf>,
+    pub list_only: bool,
+    /// writes a verification job per viable algorithm in this directory instead of verifying them (see [jobs])
+    pub emit_jobs: Option<PathBuf>,
+    /// only estimates the cost of the run, calibrated on the given number of verifications (see [estimate])
+    pub dry_run: Option<usize>,
+    /// verifies only this number of viable algorithms sampled at random (see [SynthesisConfig::sample])
+    pub sample: Option<usize>,
+    /// draws at most this number of algorithms to fill the sample ([SAMPLE_DRAWS_PER_ALGORITHM] per algorithm of the
+    /// sample if not given)
+    pub sample_draws: Option<u64>,
+    /// verifies only the viable algorithms from this index (see [SynthesisConfig::slice])
+    pub offset: usize,
+    /// verifies at most this number of viable algorithms (see [SynthesisConfig::slice])
+    pub limit: Option<usize>,
+    pub seed: u64,
+    /// writes the manifest of the enumeration of the viable algorithms to this file (see [crate::manifest])
+    pub write_manifest: Option<PathBuf>,
+    /// checks the enumeration of the viable algorithms against the manifest in this file (see [crate::manifest])
+    pub manifest: Option<PathBuf>,
+    /// writes a self-contained HTML report of the run to this file (see [html])
+    pub report_html: Option<PathBuf>,
+    pub search: Option<SearchOptions>,
+    /// falsifies algorithms by simulation before running spin (see [simulator])
+    pub prefilter: bool,
+    /// verifies each algorithm first under the weaker schedulers of [Scheduler::escalation] (see [verify_one])
+    pub escalate: bool,
+    pub backend: Backend,
+    pub order: Order,
+    /// order of the listing of the outcomes in the report, if not as soon as they are obtained (see [ReportSort])
+    pub sort: Option<ReportSort>,
+    /// stops the verification once the given number of PASS algorithms are found
+    pub find: Option<usize>,
+    /// interrupts the run once this wall time is elapsed (see [interrupt::interrupt_after])
+    pub time_budget: Option<Duration>,
+    /// defines of the build of the pan verifier
+    pub pan_build: PanBuildOptions,
+    /// raises the search depth of pan when too many verifications are truncated by it (see [depth])
+    pub adaptive_depth: bool,
+    /// limits on the pan processes running concurrently (see [throttle])
+    pub memory_limits: MemoryLimits,
+    pub parallelism: Parallelism,
+    pub output: ReportOutput,
+    pub output_mode: OutputMode,
+    pub workdir: WorkdirStrategy,
+    /// free space of the working directory (in MB) under which a parallel verification moves the enclosures of its
+    /// threads to a disk-backed directory (see [WorkdirSpace])
+    pub min_free_space: Option<usize>,
+}
+
+impl SynthesisConfig {
+    /// configuration for the given model with default settings
+    /// (ASYNC scheduler, non-rigid, self-stabilizing, default filters, parallel, report on stdout).
+    pub fn new(model: Model) -> Self {
+        Self {
+            models: vec![model],
+            schedulers: vec![Scheduler::ASYNC],
+            rigid: false,
+            quasi_ss: false,
+            fairness: Fairness::Weak,
+            delta_steps: DEFAULT_DELTA_STEPS,
+            moves: Moves::ALL,
+            initial: InitialConditions::default(),
+            no_multiplicity: false,
+            variants: Variants::Selected,
+            filters: FilterSet::default(),
+            exclude_lower: None,
+            code_match: None,
+            group_classes: false,
+            stats: false,
+            classify: false,
+            pretty: false,
+            quiet: false,
+            report_fails: false,
+            near_misses: false,
+            verbose_errors: false,
+            save_trails: None,
+            save_failures: None,
+            list_only: false,
+            emit_jobs: None,
+            dry_run: None,
+            sample: None,
+            sample_draws: None,
+            offset: 0,
+            limit: None,
+            seed: 0,
+            write_manifest: None,
+            manifest: None,
+            report_html: None,
+            search: None,
+            prefilter: false,
+            escalate: false,
+            backend: Backend::Spin,
+            order: Order::Index,
+            sort: None,
+            find: None,
+            time_budget: None,
+            pan_build: PanBuildOptions::default(),
+            adaptive_depth: false,
+            memory_limits: MemoryLimits::default(),
+            parallelism: Parallelism::Parallel,
+            output: ReportOutput::Stdout,
+            output_mode: OutputMode::CreateNew,
+            workdir: WorkdirStrategy::Ramdisk,
+            min_free_space: None,
+        }
+    }
+
+    pub fn models(mut self, models: impl IntoIterator<Item = Model>) -> Self {
+        self.models = models.into_iter().collect();
+        self
+    }
+
+    pub fn scheduler(mut self, scheduler: Scheduler) -> Self {
+        self.schedulers = vec![scheduler];
+        self
+    }
+
+    pub fn schedulers(mut self, schedulers: impl IntoIterator<Item = Scheduler>) -> Self {
+        self.schedulers = schedulers.into_iter().collect();
+        self
+    }
+
+    pub fn rigid(mut self, rigid: bool) -> Self {
+        self.rigid = rigid;
+        self
+    }
+
+    pub fn quasi_ss(mut self, quasi_ss: bool) -> Self {
+        self.quasi_ss = quasi_ss;
+        self
+    }
+
+    pub fn fairness(mut self, fairness: Fairness) -> Self {
+        self.fairness = fairness;
+        self
+    }
+
+    pub fn delta_steps(mut self, delta_steps: u8) -> Self {
+        self.delta_steps = delta_steps;
+        self
+    }
+
+    pub fn moves(mut self, moves: Moves) -> Self {
+        self.moves = moves;
+        self
+    }
+
+    pub fn initial_conditions(mut self, initial: InitialConditions) -> Self {
+        self.initial = initial;
+        self
+    }
+
+    pub fn no_multiplicity(mut self, no_multiplicity: bool) -> Self {
+        self.no_multiplicity = no_multiplicity;
+        self
+    }
+
+    pub fn variants(mut self, variants: Variants) -> Self {
+        self.variants = variants;
+        self
+    }
+
+    pub fn filters(mut self, filters: FilterSet) -> Self {
+        self.filters = filters;
+        self
+    }
+
+    pub fn exclude_lower(mut self, results_file: Option<PathBuf>) -> Self {
+        self.exclude_lower = results_file;
+        self
+    }
+
+    pub fn code_match(mut self, pattern: Option<String>) -> Self {
+        self.code_match = pattern;
+        self
+    }
+
+    pub fn group_classes(mut self, group_classes: bool) -> Self {
+        self.group_classes = group_classes;
+        self
+    }
+
+    pub fn stats(mut self, stats: bool) -> Self {
+        self.stats = stats;
+        self
+    }
+
+    pub fn classify(mut self, classify: bool) -> Self {
+        self.classify = classify;
+        self
+    }
+
+    pub fn pretty(mut self, pretty: bool) -> Self {
+        self.pretty = pretty;
+        self
+    }
+
+    pub fn quiet(mut self, quiet: bool) -> Self {
+        self.quiet = quiet;
+        self
+    }
+
+    pub fn report_fails(mut self, report_fails: bool) -> Self {
+        self.report_fails = report_fails;
+        self
+    }
+
+    pub fn near_misses(mut self, near_misses: bool) -> Self {
+        self.near_misses = near_misses;
+        self
+    }
+
+    pub fn verbose_errors(mut self, verbose_errors: bool) -> Self {
+        self.verbose_errors = verbose_errors;
+        self
+    }
+
+    pub fn save_trails(mut self, dir: Option<PathBuf>) -> Self {
+        self.save_trails = dir;
+        self
+    }
+
+    pub fn save_failures(mut self, dir: Option<PathBuf>) -> Self {
+        self.save_failures = dir;
+        self
+    }
+
+    pub fn list_only(mut self, list_only: bool) -> Self {
+        self.list_only = list_only;
+        self
+    }
+
+    /// writes the verification jobs of the viable algorithms in the directory instead of verifying them (see [jobs]).
+    pub fn emit_jobs(mut self, dir: Option<PathBuf>) -> Self {
+        self.emit_jobs = dir;
+        self
+    }
+
+    /// only estimates the wall time and disk usage of the run, from `calibration` verifications (see [estimate]).
+    pub fn dry_run(mut self, calibration: Option<usize>) -> Self {
+        self.dry_run = calibration;
+        self
+    }
+
+    /// verifies only the viable algorithms with indices from `offset`, at most `limit` of them;
+    /// the algorithms keep their indices in the whole enumeration.
+    pub fn slice(mut self, offset: usize, limit: Option<usize>) -> Self {
+        self.offset = offset;
+        self.limit = limit;
+        self
+    }
+
+    /// writes the manifest of the whole enumeration of the viable algorithms of the model (see [crate::manifest]).
+    pub fn write_manifest(mut self, path: Option<PathBuf>) -> Self {
+        self.write_manifest = path;
+        self
+    }
+
+    /// checks the indices of the viable algorithms against a manifest before verifying them (see [crate::manifest]).
+    pub fn manifest(mut self, path: Option<PathBuf>) -> Self {
+        self.manifest = path;
+        self
+    }
+
+    /// also writes a self-contained HTML report of the run, with the diagrams of the PASS algorithms (see [html]).
+    pub fn report_html(mut self, path: Option<PathBuf>) -> Self {
+        self.report_html = path;
+        self
+    }
+
+    /// verifies only `n` viable algorithms sampled uniformly at random (see [generator::sample_algorithms_in_model]),
+    /// numbered in the order of the draws. The draws stop once `n` viable algorithms are found, or after at most
+    /// [SynthesisConfig::sample_draws] draws if the viable algorithms are too few.
+    pub fn sample(mut self, n: Option<usize>, seed: u64) -> Self {
+        self.sample = n;
+        self.seed = seed;
+        self
+    }
+
+    /// draws at most `max` algorithms to fill the sample (see [SynthesisConfig::sample]).
+    pub fn sample_draws(mut self, max: Option<u64>) -> Self {
+        self.sample_draws = max;
+        self
+    }
+
+    /// maximal number of algorithms drawn to fill the sample of `n` viable algorithms.
+    fn max_sample_draws(&self, n: usize) -> u64 {
+        self.sample_draws
+            .unwrap_or((n as u64).saturating_mul(SAMPLE_DRAWS_PER_ALGORITHM))
+    }
+
+    /// runs a local search instead of the exhaustive search (see [search::hill_climb]).
+    pub fn search(mut self, options: Option<SearchOptions>) -> Self {
+        self.search = options;
+        self
+    }
+
+    pub fn prefilter(mut self, prefilter: bool) -> Self {
+        self.prefilter = prefilter;
+        self
+    }
+
+    /// verifies each algorithm first under cheaper, weaker schedulers, from the weakest (see [Scheduler::escalation]):
+    /// an algorithm whose variants all fail under one of them fails under the scheduler of the run, and is not
+    /// verified further. The outcomes are the same; the failing variants are those of the weaker scheduler.
+    pub fn escalate(mut self, escalate: bool) -> Self {
+        self.escalate = escalate;
+        self
+    }
+
+    pub fn backend(mut self, backend: Backend) -> Self {
+        self.backend = backend;
+        self
+    }
+
+    pub fn order(mut self, order: Order) -> Self {
+        self.order = order;
+        self
+    }
+
+    pub fn sort(mut self, sort: Option<ReportSort>) -> Self {
+        self.sort = sort;
+        self
+    }
+
+    pub fn find(mut self, find: Option<usize>) -> Self {
+        self.find = find;
+        self
+    }
+
+    pub fn time_budget(mut self, budget: Option<Duration>) -> Self {
+        self.time_budget = budget;
+        self
+    }
+
+    pub fn pan_build(mut self, pan_build: PanBuildOptions) -> Self {
+        self.pan_build = pan_build;
+        self
+    }
+
+    /// raises the search depth of pan (from that of the build options) for the next verifications when too many
+    /// verifications are truncated by it, and lists the adjustments in the report (see [depth]).
+    pub fn adaptive_depth(mut self, adaptive_depth: bool) -> Self {
+        self.adaptive_depth = adaptive_depth;
+        self
+    }
+
+    pub fn memory_limits(mut self, memory_limits: MemoryLimits) -> Self {
+        self.memory_limits = memory_limits;
+        self
+    }
+
+    pub fn min_free_space(mut self, mb: Option<usize>) -> Self {
+        self.min_free_space = mb;
+        self
+    }
+
+    pub fn parallelism(mut self, parallelism: Parallelism) -> Self {
+        self.parallelism = parallelism;
+        self
+    }
+
+    pub fn output(mut self, output: ReportOutput) -> Self {
+        self.output = output;
+        self
+    }
+
+    pub fn output_mode(mut self, mode: OutputMode) -> Self {
+        self.output_mode = mode;
+        self
+    }
+
+    pub fn workdir(mut self, workdir: WorkdirStrategy) -> Self {
+        self.workdir = workdir;
+        self
+    }
+
+    /// returns the (model, scheduler) pairs of the run, in the order in which they are run:
+    /// all schedulers for the first model, then all schedulers for the second model, etc.
+    pub fn runs(&self) -> Vec<(Model, Scheduler)> {
+        self.models
+            .iter()
+            .flat_map(|&m| self.schedulers.iter().map(move |&s| (m, s)))
+            .collect()
+    }
+
+    /// options of the model checker for each variant verified with the given scheduler (see [Variants]).
+    pub fn variants_to_check(&self, scheduler: Scheduler) -> Vec<ModelRunOptions> {
+        match self.variants {
+            Variants::Selected => vec![self.run_options(scheduler)],
+            Variants::All => self.run_options(scheduler).variants().to_vec(),
+        }
+    }
+
+    /// checks if the verified algorithms include enough PASS algorithms to stop (see [SynthesisConfig::find]).
+    pub fn found_enough<'a>(&self, verified: impl IntoIterator<Item = &'a Verified>) -> bool {
+        self.find.is_some_and(|n| {
+            verified
+                .into_iter()
+                .filter(|v| v.outcome == SpinOutcome::Pass)
+                .count()
+                >= n
+        })
+    }
+
+    /// checks if spin is needed by the run for the given scheduler (the local search always uses spin).
+    pub fn needs_spin(&self, scheduler: Scheduler) -> bool {
+        self.search.is_some() || self.backend.uses_spin(self.run_options(scheduler))
+    }
+
+    /// checks the output of the report (see [check_output]); a report appended to must have been written under the
+    /// same model (see [metadata::check_model_hash]).
+    pub fn check_output(&self) -> Result<()> {
+        check_output(&self.output, self.output_mode)?;
+        if self.output_mode == OutputMode::Append {
+            let hash = self.model_hash();
+            for path in self.output.files().iter().filter(|path| path.exists()) {
+                metadata::check_model_hash(path, &hash)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// hash of the semantics of the model of the run: the Promela templates and the options of the verifications of
+    /// all its runs (see [metadata::model_hash]).
+    pub fn model_hash(&self) -> String {
+        let variants = self
+            .runs()
+            .into_iter()
+            .flat_map(|(_, scheduler)| self.variants_to_check(scheduler));
+        metadata::model_hash(variants, &self.pan_build)
+    }
+
+    /// options of the model checker for the given scheduler.
+    pub fn run_options(&self, scheduler: Scheduler) -> ModelRunOptions {
+        ModelRunOptions {
+            scheduler,
+            rigid: self.rigid,
+            quasi_ss: self.quasi_ss,
+            fairness: self.fairness,
+            delta_steps: self.delta_steps,
+            initial: self.initial,
+            no_multiplicity: self.no_multiplicity,
+        }
+    }
+}
+
+/// outcome counts, PASS algorithms, and duration of the synthesis for one model and scheduler.
+#[derive(Clone, Debug)]
+pub struct RunResult {
+    pub model: Model,
+    pub scheduler: Scheduler,
+    /// number of algorithms verified (or listed or counted, see [SynthesisConfig::list_only] and [SynthesisConfig::dry_run])
+    pub n_algos: usize,
+    pub n_pass: usize,
+    pub n_fail: usize,
+    pub n_incomplete: usize,
+    /// number of the incomplete algorithms whose verification ran out of memory (see [SpinOutcome::OutOfMemory])
+    pub n_out_of_memory: usize,
+    pub n_errors: usize,
+    /// algorithms passing the verification, with their index among the viable algorithms
+    pub pass: Vec<(usize, Algorithm)>,
+    /// number of algorithms passing each variant (see [Variants]), in the order of [ModelRunOptions::variants]
+    pub variants_pass: Vec<usize>,
+    /// wall time of the verification of each algorithm (without errors), with its index among the viable algorithms
+    pub verification_times: Vec<(usize, Duration)>,
+    /// failing algorithms passing under a weaker scheduler (see [SynthesisConfig::near_misses])
+    pub near_misses: Vec<NearMiss>,
+    /// offset from which an interrupted run resumes (see [resume_offset])
+    pub resume_offset: Option<usize>,
+    /// number of algorithms enumerated by the generation (see [GenerationProgress])
+    pub n_enumerated: usize,
+    /// name of each filter, in the order in which they are checked, with the number of algorithms it eliminated
+    pub eliminated: Vec<(String, usize)>,
+    pub duration: Duration,
+}
+
+impl RunResult {
+    /// statistics over the PASS algorithms.
+    pub fn statistics(&self) -> AlgorithmStatistics {
+        AlgorithmStatistics::new(self.pass.iter().map(|(_, algo)| algo))
+    }
+
+    fn new(model: Model, scheduler: Scheduler) -> Self {
+        Self {
+            model,
+            scheduler,
+            n_algos: 0,
+            n_pass: 0,
+            n_fail: 0,
+            n_incomplete: 0,
+            n_out_of_memory: 0,
+            n_errors: 0,
+            pass: Vec::new(),
+            variants_pass: Vec::new(),
+            verification_times: Vec::new(),
+            near_misses: Vec::new(),
+            resume_offset: None,
+            n_enumerated: 0,
+            eliminated: Vec::new(),
+            duration: Duration::ZERO,
+        }
+    }
+}
+
+/// results of a synthesis run, with one [RunResult] per (model, scheduler) pair.
+#[derive(Clone, Debug, Default)]
+pub struct SynthesisResult {
+    pub runs: Vec<RunResult>,
+}
+
+impl SynthesisResult {
+    /// total number of PASS algorithms over all runs.
+    pub fn n_pass(&self) -> usize {
+        self.runs.iter().map(|r| r.n_pass).sum()
+    }
+}
+
+/// smallest index from the offset of the run that was not verified: an interrupted run resumes from there with
+/// `--offset` (the algorithms verified after it in a parallel run are verified again).
+pub fn resume_offset(offset: usize, verified: impl IntoIterator<Item = usize>) -> usize {
+    let verified = verified
+        .into_iter()
+        .collect::<std::collections::BTreeSet<_>>();
+    (offset..)
+        .find(|i| !verified.contains(i))
+        .expect("finite number of verified algorithms")
+}
+
+/// a synthesis run: a configuration together with filters and observers provided by the caller.
+pub struct SynthesisRun<'a> {
+    config: SynthesisConfig,
+    extra_filters: Vec<&'a dyn ViabilityFilter>,
+    observers: Vec<&'a dyn RunObserver>,
+}
+
+impl<'a> SynthesisRun<'a> {
+    pub fn new(config: SynthesisConfig) -> Self {
+        Self {
+            config,
+            extra_filters: Vec::new(),
+            observers: Vec::new(),
+        }
+    }
+
+    /// adds a filter applied after those of the configuration (see [ViabilityFilter]).
+    pub fn filter(mut self, filter: &'a dyn ViabilityFilter) -> Self {
+        self.extra_filters.push(filter);
+        self
+    }
+
+    /// adds an observer notified of the progress and outcomes of the run (see [RunObserver]).
+    pub fn observer(mut self, observer: &'a dyn RunObserver) -> Self {
+        self.observers.push(observer);
+        self
+    }
+
+    pub fn config(&self) -> &SynthesisConfig {
+        &self.config
+    }
+
+    /// executes the run, writing the report to the output of the configuration.
+    pub fn execute(&self) -> Result<SynthesisResult> {
+        let mut output = open_output(&self.config.output, self.config.output_mode)?;
+        writeln!(output, "Run options: {:?}", self.config)?;
+        self.execute_with_output(&mut output)
+    }
+
+    /// executes the run, writing the report to the given output (the output of the configuration is ignored).
+    /// If an error occurs, the report is completed before the error is returned.
+    pub fn execute_with_output(&self, output: &mut dyn Write) -> Result<SynthesisResult> {
+        let config = &self.config;
+        throttle::set_memory_limits(config.memory_limits);
+        if let Some(budget) = config.time_budget {
+            interrupt::interrupt_after(budget);
+        }
+        let extra_filters = self.extra_filters.as_slice();
+        if !extra_filters.is_empty() {
+            let names = extra_filters.iter().map(|f| f.name()).collect::<Vec<_>>();
+            writeln!(output, "Extra filters: {}", names.join(", "))?;
+        }
+
+        if config.models.len() > 1 && (config.write_manifest.is_some() || config.manifest.is_some())
+        {
+            anyhow::bail!("a manifest describes the enumeration of a single model");
+        }
+
+        let runs = config.runs();
+        let is_sweep = runs.len() > 1;
+        let mut result = SynthesisResult::default();
+        let mut run_outcome: Result<()> = Ok(());
+        for (model, scheduler) in runs {
+            if is_sweep {
+                writeln!(output, "\nSWEEP > Model {model} / {scheduler}")?;
+                info!("Sweep: model {model} / {scheduler}");
+            }
+            let model_run_options = config.run_options(scheduler);
+            let outcome = run_model(
+                output,
+                config,
+                model,
+                model_run_options,
+                extra_filters,
+                &self.observers,
+            );
+            match outcome {
+                Ok(run) => result.runs.push(run),
+                Err(e) => {
+                    run_outcome = Err(e);
+                    break;
+                }
+            }
+            if interrupt::is_interrupted() {
+                run_outcome = Err(anyhow::Error::msg(
+                    "interrupted (the report lists the algorithms verified so far)",
+                ));
+                break;
+            }
+        }
+
+        if is_sweep {
+            write_sweep_summary(output, &result.runs)?;
+        }
+        if let Some(ref path) = config.report_html {
+            html::save_report(path, &format!("{config:?}"), &result.runs)?;
+            writeln!(output, "HTML report written to {}", path.display())?;
+        }
+        if !config.list_only && config.search.is_none() {
+            writeln!(output)?;
+            writeln!(output, "Uname: {}", system_info())?;
+            writeln!(output, "Num cpus: {}", num_cpus::get())?;
+            writeln!(
+                output,
+                "OS/Arch: {} {}",
+                std::env::consts::OS,
+                std::env::consts::ARCH
+            )?;
+        }
+        output.flush()?;
+
+        // delayed reporting of the run error (e.g., cleanup)
+        // this is to ensure that the reporting is saved before unrolling everything
+        run_outcome.map(|_| result)
+    }
+}
+
+/// output mode selected by the `--force` and `--append` flags.
+pub fn output_mode(force: bool, append: bool) -> OutputMode {
+    match (force, append) {
+        (true, _) => OutputMode::Truncate,
+        (false, true) => OutputMode::Append,
+        (false, false) => OutputMode::CreateNew,
+    }
+}
+
+/// checks that the output of a report can be opened with the given mode, without opening it,
+/// so that a run fails on its arguments rather than after setting up its environment.
+pub fn check_output(output: &ReportOutput, mode: OutputMode) -> Result<()> {
+    if mode != OutputMode::CreateNew {
+        return Ok(());
+    }
+    for sink in output.sinks() {
+        match sink.target {
+            SinkTarget::File(path) if path.exists() => anyhow::bail!(
+                "output file {} already exists (use --force to overwrite it or --append to append to it)",
+                path.display()
+            ),
+            _ => (),
+        }
+    }
+    Ok(())
+}
+
+/// opens the output of a report (see [ReportOutput] and [OutputMode]).
+pub fn open_output(output: &ReportOutput, mode: OutputMode) -> Result<Box<dyn Write>> {
+    use std::fs::File;
+
+    let create = |path: &Path| -> Result<File> {
+        info!("Output to file: {} ({mode:?})", path.display());
+        if let Some(dir) = path.parent().filter(|d| !d.as_os_str().is_empty()) {
+            std::fs::create_dir_all(dir)
+                .with_context(|| format!("failed to create output directory {}", dir.display()))?;
+        }
+        let mut options = File::options();
+        match mode {
+            OutputMode::CreateNew => options.write(true).create_new(true),
+            OutputMode::Truncate => options.write(true).create(true).truncate(true),
+            OutputMode::Append => options.append(true).create(true),
+        };
+        options
+            .open(path)
+            .with_context(|| format!("failed to open output file {}", path.display()))
+    };
+
+    Ok(match output {
+        ReportOutput::Stdout => Box::new(std::io::stdout()),
+        ReportOutput::File(path) => Box::new(create(path)?),
+        ReportOutput::Discard => Box::new(std::io::sink()),
+        ReportOutput::Tee(_) | ReportOutput::Sinks(_) => {
+            let mut sink = OutputSink::new();
+            for spec in output.sinks() {
+                match spec.target {
+                    SinkTarget::Stdout => sink.add(std::io::stdout(), spec.format),
+                    SinkTarget::File(ref path) => sink.add(create(path)?, spec.format),
+                    SinkTarget::Tcp(ref address) => {
+                        info!("Output to {address} ({:?})", spec.format);
+                        sink.add(OutputSink::connect(address)?, spec.format)
+                    }
+                }
+            }
+            Box::new(sink)
+        }
+    })
+}
+
+/// writes the combined summary of a sweep, with one line per run.
+fn write_sweep_summary(output: &mut dyn std::io::Write, summaries: &[RunResult]) -> Result<()> {
+    let total = summaries
+        .iter()
+        .map(|s| s.duration)
+        .sum::<std::time::Duration>();
+    writeln!(
+        output,
+        "\nSweep summary ({} runs, Total: {} ms):",
+        summaries.len(),
+        total.as_millis()
+    )?;
+    writeln!(
+        output,
+        "| model | scheduler | pass | fail | incomplete | errors | algorithms | ms |"
+    )?;
+    writeln!(
+        output,
+        "| ----- | --------- | ---- | ---- | ---------- | ------ | ---------- | -- |"
+    )?;
+    for s in summaries {
+        writeln!(
+            output,
+            "| {} | {} | {} | {} | {} | {} | {} | {} |",
+            s.model,
+            s.scheduler.to_string().to_case(Case::Kebab),
+            s.n_pass,
+            s.n_fail,
+            s.n_incomplete,
+            s.n_errors,
+            s.n_algos,
+            s.duration.as_millis()
+        )?;
+    }
+    Ok(())
+}
+
+/// runs the synthesis for one model and scheduler, and writes its report to the output.
+/// Returns the result of the run, or the first error (the report is written before cleanup errors are returned).
+fn run_model(
+    output: &mut dyn std::io::Write,
+    config: &SynthesisConfig,
+    model: Model,
+    model_run_options: ModelRunOptions,
+    extra_filters: &[&dyn ViabilityFilter],
+    observer: &dyn RunObserver,
+) -> Result<RunResult> {
+    info!("Preparing environment");
+    let scheduler = model_run_options.scheduler;
+    let enter = |phase: Phase| observer.on_phase_change(model, scheduler, phase);
+    enter(Phase::Prepare);
+
+    let exclude_lower = match config.exclude_lower {
+        Some(ref path) => {
+            let report = std::fs::read_to_string(path)
+                .with_context(|| format!("failed to read results file {}", path.display()))?;
+            Some(ExcludeLower::from_report(
+                &report,
+                model.category,
+                model.class_L,
+            )?)
+        }
+        None => None,
+    };
+    let mut filters = extra_filters.to_vec();
+    if let Some(ref f) = exclude_lower {
+        writeln!(
+            output,
+            "Excluding algorithms embedding {} lower solutions",
+            f.lower().len()
+        )?;
+        filters.push(f);
+    }
+    let code_match = match config.code_match {
+        Some(ref pattern) => {
+            writeln!(output, "Only algorithms whose code matches {pattern}")?;
+            Some(MatchCode::new(pattern)?)
+        }
+        None => None,
+    };
+    if let Some(ref f) = code_match {
+        filters.push(f);
+    }
+    if let Some(n) = config.sample {
+        writeln!(
+            output,
+            "Sample of {n} viable algorithms drawn at random (seed {}, at most {} draws): the indices are the \
+            positions of the algorithms in the sample",
+            config.seed,
+            config.max_sample_draws(n)
+        )?;
+    }
+    let extra_filters = filters.as_slice();
+    check_manifest(output, config, model, extra_filters)?;
+
+    let mut timings = Timings::start();
+    if config.list_only {
+        enter(Phase::Generate);
+        let mut summary = RunResult::new(model, scheduler);
+        for (i, algo) in generate_and_filter(config, model, extra_filters) {
+            observer.on_generated(i, &algo);
+            writeln!(output, "{:4} : {}", i, algo.as_code())?;
+            summary.n_algos += 1;
+        }
+        writeln!(output, "Listed {} viable algorithms", summary.n_algos)?;
+        output.flush()?;
+        summary.duration = timings.elapsed();
+        enter(Phase::Done);
+        return Ok(summary);
+    }
+
+    if let Some(ref dir) = config.emit_jobs {
+        enter(Phase::Generate);
+        let mut summary = RunResult::new(model, scheduler);
+        summary.n_algos = emit_jobs(
+            output,
+            config,
+            model,
+            scheduler,
+            extra_filters,
+            observer,
+            dir,
+        )?;
+        summary.duration = timings.elapsed();
+        enter(Phase::Done);
+        return Ok(summary);
+    }
+
+    if let Some(calibration) = config.dry_run {
+        let outcome = dry_run(
+            output,
+            config,
+            model,
+            &config.variants_to_check(scheduler),
+            extra_filters,
+            observer,
+            calibration,
+        );
+        let mut summary = RunResult::new(model, scheduler);
+        summary.n_algos = outcome?.n_algos;
+        summary.duration = timings.elapsed();
+        enter(Phase::Done);
+        return Ok(summary);
+    }
+
+    // closed on every exit path, also with an error or an interruption
+    let workdir = runner::WorkdirGuard::new(prepare_checked_workdirs(config, model, scheduler)?);
+    timings.prepare = timings.elapsed();
+
+    if let Some(search_options) = config.search {
+        enter(Phase::Verify);
+        let outcome = run_search(
+            output,
+            &workdir,
+            model,
+            search_options,
+            model_run_options,
+            &config.pan_build,
+        );
+        enter(Phase::Cleanup);
+        let cleanup_outcome = workdir.close();
+        let mut summary = RunResult::new(model, scheduler);
+        summary.pass = outcome.and_then(|found| cleanup_outcome.map(|_| found))?;
+        for (i, algo) in summary.pass.iter() {
+            observer.on_outcome(*i, algo, SpinOutcome::Pass, &[SpinOutcome::Pass]);
+        }
+        summary.n_pass = summary.pass.len();
+        summary.duration = timings.elapsed();
+        enter(Phase::Done);
+        return Ok(summary);
+    }
+    enter(Phase::Generate);
+    // in sequential mode, the generation is interleaved with the verification
+    let progress = GenerationProgress::new(
+        config,
+        model,
+        extra_filters,
+        observer.shows_progress() || config.parallelism == Parallelism::Sequential,
+    );
+    let all_viable_algos =
+        generate_and_filter_with_progress(config, model, extra_filters, Some(&progress))
+            .inspect(|(i, algo)| observer.on_generated(*i, algo));
+
+    let variants = config.variants_to_check(scheduler);
+
+    let verified: Vec<Result<Verified>>;
+    let cleanup_outcome: Result<_>; // used later
+    let mut near_misses = Vec::new();
+    depth::set_adaptive_depth(
+        config
+            .adaptive_depth
+            .then(|| DepthTuner::new(config.pan_build.depth())),
+    );
+
+    if config.parallelism == Parallelism::Sequential {
+        //
+        // Sequential verification (outcomes are reported as soon as they are obtained)
+        //
+        let enclosure = runner::create_enclosure(workdir.path())?;
+
+        let all_viable_algos: Box<dyn Iterator<Item = (usize, Algorithm)>> = match config.order {
+            Order::Index => Box::new(all_viable_algos),
+            order => {
+                let mut algos = all_viable_algos.collect::<Vec<_>>();
+                order.sort(&mut algos, |(_, algo)| algo);
+                Box::new(algos.into_iter())
+            }
+        };
+
+        info!("Starting verification");
+        timings.generate = timings.elapsed();
+        enter(Phase::Verify);
+        let mut results = Vec::new();
+        for (i, algo) in all_viable_algos {
+            if interrupt::is_interrupted() {
+                break;
+            }
+            let v = match verify_one(&enclosure, i, algo, &variants, config, observer) {
+                Ok(v) => v,
+                // the tools may have been killed at the end of the grace period
+                Err(_) if interrupt::is_interrupted() => break,
+                // the error ends the run: its output is reported first
+                Err(e) if config.verbose_errors => {
+                    writeln!(output)?;
+                    write_error(output, &e)?;
+                    write_error_output(output, &e)?;
+                    return Err(e);
+                }
+                Err(e) => return Err(e),
+            };
+            // with a sorted listing, all the outcomes are listed once the verification is complete
+            let listed_now = config.sort.is_none();
+            if listed_now && !v.outcome.is_fail() {
+                writeln!(output)?;
+                writeln!(output, "{:4} : {} {}", i, v.outcome, &v.algo.as_code())?;
+                write_variants(output, i, &variants, &v.variants)?;
+                if config.pretty && v.outcome == SpinOutcome::Pass {
+                    write_rules(output, i, &v.algo)?;
+                }
+                if config.verbose_errors {
+                    write_tool_output(output, i, v.tool_output.as_deref())?;
+                }
+            } else if listed_now && config.report_fails {
+                writeln!(output)?;
+                write_fail(output, i, &v.algo)?;
+                write_variants(output, i, &variants, &v.variants)?;
+                write_violations(output, i, &variants, &v.violations)?;
+            } else if config.quiet {
+                // no progress dots
+            } else if (i + 1) % 100 == 0 {
+                write!(output, "\n.")?;
+            } else if (i + 1) % 10 == 0 {
+                write!(output, ". ")?;
+            } else {
+                write!(output, ".")?;
+            }
+            output.flush()?;
+            results.push(Ok(v));
+            if config.found_enough(results.iter().flatten()) {
+                break;
+            }
+        }
+        results.sort_by_key(|v| v.as_ref().map_or(0, |v| v.index));
+        writeln!(output)?;
+        progress.finish(output)?;
+        if let Some(sort) = config.sort {
+            write_sorted_outcomes(output, &results, &variants, config, sort)?;
+        }
+        if config.near_misses {
+            near_misses = check_near_misses(&workdir, &results, scheduler, config)?;
+            write_near_misses(output, &near_misses, scheduler)?;
+        }
+
+        info!("Cleaning up");
+        timings.verify = timings.elapsed();
+        enter(Phase::Cleanup);
+        cleanup_outcome = workdir.close();
+        timings.cleanup = timings.elapsed();
+        verified = results;
+    } else {
+        //
+        // Parallel verification
+        //
+        let all_viable_algos = all_viable_algos.collect::<Vec<_>>();
+        progress.finish(output)?;
+        timings.generate = timings.elapsed();
+
+        info!("Starting verification (parallel)");
+        enter(Phase::Verify);
+        // report PASS results / incomplete search / errors as they are obtained
+        let results = verify_all_streaming(
+            &workdir,
+            all_viable_algos,
+            &variants,
+            config,
+            observer,
+            &mut |result| match config.sort {
+                Some(_) => Ok(()),
+                None => write_outcomes(output, std::slice::from_ref(result), &variants, config),
+            },
+        )?;
+        if let Some(sort) = config.sort {
+            write_sorted_outcomes(output, &results, &variants, config, sort)?;
+        }
+        if config.near_misses {
+            near_misses = check_near_misses(&workdir, &results, scheduler, config)?;
+            write_near_misses(output, &near_misses, scheduler)?;
+        }
+
+        info!("Cleaning up");
+        // eject ramdisk (if any)
+        timings.verify = timings.elapsed();
+        enter(Phase::Cleanup);
+        cleanup_outcome = workdir.close();
+        timings.cleanup = timings.elapsed();
+        verified = results;
+    }
+
+    info!("Generating reports");
+    enter(Phase::Report);
+    let adjustments = depth::adjustments();
+    depth::set_adaptive_depth(None);
+    for adjustment in adjustments {
+        writeln!(output, "Adaptive depth: {adjustment}")?;
+    }
+    let mut summary = report(output, config, model, &variants, verified, timings)?;
+    summary.near_misses = near_misses;
+    summary.n_enumerated = progress.n_enumerated();
+    summary.eliminated = progress
+        .eliminated()
+        .map(|(name, n)| (name.to_string(), n))
+        .collect();
+    enter(Phase::Done);
+
+    // delayed reporting of the cleanup error (the report is written first)
+    cleanup_outcome.map(|_| summary)
+}
+
+/// writes the verification job of each viable algorithm in a subdirectory of `dir` for the model and scheduler,
+/// with the scripts running them (see [jobs]); returns the number of jobs.
+fn emit_jobs(
+    output: &mut dyn std::io::Write,
+    config: &SynthesisConfig,
+    model: Model,
+    scheduler: Scheduler,
+    extra_filters: &[&dyn ViabilityFilter],
+    observer: &dyn RunObserver,
+    dir: &Path,
+) -> Result<usize> {
+    let dir = dir.join(format!("{model}_{scheduler}"));
+    let variants = config.variants_to_check(scheduler);
+    let mut names = Vec::new();
+    for (i, algo) in generate_and_filter(config, model, extra_filters) {
+        if interrupt::is_interrupted() {
+            break;
+        }
+        observer.on_generated(i, &algo);
+        names.push(jobs::write_job(
+            &dir,
+            i,
+            &algo,
+            &variants,
+            &config.pan_build,
+        )?);
+    }
+    let (run_all, slurm) = jobs::write_scripts(&dir, &format!("{model} {scheduler}"), &names)?;
+    writeln!(output, "Emitted {} jobs in {}", names.len(), dir.display())?;
+    writeln!(
+        output,
+        "Run them with `JOBS=4 sh {}`, or submit `sbatch {}` from {}",
+        run_all.display(),
+        jobs::SLURM_SCRIPT,
+        slurm.parent().unwrap_or(&dir).display()
+    )?;
+    output.flush()?;
+    Ok(names.len())
+}
+
+/// an algorithm together with the outcome of its verification.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Verified {
+    /// index among the viable algorithms
+    pub index: usize,
+    pub algo: Algorithm,
+    /// best outcome over the variants checked
+    pub outcome: SpinOutcome,
+    /// outcome for each variant checked
+    pub variants: Vec<SpinOutcome>,
+    /// wall time of the verification (all variants)
+    pub duration: Duration,
+    /// whether all variants were falsified by simulation, without running spin
+    #[serde(default)]
+    pub prefiltered: bool,
+    /// worst-case gathering time (in epochs) of each variant that passes, when measured
+    /// (centralized, FSYNC and SSYNC schedulers, see [checker::gathering_time])
+    #[serde(default)]
+    pub gathering_times: Vec<Option<usize>>,
+    /// raw output of pan for the last variant whose search was incomplete
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tool_output: Option<String>,
+    /// property violated for each variant that fails, when verified by spin
+    #[serde(default)]
+    pub violations: Vec<Option<Violation>>,
+    /// weaker scheduler under which all variants failed, so that the algorithm was not verified under the scheduler
+    /// of the run (see [SynthesisConfig::escalate])
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub escalated_fail: Option<Scheduler>,
+}
+
+impl Verified {
+    /// shortest worst-case gathering time over the variants, if measured.
+    pub fn gathering_time(&self) -> Option<usize> {
+        self.gathering_times.iter().flatten().min().copied()
+    }
+}
+
+/// cumulative durations of the phases of a run, measured from its start (as in the timing report).
+#[derive(Clone, Copy, Debug)]
+pub struct Timings {
+    pub start: Instant,
+    pub prepare: Duration,
+    pub generate: Duration,
+    pub verify: Duration,
+    pub cleanup: Duration,
+}
+
+impl Timings {
+    /// starts measuring a run now.
+    pub fn start() -> Self {
+        Self {
+            start: Instant::now(),
+            prepare: Duration::ZERO,
+            generate: Duration::ZERO,
+            verify: Duration::ZERO,
+            cleanup: Duration::ZERO,
+        }
+    }
+
+    /// duration since the start of the run.
+    pub fn elapsed(&self) -> Duration {
+        Instant::now() - self.start
+    }
+}
+
+/// creates the root working directory of the verification for the scheduler (see [WorkdirStrategy]),
+/// or a temporary directory if spin is not needed (see [SynthesisConfig::needs_spin]).
+/// The enclosures of the verification threads are created in it by [verify_all].
+pub fn prepare_workdirs(config: &SynthesisConfig, scheduler: Scheduler) -> Result<runner::Workdir> {
+    match config.workdir {
+        WorkdirStrategy::Shm => runner::create_shm_workdir(),
+        _ if !config.needs_spin(scheduler) => runner::create_temp_workdir(),
+        WorkdirStrategy::ReusedRamdisk(ref name) => runner::reuse_root_workdir(name.clone()),
+        ref workdir => runner::create_root_workdir(workdir.ramdisk()),
+    }
+}
+
+/// same as [prepare_workdirs], then checks the model with spin on a representative algorithm (the first of the model)
+/// under each variant verified by spin (see [runner::lint_model]), so that inconsistent promela files or options fail
+/// the run once, with the messages of spin, rather than each verification with its own error.
+fn prepare_checked_workdirs(
+    config: &SynthesisConfig,
+    model: Model,
+    scheduler: Scheduler,
+) -> Result<runner::Workdir> {
+    let workdir = prepare_workdirs(config, scheduler)?;
+    if let Err(e) = lint_model(config, &workdir, model, scheduler) {
+        runner::close_workdir(workdir)?;
+        return Err(e);
+    }
+    Ok(workdir)
+}
+
+fn lint_model(
+    config: &SynthesisConfig,
+    workdir: &runner::Workdir,
+    model: Model,
+    scheduler: Scheduler,
+) -> Result<()> {
+    let variants = config
+        .variants_to_check(scheduler)
+        .into_iter()
+        .filter(|&options| config.search.is_some() || config.backend.uses_spin(options))
+        .collect::<Vec<_>>();
+    let first = generator::generate_algorithms_with_moves(
+        model.category,
+        model.n_colors,
+        model.class_L,
+        config.moves,
+    )
+    .next();
+    let Some(algo) = first.filter(|_| !variants.is_empty()) else {
+        return Ok(());
+    };
+    info!("Checking the model with spin on {}", algo.as_code());
+    let enclosure = runner::create_enclosure(workdir.path())?;
+    for options in variants {
+        runner::lint_model(&enclosure, &algo, options).with_context(|| {
+            format!(
+                "invalid model {model} under {scheduler} {}",
+                options.variant_name()
+            )
+        })?;
+    }
+    std::fs::remove_dir_all(&enclosure)?;
+    Ok(())
+}
+
+/// description of the enumeration of the viable algorithms (filters and sampling), recorded in manifests.
+pub fn enumeration_description(
+    config: &SynthesisConfig,
+    extra_filters: &[&dyn ViabilityFilter],
+) -> String {
+    let names = config
+        .filters
+        .filters()
+        .iter()
+        .map(|f| f.name())
+        .chain(extra_filters.iter().map(|f| f.name()))
+        .collect::<Vec<_>>();
+    let moves = match config.moves {
+        moves if moves.is_all() => String::new(),
+        moves => format!(" moves={moves}"),
+    };
+    match config.sample {
+        Some(n) => format!(
+            "filters={}{moves} sample={n} seed={} draws={}",
+            names.join(","),
+            config.seed,
+            config.max_sample_draws(n)
+        ),
+        None => format!("filters={}{moves}", names.join(",")),
+    }
+}
+
+/// writes the manifest of the whole enumeration of the viable algorithms and/or checks the indices of the viable
+/// algorithms of the slice of the configuration against a manifest, as requested by the configuration.
+fn check_manifest(
+    output: &mut dyn std::io::Write,
+    config: &SynthesisConfig,
+    model: Model,
+    extra_filters: &[&dyn ViabilityFilter],
+) -> Result<()> {
+    let enumeration = enumeration_description(config, extra_filters);
+    if let Some(ref path) = config.write_manifest {
+        let whole = config.clone().slice(0, None);
+        let manifest = Manifest::new(
+            model,
+            enumeration.clone(),
+            generate_and_filter(&whole, model, extra_filters),
+        );
+        manifest.save(path)?;
+        writeln!(
+            output,
+            "Manifest of {} viable algorithms written to {}",
+            manifest.hashes.len(),
+            path.display()
+        )?;
+    }
+    if let Some(ref path) = config.manifest {
+        let manifest = Manifest::read(path)?;
+        manifest
+            .check_enumeration(model, &enumeration)
+            .with_context(|| format!("incompatible manifest {}", path.display()))?;
+        let mut n_checked = 0;
+        for (i, algo) in generate_and_filter(config, model, extra_filters) {
+            manifest
+                .check(i, &algo.as_code())
+                .with_context(|| format!("enumeration differs from manifest {}", path.display()))?;
+            n_checked += 1;
+        }
+        writeln!(
+            output,
+            "Enumeration of {n_checked} viable algorithms checked against manifest {}",
+            path.display()
+        )?;
+    }
+    Ok(())
+}
+
+/// enumeration of the algorithms of the model for the configuration (all, canonical or sampled), with the filters of
+/// the configuration that remain to be checked, and the number of algorithms enumerated if known in advance.
+/// Sampled algorithms are drawn up to the bound of the configuration (see [SynthesisConfig::sample_draws]).
+fn enumerate_algorithms(
+    config: &SynthesisConfig,
+    model: Model,
+) -> (Box<dyn Iterator<Item = Algorithm>>, FilterSet, Option<u64>) {
+    let mut filters = config.filters.clone();
+    filters.restrict_to(config.moves);
+    let category = model.category;
+    let n_colors = model.n_colors;
+    #[allow(non_snake_case)]
+    let class_L = model.class_L;
+    let moves = config.moves;
+
+    if let Some(n) = config.sample {
+        // the viable algorithms may be too few to fill the sample: the draws are bounded
+        let algos = generator::sample_algorithms_with_moves(
+            category,
+            n_colors,
+            class_L,
+            moves,
+            config.seed,
+        )
+        .take(config.max_sample_draws(n).try_into().unwrap_or(usize::MAX))
+        .map(|(_, algo)| algo);
+        (Box::new(algos), filters, None)
+    } else if filters.contains(Filter::Canonical) {
+        // canonical algorithms are enumerated directly; no need to check them again
+        filters.disable(Filter::Canonical);
+        let algos =
+            generator::generate_canonical_algorithms_with_moves(category, n_colors, class_L, moves);
+        (Box::new(algos), filters, None)
+    } else {
+        let algos = generator::generate_algorithms_with_moves(category, n_colors, class_L, moves);
+        let count = generator::count_algorithms_with_moves(category, n_colors, class_L, moves);
+        (Box::new(algos), filters, Some(count))
+    }
+}
+
+/// progress of the generation of the viable algorithms of a run: progress bar over the algorithms enumerated (see
+/// [generate_and_filter_with_progress]), and number of algorithms remaining after each filter in turn.
+pub struct GenerationProgress {
+    bar: indicatif::ProgressBar,
+    n_enumerated: Cell<usize>,
+    /// name of each filter (of the configuration, then extra filters), with the number of algorithms it accepted
+    stages: Vec<(String, Cell<usize>)>,
+}
+
+impl GenerationProgress {
+    /// progress of the generation for the model, with a progress bar unless `hidden`
+    /// (a spinner if the number of algorithms is not known in advance).
+    pub fn new(
+        config: &SynthesisConfig,
+        model: Model,
+        extra_filters: &[&dyn ViabilityFilter],
+        hidden: bool,
+    ) -> Self {
+        use indicatif::ProgressBar;
+        let (_, filters, count) = enumerate_algorithms(config, model);
+        let bar = match count {
+            _ if hidden => ProgressBar::hidden(),
+            Some(count) => ProgressBar::new(count),
+            None => ProgressBar::new_spinner(),
+        };
+        let stages = filters
+            .filters()
+            .iter()
+            .map(|f| f.name())
+            .chain(extra_filters.iter().map(|f| f.name()))
+            .map(|name| (name, Cell::new(0)))
+            .collect();
+        Self {
+            bar,
+            n_enumerated: Cell::new(0),
+            stages,
+        }
+    }
+
+    /// counts the algorithm and checks it with the filters in turn, counting the algorithms accepted by each.
+    fn accept(
+        &self,
+        algo: &Algorithm,
+        filters: &FilterSet,
+        extra_filters: &[&dyn ViabilityFilter],
+    ) -> bool {
+        self.n_enumerated.set(self.n_enumerated.get() + 1);
+        self.bar.inc(1);
+        let all_filters = filters
+            .filters()
+            .iter()
+            .map(|f| f as &dyn ViabilityFilter)
+            .chain(extra_filters.iter().copied());
+        for (f, (_, count)) in all_filters.zip(self.stages.iter()) {
+            if !f.accept(algo) {
+                return false;
+            }
+            count.set(count.get() + 1);
+        }
+        true
+    }
+
+    /// number of algorithms enumerated so far.
+    pub fn n_enumerated(&self) -> usize {
+        self.n_enumerated.get()
+    }
+
+    /// number of algorithms accepted so far by each filter (in the order in which they are checked).
+    pub fn stages(&self) -> impl Iterator<Item = (&str, usize)> {
+        self.stages.iter().map(|(name, n)| (name.as_str(), n.get()))
+    }
+
+    /// number of algorithms eliminated so far by each filter, that is, accepted by the filters before it but not by
+    /// this one (in the order in which they are checked).
+    pub fn eliminated(&self) -> impl Iterator<Item = (&str, usize)> {
+        let before = std::iter::once(self.n_enumerated()).chain(self.stages().map(|(_, n)| n));
+        self.stages()
+            .zip(before)
+            .map(|((name, n), before)| (name, before - n))
+    }
+
+    /// clears the progress bar and writes the counts of the generation to the report.
+    pub fn finish(&self, output: &mut dyn std::io::Write) -> std::io::Result<()> {
+        use num_format::{Locale, ToFormattedString};
+        self.bar.finish_and_clear();
+        writeln!(
+            output,
+            "Generation: {} algorithms enumerated",
+            self.n_enumerated().to_formatted_string(&Locale::en)
+        )?;
+        for ((name, n), (_, eliminated)) in self.stages().zip(self.eliminated()) {
+            writeln!(
+                output,
+                "    {:<40}{:>13}  (-{})",
+                format!("{name}:"),
+                n.to_formatted_string(&Locale::en),
+                eliminated.to_formatted_string(&Locale::en)
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// generates the algorithms of the model and filters out the non-viable ones,
+/// with the filters of the configuration followed by the extra filters.
+/// The viable algorithms are numbered consecutively, starting from 0; only those of the slice of the configuration
+/// are returned (see [SynthesisConfig::slice]), with their indices.
+pub fn generate_and_filter<'a>(
+    config: &'a SynthesisConfig,
+    model: Model,
+    extra_filters: &'a [&'a dyn ViabilityFilter],
+) -> impl Iterator<Item = (usize, Algorithm)> + 'a {
+    generate_and_filter_with_progress(config, model, extra_filters, None)
+}
+
+/// same as [generate_and_filter], updating the progress of the generation (if any) with each algorithm generated.
+pub fn generate_and_filter_with_progress<'a>(
+    config: &'a SynthesisConfig,
+    model: Model,
+    extra_filters: &'a [&'a dyn ViabilityFilter],
+    progress: Option<&'a GenerationProgress>,
+) -> impl Iterator<Item = (usize, Algorithm)> + 'a {
+    let (all_algos, filters, _) = enumerate_algorithms(config, model);
+    all_algos
+        // the algorithms not generated before an interruption are not verified either
+        .take_while(|_| !interrupt::is_interrupted())
+        .filter(move |a| match progress {
+            Some(progress) => progress.accept(a, &filters, extra_filters),
+            None => filters.accept(a) && extra_filters.iter().all(|f| f.accept(a)),
+        })
+        .take(config.sample.unwrap_or(usize::MAX))
+        .enumerate()
+        .skip(config.offset)
+        .take(config.limit.unwrap_or(usize::MAX))
+}
+
+/// default number of draws per algorithm of a sample, bounding the draws when the viable algorithms are too few
+/// (see [SynthesisConfig::sample_draws]).
+pub const SAMPLE_DRAWS_PER_ALGORITHM: u64 = 1000;
+
+/// interval between two measures of the free space of the working directory.
+const SPACE_POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+/// monitor of the free space of the working directory during a parallel verification (see
+/// [SynthesisConfig::min_free_space]). When the space is low, a thread first cleans its enclosure
+/// (see [runner::clean_enclosure]), then moves it to a disk-backed temporary directory (the spill directory),
+/// instead of letting spin, clang or pan fail with I/O errors on a full ramdisk.
+pub struct WorkdirSpace {
+    min_free_mb: Option<usize>,
+    /// last measure of the free space (in MB, if known) with its time
+    last: std::sync::Mutex<Option<(Instant, Option<usize>)>>,
+    spill: std::sync::OnceLock<runner::Workdir>,
+}
+
+impl WorkdirSpace {
+    pub fn new(min_free_mb: Option<usize>) -> Self {
+        Self {
+            min_free_mb,
+            last: std::sync::Mutex::new(None),
+            spill: std::sync::OnceLock::new(),
+        }
+    }
+
+    /// checks if the free space of the working directory is under the threshold (measured again if `fresh`).
+    fn is_low(&self, work_dir: &Path, fresh: bool) -> bool {
+        let Some(min_free_mb) = self.min_free_mb else {
+            return false;
+        };
+        let mut last = self.last.lock().expect("free space");
+        let free_mb = match *last {
+            Some((time, free_mb)) if !fresh && time.elapsed() < SPACE_POLL_INTERVAL => free_mb,
+            _ => {
+                let free_mb = runner::available_space_mb(work_dir);
+                *last = Some((Instant::now(), free_mb));
+                free_mb
+            }
+        };
+        free_mb.is_some_and(|free_mb| free_mb < min_free_mb)
+    }
+
+    /// cleans the enclosure if the free space of the working directory is low, then moves it to the spill directory
+    /// if the space is still low (the enclosure is updated).
+    pub fn relieve(&self, work_dir: &Path, enclosure: &mut PathBuf) -> Result<()> {
+        if !enclosure.starts_with(work_dir) || !self.is_low(work_dir, false) {
+            return Ok(());
+        }
+        runner::clean_enclosure(enclosure)?;
+        if !self.is_low(work_dir, true) {
+            return Ok(());
+        }
+        let spill = match self.spill.get() {
+            Some(spill) => spill,
+            None => {
+                let spill = runner::create_temp_workdir()?;
+                log::warn!(
+                    "low free space in {}: moving enclosures to {}",
+                    work_dir.display(),
+                    spill.path().display()
+                );
+                // another thread may have created it meanwhile
+                if let Err(spill) = self.spill.set(spill) {
+                    runner::close_workdir(spill)?;
+                }
+                self.spill.get().expect("spill directory")
+            }
+        };
+        let moved = runner::create_enclosure(spill.path())?;
+        std::fs::remove_dir_all(&*enclosure)?;
+        *enclosure = moved;
+        Ok(())
+    }
+
+    /// removes the spill directory, if any.
+    pub fn close(self) -> Result<()> {
+        match self.spill.into_inner() {
+            Some(spill) => runner::close_workdir(spill),
+            None => Ok(()),
+        }
+    }
+}
+
+/// verifies the given algorithms for each variant, in enclosures created in the working directory,
+/// with the parallelism, prefilter, backend and order of the configuration. Verification errors are returned for each algorithm (in the order of the algorithms)
+/// rather than interrupting the verification; the observer is notified of each outcome or error.
+/// Once enough PASS algorithms are found ([SynthesisConfig::find]), the remaining algorithms are skipped (the
+/// verifications in progress are completed) and omitted from the results.
+pub fn verify_all(
+    workdir: &runner::Workdir,
+    algos: Vec<(usize, Algorithm)>,
+    variants: &[ModelRunOptions],
+    config: &SynthesisConfig,
+    observer: &dyn RunObserver,
+) -> Result<Vec<Result<Verified>>> {
+    verify_all_streaming(workdir, algos, variants, config, observer, &mut |_| Ok(()))
+}
+
+/// same as [verify_all], and passes each result to `on_result` on the calling thread as soon as it is obtained (in
+/// the order of completion), e.g., to write it to the report. An error of `on_result` stops the verification: the
+/// verifications in progress are completed, and the error is returned.
+pub fn verify_all_streaming(
+    workdir: &runner::Workdir,
+    algos: Vec<(usize, Algorithm)>,
+    variants: &[ModelRunOptions],
+    config: &SynthesisConfig,
+    observer: &dyn RunObserver,
+    on_result: &mut dyn FnMut(&Result<Verified>) -> Result<()>,
+) -> Result<Vec<Result<Verified>>> {
+    use indicatif::{ParallelProgressIterator, ProgressBar};
+    use rayon::prelude::*;
+    use std::cell::RefCell;
+    use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+
+    thread_local! {
+        // (root working directory, enclosure created in it by the current thread)
+        static ENCLOSURE: RefCell<Option<(PathBuf, PathBuf)>> = const { RefCell::new(None) };
+    }
+
+    fn with_enclosure_do<F>(work_dir: &Path, space: &WorkdirSpace, action: F) -> Result<Verified>
+    where
+        F: Fn(&Path) -> Result<Verified>,
+    {
+        ENCLOSURE.with(|cell| {
+            let mut enclosure = cell.borrow_mut();
+            // threads outlive the working directory of a run (e.g., in a sweep), and the spill directory
+            if enclosure
+                .as_ref()
+                .is_none_or(|(root, path)| root != work_dir || !path.exists())
+            {
+                let path = runner::create_enclosure(work_dir)?;
+                *enclosure = Some((work_dir.to_path_buf(), path));
+            }
+            if let Some((_, ref mut path)) = *enclosure {
+                space.relieve(work_dir, path)?;
+            }
+            let (_, thread_enclosure) = enclosure
+                .as_ref()
+                .ok_or_else(|| anyhow::Error::msg("Could not obtain enclosure"))?;
+            action(thread_enclosure)
+        })
+    }
+
+    // algorithms with their positions, in the order of verification
+    let num_algos = algos.len() as u64;
+    let mut queue = algos.into_iter().enumerate().collect::<Vec<_>>();
+    config.order.sort(&mut queue, |(_, (_, algo))| algo);
+    // the algorithms not verified because enough PASS algorithms were found (see [SynthesisConfig::find]) are omitted
+    let in_positions = |mut results: Vec<(usize, Option<Result<Verified>>)>| {
+        results.sort_by_key(|(position, _)| *position);
+        results
+            .into_iter()
+            .filter_map(|(_, result)| result)
+            .collect()
+    };
+    let n_found = AtomicUsize::new(0);
+    // set when `on_result` fails
+    let stopped = AtomicBool::new(false);
+    let verify_unless_found = |verify: &dyn Fn() -> Result<Verified>| {
+        if stopped.load(Ordering::SeqCst)
+            || interrupt::is_interrupted()
+            || config
+                .find
+                .is_some_and(|n| n_found.load(Ordering::SeqCst) >= n)
+        {
+            return None;
+        }
+        let result = verify();
+        if result.is_err() && interrupt::is_interrupted() {
+            // the tools may have been killed at the end of the grace period
+            return None;
+        }
+        if matches!(result, Ok(ref v) if v.outcome == SpinOutcome::Pass) {
+            n_found.fetch_add(1, Ordering::SeqCst);
+        }
+        Some(result)
+    };
+
+    if config.parallelism == Parallelism::Sequential {
+        let enclosure = runner::create_enclosure(workdir.path())?;
+        let mut results = Vec::new();
+        for (position, (i, algo)) in queue {
+            let result = verify_unless_found(&|| {
+                verify_one(&enclosure, i, algo.clone(), variants, config, observer)
+            });
+            if let Some(ref result) = result {
+                on_result(result)?;
+            }
+            results.push((position, result));
+        }
+        return Ok(in_positions(results));
+    }
+
+    let progress = if observer.shows_progress() {
+        ProgressBar::hidden()
+    } else {
+        ProgressBar::new(num_algos)
+    };
+    let space = WorkdirSpace::new(config.min_free_space);
+    let verify_item = |(position, (i, algo)): (usize, (usize, Algorithm))| {
+        let result = verify_unless_found(&|| {
+            with_enclosure_do(workdir.path(), &space, |thread_enclosure| {
+                verify_one(
+                    thread_enclosure,
+                    i,
+                    algo.clone(),
+                    variants,
+                    config,
+                    observer,
+                )
+            })
+        });
+        (position, result)
+    };
+    // the results are sent to the calling thread as soon as they are obtained
+    let (sender, receiver) = std::sync::mpsc::channel();
+    let verify = move || {
+        let send = |result| {
+            // the receiver outlives the verification
+            let _ = sender.send(result);
+        };
+        match config.order {
+            Order::Index => queue
+                .into_par_iter()
+                .map(verify_item)
+                .progress_with(progress)
+                .for_each(send),
+            // the threads take the algorithms in the order of the queue, instead of splitting it into chunks
+            Order::Promising => queue
+                .into_iter()
+                .par_bridge()
+                .map(verify_item)
+                .progress_with(progress)
+                .for_each(send),
+        }
+    };
+    let pool = match config.parallelism {
+        Parallelism::Threads(n) => Some(
+            rayon::ThreadPoolBuilder::new()
+                .num_threads(n)
+                .build()
+                .context("failed to create thread pool")?,
+        ),
+        _ => None,
+    };
+    let mut results = Vec::new();
+    let mut outcome = Ok(());
+    std::thread::scope(|scope| {
+        let worker = scope.spawn(|| match pool {
+            Some(pool) => pool.install(verify),
+            None => verify(),
+        });
+        for (position, result) in receiver.iter() {
+            if let Some(ref result) = result {
+                if outcome.is_ok() {
+                    outcome = on_result(result);
+                    stopped.store(outcome.is_err(), Ordering::SeqCst);
+                }
+            }
+            results.push((position, result));
+        }
+        if let Err(panic) = worker.join() {
+            std::panic::resume_unwind(panic);
+        }
+    });
+    space.close()?;
+    outcome?;
+    Ok(in_positions(results))
+}
+
+/// verifies one algorithm for each variant in the given enclosure, and notifies the observer.
+/// With the prefilter of the configuration, the model checker is only run for the variants
+/// that the simulator does not falsify. With escalation (see [SynthesisConfig::escalate]), the variants are first
+/// verified under the weaker schedulers of [Scheduler::escalation], and the algorithm fails as soon as all of them
+/// fail under one of these.
+fn verify_one(
+    enclosure: &Path,
+    index: usize,
+    algo: Algorithm,
+    variants: &[ModelRunOptions],
+    config: &SynthesisConfig,
+    observer: &dyn RunObserver,
+) -> Result<Verified> {
+    let start = Instant::now();
+    let verifier = config.backend.verifier(enclosure, &config.pan_build);
+    let mut n_falsified = 0;
+    let mut tool_output = None;
+    let mut violations = vec![None; variants.len()];
+    let mut escalated_fail = None;
+    let mut verify_variants = |variants: &[ModelRunOptions]| -> Result<Vec<SpinOutcome>> {
+        n_falsified = 0;
+        variants
+            .iter()
+            .zip(violations.iter_mut())
+            .map(|(&options, violation)| {
+                if let Some(counterexample) = config
+                    .prefilter
+                    .then(|| simulator::falsify(&algo, options))
+                    .flatten()
+                {
+                    log::debug!("falsified by simulation: {counterexample}");
+                    n_falsified += 1;
+                    return Ok(SpinOutcome::Fail);
+                }
+                let outcome =
+                    verifier
+                        .verify(&algo, &options)
+                        .map_err(|e| match config.save_failures {
+                            Some(ref dir) => save_failure(enclosure, dir, &algo, options, e),
+                            None => e,
+                        })?;
+                if outcome == SpinOutcome::SearchIncomplete || outcome == SpinOutcome::OutOfMemory {
+                    tool_output = runner::read_pan_output(enclosure)?;
+                }
+                if outcome.is_fail() && config.backend.uses_spin(options) {
+                    *violation = runner::read_violation(enclosure)?;
+                    if let Some(ref dir) = config.save_trails {
+                        save_trail(enclosure, dir, &algo, options)?;
+                    }
+                }
+                Ok(outcome)
+            })
+            .collect::<Result<Vec<_>>>()
+    };
+    let outcomes = logging::with_algorithm(index, &algo, || {
+        let stages = match (config.escalate, variants.first()) {
+            (true, Some(options)) => options.scheduler.escalation(),
+            _ => Vec::new(),
+        };
+        for stage in stages {
+            let stage_variants = variants
+                .iter()
+                .map(|&options| ModelRunOptions {
+                    scheduler: stage,
+                    ..options
+                })
+                .collect::<Vec<_>>();
+            let outcomes = verify_variants(&stage_variants)?;
+            // each variant also fails under the stronger scheduler
+            if outcomes.iter().all(|&outcome| outcome == SpinOutcome::Fail) {
+                escalated_fail = Some(stage);
+                return Ok(outcomes);
+            }
+        }
+        verify_variants(variants)
+    });
+    let duration = start.elapsed();
+    match outcomes {
+        Ok(outcomes) => {
+            let outcome = best_outcome(&outcomes);
+            observer.on_outcome(index, &algo, outcome, &outcomes);
+            Ok(Verified {
+                gathering_times: gathering_times(&algo, variants, &outcomes),
+                index,
+                algo,
+                outcome,
+                variants: outcomes,
+                duration,
+                prefiltered: n_falsified == variants.len(),
+                tool_output,
+                violations,
+                escalated_fail,
+            })
+        }
+        Err(e) => {
+            observer.on_error(index, &algo, &e);
+            Err(e)
+        }
+    }
+}
+
+/// name of the files of a verification in the archives of trails and failures: `<scheduler>_<variant>`.
+fn archive_name(options: ModelRunOptions) -> String {
+    format!(
+        "{}_{}",
+        options.scheduler,
+        options.variant_name().replace('/', "-")
+    )
+}
+
+/// archives the trail of a failing verification in `<dir>/<code>/<scheduler>_<variant>.trail`.
+fn save_trail(
+    enclosure: &Path,
+    dir: &Path,
+    algo: &Algorithm,
+    options: ModelRunOptions,
+) -> Result<()> {
+    let name = archive_name(options);
+    if !runner::save_trail(enclosure, &dir.join(algo.as_code()), &name)? {
+        log::warn!("no trail for failing algorithm {} ({name})", algo.as_code());
+    }
+    Ok(())
+}
+
+/// location of the saved output of a verification failing with an error (see [SynthesisConfig::save_failures]),
+/// attached as a context to the error and written in its line of the report.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct FailureSaved(pub PathBuf);
+
+impl std::fmt::Display for FailureSaved {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "failure saved in {}", self.0.display())
+    }
+}
+
+/// saves the output of a verification failing with an error in `<dir>/<code>/` (see [runner::save_failure]), and
+/// returns the error with the location (see [FailureSaved]); errors caused by an interruption are not saved.
+fn save_failure(
+    enclosure: &Path,
+    dir: &Path,
+    algo: &Algorithm,
+    options: ModelRunOptions,
+    error: anyhow::Error,
+) -> anyhow::Error {
+    if interrupt::is_interrupted() {
+        return error;
+    }
+    let archive = dir.join(algo.as_code());
+    match runner::save_failure(enclosure, &archive, &archive_name(options), &error) {
+        Ok(()) => error.context(FailureSaved(archive)),
+        Err(e) => {
+            log::warn!("failed to save the failure of {}: {e:#}", algo.as_code());
+            error
+        }
+    }
+}
+
+/// worst-case gathering time of the algorithm for each variant that passes under a scheduler of the native model
+/// checker (`None` for the other variants).
+fn gathering_times(
+    algo: &Algorithm,
+    variants: &[ModelRunOptions],
+    outcomes: &[SpinOutcome],
+) -> Vec<Option<usize>> {
+    variants
+        .iter()
+        .zip(outcomes)
+        .map(|(&options, &outcome)| {
+            if outcome != SpinOutcome::Pass || !checker::supports_options(options) {
+                return None;
+            }
+            checker::gathering_time(algo, options).unwrap_or_else(|e| {
+                log::warn!(
+                    "failed to measure the gathering time of {}: {e}",
+                    algo.as_code()
+                );
+                None
+            })
+        })
+        .collect()
+}
+
+/// writes the PASS and incomplete algorithms, and the errors, of a verification.
+fn write_outcomes<'a>(
+    output: &mut dyn std::io::Write,
+    verified: impl IntoIterator<Item = &'a Result<Verified>>,
+    variants: &[ModelRunOptions],
+    config: &SynthesisConfig,
+) -> Result<()> {
+    for res in verified {
+        match res {
+            Ok(v) if v.outcome == SpinOutcome::Pass => {
+                writeln!(output, "{:4} : PASS {}", v.index, v.algo.as_code())?;
+                write_variants(output, v.index, variants, &v.variants)?;
+                write_gathering_times(output, v.index, variants, &v.gathering_times)?;
+                if config.pretty {
+                    write_rules(output, v.index, &v.algo)?;
+                }
+                output.flush()?;
+            }
+            Ok(v) if v.outcome == SpinOutcome::SearchIncomplete => {
+                writeln!(
+                    output,
+                    "INCOMPLETE > {:4} : SearchIncomplete {}",
+                    v.index,
+                    v.algo.as_code()
+                )?;
+                write_variants(output, v.index, variants, &v.variants)?;
+                if config.verbose_errors {
+                    write_tool_output(output, v.index, v.tool_output.as_deref())?;
+                }
+                output.flush()?;
+            }
+            Ok(v) if v.outcome == SpinOutcome::OutOfMemory => {
+                writeln!(
+                    output,
+                    "OUT-OF-MEMORY > {:4} : OutOfMemory {}",
+                    v.index,
+                    v.algo.as_code()
+                )?;
+                write_variants(output, v.index, variants, &v.variants)?;
+                if config.verbose_errors {
+                    write_tool_output(output, v.index, v.tool_output.as_deref())?;
+                }
+                output.flush()?;
+            }
+            Ok(v) if config.report_fails && v.outcome.is_fail() => {
+                write_fail(output, v.index, &v.algo)?;
+                write_variants(output, v.index, variants, &v.variants)?;
+                write_violations(output, v.index, variants, &v.violations)?;
+                output.flush()?;
+            }
+            Ok(_) => { /* skip */ }
+            Err(e) => {
+                write_error(output, e)?;
+                if config.verbose_errors {
+                    write_error_output(output, e)?;
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+/// writes the outcomes (see [write_outcomes]) in the order of the listing.
+fn write_sorted_outcomes(
+    output: &mut dyn std::io::Write,
+    verified: &[Result<Verified>],
+    variants: &[ModelRunOptions],
+    config: &SynthesisConfig,
+    sort: ReportSort,
+) -> Result<()> {
+    let mut sorted = verified.iter().collect::<Vec<_>>();
+    sort.sort(&mut sorted);
+    writeln!(output, "Outcomes sorted by {sort}:")?;
+    write_outcomes(output, sorted, variants, config)
+}
+
+/// line of the report with the range of the worst-case gathering times of the PASS algorithms, and the fastest one.
+fn gathering_line(verified: &[Result<Verified>]) -> Option<String> {
+    let times = verified
+        .iter()
+        .filter_map(|res| res.as_ref().ok())
+        .filter_map(|v| v.gathering_time().map(|t| (t, v)))
+        .collect::<Vec<_>>();
+    let &(fastest, v) = times.iter().min_by_key(|(t, v)| (*t, v.index))?;
+    let slowest = times.iter().map(|(t, _)| *t).max().unwrap_or(fastest);
+    Some(format!(
+        "Gathering time: {fastest} to {slowest} epochs in the worst case ({} PASS algorithms), fastest {} {}",
+        times.len(),
+        v.index,
+        v.algo.as_code()
+    ))
+}
+
+/// number of slowest verifications listed in the report.
+const N_SLOWEST: usize = 5;
+
+/// lines of the report with the mean and maximal verification times, and the slowest verifications.
+fn slowest_lines(verified: &[Result<Verified>]) -> Vec<String> {
+    let mut verified = verified
+        .iter()
+        .filter_map(|res| res.as_ref().ok())
+        .collect::<Vec<_>>();
+    if verified.is_empty() {
+        return Vec::new();
+    }
+    verified.sort_by_key(|v| std::cmp::Reverse(v.duration));
+    let total = verified.iter().map(|v| v.duration).sum::<Duration>();
+    let mut lines = vec![format!(
+        "Verification time per algorithm: mean {} ms, max {} ms",
+        (total / verified.len() as u32).as_millis(),
+        verified[0].duration.as_millis()
+    )];
+    lines.extend(verified.iter().take(N_SLOWEST).map(|v| {
+        format!(
+            "SLOW > {:4} : {} ms {} {}",
+            v.index,
+            v.duration.as_millis(),
+            v.outcome,
+            v.algo.as_code()
+        )
+    }));
+    lines
+}
+
+/// writes the outcome of each variant (only if several variants are checked).
+fn write_variants(
+    output: &mut dyn std::io::Write,
+    i: usize,
+    variants: &[ModelRunOptions],
+    outcomes: &[SpinOutcome],
+) -> Result<()> {
+    if variants.len() > 1 {
+        let details = variants
+            .iter()
+            .zip(outcomes)
+            .map(|(v, o)| format!("{} {}", v.variant_name(), o))
+            .collect::<Vec<_>>();
+        writeln!(output, "VARIANTS > {:4} : {}", i, details.join(", "))?;
+    }
+    Ok(())
+}
+
+/// writes the property violated in each failing variant (when known, see [Violation]), one line per variant
+/// prefixed by `VIOLATION >`.
+fn write_violations(
+    output: &mut dyn std::io::Write,
+    i: usize,
+    variants: &[ModelRunOptions],
+    violations: &[Option<Violation>],
+) -> Result<()> {
+    for (variant, violation) in variants.iter().zip(violations) {
+        if let Some(violation) = violation {
+            writeln!(
+                output,
+                "VIOLATION > {:4} : {} {}",
+                i,
+                variant.variant_name(),
+                violation
+            )?;
+        }
+    }
+    Ok(())
+}
+
+/// failing algorithm passing under some of the next weaker schedulers (see [Scheduler::next_weaker]): e.g., an
+/// algorithm solving gathering under SSYNC but not under ASYNC_LC_Strict.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct NearMiss {
+    /// index among the viable algorithms
+    pub index: usize,
+    pub algo: Algorithm,
+    /// weaker schedulers under which the algorithm passes
+    pub schedulers: Vec<Scheduler>,
+}
+
+/// verifies the failing algorithms again under each of the next weaker schedulers, with the same variants, and
+/// returns those passing under some of them. The errors of these verifications are only logged.
+fn check_near_misses(
+    workdir: &runner::Workdir,
+    verified: &[Result<Verified>],
+    scheduler: Scheduler,
+    config: &SynthesisConfig,
+) -> Result<Vec<NearMiss>> {
+    let failing = verified
+        .iter()
+        .flatten()
+        .filter(|v| v.outcome.is_fail())
+        .map(|v| (v.index, v.algo.clone()))
+        .collect::<Vec<_>>();
+    // all failing algorithms are verified, and only the trails of the run itself are archived
+    let config = SynthesisConfig {
+        find: None,
+        save_trails: None,
+        ..config.clone()
+    };
+    let mut near_misses = std::collections::BTreeMap::<usize, NearMiss>::new();
+    for weaker in scheduler.next_weaker() {
+        if failing.is_empty() {
+            break;
+        }
+        info!(
+            "Checking {} failing algorithms under {weaker}",
+            failing.len()
+        );
+        let variants = config.variants_to_check(weaker);
+        for result in verify_all(workdir, failing.clone(), &variants, &config, &())? {
+            match result {
+                Ok(v) if v.outcome == SpinOutcome::Pass => {
+                    near_misses
+                        .entry(v.index)
+                        .or_insert_with(|| NearMiss {
+                            index: v.index,
+                            algo: v.algo,
+                            schedulers: Vec::new(),
+                        })
+                        .schedulers
+                        .push(weaker);
+                }
+                Ok(_) => {}
+                Err(e) => log::warn!("near-miss verification under {weaker}: {e:#}"),
+            }
+        }
+    }
+    Ok(near_misses.into_values().collect())
+}
+
+/// writes the near misses, one line each prefixed by `NEAR-MISS >`.
+fn write_near_misses(
+    output: &mut dyn std::io::Write,
+    near_misses: &[NearMiss],
+    scheduler: Scheduler,
+) -> Result<()> {
+    for near_miss in near_misses {
+        let schedulers = near_miss
+            .schedulers
+            .iter()
+            .map(Scheduler::to_string)
+            .collect::<Vec<_>>();
+        writeln!(
+            output,
+            "NEAR-MISS > {:4} : passes under {} but not {scheduler} {}",
+            near_miss.index,
+            schedulers.join(", "),
+            near_miss.algo.as_code()
+        )?;
+    }
+    output.flush()?;
+    Ok(())
+}
+
+/// writes the raw output of pan for an incomplete verification, one line prefixed by `OUTPUT >` per line.
+fn write_tool_output(
+    output: &mut dyn std::io::Write,
+    index: usize,
+    tool_output: Option<&str>,
+) -> Result<()> {
+    for line in tool_output.unwrap_or_default().lines() {
+        writeln!(output, "OUTPUT > {index:4} : {line}")?;
+    }
+    Ok(())
+}
+
+/// writes the raw output of the tool that failed (if any, see [ToolError]), one line prefixed by `ERROR >` per line.
+/// writes the line of an error, followed by the location of its saved output, if any (see [FailureSaved]).
+fn write_error(output: &mut dyn std::io::Write, error: &anyhow::Error) -> Result<()> {
+    match error.downcast_ref::<FailureSaved>() {
+        Some(saved) => {
+            let causes = error.chain().skip(1).map(|e| e.to_string());
+            writeln!(
+                output,
+                "ERROR : {} ({saved})",
+                causes.collect::<Vec<_>>().join(": ")
+            )?;
+        }
+        None => writeln!(output, "ERROR : {:?}", error)?,
+    }
+    Ok(())
+}
+
+pub fn write_error_output(output: &mut dyn std::io::Write, error: &anyhow::Error) -> Result<()> {
+    if let Some(tool_error) = error.chain().find_map(|e| e.downcast_ref::<ToolError>()) {
+        for line in tool_error.output().lines() {
+            writeln!(output, "ERROR > {line}")?;
+        }
+    }
+    Ok(())
+}
+
+/// writes the line of a failing algorithm (listed when the report is parsed, see [crate::report::Entry::parse]).
+fn write_fail(output: &mut dyn std::io::Write, index: usize, algo: &Algorithm) -> Result<()> {
+    writeln!(output, "FAIL > {index:4} : FAIL {}", algo.as_code())?;
+    Ok(())
+}
+
+/// writes the rules of an algorithm in human-readable form, one per line (see [Algorithm::to_pretty_table]).
+fn write_rules(output: &mut dyn std::io::Write, i: usize, algo: &Algorithm) -> Result<()> {
+    for line in algo.to_pretty_table().lines() {
+        writeln!(output, "RULES > {:4} : {}", i, line)?;
+    }
+    Ok(())
+}
+
+/// writes the worst-case gathering times of an algorithm, if measured.
+fn write_gathering_times(
+    output: &mut dyn std::io::Write,
+    i: usize,
+    variants: &[ModelRunOptions],
+    times: &[Option<usize>],
+) -> Result<()> {
+    let time = |t: &Option<usize>| t.map_or("-".to_string(), |t| format!("{t} epochs"));
+    match times {
+        [] => {}
+        _ if times.iter().all(Option::is_none) => {}
+        [t] => writeln!(output, "GATHERING > {:4} : {}", i, time(t))?,
+        _ => {
+            let details = variants
+                .iter()
+                .zip(times)
+                .map(|(v, t)| format!("{} {}", v.variant_name(), time(t)))
+                .collect::<Vec<_>>();
+            writeln!(output, "GATHERING > {:4} : {}", i, details.join(", "))?;
+        }
+    }
+    Ok(())
+}
+
+/// counts the outcomes of a verification and writes the report of the run
+/// (verification summary, PASS equivalence classes, timing report), with the time of the report taken now.
+/// The outcome of each algorithm is not written.
+pub fn report(
+    output: &mut dyn std::io::Write,
+    config: &SynthesisConfig,
+    model: Model,
+    variants: &[ModelRunOptions],
+    verified: Vec<Result<Verified>>,
+    timings: Timings,
+) -> Result<RunResult> {
+    let t_report = timings.elapsed();
+    let scheduler = variants
+        .first()
+        .map(|v| v.scheduler)
+        .unwrap_or(Scheduler::ASYNC);
+
+    let count = |outcome: SpinOutcome| {
+        verified
+            .iter()
+            .filter_map(|res| res.as_ref().ok())
+            .filter(|v| v.outcome == outcome)
+            .count()
+    };
+    let n_algos = verified.len();
+    let n_errors = verified.iter().filter(|res| res.is_err()).count();
+    let n_pass = count(SpinOutcome::Pass);
+    let n_fail = count(SpinOutcome::Fail);
+    // out of memory is also inconclusive: counted with the incomplete algorithms, and detailed on its own line
+    let n_out_of_memory = count(SpinOutcome::OutOfMemory);
+    let n_incomplete = count(SpinOutcome::SearchIncomplete) + n_out_of_memory;
+    let n_prefiltered = verified
+        .iter()
+        .filter_map(|res| res.as_ref().ok())
+        .filter(|v| v.prefiltered)
+        .count();
+    let n_escalated = Scheduler::iter()
+        .map(|scheduler| {
+            let n = verified
+                .iter()
+                .filter_map(|res| res.as_ref().ok())
+                .filter(|v| v.escalated_fail == Some(scheduler))
+                .count();
+            (scheduler, n)
+        })
+        .filter(|&(_, n)| n > 0)
+        .collect::<Vec<_>>();
+    let n_pass_variants = (0..variants.len())
+        .map(|k| {
+            verified
+                .iter()
+                .filter_map(|res| res.as_ref().ok())
+                .filter(|v| v.variants.get(k) == Some(&SpinOutcome::Pass))
+                .count()
+        })
+        .collect::<Vec<_>>();
+    let verification_times = verified
+        .iter()
+        .filter_map(|res| res.as_ref().ok())
+        .map(|v| (v.index, v.duration))
+        .collect::<Vec<_>>();
+    let slowest = slowest_lines(&verified);
+    let gathering = gathering_line(&verified);
+    let pass_algos = verified
+        .into_iter()
+        .filter_map(|res| res.ok())
+        .filter(|v| v.outcome == SpinOutcome::Pass)
+        .map(|v| (v.index, v.algo))
+        .collect::<Vec<_>>();
+
+    // output verification summary
+    writeln!(output, "Verification Finished with {n_pass} pass, {n_fail} fail, {n_incomplete} incomplete, {n_errors} errors ({n_algos} algorithms)")?;
+    if variants.len() > 1 {
+        let details = variants
+            .iter()
+            .zip(n_pass_variants.iter())
+            .map(|(v, n)| format!("{}: {n} pass", v.variant_name()))
+            .collect::<Vec<_>>();
+        writeln!(output, "Variants: {}", details.join(", "))?;
+    }
+    if config.offset > 0 || config.limit.is_some() {
+        let limit = config
+            .limit
+            .map_or(String::new(), |n| format!(", {n} at most"));
+        writeln!(
+            output,
+            "Slice: viable algorithms from index {}{limit}",
+            config.offset
+        )?;
+    }
+    let sample_short = config.sample.filter(|&n| {
+        config.offset + n_algos < n
+            && config.limit.is_none()
+            && config.find.is_none()
+            && !interrupt::is_interrupted()
+    });
+    if let Some(n) = sample_short {
+        writeln!(
+            output,
+            "Sample: {} viable algorithms found in {} draws, fewer than the {n} requested (see --sample-draws)",
+            config.offset + n_algos,
+            config.max_sample_draws(n)
+        )?;
+    }
+    if let Some(n) = config.find.filter(|&n| n_pass >= n) {
+        writeln!(
+            output,
+            "Early stop: {n_pass} PASS algorithms found, {n} requested with --find (the remaining algorithms, if any, were not verified)"
+        )?;
+    }
+    let resume = interrupt::is_interrupted().then(|| {
+        resume_offset(
+            config.offset,
+            verification_times.iter().map(|(index, _)| *index),
+        )
+    });
+    if let Some(resume) = resume {
+        writeln!(
+            output,
+            "Interrupted ({}): {n_algos} algorithms verified before the interruption (the remaining algorithms, if any, were not verified); resume with --offset {resume}",
+            interrupt::reason()
+        )?;
+    }
+    if n_out_of_memory > 0 {
+        writeln!(
+            output,
+            "Out of memory: {n_out_of_memory} of the incomplete algorithms (pan reached its memory limit, see --memlim)"
+        )?;
+    }
+    if n_prefiltered > 0 {
+        writeln!(
+            output,
+            "Prefilter: {n_prefiltered} algorithms falsified by simulation (spin not run)"
+        )?;
+    }
+    if !n_escalated.is_empty() {
+        let details = n_escalated
+            .iter()
+            .map(|(scheduler, n)| format!("{n} under {scheduler}"))
+            .collect::<Vec<_>>();
+        writeln!(
+            output,
+            "Escalation: algorithms failing under a weaker scheduler (not verified further): {}",
+            details.join(", ")
+        )?;
+    }
+    if let Some(line) = gathering {
+        writeln!(output, "{line}")?;
+    }
+    for line in slowest {
+        writeln!(output, "{line}")?;
+    }
+
+    if config.group_classes {
+        let classes = group_by_class(pass_algos.clone());
+        writeln!(
+            output,
+            "\nPASS equivalence classes (color permutations): {} classes",
+            classes.len()
+        )?;
+        for (representative, members) in classes.iter() {
+            writeln!(
+                output,
+                "CLASS > {:4} : {} ({} algorithms)",
+                members[0],
+                representative.as_code(),
+                members.len()
+            )?;
+        }
+    }
+
+    if config.stats {
+        let stats = AlgorithmStatistics::new(pass_algos.iter().map(|(_, algo)| algo));
+        writeln!(output, "\nPASS statistics ({} algorithms):", stats.n_algos)?;
+        stats.write(output)?;
+    }
+
+    if config.classify {
+        let classes = classify::classify(&pass_algos);
+        writeln!(
+            output,
+            "\nPASS structural classes: {} classes",
+            classes.len()
+        )?;
+        for (features, members) in classes.iter() {
+            let (i, example) = pass_algos
+                .iter()
+                .find(|(i, _)| *i == members[0])
+                .expect("member of the PASS algorithms");
+            writeln!(
+                output,
+                "FEATURES > {:5} algorithms : {} (e.g., {} {})",
+                members.len(),
+                classify::feature_names(features),
+                i,
+                example.as_code()
+            )?;
+        }
+    }
+
+    // output time report:
+    // express all durations in millis
+    let t_prepare = timings.prepare.as_millis();
+    let t_gen = timings.generate.as_millis();
+    let t_verif = timings.verify.as_millis();
+    let t_cleanup = timings.cleanup.as_millis();
+    let t_report = t_report.as_millis();
+    // compute intervals
+    let delta_prepare = t_prepare;
+    let delta_gen = t_gen - t_prepare;
+    let delta_verif = t_verif - t_gen;
+    let delta_cleanup = t_cleanup - t_verif;
+    let delta_report = t_report - t_cleanup;
+    writeln!(output, "\nTiming report (Total: {} ms):", t_report)?;
+    writeln!(
+        output,
+        "| unit: ms       | prepare | generate | verify | cleanup | report |"
+    )?;
+    writeln!(
+        output,
+        "| -------------- | ------- | -------- | ------ | ------- | ------ |"
+    )?;
+    writeln!(
+        output,
+        "| **cumulative** | {} | {} | {} | {} | {} |",
+        t_prepare, t_gen, t_verif, t_cleanup, t_report
+    )?;
+    writeln!(
+        output,
+        "| **additive** | {} | {} | {} | {} | {} |",
+        delta_prepare, delta_gen, delta_verif, delta_cleanup, delta_report
+    )?;
+    output.flush()?;
+
+    let mut summary = RunResult::new(model, scheduler);
+    summary.n_algos = n_algos;
+    summary.n_pass = n_pass;
+    summary.n_fail = n_fail;
+    summary.n_incomplete = n_incomplete;
+    summary.n_out_of_memory = n_out_of_memory;
+    summary.n_errors = n_errors;
+    summary.pass = pass_algos;
+    summary.variants_pass = n_pass_variants;
+    summary.verification_times = verification_times;
+    summary.resume_offset = resume;
+    summary.duration = timings.elapsed();
+    Ok(summary)
+}
+
+/// reads the algorithms of the model listed in a text: either one code string per line,
+/// or the report of a previous run (whose PASS algorithms are read).
+/// Empty lines, comments (starting with `#`), and other lines of a report are skipped.
+pub fn read_algorithm_codes(text: &str, model: Model) -> Result<Vec<Algorithm>> {
+    use lazy_regex::regex_is_match;
+    let mut algos = Vec::new();
+    for (n, line) in text.lines().enumerate() {
+        let entry = crate::report::Entry::parse(line)
+            .with_context(|| format!("line {}: invalid entry of a report", n + 1))?;
+        let code = if let Some(ref entry) = entry {
+            if entry.outcome != SpinOutcome::Pass {
+                continue;
+            }
+            entry.code.as_str()
+        } else if regex_is_match!(r"^\s*(?:[FEI]\d+L?:)?[0-9sdSOH_]*__[0-9sdSOH_]+\s*$", line) {
+            line.trim()
+        } else {
+            continue;
+        };
+        let algo = Algorithm::try_parse(model.category, model.n_colors, model.class_L, code)
+            .with_context(|| format!("line {}: invalid algorithm for model {model}", n + 1))?;
+        algos.push(algo);
+    }
+    Ok(algos)
+}
+
+/// verifies the given algorithms of the model (instead of the generated ones) with the parallelism of the configuration,
+/// and writes the report of the run (outcomes, summary, timing report) to the output.
+/// The algorithms are numbered in the order of the list, starting from 0.
+pub fn verify_list(
+    output: &mut dyn Write,
+    config: &SynthesisConfig,
+    model: Model,
+    scheduler: Scheduler,
+    algos: Vec<Algorithm>,
+    observer: &dyn RunObserver,
+) -> Result<RunResult> {
+    let enter = |phase: Phase| observer.on_phase_change(model, scheduler, phase);
+    let variants = config.variants_to_check(scheduler);
+    throttle::set_memory_limits(config.memory_limits);
+
+    enter(Phase::Prepare);
+    let mut timings = Timings::start();
+    let workdir = prepare_checked_workdirs(config, model, scheduler)?;
+    timings.prepare = timings.elapsed();
+
+    enter(Phase::Generate);
+    let algos = algos.into_iter().enumerate().collect::<Vec<_>>();
+    for (i, algo) in algos.iter() {
+        observer.on_generated(*i, algo);
+    }
+    timings.generate = timings.elapsed();
+
+    info!("Starting verification of {} algorithms", algos.len());
+    enter(Phase::Verify);
+    let verified = verify_all_streaming(
+        &workdir,
+        algos,
+        &variants,
+        config,
+        observer,
+        &mut |result| match config.sort {
+            Some(_) => Ok(()),
+            None => write_outcomes(output, std::slice::from_ref(result), &variants, config),
+        },
+    );
+    timings.verify = timings.elapsed();
+
+    enter(Phase::Cleanup);
+    let cleanup_outcome = runner::close_workdir(workdir);
+    timings.cleanup = timings.elapsed();
+
+    let verified = verified?;
+    if let Some(sort) = config.sort {
+        write_sorted_outcomes(output, &verified, &variants, config, sort)?;
+    }
+    enter(Phase::Report);
+    let summary = report(output, config, model, &variants, verified, timings)?;
+    enter(Phase::Done);
+
+    // delayed reporting of the cleanup error (the report is written first)
+    cleanup_outcome.map(|_| summary)
+}
+
+/// counts the viable algorithms, verifies `calibration` of them (evenly spaced) in a single enclosure,
+/// and writes the estimated cost of verifying all of them with the parallelism of the configuration.
+fn dry_run(
+    output: &mut dyn std::io::Write,
+    config: &SynthesisConfig,
+    model: Model,
+    variants: &[ModelRunOptions],
+    extra_filters: &[&dyn ViabilityFilter],
+    observer: &dyn RunObserver,
+    calibration: usize,
+) -> Result<Estimate> {
+    let scheduler = variants
+        .first()
+        .map(|v| v.scheduler)
+        .unwrap_or(Scheduler::ASYNC);
+    let enter = |phase: Phase| observer.on_phase_change(model, scheduler, phase);
+
+    enter(Phase::Generate);
+    let mut n_algos = 0;
+    let mut samples = Vec::new();
+    let all_viable_algos = generate_and_filter(config, model, extra_filters)
+        .inspect(|(i, algo)| observer.on_generated(*i, algo));
+    for (i, algo) in all_viable_algos {
+        n_algos += 1;
+        samples.push((i, algo));
+    }
+    let samples = estimate::calibration_indices(n_algos, calibration)
+        .into_iter()
+        .map(|k| samples[k].clone())
+        .collect::<Vec<_>>();
+
+    info!("Calibrating on {} verifications", samples.len());
+    let workdir = prepare_workdirs(config, scheduler)?;
+    let enclosure = runner::create_enclosure(workdir.path());
+    enter(Phase::Verify);
+    let calibrated = enclosure.and_then(|enclosure| {
+        let mut durations = Vec::new();
+        let mut enclosure_bytes = 0;
+        for (i, algo) in samples {
+            let start = Instant::now();
+            verify_one(&enclosure, i, algo, variants, config, observer)?;
+            durations.push(start.elapsed());
+            enclosure_bytes = enclosure_bytes.max(estimate::dir_size(&enclosure)?);
+        }
+        Ok((durations, enclosure_bytes))
+    });
+    enter(Phase::Cleanup);
+    let cleanup_outcome = runner::close_workdir(workdir);
+    let (durations, enclosure_bytes) = calibrated?;
+    cleanup_outcome?;
+
+    let threads = match config.parallelism {
+        Parallelism::Sequential => 1,
+        Parallelism::Parallel => rayon::current_num_threads(),
+        Parallelism::Threads(n) => n,
+    };
+    enter(Phase::Report);
+    let estimate = Estimate::new(n_algos, &durations, threads, enclosure_bytes);
+    estimate.write(output)?;
+    output.flush()?;
+    Ok(estimate)
+}
+
+/// runs the local search (see [search]) and reports the algorithms found to pass.
+fn run_search(
+    output: &mut dyn std::io::Write,
+    workdir: &runner::Workdir,
+    model: Model,
+    search_options: SearchOptions,
+    model_run_options: ModelRunOptions,
+    pan_build: &PanBuildOptions,
+) -> Result<Vec<(usize, Algorithm)>> {
+    let enclosure = runner::create_enclosure(workdir.path())?;
+    let spin = runner::SpinBackend::new(&enclosure).build_options(pan_build.clone());
+    let ladder = search::scheduler_ladder(model_run_options.scheduler);
+    info!("Starting local search (ladder: {:?})", ladder);
+    let found = search::hill_climb(
+        model.category,
+        model.n_colors,
+        model.class_L,
+        search_options,
+        ladder.len(),
+        |algo| search::spin_score(&spin, algo, &ladder, model_run_options),
+    )?;
+    for (i, algo) in found.iter().enumerate() {
+        writeln!(output, "{:4} : PASS {}", i, algo.as_code())?;
+    }
+    writeln!(
+        output,
+        "Search Finished with {} pass ({} restarts)",
+        found.len(),
+        search_options.restarts
+    )?;
+    output.flush()?;
+    Ok(found.into_iter().enumerate().collect())
+}
+
+/// returns the best outcome among those obtained for the variants of the model:
+/// an algorithm passes if it passes at least one variant.
+fn best_outcome(outcomes: &[SpinOutcome]) -> SpinOutcome {
+    outcomes.iter().copied().max().unwrap_or(SpinOutcome::Fail)
+}
+
+/// groups algorithms by equivalence class under color permutation.
+/// Each class is given by its canonical representative and the (sorted) indices of its members,
+/// and classes are ordered by the index of their first member.
+pub fn group_by_class(algos: Vec<(usize, Algorithm)>) -> Vec<(Algorithm, Vec<usize>)> {
+    use std::collections::BTreeMap;
+    let mut classes: BTreeMap<Algorithm, Vec<usize>> = BTreeMap::new();
+    for (i, algo) in algos {
+        classes.entry(algo.canonical()).or_default().push(i);
+    }
+    let mut classes = classes
+        .into_iter()
+        .map(|(repr, mut members)| {
+            members.sort_unstable();
+            (repr, members)
+        })
+        .collect::<Vec<_>>();
+    classes.sort_by_key(|(_, members)| members[0]);
+    classes
+}
+
+fn system_info() -> String {
+    duct::cmd!("uname", "-a")
+        .read()
+        .unwrap_or("<undetermined>".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::algorithm::*;
+    use crate::common::*;
+    use crate::generator::tests::*;
+    use crate::ModelKind;
+
+    #[test]
+    fn test_config() {
+        let f2 = Model::from((ModelKind::Full, 2, false));
+        let e3l = Model::from((ModelKind::External, 3, true));
+        let config = SynthesisConfig::new(f2)
+            .models([f2, e3l])
+            .schedulers([Scheduler::SSYNC, Scheduler::ASYNC])
+            .rigid(true)
+            .parallelism(Parallelism::Threads(2));
+        assert_eq!(
+            config.runs(),
+            vec![
+                (f2, Scheduler::SSYNC),
+                (f2, Scheduler::ASYNC),
+                (e3l, Scheduler::SSYNC),
+                (e3l, Scheduler::ASYNC),
+            ]
+        );
+        let options = config.run_options(Scheduler::SSYNC);
+        assert!(options.rigid && !options.quasi_ss);
+        assert_eq!(config.workdir.ramdisk(), None);
+    }
+
+    #[test]
+    fn test_output_mode() {
+        let dir = std::env::temp_dir().join(format!("output-mode-{}", uuid::Uuid::new_v4()));
+        let path = dir.join("results").join("report.txt");
+        let output = ReportOutput::File(path.clone());
+        let write = |mode, text: &str| {
+            let mut file = open_output(&output, mode)?;
+            write!(file, "{text}")?;
+            anyhow::Ok(())
+        };
+        assert!(check_output(&output, OutputMode::CreateNew).is_ok());
+        write(OutputMode::CreateNew, "first").unwrap();
+        let error = check_output(&output, OutputMode::CreateNew).unwrap_err();
+        assert!(error.to_string().contains("--force"));
+        assert!(write(OutputMode::CreateNew, "again").is_err());
+        assert!(check_output(&output, output_mode(true, false)).is_ok());
+
+        write(output_mode(false, true), " second").unwrap();
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "first second");
+        write(output_mode(true, false), "third").unwrap();
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "third");
+
+        // additional JSON lines sink: the existing text file is still checked
+        let json = dir.join("report.jsonl");
+        let sinks = output.clone().with_sinks([SinkSpec::new(
+            SinkTarget::File(json.clone()),
+            SinkFormat::Jsonl,
+        )]);
+        assert_eq!(sinks.sinks().len(), 2);
+        assert!(check_output(&sinks, OutputMode::CreateNew).is_err());
+        let mut file = open_output(&sinks, output_mode(true, false)).unwrap();
+        writeln!(file, "fourth").unwrap();
+        drop(file);
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "fourth\n");
+        assert_eq!(
+            std::fs::read_to_string(&json).unwrap(),
+            "{\"line\":\"fourth\"}\n"
+        );
+        assert_eq!(output.clone().with_sinks([]), output);
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_workdir_space() {
+        let workdir = runner::create_temp_workdir().unwrap();
+        let root = workdir.path();
+        let mut enclosure = runner::create_enclosure(root).unwrap();
+        let original = enclosure.clone();
+
+        // enough space: unchanged
+        let space = WorkdirSpace::new(Some(0));
+        space.relieve(root, &mut enclosure).unwrap();
+        assert_eq!(enclosure, original);
+        space.close().unwrap();
+
+        // never enough space: moved to the spill directory, removed when closed
+        let space = WorkdirSpace::new(Some(usize::MAX));
+        space.relieve(root, &mut enclosure).unwrap();
+        assert!(!enclosure.starts_with(root) && !original.exists());
+        assert!(enclosure.join("MainGathering.pml").is_file());
+        let moved = enclosure.clone();
+        space.relieve(root, &mut enclosure).unwrap();
+        assert_eq!(enclosure, moved);
+        space.close().unwrap();
+        assert!(!moved.exists());
+        runner::close_workdir(workdir).unwrap();
+    }
+
+    #[test]
+    fn test_execute_list_only() {
+        let config = SynthesisConfig::new(Model::from((ModelKind::Full, 2, true)))
+            .models([
+                Model::from((ModelKind::Full, 2, true)),
+                Model::from((ModelKind::External, 3, true)),
+            ])
+            .list_only(true);
+        let single_color = crate::filter::FnFilter::new("single-color", |a: &Algorithm| {
+            a.rules().all(|r| r.action().color().0 == 0)
+        });
+        let mut report = Vec::new();
+        let result = SynthesisRun::new(config.clone())
+            .execute_with_output(&mut report)
+            .unwrap();
+        let n_listed = result.runs.iter().map(|r| r.n_algos).collect::<Vec<_>>();
+        assert_eq!(n_listed, vec![294, 6]);
+        let report = String::from_utf8(report).unwrap();
+        assert!(report.contains("SWEEP > Model E3L / ASYNC"));
+        assert!(report.contains("Listed 294 viable algorithms"));
+
+        // slices keep the indices of the whole enumeration
+        let model = Model::from((ModelKind::Full, 2, true));
+        let all = generate_and_filter(&config, model, &[]).collect::<Vec<_>>();
+        let sliced = config.clone().slice(100, Some(50));
+        let slice = generate_and_filter(&sliced, model, &[]).collect::<Vec<_>>();
+        assert_eq!(slice, all[100..150]);
+        let tail = config.clone().slice(290, None);
+        assert_eq!(generate_and_filter(&tail, model, &[]).count(), 4);
+
+        let result = SynthesisRun::new(config.output(ReportOutput::Discard))
+            .filter(&single_color)
+            .execute()
+            .unwrap();
+        assert!(result.runs.iter().all(|r| r.n_algos == 0));
+    }
+
+    #[test]
+    fn test_generation_progress() {
+        let model = Model::from((ModelKind::Full, 2, true));
+        let config = SynthesisConfig::new(model);
+        let single_color = crate::filter::FnFilter::new("single-color", |a: &Algorithm| {
+            a.rules().all(|r| r.action().color().0 == 0)
+        });
+        let extra_filters: &[&dyn ViabilityFilter] = &[&single_color];
+        let progress = GenerationProgress::new(&config, model, extra_filters, true);
+        let n_viable =
+            generate_and_filter_with_progress(&config, model, extra_filters, Some(&progress))
+                .count();
+        assert_eq!(
+            n_viable,
+            generate_and_filter(&config, model, extra_filters).count()
+        );
+        assert_eq!(
+            progress.n_enumerated() as u64,
+            generator::count_algorithms_in_model(model.category, model.n_colors, model.class_L)
+        );
+        let stages = progress.stages().collect::<Vec<_>>();
+        assert_eq!(stages.len(), config.filters.filters().len() + 1);
+        assert!(stages.windows(2).all(|w| w[0].1 >= w[1].1));
+        assert_eq!(stages.last(), Some(&("single-color", n_viable)));
+        assert_eq!(stages[stages.len() - 2].1, 294);
+        let eliminated = progress.eliminated().collect::<Vec<_>>();
+        assert_eq!(
+            eliminated.iter().map(|(_, n)| n).sum::<usize>() + n_viable,
+            progress.n_enumerated()
+        );
+        assert_eq!(eliminated.last(), Some(&("single-color", 294 - n_viable)));
+
+        let mut report = Vec::new();
+        progress.finish(&mut report).unwrap();
+        let report = String::from_utf8(report).unwrap();
+        assert!(report.starts_with("Generation: "));
+        assert_eq!(report.lines().count(), stages.len() + 1);
+    }
+
+    #[test]
+    fn test_execute_manifest() {
+        let dir = std::env::temp_dir().join(format!("manifest-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("manifest.txt");
+        let config = SynthesisConfig::new(Model::from((ModelKind::Full, 2, true)))
+            .list_only(true)
+            .output(ReportOutput::Discard);
+        let execute = |config: SynthesisConfig| SynthesisRun::new(config).execute();
+
+        // the manifest covers the whole enumeration, whatever the slice
+        execute(config.clone().slice(10, Some(5)).write_manifest(Some(path.clone()))).unwrap();
+        let manifest = Manifest::read(&path).unwrap();
+        assert_eq!(manifest.hashes.len(), 294);
+        execute(config.clone().slice(100, None).manifest(Some(path.clone()))).unwrap();
+
+        let mut filters = FilterSet::default();
+        filters.enable(Filter::Retain);
+        assert!(execute(config.clone().filters(filters).manifest(Some(path.clone()))).is_err());
+        let sweep = config.models([
+            Model::from((ModelKind::Full, 2, true)),
+            Model::from((ModelKind::External, 3, true)),
+        ]);
+        assert!(execute(sweep.manifest(Some(path.clone()))).is_err());
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_execute_escalate() {
+        let config = SynthesisConfig::new(Model::from((ModelKind::Full, 2, true)))
+            .schedulers([Scheduler::SSYNC])
+            .backend(Backend::Native)
+            .variants(Variants::All)
+            .output(ReportOutput::Discard);
+        let mut report = Vec::new();
+        let escalated = SynthesisRun::new(config.clone().escalate(true))
+            .execute_with_output(&mut report)
+            .unwrap();
+        let result = SynthesisRun::new(config).execute().unwrap();
+        let (run, escalated) = (&result.runs[0], &escalated.runs[0]);
+        assert_eq!(run.pass, escalated.pass);
+        assert_eq!(run.variants_pass, escalated.variants_pass);
+        assert_eq!(run.n_fail, escalated.n_fail);
+        let report = String::from_utf8(report).unwrap();
+        assert!(report.contains(
+            "Escalation: algorithms failing under a weaker scheduler (not verified further): "
+        ));
+    }
+
+    #[test]
+    fn test_execute_report_html() {
+        let path = std::env::temp_dir().join(format!("report-{}.html", uuid::Uuid::new_v4()));
+        let config = SynthesisConfig::new(Model::from((ModelKind::Full, 2, true)))
+            .schedulers([Scheduler::Centralized])
+            .backend(Backend::Native)
+            .output(ReportOutput::Discard)
+            .report_html(Some(path.clone()));
+        let result = SynthesisRun::new(config).execute().unwrap();
+        let html = std::fs::read_to_string(&path).unwrap();
+        assert!(html.starts_with("<!DOCTYPE html>") && html.ends_with("</body></html>\n"));
+        let run = &result.runs[0];
+        assert!(html.contains(&format!(
+            "PASS algorithms: {} / Centralized ({})",
+            run.model, run.n_pass
+        )));
+        // one row per run in the summary, and per PASS algorithm with its diagram
+        assert_eq!(html.matches("<tr><td").count(), 1 + run.n_pass);
+        assert_eq!(
+            html.matches("<details><summary>show</summary>").count(),
+            run.n_pass
+        );
+        for (i, algo) in run.pass.iter() {
+            assert!(html.contains(&format!(
+                "<tr><td>{i}</td><td class=\"code\">{}</td>",
+                algo.as_code()
+            )));
+        }
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn test_execute_sample() {
+        let config = SynthesisConfig::new(Model::from((ModelKind::Full, 2, true)))
+            .scheduler(Scheduler::SSYNC)
+            .backend(Backend::Native)
+            .sample(Some(150), 3)
+            .output(ReportOutput::Discard);
+        let result = SynthesisRun::new(config).execute().unwrap();
+        let run = &result.runs[0];
+        // the sample is filled with viable algorithms, numbered in the order of the draws
+        assert_eq!(run.n_algos, 150);
+        assert!(run.n_pass > 0);
+        assert!(run.pass.iter().all(|(i, _)| *i < 150));
+    }
+
+    #[test]
+    fn test_sample_bounded() {
+        // a sample larger than the model draws each algorithm once, and ends with the viable ones
+        let model = Model::from((ModelKind::Full, 2, true));
+        let total = generator::count_algorithms_in_model(ModelKind::Full, 2, true) as usize;
+        let config = SynthesisConfig::new(model);
+        let viable = generate_and_filter(&config, model, &[])
+            .map(|(_, algo)| algo)
+            .collect::<std::collections::BTreeSet<_>>();
+        let sampled = config.clone().sample(Some(10 * total), 5);
+        let sampled = generate_and_filter(&sampled, model, &[])
+            .map(|(_, algo)| algo)
+            .collect::<Vec<_>>();
+        assert_eq!(sampled.len(), viable.len());
+        assert_eq!(
+            sampled
+                .into_iter()
+                .collect::<std::collections::BTreeSet<_>>(),
+            viable
+        );
+
+        // the draws stop at the bound, even if the sample is not filled
+        let bounded = config.clone().sample(Some(viable.len()), 5).sample_draws(Some(1));
+        assert!(generate_and_filter(&bounded, model, &[]).count() <= 1);
+
+        // a slice of a sample is a slice of the same sequence
+        let sampled = config.clone().sample(Some(10), 5);
+        let all = generate_and_filter(&sampled, model, &[]).collect::<Vec<_>>();
+        let sliced = sampled.slice(3, Some(4));
+        let slice = generate_and_filter(&sliced, model, &[]).collect::<Vec<_>>();
+        assert_eq!(slice, all[3..7]);
+    }
+
+    #[test]
+    fn test_prefilter_sound() {
+        use rayon::prelude::*;
+        // every algorithm of the model falsified by simulation also fails the verification with spin
+        let config = SynthesisConfig::new(Model::from((ModelKind::Full, 2, true)));
+        let algos = generate_and_filter(&config, config.models[0], &[])
+            .map(|(_, algo)| algo)
+            .collect::<Vec<_>>();
+        let workdir = runner::create_temp_workdir().unwrap();
+        for scheduler in [Scheduler::Centralized, Scheduler::SSYNC, Scheduler::ASYNC] {
+            let falsified = algos
+                .iter()
+                .flat_map(|algo| {
+                    config
+                        .variants_to_check(scheduler)
+                        .into_iter()
+                        .filter(|&options| simulator::falsify(algo, options).is_some())
+                        .map(move |options| (algo, options))
+                })
+                .collect::<Vec<_>>();
+            assert!(!falsified.is_empty());
+            falsified.par_iter().for_each_init(
+                || runner::create_enclosure(workdir.path()).unwrap(),
+                |enclosure, &(algo, options)| {
+                    let outcome = runner::SpinBackend::new(&*enclosure)
+                        .verify(algo, &options)
+                        .unwrap();
+                    assert_eq!(
+                        outcome,
+                        SpinOutcome::Fail,
+                        "{} ({})",
+                        algo.as_code(),
+                        options.variant_name()
+                    );
+                },
+            );
+        }
+        runner::close_workdir(workdir).unwrap();
+    }
+
+    #[test]
+    fn test_execute_native() {
+        let config = SynthesisConfig::new(Model::from((ModelKind::Full, 2, true)))
+            .schedulers([Scheduler::Centralized, Scheduler::SSYNC])
+            .backend(Backend::Native)
+            .output(ReportOutput::Discard);
+        assert!(!config.needs_spin(Scheduler::SSYNC) && config.needs_spin(Scheduler::ASYNC));
+        let result = SynthesisRun::new(config.clone()).execute().unwrap();
+        // the order of verification does not change the results
+        for parallelism in [Parallelism::Parallel, Parallelism::Sequential] {
+            let promising = SynthesisRun::new(
+                config
+                    .clone()
+                    .order(Order::Promising)
+                    .parallelism(parallelism),
+            )
+            .execute()
+            .unwrap();
+            for (run, promising) in result.runs.iter().zip(&promising.runs) {
+                assert_eq!(run.pass, promising.pass);
+            }
+            let first = SynthesisRun::new(config.clone().find(Some(1)).parallelism(parallelism))
+                .execute()
+                .unwrap();
+            for (run, first) in result.runs.iter().zip(&first.runs) {
+                assert!(first.n_pass >= 1 && first.n_algos < run.n_algos);
+                assert!(first.pass.iter().all(|p| run.pass.contains(p)));
+            }
+        }
+        let prefiltered = SynthesisRun::new(config.clone().prefilter(true))
+            .execute()
+            .unwrap();
+        for (run, prefiltered) in result.runs.iter().zip(&prefiltered.runs) {
+            assert_eq!(run.n_errors, 0);
+            assert!(run.n_pass > 0 && run.n_fail > 0);
+            // the simulator never falsifies an algorithm that passes
+            assert_eq!(run.pass, prefiltered.pass);
+        }
+
+        // failing algorithms listed (the report still parses) or not even shown as dots
+        for parallelism in [Parallelism::Parallel, Parallelism::Sequential] {
+            let config = config
+                .clone()
+                .scheduler(Scheduler::SSYNC)
+                .parallelism(parallelism);
+            let mut report = Vec::new();
+            let fails = SynthesisRun::new(config.clone().report_fails(true))
+                .execute_with_output(&mut report)
+                .unwrap();
+            let report = String::from_utf8(report).unwrap();
+            assert_eq!(
+                report.lines().filter(|l| l.starts_with("FAIL > ")).count(),
+                fails.runs[0].n_fail
+            );
+            let parsed = crate::report::Report::parse(&report).unwrap();
+            let listed = |outcome| {
+                parsed.runs[0]
+                    .entries
+                    .iter()
+                    .filter(|e| e.outcome == outcome)
+                    .count()
+            };
+            assert_eq!(listed(SpinOutcome::Pass), fails.runs[0].n_pass);
+            assert_eq!(listed(SpinOutcome::Fail), fails.runs[0].n_fail);
+
+            let mut report = Vec::new();
+            SynthesisRun::new(config.clone().quiet(true))
+                .execute_with_output(&mut report)
+                .unwrap();
+            let report = String::from_utf8(report).unwrap();
+            assert!(!report.lines().any(|l| l.starts_with('.')));
+
+            // failing algorithms passing under the centralized scheduler
+            let mut report = Vec::new();
+            let near = SynthesisRun::new(config.near_misses(true))
+                .execute_with_output(&mut report)
+                .unwrap();
+            let near_misses = &near.runs[0].near_misses;
+            let centralized = near_misses
+                .iter()
+                .filter(|n| n.schedulers.contains(&Scheduler::Centralized))
+                .map(|n| (n.index, n.algo.clone()))
+                .collect::<Vec<_>>();
+            assert!(!centralized.is_empty());
+            assert_eq!(
+                centralized.len() + near.runs[0].n_pass,
+                result.runs[0].n_pass
+            );
+            assert!(centralized.iter().all(|p| result.runs[0].pass.contains(p)));
+            let report = String::from_utf8(report).unwrap();
+            assert_eq!(
+                report
+                    .lines()
+                    .filter(|l| l.starts_with("NEAR-MISS > "))
+                    .count(),
+                near_misses.len()
+            );
+            assert!(report.contains(" but not SSYNC "));
+        }
+
+        let mut report = Vec::new();
+        let pretty = SynthesisRun::new(config.scheduler(Scheduler::SSYNC).pretty(true))
+            .execute_with_output(&mut report)
+            .unwrap();
+        let report = String::from_utf8(report).unwrap();
+        let (i, algo) = &pretty.runs[0].pass[0];
+        assert!(report.contains(&format!("RULES > {i:4} : when I am 0 and see 0 → ")));
+        assert_eq!(
+            report.lines().filter(|l| l.starts_with("RULES > ")).count(),
+            pretty.runs[0].n_pass * algo.rules().count()
+        );
+        let parsed = crate::report::Report::parse(&report).unwrap();
+        assert_eq!(parsed.runs[0].entries.len(), pretty.runs[0].n_pass);
+    }
+
+    #[test]
+    fn test_write_fail() {
+        let algo = Algorithm::try_parse(ModelKind::External, 3, true, "0_1_2__S2_H0_O1").unwrap();
+        let mut output = Vec::new();
+        write_fail(&mut output, 12, &algo).unwrap();
+        let line = String::from_utf8(output).unwrap();
+        let entry = crate::report::Entry::parse(line.trim_end())
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            entry,
+            crate::report::Entry {
+                index: 12,
+                outcome: SpinOutcome::Fail,
+                code: algo.as_code(),
+            }
+        );
+        assert_eq!(entry.to_string(), line.trim_end());
+    }
+
+    #[test]
+    fn test_verify_all_streaming() {
+        let model = Model::from((ModelKind::Full, 2, true));
+        let config = SynthesisConfig::new(model)
+            .scheduler(Scheduler::SSYNC)
+            .backend(Backend::Native);
+        let algos = generate_and_filter(&config, model, &[])
+            .take(40)
+            .collect::<Vec<_>>();
+        let variants = config.variants_to_check(Scheduler::SSYNC);
+        let workdir = prepare_workdirs(&config, Scheduler::SSYNC).unwrap();
+        for parallelism in [Parallelism::Parallel, Parallelism::Sequential] {
+            let config = config.clone().parallelism(parallelism);
+            let mut streamed = Vec::new();
+            let verified = verify_all_streaming(
+                &workdir,
+                algos.clone(),
+                &variants,
+                &config,
+                &(),
+                &mut |result| {
+                    streamed.push(result.as_ref().unwrap().index);
+                    Ok(())
+                },
+            )
+            .unwrap();
+            // results in the order of the algorithms, whatever the order of completion
+            let indices = verified
+                .iter()
+                .map(|v| v.as_ref().unwrap().index)
+                .collect::<Vec<_>>();
+            assert_eq!(indices, algos.iter().map(|(i, _)| *i).collect::<Vec<_>>());
+            streamed.sort();
+            assert_eq!(streamed, indices);
+
+            // an error of the callback stops the verification
+            let mut n_streamed = 0;
+            let stopped = verify_all_streaming(
+                &workdir,
+                algos.clone(),
+                &variants,
+                &config,
+                &(),
+                &mut |_| {
+                    n_streamed += 1;
+                    anyhow::bail!("stop")
+                },
+            );
+            assert!(stopped.is_err());
+            assert_eq!(n_streamed, 1);
+        }
+        runner::close_workdir(workdir).unwrap();
+    }
+
+    #[test]
+    fn test_observer() {
+        use std::sync::Mutex;
+
+        #[derive(Default)]
+        struct Recorder {
+            phases: Mutex<Vec<(Model, Phase)>>,
+            generated: Mutex<Vec<usize>>,
+        }
+        impl RunObserver for Recorder {
+            fn on_phase_change(&self, model: Model, _scheduler: Scheduler, phase: Phase) {
+                self.phases.lock().unwrap().push((model, phase));
+            }
+            fn on_generated(&self, index: usize, _algo: &Algorithm) {
+                self.generated.lock().unwrap().push(index);
+            }
+        }
+
+        let f2l = Model::from((ModelKind::Full, 2, true));
+        let e3l = Model::from((ModelKind::External, 3, true));
+        let config = SynthesisConfig::new(f2l)
+            .models([f2l, e3l])
+            .list_only(true)
+            .output(ReportOutput::Discard);
+        let recorder = Recorder::default();
+        SynthesisRun::new(config)
+            .observer(&recorder)
+            .execute()
+            .unwrap();
+        assert_eq!(
+            *recorder.phases.lock().unwrap(),
+            vec![
+                (f2l, Phase::Prepare),
+                (f2l, Phase::Generate),
+                (f2l, Phase::Done),
+                (e3l, Phase::Prepare),
+                (e3l, Phase::Generate),
+                (e3l, Phase::Done),
+            ]
+        );
+        let generated = recorder.generated.lock().unwrap();
+        assert_eq!(generated.len(), 294 + 6);
+        assert_eq!(generated[294..], [0, 1, 2, 3, 4, 5]);
+    }
+
+    #[test]
+    fn test_interrupt_sequential_cleanup() {
+        // the interruption is global to the process: the run is interrupted in a child process running this test,
+        // with its own temporary directory holding the working directory
+        const CHILD_VAR: &str = "SYNTH_LIGHTS_INTERRUPTED_CHILD";
+        if std::env::var_os(CHILD_VAR).is_some() {
+            struct Interrupter;
+            impl RunObserver for Interrupter {
+                fn on_outcome(&self, _: usize, _: &Algorithm, _: SpinOutcome, _: &[SpinOutcome]) {
+                    interrupt::interrupt();
+                }
+            }
+            let config = SynthesisConfig::new(Model::from((ModelKind::Full, 2, true)))
+                .scheduler(Scheduler::SSYNC)
+                .backend(Backend::Native)
+                .parallelism(Parallelism::Sequential)
+                .output(ReportOutput::Discard);
+            let result = SynthesisRun::new(config).observer(&Interrupter).execute();
+            assert!(result.unwrap_err().to_string().starts_with("interrupted"));
+            return;
+        }
+
+        let tmp = std::env::temp_dir().join(format!("interrupted-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&tmp).unwrap();
+        let status = std::process::Command::new(std::env::current_exe().unwrap())
+            .args([
+                "--exact",
+                "synthesis::tests::test_interrupt_sequential_cleanup",
+            ])
+            .env(CHILD_VAR, "1")
+            .env("TMPDIR", &tmp)
+            .stdout(std::process::Stdio::null())
+            .status()
+            .unwrap();
+        let left = std::fs::read_dir(&tmp).unwrap().count();
+        std::fs::remove_dir_all(&tmp).unwrap();
+        assert!(status.success());
+        assert_eq!(left, 0, "working directory left after the interruption");
+    }
+
+    #[test]
+    fn test_report() {
+        let model = Model::from((ModelKind::Full, 2, true));
+        let config = SynthesisConfig::new(model)
+            .variants(Variants::All)
+            .stats(true)
+            .classify(true);
+        let variants = config.variants_to_check(Scheduler::SSYNC);
+        let algos = generate_and_filter(&config, model, &[])
+            .take(3)
+            .collect::<Vec<_>>();
+        use SpinOutcome::*;
+        let verified = |(index, algo): (usize, Algorithm), outcomes: [SpinOutcome; 4]| {
+            Ok(Verified {
+                index,
+                algo,
+                outcome: best_outcome(&outcomes),
+                variants: outcomes.to_vec(),
+                duration: Duration::from_millis(10 * index as u64 + 10),
+                prefiltered: outcomes == [Fail; 4],
+                gathering_times: outcomes.map(|o| (o == Pass).then_some(4)).to_vec(),
+                tool_output: None,
+                violations: vec![],
+                escalated_fail: None,
+            })
+        };
+        let results = vec![
+            verified(algos[0].clone(), [Fail, Fail, Fail, Fail]),
+            verified(algos[1].clone(), [Fail, Pass, SearchIncomplete, Fail]),
+            Err(anyhow::Error::msg("broken")),
+        ];
+
+        // sorted listings, errors last
+        let indices = |sort: ReportSort| {
+            let mut sorted = results.iter().rev().collect::<Vec<_>>();
+            sort.sort(&mut sorted);
+            sorted
+                .iter()
+                .map(|res| res.as_ref().ok().map(|v| v.index))
+                .collect::<Vec<_>>()
+        };
+        let (i0, i1) = (Some(algos[0].0), Some(algos[1].0));
+        assert_eq!(indices(ReportSort::Index), [i0, i1, None]);
+        assert_eq!(indices(ReportSort::Outcome), [i1, i0, None]);
+        assert_eq!(indices(ReportSort::Time), [i1, i0, None]);
+        let mut output = Vec::new();
+        write_sorted_outcomes(
+            &mut output,
+            &results,
+            &variants,
+            &config,
+            ReportSort::Outcome,
+        )
+        .unwrap();
+        let output = String::from_utf8(output).unwrap();
+        assert!(output.starts_with("Outcomes sorted by outcome:\n"));
+        assert!(output.contains("ERROR : broken"));
+
+        // raw output of the tools
+        let mut incomplete =
+            verified(algos[2].clone(), [Fail, SearchIncomplete, Fail, Fail]).unwrap();
+        incomplete.tool_output = Some("Warning: Search not completed\nState-vector 88 byte".into());
+        let tool_error = anyhow::Error::new(ToolError {
+            tool: "pan".into(),
+            code: None,
+            stdout: "pan: out of memory".into(),
+            stderr: String::new(),
+        });
+        let mut output = Vec::new();
+        let verbose = config.clone().verbose_errors(true);
+        write_outcomes(
+            &mut output,
+            &[Ok(incomplete), Err(tool_error)],
+            &variants,
+            &verbose,
+        )
+        .unwrap();
+        let output = String::from_utf8(output).unwrap();
+        assert!(output.contains(
+            "OUTPUT >    2 : Warning: Search not completed\nOUTPUT >    2 : State-vector"
+        ));
+        assert!(output.contains("ERROR : pan was terminated by a signal"));
+        assert!(output.contains("\nERROR > pan: out of memory\n"));
+
+        // property violated by the failing variants
+        let mut failed = verified(algos[0].clone(), [Fail, Fail, Fail, Fail]).unwrap();
+        failed.violations = vec![
+            Some(Violation::Assertion("gathered".into())),
+            None,
+            None,
+            Some(Violation::NonProgressCycle),
+        ];
+        let mut output = Vec::new();
+        let fails = config.clone().report_fails(true);
+        write_outcomes(&mut output, &[Ok(failed)], &variants, &fails).unwrap();
+        let output = String::from_utf8(output).unwrap();
+        let violations = output
+            .lines()
+            .filter(|l| l.starts_with("VIOLATION > "))
+            .collect::<Vec<_>>();
+        assert_eq!(violations.len(), 2);
+        assert!(violations[0].contains(&variants[0].variant_name()));
+        assert!(violations[0].ends_with("gathered"));
+
+        let mut output = Vec::new();
+        let run = report(
+            &mut output,
+            &config,
+            model,
+            &variants,
+            results,
+            Timings::start(),
+        )
+        .unwrap();
+        assert_eq!(run.scheduler, Scheduler::SSYNC);
+        assert_eq!(
+            (
+                run.n_algos,
+                run.n_pass,
+                run.n_fail,
+                run.n_incomplete,
+                run.n_errors
+            ),
+            (3, 1, 1, 0, 1)
+        );
+        assert_eq!(run.pass, vec![algos[1].clone()]);
+        assert_eq!(run.variants_pass, vec![0, 1, 0, 0]);
+        let output = String::from_utf8(output).unwrap();
+        assert!(output.contains(
+            "Verification Finished with 1 pass, 1 fail, 0 incomplete, 1 errors (3 algorithms)"
+        ));
+        assert!(output.contains("Verification time per algorithm: mean 15 ms, max 20 ms"));
+        assert!(output.contains("Prefilter: 1 algorithms falsified by simulation (spin not run)"));
+        assert!(output.contains(&format!(
+            "Gathering time: 4 to 4 epochs in the worst case (1 PASS algorithms), fastest 1 {}",
+            algos[1].1.as_code()
+        )));
+        assert!(output.contains(&format!(
+            "SLOW >    1 : 20 ms PASS {}",
+            algos[1].1.as_code()
+        )));
+        assert_eq!(
+            run.verification_times,
+            vec![
+                (0, Duration::from_millis(10)),
+                (1, Duration::from_millis(20))
+            ]
+        );
+        assert!(output.contains("PASS statistics (1 algorithms):"));
+        assert!(output.contains("PASS structural classes: 1 classes"));
+        assert_eq!(run.statistics().n_algos, 1);
+    }
+
+    #[test]
+    fn test_save_failure() {
+        let base =
+            std::env::temp_dir().join(format!("synth-lights-failure-{}", std::process::id()));
+        let enclosure = base.join("enclosure");
+        std::fs::create_dir_all(&enclosure).unwrap();
+        std::fs::write(
+            enclosure.join(crate::promela::ALGORITHM_FILE),
+            "/* algorithm */",
+        )
+        .unwrap();
+        let algo = Algorithm::try_parse_v2("E3L:0_1_2__S2_H0_O1").unwrap();
+        let options = SynthesisConfig::new(Model::try_from("E3L").unwrap())
+            .variants_to_check(Scheduler::SSYNC)[0];
+        let tool_error = anyhow::Error::new(ToolError {
+            tool: "clang".into(),
+            code: Some(1),
+            stdout: "compiling".into(),
+            stderr: "pan.c: error".into(),
+        });
+        let failures = base.join("failures");
+        let error = save_failure(&enclosure, &failures, &algo, options, tool_error);
+
+        let archive = failures.join(algo.as_code());
+        assert_eq!(
+            error.downcast_ref::<FailureSaved>(),
+            Some(&FailureSaved(archive.clone()))
+        );
+        let name = archive_name(options);
+        let read =
+            |ext: &str| std::fs::read_to_string(archive.join(format!("{name}.{ext}"))).unwrap();
+        assert_eq!(read("stdout"), "compiling");
+        assert_eq!(read("stderr"), "pan.c: error");
+        assert!(read("error").starts_with("clang exited with code 1"));
+        assert!(archive.join(crate::promela::ALGORITHM_FILE).exists());
+
+        let mut output = Vec::new();
+        write_error(&mut output, &error).unwrap();
+        assert_eq!(
+            String::from_utf8(output).unwrap(),
+            format!(
+                "ERROR : clang exited with code 1 (failure saved in {})\n",
+                archive.display()
+            )
+        );
+        std::fs::remove_dir_all(&base).unwrap();
+    }
+
+    #[test]
+    fn test_read_algorithm_codes() {
+        let model = Model::from((ModelKind::External, 3, true));
+        let codes = "# candidates\n0_1_2__S2_H0_O1\n\n0_1_2__S2_H1_O0\n";
+        let algos = read_algorithm_codes(codes, model).unwrap();
+        assert_eq!(algos.len(), 2);
+        assert_eq!(algos[1].as_code(), "0_1_2__S2_H1_O0");
+
+        let report = "Run options: Cli { .. }\n.....\n   4 : PASS 0_1_2__S2_H0_O1\nINCOMPLETE >    5 : SearchIncomplete 0_1_2__S2_H1_O0\nVerification Finished with 1 pass, 4 fail, 1 incomplete, 0 errors (6 algorithms)\n";
+        let algos = read_algorithm_codes(report, model).unwrap();
+        assert_eq!(algos.len(), 1);
+        assert_eq!(algos[0].as_code(), "0_1_2__S2_H0_O1");
+
+        assert!(read_algorithm_codes("0_1__S1_H0", model).is_err());
+
+        let algos = read_algorithm_codes("E3L:0_1_2__S2_H0_O1\n", model).unwrap();
+        assert_eq!(algos[0].as_code(), "0_1_2__S2_H0_O1");
+        assert!(read_algorithm_codes("E4L:0_1_2_3__S2_H0_O1_S0\n", model).is_err());
+        let report = "   4 : PASS E3L:0_1_2__S2_H0_O1\nFAIL >    5 : FAIL E3L:0_1_2__S2_H1_O0\n";
+        let algos = read_algorithm_codes(report, model).unwrap();
+        assert_eq!(algos.len(), 1);
+        assert_eq!(algos[0].as_code(), "0_1_2__S2_H0_O1");
+    }
+
+    #[test]
+    fn test_group_by_class() {
+        let num_colors = 2;
+        let guards = guards_for_full_lights_2_cols();
+        let algo = Algorithm::new(
+            num_colors,
+            &guards,
+            &[
+                Action(Color(0), Move::Stay),
+                Action(Color(0), Move::Stay),
+                Action(Color(1), Move::Stay),
+                Action(Color(1), Move::Stay),
+                Action(Color(1), Move::Stay),
+                Action(Color(0), Move::Stay),
+                Action(Color(1), Move::ToOther),
+                Action(Color(0), Move::ToHalf),
+            ],
+        );
+        let swapped = algo.permute_colors(&[Color(1), Color(0)]);
+        let other = Algorithm::new(num_colors, &guards, &[Action(Color(0), Move::Stay); 8]);
+
+        let classes = group_by_class(vec![(7, swapped), (3, other.clone()), (2, algo.clone())]);
+        assert_eq!(classes.len(), 2);
+        assert_eq!(classes[0], (algo.canonical(), vec![2, 7]));
+        assert_eq!(classes[1], (other, vec![3]));
+    }
+
+    #[test]
+    fn test_resume_offset() {
+        assert_eq!(resume_offset(0, []), 0);
+        assert_eq!(resume_offset(0, [0, 1, 2]), 3);
+        assert_eq!(resume_offset(0, [2, 0, 3]), 1);
+        assert_eq!(resume_offset(10, [10, 11, 13]), 12);
+    }
+
+    #[test]
+    fn test_best_outcome() {
+        use SpinOutcome::*;
+        assert_eq!(best_outcome(&[Fail, Pass, SearchIncomplete, Fail]), Pass);
+        assert_eq!(best_outcome(&[Fail, SearchIncomplete]), SearchIncomplete);
+        assert_eq!(best_outcome(&[Fail]), Fail);
+
+        let options = ModelRunOptions {
+            rigid: true,
+            ..ModelRunOptions::new(Scheduler::SSYNC)
+        };
+        let names = options
+            .variants()
+            .iter()
+            .map(|v| v.variant_name())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            names,
+            ["non-rigid/ss", "non-rigid/qss", "rigid/ss", "rigid/qss"]
+        );
+        assert!(options
+            .variants()
+            .iter()
+            .all(|v| v.scheduler == Scheduler::SSYNC));
+    }
+}
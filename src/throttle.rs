@@ -0,0 +1,156 @@
+//! Memory-aware throttling of the `pan` processes: each pan process may allocate up to its memory limit
+//! (`-DMEMLIM`, see [PanBuildOptions](crate::runner::PanBuildOptions)), so dozens of them running concurrently can
+//! exhaust the memory of the host. The limits set with [set_memory_limits] apply to all the pan processes started by
+//! [crate::runner] in the process, independently of the number of threads of the verification.
+
+use std::sync::{Condvar, Mutex};
+use std::time::Duration;
+
+/// interval between two checks of the free memory while waiting for a pan process to finish.
+const POLL_INTERVAL: Duration = Duration::from_millis(200);
+
+/// limits on the pan processes running concurrently (none by default).
+/// A pan process is always started when no other is running, whatever the limits.
+#[derive(clap::Args, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct MemoryLimits {
+    /// Maximal total memory of the pan processes running concurrently, in MB (each counts for its memory limit, see --memlim)
+    #[arg(long = "max-total-mem", value_name = "MB")]
+    pub max_total_mb: Option<usize>,
+
+    /// Minimal free memory of the host to start a pan process, in MB (read from /proc/meminfo, ignored elsewhere)
+    #[arg(long = "min-free-mem", value_name = "MB")]
+    pub min_free_mb: Option<usize>,
+}
+
+impl MemoryLimits {
+    /// checks if a pan process with the given memory limit can start, given the number and the total memory limit
+    /// of the pan processes running, and the free memory of the host (if known).
+    pub fn admits(
+        &self,
+        running: usize,
+        used_mb: usize,
+        mb: usize,
+        free_mb: Option<usize>,
+    ) -> bool {
+        running == 0
+            || (self.max_total_mb.is_none_or(|max| used_mb + mb <= max)
+                && self
+                    .min_free_mb
+                    .is_none_or(|min| free_mb.is_none_or(|free| free >= min)))
+    }
+}
+
+struct State {
+    limits: MemoryLimits,
+    /// number of pan processes running
+    running: usize,
+    /// total memory limit of the pan processes running, in MB
+    used_mb: usize,
+}
+
+static STATE: Mutex<State> = Mutex::new(State {
+    limits: MemoryLimits {
+        max_total_mb: None,
+        min_free_mb: None,
+    },
+    running: 0,
+    used_mb: 0,
+});
+static RELEASED: Condvar = Condvar::new();
+
+/// sets the limits on the pan processes started from now on.
+pub fn set_memory_limits(limits: MemoryLimits) {
+    STATE.lock().unwrap().limits = limits;
+    RELEASED.notify_all();
+}
+
+/// permission to run a pan process, released when dropped.
+pub struct Permit {
+    mb: usize,
+}
+
+impl Drop for Permit {
+    fn drop(&mut self) {
+        let mut state = STATE.lock().unwrap();
+        state.running -= 1;
+        state.used_mb -= self.mb;
+        RELEASED.notify_all();
+    }
+}
+
+/// waits until a pan process with the given memory limit (in MB) is allowed to run.
+pub fn acquire(mb: usize) -> Permit {
+    let mut state = STATE.lock().unwrap();
+    let mut waiting = false;
+    loop {
+        let free_mb = if state.limits.min_free_mb.is_some() {
+            available_memory_mb()
+        } else {
+            None
+        };
+        if state
+            .limits
+            .admits(state.running, state.used_mb, mb, free_mb)
+        {
+            break;
+        }
+        if !waiting {
+            log::debug!(
+                "waiting to start pan ({} running, {} MB, free: {:?} MB)",
+                state.running,
+                state.used_mb,
+                free_mb
+            );
+            waiting = true;
+        }
+        state = RELEASED.wait_timeout(state, POLL_INTERVAL).unwrap().0;
+    }
+    state.running += 1;
+    state.used_mb += mb;
+    Permit { mb }
+}
+
+/// memory available to new processes, in MB (`MemAvailable` in `/proc/meminfo`), if known.
+pub fn available_memory_mb() -> Option<usize> {
+    let meminfo = std::fs::read_to_string("/proc/meminfo").ok()?;
+    meminfo.lines().find_map(|line| {
+        let kb = line
+            .strip_prefix("MemAvailable:")?
+            .trim()
+            .strip_suffix("kB")?;
+        kb.trim().parse::<usize>().ok().map(|kb| kb / 1024)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_memory_limits() {
+        let unlimited = MemoryLimits::default();
+        assert!(unlimited.admits(32, 32 * 16384, 16384, Some(0)));
+
+        let total = MemoryLimits {
+            max_total_mb: Some(40000),
+            min_free_mb: None,
+        };
+        assert!(total.admits(1, 16384, 16384, None));
+        assert!(!total.admits(2, 32768, 16384, None));
+        // a single process may exceed the limit
+        assert!(total.admits(0, 0, 65536, None));
+
+        let free = MemoryLimits {
+            max_total_mb: None,
+            min_free_mb: Some(8192),
+        };
+        assert!(free.admits(4, 0, 16384, Some(10000)));
+        assert!(!free.admits(4, 0, 16384, Some(4000)));
+        assert!(free.admits(0, 0, 16384, Some(4000)));
+        assert!(free.admits(4, 0, 16384, None));
+
+        // no limits set in the tests: never waits
+        let permits = (0..4).map(|_| acquire(16384)).collect::<Vec<_>>();
+        drop(permits);
+    }
+}